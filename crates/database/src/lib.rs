@@ -1,8 +1,10 @@
 //! The graph database.
 
+mod remote;
 mod store;
 
 pub mod prelude {
     //! Export all types of the crate.
+    pub use crate::remote::{fetch_ontology, query_endpoint, response_format};
     pub use crate::store::VOWLGrapherStore;
 }
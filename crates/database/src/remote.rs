@@ -0,0 +1,213 @@
+//! Fetching ontologies and query results over HTTP.
+//!
+//! Requests ask for the RDF and OWL formats by their MIME types, and the `Content-Type`
+//! of the response decides the format. The extension of the URL is only consulted if the
+//! server does not name a known format, which makes extensionless IRIs such as PURLs
+//! and `w3id.org` redirects load reliably.
+
+use log::debug;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, Url, header};
+use spargebra::{Query, SparqlParser};
+use std::path::Path;
+use std::sync::LazyLock;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::DataType;
+
+/// How many redirects are followed before a fetch is given up.
+const MAX_REDIRECTS: usize = 10;
+
+/// The HTTP client used for all fetches, shared so connections are reused.
+static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .redirect(Policy::custom(|attempt| {
+            if attempt.previous().len() > MAX_REDIRECTS {
+                attempt.error(format!("more than {MAX_REDIRECTS} redirects"))
+            } else {
+                debug!("Following redirect to {}", attempt.url());
+                attempt.follow()
+            }
+        }))
+        .build()
+        .unwrap_or_default()
+});
+
+/// Fetches an ontology from `url`, following redirects.
+///
+/// # Errors
+/// Returns an error if the request fails or the server responds with an error status.
+pub async fn fetch_ontology(url: &Url) -> Result<Response, VOWLGrapherStoreError> {
+    HTTP_CLIENT
+        .get(url.clone())
+        .header(header::ACCEPT, DataType::ontology_accept_header())
+        .send()
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(|e| {
+            VOWLGrapherStoreErrorKind::RemoteFetchError(format!("Failed to fetch {url}: {e}"))
+                .into()
+        })
+}
+
+/// Sends a query to a SPARQL endpoint with the SPARQL 1.1 Protocol, following redirects.
+///
+/// The results of CONSTRUCT and DESCRIBE queries are requested as Turtle or N-Triples,
+/// the results of SELECT and ASK queries as `results_type`. Queries which fail to parse,
+/// such as those using extensions of the endpoint, accept both.
+///
+/// Returns the response and the format of its results: the format named by the `Content-Type`
+/// of the response, or else the format requested first.
+///
+/// # Errors
+/// Returns an error if the request fails or the endpoint responds with an error status.
+pub async fn query_endpoint(
+    endpoint: &Url,
+    query: &str,
+    results_type: DataType,
+) -> Result<(Response, DataType), VOWLGrapherStoreError> {
+    let turtle = DataType::TTL.mime_type();
+    let n_triples = DataType::NTriples.mime_type();
+    let (accept, requested) = match SparqlParser::new().parse_query(query) {
+        Ok(Query::Construct { .. } | Query::Describe { .. }) => {
+            (format!("{turtle}, {n_triples};q=0.9"), DataType::TTL)
+        }
+        Ok(Query::Select { .. } | Query::Ask { .. }) => {
+            (results_type.mime_type().to_string(), results_type)
+        }
+        Err(e) => {
+            debug!("Sending a query the parser rejected: {e}");
+            (
+                format!(
+                    "{}, {turtle};q=0.9, {n_triples};q=0.8",
+                    results_type.mime_type()
+                ),
+                results_type,
+            )
+        }
+    };
+
+    let response = HTTP_CLIENT
+        .post(endpoint.clone())
+        .header(header::ACCEPT, accept)
+        .form(&[("query", query)])
+        .send()
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(|e| {
+            VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
+                "Failed to query {endpoint}: {e}"
+            )))
+        })?;
+    let format = match response_format(&response) {
+        DataType::UNKNOWN => requested,
+        format => format,
+    };
+    Ok((response, format))
+}
+
+/// Returns the format of a fetched ontology.
+///
+/// The `Content-Type` of the response takes precedence over the extension of the URL
+/// the response was finally served from.
+pub fn response_format(response: &Response) -> DataType {
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(DataType::UNKNOWN, DataType::from_mime_type);
+    if content_type == DataType::UNKNOWN {
+        DataType::from(Path::new(response.url().path()))
+    } else {
+        debug!(
+            "Using format {content_type:?} announced by {}",
+            response.url()
+        );
+        content_type
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Answers every request on a local port in a background thread, and returns the URL of the server.
+    ///
+    /// `routes` maps paths to the status line and headers of their responses.
+    /// Other paths are answered with `404 Not Found`.
+    fn serve(routes: &'static [(&'static str, &'static str)]) -> std::io::Result<Url> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let base = format!("http://{}", listener.local_addr()?);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut lines = BufReader::new(&stream).lines().map_while(Result::ok);
+                let request_line = lines.next().unwrap_or_default();
+                // Skip the headers of the request.
+                for _ in lines.by_ref().take_while(|line| !line.is_empty()) {}
+
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let head = routes
+                    .iter()
+                    .find(|(route, _)| *route == path)
+                    .map_or("404 Not Found", |(_, head)| head);
+                let _ = (&stream).write_all(
+                    format!("HTTP/1.1 {head}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                );
+            }
+        });
+        Url::parse(&base).map_err(std::io::Error::other)
+    }
+
+    #[tokio::test]
+    async fn test_response_format() -> Result<(), Box<dyn std::error::Error>> {
+        let base = serve(&[
+            (
+                "/typed.nt",
+                "200 OK\r\nContent-Type: text/turtle; charset=utf-8",
+            ),
+            ("/untyped.nt", "200 OK\r\nContent-Type: text/plain"),
+        ])?;
+
+        let response = fetch_ontology(&base.join("/typed.nt")?).await?;
+        assert_eq!(
+            response_format(&response),
+            DataType::TTL,
+            "the Content-Type should take precedence over the extension"
+        );
+        let response = fetch_ontology(&base.join("/untyped.nt")?).await?;
+        assert_eq!(response_format(&response), DataType::NTriples);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_redirects() -> Result<(), Box<dyn std::error::Error>> {
+        let base = serve(&[
+            (
+                "/ontology",
+                "303 See Other\r\nLocation: /files/ontology.ttl",
+            ),
+            ("/files/ontology.ttl", "200 OK"),
+            ("/loop", "302 Found\r\nLocation: /loop"),
+        ])?;
+
+        let response = fetch_ontology(&base.join("/ontology")?).await?;
+        assert_eq!(response.url().path(), "/files/ontology.ttl");
+        assert_eq!(
+            response_format(&response),
+            DataType::TTL,
+            "the format should be told by the location the response was served from"
+        );
+
+        assert!(
+            fetch_ontology(&base.join("/loop")?).await.is_err(),
+            "endless redirects should fail"
+        );
+        assert!(
+            fetch_ontology(&base.join("/missing")?).await.is_err(),
+            "error statuses should fail"
+        );
+        Ok(())
+    }
+}
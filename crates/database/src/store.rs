@@ -1,24 +1,90 @@
-use futures::stream::{BoxStream, StreamExt};
+mod archive;
+mod classification;
+mod dataset;
+mod diff;
+mod endpoint;
+mod harvest;
+mod imports;
+mod inference;
+mod lifecycle;
+mod metrics;
+mod persistence;
+mod profiles;
+mod query_results;
+mod search;
+mod shacl;
+mod update;
+mod workspace;
+
+use dashmap::DashMap;
+use futures::stream::{BoxStream, Stream, StreamExt};
 use grapher::prelude::GraphDisplayData;
 use log::{debug, info, warn};
 use rdf_fusion::execution::results::QueryResults;
-use rdf_fusion::model::{NamedNodeRef, Quad};
+use rdf_fusion::model::{NamedNode, NamedNodeRef, Quad, Term, Triple};
 use rdf_fusion::store::Store;
-use reqwest::{Client, Url};
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufReader, Read};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::time::Duration;
-use std::time::Instant;
-use strum::IntoEnumIterator;
+use std::time::{Instant, SystemTime};
+use tokio::sync::OnceCell;
 use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
 use vowlgrapher_parser::parser_util::{
-    format_from_resource_type, parse_quads_to_format, parser_from_bytes, parser_from_path,
-    path_type,
+    QuadIter, format_from_resource_type, parse_quads_to_format, quads_from_reader,
 };
+use vowlgrapher_parser::sniff::{SNIFF_LENGTH, sniff_format};
 use vowlgrapher_serializer::prelude::GraphDisplayDataSolutionSerializer;
-use vowlgrapher_util::prelude::{DataType, ErrorRecord, VOWLGRAPHER_ENVIRONMENT, VOWLGrapherError};
+use vowlgrapher_sparql_queries::prelude::DEFAULT_QUERY;
+use vowlgrapher_util::prelude::{
+    Annotation, DataType, ErrorRecord, GraphAnnotations, VOWLGRAPHER_ENVIRONMENT, VOWLGrapherError,
+    decompressed_name,
+};
+
+use crate::store::imports::{
+    IMPORT_GRAPH_INFIX, ImportBase, ImportLimits, ImportResolver, declared_ontology_iris,
+    extract_import_iris, fetch_import_source, import_graph_name, inferred_graph_name,
+    provenance_graph_name, provenance_quads,
+};
+use crate::store::persistence::StoreBacking;
+
+static GLOBAL_STORE: OnceLock<Store> = OnceLock::new();
+
+/// The on-disk backing of [`GLOBAL_STORE`], if enabled by `VOWLGRAPHER_STORE_PATH`.
+static STORE_BACKING: LazyLock<Option<StoreBacking>> =
+    LazyLock::new(StoreBacking::from_environment);
+
+/// Set once the persisted graphs of an owner, keyed by the prefix of their graph names,
+/// have been loaded into [`GLOBAL_STORE`].
+static RESTORED_OWNERS: LazyLock<DashMap<String, Arc<OnceCell<()>>>> = LazyLock::new(DashMap::new);
+
+/// Set once the persisted graphs which expired while the server was down have been removed.
+static STORE_SWEPT: OnceCell<()> = OnceCell::const_new();
+
+/// When the graphs of an owner were last marked as used on disk, keyed by the prefix of their graph names.
+static DISK_TOUCHED: LazyLock<DashMap<String, Instant>> = LazyLock::new(DashMap::new);
 
-static GLOBAL_STORE: std::sync::OnceLock<Store> = std::sync::OnceLock::new();
+/// How often the persisted graphs of an active owner are marked as used on disk.
+const DISK_TOUCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The prefix of the temporary graphs holding the results of CONSTRUCT and DESCRIBE queries.
+const QUERY_GRAPH_PREFIX: &str = "urn:vowlgrapher:query:";
+
+/// Makes the name of every temporary query graph unique.
+static QUERY_GRAPH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The prefix of the graphs holding the quads of an input until it has been parsed completely.
+const STAGING_GRAPH_PREFIX: &str = "urn:vowlgrapher:staging:";
+
+/// Makes the name of every staging graph unique.
+static STAGING_GRAPH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+
+/// How many parsed quads are inserted into the store at once.
+const INSERT_BATCH_SIZE: usize = 100_000;
 
 /// The graph database.
 pub struct VOWLGrapherStore {
@@ -26,16 +92,22 @@ pub struct VOWLGrapherStore {
     pub session: Store,
     /// The unique ID for the current user.
     pub user_id: Option<String>,
-    upload_handle: Option<tempfile::NamedTempFile>,
+    /// The files uploaded since the last completed upload, with the names they were uploaded under.
+    uploads: Vec<(String, tempfile::NamedTempFile)>,
+    /// Whether `session` is the global store, which is mirrored to disk if persistence is enabled.
+    persistent: bool,
 }
 
 impl VOWLGrapherStore {
     /// Create a new database instance.
+    ///
+    /// The instance is never persisted to disk.
     pub const fn new(session: Store) -> Self {
         Self {
             session,
             user_id: None,
-            upload_handle: None,
+            uploads: Vec::new(),
+            persistent: false,
         }
     }
 
@@ -45,8 +117,321 @@ impl VOWLGrapherStore {
         Self {
             session,
             user_id: Some(user_id),
-            upload_handle: None,
+            uploads: Vec::new(),
+            persistent: true,
+        }
+    }
+
+    /// Returns the on-disk backing of this instance, if any.
+    fn backing(&self) -> Option<&'static StoreBacking> {
+        if self.persistent {
+            STORE_BACKING.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the key signing the session cookies, kept next to the persisted graphs
+    /// and created with `generate` on first use.
+    ///
+    /// Returns `None` if persistence is disabled or the key could not be read or created.
+    pub fn persisted_session_key(generate: impl FnOnce() -> Vec<u8>) -> Option<Vec<u8>> {
+        let backing = STORE_BACKING.as_ref()?;
+        backing
+            .session_key(generate)
+            .inspect_err(|e| warn!("Could not read or create the session key: {e}"))
+            .ok()
+    }
+
+    /// Loads the persisted graphs of the user into the store, if this has not happened yet.
+    ///
+    /// On first use of the store, persisted graphs which expired while the server was down are removed.
+    /// Afterwards, the graphs of the user are marked as used on disk every [`DISK_TOUCH_INTERVAL`].
+    ///
+    /// # Errors
+    /// Returns an error if the persisted graphs could not be loaded.
+    async fn ensure_restored(&self) -> Result<(), VOWLGrapherStoreError> {
+        let Some(backing) = self.backing() else {
+            return Ok(());
+        };
+        let ttl = VOWLGRAPHER_ENVIRONMENT.graph_idle_ttl_secs;
+        if ttl > 0 {
+            STORE_SWEPT
+                .get_or_try_init(|| async {
+                    backing.remove_expired(Duration::from_secs(ttl))?;
+                    Ok::<(), VOWLGrapherStoreError>(())
+                })
+                .await?;
+        }
+
+        let prefix = self.get_graph_name("");
+        let restored = Arc::clone(&RESTORED_OWNERS.entry(prefix.clone()).or_default());
+        restored
+            .get_or_try_init(|| async {
+                for graph in backing.restore(&self.session, &prefix).await? {
+                    lifecycle::register_graph(
+                        &graph.graph_name,
+                        graph.quad_count,
+                        graph.byte_size,
+                        graph.loaded_at,
+                    );
+                }
+                DISK_TOUCHED.insert(prefix.clone(), Instant::now());
+                Ok::<(), VOWLGrapherStoreError>(())
+            })
+            .await?;
+
+        let touch_due = DISK_TOUCHED
+            .get(&prefix)
+            .is_none_or(|touched| touched.elapsed() > DISK_TOUCH_INTERVAL);
+        if touch_due {
+            DISK_TOUCHED.insert(prefix.clone(), Instant::now());
+            for (graph_name, _) in lifecycle::graph_usages_with_prefix(&prefix) {
+                backing.touch_graph(&graph_name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Prepares the global store for use.
+    ///
+    /// This restores persisted graphs and evicts user graphs which have been idle for too long.
+    ///
+    /// # Errors
+    /// Returns an error if the persisted graphs could not be loaded or an idle graph could not be removed.
+    async fn maintain(&self) -> Result<(), VOWLGrapherStoreError> {
+        if !self.persistent {
+            return Ok(());
+        }
+        self.ensure_restored().await?;
+
+        for graph_name in lifecycle::idle_graphs() {
+            info!("Evicting idle graph '{graph_name}'");
+            self.remove_graph(&graph_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes a named graph and its import graphs from the store, the lifecycle registry and disk.
+    ///
+    /// # Errors
+    /// Returns an error if the graph could not be removed.
+    async fn remove_graph(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        for graph_name in self
+            .import_graphs(graph_name)
+            .await?
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(graph_name))
+        {
+            self.session
+                .remove_named_graph(NamedNodeRef::new(graph_name)?)
+                .await?;
+            lifecycle::unregister_graph(graph_name);
+            search::forget_index(graph_name);
+            harvest::forget_harvest(graph_name);
+            if let Some(backing) = self.backing() {
+                backing.remove_graph(graph_name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the statements inferred from a named graph, which no longer follow once it changed.
+    ///
+    /// # Errors
+    /// Returns an error if the inferred statements could not be removed.
+    async fn forget_inferences(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        let inferred_graph = inferred_graph_name(graph_name);
+        if self.graph_len(&inferred_graph).await? > 0 {
+            self.remove_graph(&inferred_graph).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the graphs holding the imports of a named graph and their provenance.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn import_graphs(&self, graph_name: &str) -> Result<Vec<String>, VOWLGrapherStoreError> {
+        let results = self
+            .session
+            .query(&format!(
+                "SELECT DISTINCT ?g WHERE {{ GRAPH ?g {{ ?s ?p ?o }} FILTER(STRSTARTS(STR(?g), \"{graph_name}{IMPORT_GRAPH_INFIX}\")) }}"
+            ))
+            .await?;
+
+        let mut graphs = Vec::new();
+        if let QueryResults::Solutions(mut solutions) = results {
+            while let Some(solution) = solutions.next().await {
+                if let Some(Term::NamedNode(graph)) = solution?.get("g") {
+                    graphs.push(graph.as_str().to_string());
+                }
+            }
+        }
+        Ok(graphs)
+    }
+
+    /// Returns the graphs holding the imported ontologies of a named graph,
+    /// without the graphs recording their provenance and inferred statements.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn ontology_imports(
+        &self,
+        graph_name: &str,
+    ) -> Result<Vec<String>, VOWLGrapherStoreError> {
+        let derived_graphs = [
+            provenance_graph_name(graph_name),
+            inferred_graph_name(graph_name),
+        ];
+        Ok(self
+            .import_graphs(graph_name)
+            .await?
+            .into_iter()
+            .filter(|import_graph| !derived_graphs.contains(import_graph))
+            .collect())
+    }
+
+    /// Returns the number of quads in a named graph.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn graph_len(&self, graph_name: &str) -> Result<u64, VOWLGrapherStoreError> {
+        let results = self
+            .session
+            .query(&format!(
+                "SELECT (COUNT(*) AS ?count) WHERE {{ GRAPH <{graph_name}> {{ ?s ?p ?o }} }}"
+            ))
+            .await?;
+
+        if let QueryResults::Solutions(mut solutions) = results
+            && let Some(solution) = solutions.next().await
+            && let Some(Term::Literal(count)) = solution?.get("count")
+        {
+            return Ok(count.value().parse().unwrap_or(0));
+        }
+        Ok(0)
+    }
+
+    /// Returns the statements of a graph of the user.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or the store could not be queried.
+    async fn graph_triples(&self, graph_name: &str) -> Result<Vec<Triple>, VOWLGrapherStoreError> {
+        let graph_iri = self.get_graph_name(graph_name);
+        if self.graph_len(&graph_iri).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{graph_name}' does not exist"
+            ))
+            .into());
+        }
+        lifecycle::touch_graph(&graph_iri);
+        self.named_graph_triples(&graph_iri).await
+    }
+
+    /// Returns the statements of a named graph, which are none if it does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn named_graph_triples(
+        &self,
+        graph_iri: &str,
+    ) -> Result<Vec<Triple>, VOWLGrapherStoreError> {
+        let mut triples = Vec::new();
+        let mut quads = self
+            .session
+            .quads_for_pattern(None, None, None, Some(NamedNodeRef::new(graph_iri)?.into()))
+            .await?;
+        while let Some(quad) = quads.next().await {
+            triples.push(Triple::from(quad?));
+        }
+        Ok(triples)
+    }
+
+    /// Checks the user's quota and records a graph which is about to grow by `quad_count` quads
+    /// parsed from `byte_size` bytes of input.
+    ///
+    /// # Errors
+    /// Returns an error if the user would exceed their quota.
+    fn check_quota(
+        &self,
+        graph_name: &str,
+        quad_count: usize,
+        byte_size: u64,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        if !self.persistent {
+            return Ok(());
+        }
+        lifecycle::check_quota(
+            graph_name,
+            u64::try_from(quad_count).unwrap_or(u64::MAX),
+            byte_size,
+        )
+    }
+
+    /// Records the size of a graph after it was loaded from `byte_size` bytes of input.
+    ///
+    /// A graph which is already registered, e.g. after an update, keeps its load time.
+    ///
+    /// # Errors
+    /// Returns an error if the size of the graph could not be determined.
+    async fn register_graph(
+        &self,
+        graph_name: &str,
+        byte_size: u64,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        if !self.persistent {
+            return Ok(());
+        }
+        let loaded_at = lifecycle::graph_usage(graph_name)
+            .map_or_else(SystemTime::now, |usage| usage.loaded_at);
+        lifecycle::register_graph(
+            graph_name,
+            self.graph_len(graph_name).await?,
+            byte_size,
+            loaded_at,
+        );
+        Ok(())
+    }
+
+    /// Removes the current content of a named graph and its imports, so it can be loaded again.
+    ///
+    /// # Errors
+    /// Returns an error if the graph could not be cleared.
+    async fn clear_graph(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        lifecycle::unregister_graph(graph_name);
+        search::forget_index(graph_name);
+        harvest::forget_harvest(graph_name);
+        if let Some(backing) = self.backing() {
+            backing.remove_harvest(graph_name)?;
+        }
+        for import_graph in self.import_graphs(graph_name).await? {
+            self.remove_graph(&import_graph).await?;
+        }
+        if self.graph_len(graph_name).await? > 0 {
+            self.session
+                .remove_named_graph(NamedNodeRef::new(graph_name)?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Writes a named graph to disk, if persistence is enabled.
+    ///
+    /// # Errors
+    /// Returns an error if the graph could not be written to disk.
+    async fn persist_graph(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        if let Some(backing) = self.backing() {
+            let (loaded_at, byte_size) = lifecycle::graph_usage(graph_name)
+                .map_or((SystemTime::now(), 0), |usage| {
+                    (usage.loaded_at, usage.byte_size)
+                });
+            backing
+                .persist_graph(&self.session, graph_name, loaded_at, byte_size)
+                .await?;
         }
+        Ok(())
     }
 
     /// Returns the unique graph name for a given filename and user.
@@ -59,8 +444,20 @@ impl VOWLGrapherStore {
         )
     }
 
+    /// Returns the graph IRI of `graph_name`, which is either the name given by the user
+    /// when loading a graph or the full IRI of one of the user's graphs.
+    pub fn resolve_graph_name(&self, graph_name: &str) -> String {
+        if graph_name.starts_with(&self.get_graph_name("")) {
+            graph_name.to_string()
+        } else {
+            self.get_graph_name(graph_name)
+        }
+    }
+
     /// Executes a SPARQL query and serializes the result.
     ///
+    /// `GRAPH <{GRAPH_IRI}>` refers to `graph_name` and all of its imports.
+    ///
     /// This method tries to continue serializing despite errors.
     /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
     ///
@@ -71,167 +468,421 @@ impl VOWLGrapherStore {
         query: String,
         graph_name: Option<String>,
     ) -> Result<(GraphDisplayData, Option<VOWLGrapherError>), VOWLGrapherError> {
+        self.query_with_imports(query, graph_name, &[], false, None)
+            .await
+            .map(|(display_data, _, errors)| (display_data, errors))
+    }
+
+    /// Executes a SPARQL query and serializes the result.
+    ///
+    /// `GRAPH <{GRAPH_IRI}>` refers to `graph_name` merged with every import whose IRI
+    /// is not in `excluded_imports`. Elements of the imports are then serialized as
+    /// external elements of the ontology in `graph_name`.
+    ///
+    /// If `include_inferred` is set, the statements materialized by
+    /// [`VOWLGrapherStore::materialize_inferences`] are merged in as well,
+    /// and edges which only follow from them are annotated as inferred.
+    ///
+    /// The element with the IRI `highlighted_element`, if any, is annotated as highlighted,
+    /// so the element found by a search stands out.
+    ///
+    /// The query only sees these graphs, whether or not it refers to `GRAPH <{GRAPH_IRI}>`,
+    /// so it cannot read the graphs of other users. Without `graph_name`, the query of a user
+    /// sees all of their graphs.
+    ///
+    /// Returns the serialized result along with the annotations of its elements.
+    ///
+    /// This method tries to continue serializing despite errors.
+    /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
+    ///
+    /// # Errors
+    /// Returns an error if the query or serialization encountered a fatal problem.
+    pub async fn query_with_imports(
+        &self,
+        query: String,
+        graph_name: Option<String>,
+        excluded_imports: &[String],
+        include_inferred: bool,
+        highlighted_element: Option<String>,
+    ) -> Result<(GraphDisplayData, GraphAnnotations, Option<VOWLGrapherError>), VOWLGrapherError>
+    {
         debug!("Querying with graph_name: {graph_name:#?}");
-        let user_query = graph_name.map_or_else(
-            || query.replace("GRAPH <{GRAPH_IRI}>", ""),
-            |name| {
-                let graph_name = self.get_graph_name(&name);
-                query.replace("{GRAPH_IRI}", &graph_name)
-            },
-        );
+        self.maintain().await?;
+        let Some(name) = graph_name else {
+            let query = query.replace("GRAPH <{GRAPH_IRI}>", "");
+            let serializer = GraphDisplayDataSolutionSerializer::new()
+                .with_highlighted_element(highlighted_element);
+            if self.user_id.is_none() {
+                return self.run_query_with(&query, serializer).await;
+            }
+            // A user may only query their own graphs.
+            let graphs = lifecycle::graph_usages_with_prefix(&self.get_graph_name(""))
+                .into_iter()
+                .map(|(graph_name, _)| graph_name)
+                .collect::<Vec<_>>();
+            return self
+                .run_query_with(&dataset::scope_query(&query, &graphs)?, serializer)
+                .await;
+        };
+        let graph_name = self.get_graph_name(&name);
+        lifecycle::touch_graph(&graph_name);
+
+        let excluded_graphs = excluded_imports
+            .iter()
+            .map(|iri| import_graph_name(&graph_name, iri))
+            .collect::<HashSet<_>>();
+        let mut import_graphs = self
+            .ontology_imports(&graph_name)
+            .await?
+            .into_iter()
+            .filter(|import_graph| !excluded_graphs.contains(import_graph))
+            .collect::<Vec<_>>();
+        let inferred_graph = inferred_graph_name(&graph_name);
+        let inferred_statements = if include_inferred {
+            self.named_graph_triples(&inferred_graph).await?
+        } else {
+            Vec::new()
+        };
+        if !inferred_statements.is_empty() {
+            import_graphs.push(inferred_graph);
+        }
+        let serializer = if import_graphs.is_empty() {
+            GraphDisplayDataSolutionSerializer::new()
+        } else {
+            self.ontology_iri(&graph_name)
+                .await?
+                .map_or_else(
+                    GraphDisplayDataSolutionSerializer::new,
+                    GraphDisplayDataSolutionSerializer::with_document_base,
+                )
+                .with_inferred_statements(inferred_statements.into_iter().collect())
+        }
+        .with_highlighted_element(highlighted_element);
 
-        let solution_serializer = GraphDisplayDataSolutionSerializer::new();
+        // Merging the graphs into the default graph lets patterns span the ontology and its imports,
+        // while `GRAPH` clauses cannot reach any graph but these, such as the graphs of other users.
+        let graphs = std::iter::once(graph_name.clone())
+            .chain(import_graphs)
+            .collect::<Vec<_>>();
+        let scoped_query = dataset::scope_query(
+            &query
+                .replace("GRAPH <{GRAPH_IRI}>", "")
+                .replace("{GRAPH_IRI}", &graph_name),
+            &graphs,
+        )?;
+        self.run_query_with(&scoped_query, serializer).await
+    }
+
+    /// Returns the IRI of the ontology in a named graph, if it declares one.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn ontology_iri(
+        &self,
+        graph_name: &str,
+    ) -> Result<Option<String>, VOWLGrapherStoreError> {
+        let results = self
+            .session
+            .query(&format!(
+                "SELECT ?ontology WHERE {{ GRAPH <{graph_name}> {{ ?ontology a <http://www.w3.org/2002/07/owl#Ontology> }} }} LIMIT 1"
+            ))
+            .await?;
+
+        if let QueryResults::Solutions(mut solutions) = results
+            && let Some(solution) = solutions.next().await
+            && let Some(Term::NamedNode(ontology)) = solution?.get("ontology")
+        {
+            return Ok(Some(ontology.as_str().to_string()));
+        }
+        Ok(None)
+    }
+
+    /// Executes a SPARQL query and serializes the result with `solution_serializer`.
+    ///
+    /// The result of CONSTRUCT and DESCRIBE queries is loaded into a temporary named graph,
+    /// which is then queried with [`DEFAULT_QUERY`], serialized with `solution_serializer`
+    /// and removed again.
+    ///
+    /// # Errors
+    /// Returns an error if the query or serialization encountered a fatal problem.
+    async fn run_query_with(
+        &self,
+        query: &str,
+        solution_serializer: GraphDisplayDataSolutionSerializer,
+    ) -> Result<(GraphDisplayData, GraphAnnotations, Option<VOWLGrapherError>), VOWLGrapherError>
+    {
         let query_stream = self
             .session
-            .query(&user_query)
+            .query(query)
             .await
             .map_err(|e| <VOWLGrapherStoreError as Into<VOWLGrapherError>>::into(e.into()))?;
 
         match query_stream {
             QueryResults::Solutions(query_solution_stream) => {
                 let mut data_buffer = GraphDisplayData::new();
+                let mut annotations = GraphAnnotations::default();
 
                 let maybe_errors = solution_serializer
-                    .serialize_solution_stream(&mut data_buffer, query_solution_stream)
+                    .serialize_solution_stream(
+                        &mut data_buffer,
+                        &mut annotations,
+                        query_solution_stream,
+                    )
                     .await?;
-                Ok((data_buffer, maybe_errors))
+                Ok((data_buffer, annotations, maybe_errors))
             }
             QueryResults::Boolean(_result) => Err(VOWLGrapherStoreErrorKind::UnsupportedQueryType(
-                "Query stream is not a SELECT query".to_string(),
+                "Query stream is not a SELECT, CONSTRUCT or DESCRIBE query".to_string(),
             )
             .into()),
-            QueryResults::Graph(_query_triple_stream) => {
-                // TODO: Implement to support user-defined SPARQL queries
-                Err(VOWLGrapherStoreErrorKind::UnsupportedQueryType(
-                    "Query stream is not a SELECT query".to_string(),
-                )
-                .into())
+            QueryResults::Graph(query_triple_stream) => {
+                let temporary_graph = self.load_temporary_graph(query_triple_stream).await?;
+                let result = Box::pin(self.run_query_with(
+                    &DEFAULT_QUERY.replace("{GRAPH_IRI}", temporary_graph.as_str()),
+                    solution_serializer,
+                ))
+                .await;
+                self.session
+                    .remove_named_graph(temporary_graph.as_ref())
+                    .await
+                    .map_err(VOWLGrapherStoreError::from)?;
+                result
             }
         }
     }
 
+    /// Loads the triples of a CONSTRUCT or DESCRIBE query into a new temporary named graph.
+    ///
+    /// Returns the name of the temporary graph.
+    ///
+    /// # Errors
+    /// Returns an error if the triples could not be read or inserted into the store.
+    async fn load_temporary_graph<E>(
+        &self,
+        mut triples: impl Stream<Item = Result<Triple, E>> + Unpin,
+    ) -> Result<NamedNode, VOWLGrapherStoreError>
+    where
+        VOWLGrapherStoreError: From<E>,
+    {
+        let graph_name = NamedNode::new(format!(
+            "{QUERY_GRAPH_PREFIX}{}",
+            QUERY_GRAPH_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))?;
+
+        let mut quads = Vec::new();
+        while let Some(triple) = triples.next().await {
+            let triple = triple?;
+            quads.push(Quad::new(
+                triple.subject,
+                triple.predicate,
+                triple.object,
+                graph_name.clone(),
+            ));
+        }
+        debug!(
+            "Loading {} constructed triples into '{graph_name}'",
+            quads.len()
+        );
+        self.session.extend(quads).await?;
+        Ok(graph_name)
+    }
+
+    /// Serializes triples which are not stored in any graph of the user,
+    /// annotating the elements of the IRIs in `annotated_terms`.
+    ///
+    /// The triples are loaded into a temporary named graph,
+    /// which is then queried with [`DEFAULT_QUERY`] and removed again.
+    ///
+    /// # Errors
+    /// Returns an error if the triples could not be loaded or serialized.
+    async fn display_triples(
+        &self,
+        triples: Vec<Triple>,
+        annotated_terms: HashMap<String, BTreeSet<Annotation>>,
+    ) -> Result<(GraphDisplayData, GraphAnnotations, Option<VOWLGrapherError>), VOWLGrapherError>
+    {
+        let temporary_graph = self
+            .load_temporary_graph(futures::stream::iter(
+                triples.into_iter().map(Ok::<_, VOWLGrapherStoreError>),
+            ))
+            .await?;
+        let result = self
+            .run_query_with(
+                &DEFAULT_QUERY.replace("{GRAPH_IRI}", temporary_graph.as_str()),
+                GraphDisplayDataSolutionSerializer::new().with_annotated_terms(annotated_terms),
+            )
+            .await;
+        self.session
+            .remove_named_graph(temporary_graph.as_ref())
+            .await
+            .map_err(VOWLGrapherStoreError::from)?;
+        result
+    }
+
     // TTL format -> (oxittl) RDF XML quads -> (horned_owl) Normalize OWL/RDF -> Quads -> Insert into Oxigraph
     /// Inserts a file into the store.
     ///
-    /// Files are automatically parsed.
+    /// Files are automatically parsed. Compressed files and zip archives are unpacked first.
     ///
     /// # Errors
-    /// Returns an error if the file fails to parse or fails to be inserted into the store.
+    /// Returns an error if the file fails to unpack or parse, or fails to be inserted into the store.
     pub async fn insert_file(
         &self,
         fs: &Path,
         lenient: bool,
     ) -> Result<Option<VOWLGrapherError>, VOWLGrapherStoreError> {
+        self.maintain().await?;
         let graph_name = self.get_graph_name(&fs.to_string_lossy());
-        let format = path_type(fs).ok_or_else(|| {
-            VOWLGrapherStoreErrorKind::InvalidFileType("Unknown file extension".into())
-        })?;
 
-        let root_quads = parser_from_path(fs, format, lenient, &graph_name)?;
-        let (quads, warnings) = self
-            .flatten_import_closure(root_quads, &graph_name, lenient, ImportBase::from_path(fs))
-            .await?;
+        let input = archive::unpack(fs, &fs.to_string_lossy(), DataType::UNKNOWN)?;
+        let byte_size = std::fs::metadata(&input.path)?.len();
+        let (quads, _) = Self::open_file(&input.path, input.hint, lenient, &graph_name)?;
+        let base = if input.archived {
+            ImportBase::from_path(&input.path)
+        } else {
+            ImportBase::from_path(fs)
+        };
+        self.ingest(
+            &graph_name,
+            vec![quads],
+            byte_size,
+            ImportResolver::new(base, lenient).with_bundled_files(input.bundled_files),
+            false,
+        )
+        .await
+    }
 
-        info!("Loading graph '{graph_name}' into database...");
-        let start_time = Instant::now();
-        self.session.extend(quads).await?;
-        info!(
-            "Loaded {} quads in {} s",
-            self.session.len().await?,
-            Instant::now()
-                .checked_duration_since(start_time)
-                .unwrap_or(Duration::new(0, 0))
-                .as_secs_f32()
-        );
+    /// Inserts serialized RDF into a named graph.
+    ///
+    /// The format is detected from the data, with `hinted_format` deciding between
+    /// formats the data cannot tell apart.
+    /// The graph is replaced unless `append` is set.
+    /// `graph_name` is either a name chosen by the user or the full IRI of one of their graphs.
+    ///
+    /// # Errors
+    /// Returns an error if the data fails to parse or fails to be inserted into the store.
+    pub async fn insert_bytes(
+        &self,
+        bytes: &[u8],
+        graph_name: &str,
+        hinted_format: DataType,
+        append: bool,
+    ) -> Result<(DataType, Option<VOWLGrapherError>), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.resolve_graph_name(graph_name);
+        let byte_size = u64::try_from(bytes.len()).unwrap_or(u64::MAX);
 
-        Ok(warnings)
+        let (quads, loaded_format) = Self::open_bytes(bytes, hinted_format, false, &graph_name)?;
+        let warnings = self
+            .ingest(
+                &graph_name,
+                vec![quads],
+                byte_size,
+                ImportResolver::new(ImportBase::Unknown, false),
+                append,
+            )
+            .await?;
+
+        Ok((loaded_format, warnings))
     }
 
-    fn load_file(
-        path: &Path,
-        lenient: bool,
-        graph_name: &str,
-    ) -> Result<(Vec<Quad>, DataType), VOWLGrapherStoreError> {
-        let dtype = path.into();
-        if dtype == DataType::UNKNOWN {
-            Self::try_load_fallback(path, lenient, None, graph_name)
-        } else {
-            let result =
-                std::panic::catch_unwind(|| parser_from_path(path, dtype, lenient, graph_name));
-            match result {
-                Ok(Ok(quads)) => Ok((quads, dtype)),
-                _ => Self::try_load_fallback(path, lenient, Some(dtype), graph_name),
-            }
-        }
+    /// Returns whether a named graph of the user contains any quads.
+    ///
+    /// `graph_name` is either the name of the graph or its full IRI.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    pub async fn contains_graph(&self, graph_name: &str) -> Result<bool, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        Ok(self.graph_len(&self.resolve_graph_name(graph_name)).await? > 0)
     }
 
-    /// Fallback parsers for when the main parser failed.
+    /// Opens a file for parsing with the parser chosen by [`Self::detect_format`].
     ///
-    /// This iterates over remaining parse formats and tries each until it succeeds or fails.
+    /// If `hinted_format` is [`DataType::UNKNOWN`], the extension of `path` is used as the hint.
     ///
     /// # Errors
-    /// Returns an error if no format supports the file at the given path.
-    fn try_load_fallback(
+    /// Returns an error if the format cannot be detected or the file cannot be opened.
+    fn open_file(
         path: &Path,
+        hinted_format: DataType,
         lenient: bool,
-        skip_format: Option<DataType>,
         graph_name: &str,
-    ) -> Result<(Vec<Quad>, DataType), VOWLGrapherStoreError> {
-        for format in DataType::iter().filter(|f| *f != DataType::UNKNOWN) {
-            if Some(format) == skip_format {
-                continue;
-            }
-
-            let result =
-                std::panic::catch_unwind(|| parser_from_path(path, format, lenient, graph_name));
-            if let Ok(Ok(quads)) = result {
-                info!("Parsed file as {format:?}");
-                return Ok((quads, format));
-            }
-        }
+    ) -> Result<(QuadIter<'static>, DataType), VOWLGrapherStoreError> {
+        let hint = if hinted_format == DataType::UNKNOWN {
+            path.into()
+        } else {
+            hinted_format
+        };
+        let mut head = Vec::with_capacity(SNIFF_LENGTH);
+        std::fs::File::open(path)?
+            .take(SNIFF_LENGTH as u64)
+            .read_to_end(&mut head)?;
 
-        Err(VOWLGrapherStoreErrorKind::InvalidFileType(format!(
-            "Could not parse file. Tried with the following formats: {:?}",
-            DataType::iter()
-                .filter(|f| *f != DataType::UNKNOWN)
-                .collect::<Vec<_>>()
-        ))
-        .into())
+        let format = Self::detect_format(&head, hint)?;
+        let reader = BufReader::new(std::fs::File::open(path)?);
+        let quads = quads_from_reader(reader, format, lenient, graph_name)
+            .map_err(|e| Self::parse_failure(format, e))?;
+        Ok((Self::with_parse_failures(quads, format), format))
     }
 
-    fn load_bytes_with_fallback(
-        bytes: &[u8],
+    /// Opens serialized RDF for parsing with the parser chosen by [`Self::detect_format`].
+    ///
+    /// # Errors
+    /// Returns an error if the format cannot be detected.
+    fn open_bytes<'b>(
+        bytes: &'b [u8],
         hinted_format: DataType,
         lenient: bool,
         graph_iri: &str,
-    ) -> Result<(Vec<Quad>, DataType), VOWLGrapherStoreError> {
-        let formats: Vec<DataType> = if hinted_format == DataType::UNKNOWN {
-            DataType::iter()
-                .filter(|f| *f != DataType::UNKNOWN)
-                .collect()
-        } else {
-            std::iter::once(hinted_format)
-                .chain(DataType::iter().filter(|f| *f != DataType::UNKNOWN && *f != hinted_format))
-                .collect()
-        };
+    ) -> Result<(QuadIter<'b>, DataType), VOWLGrapherStoreError> {
+        let format = Self::detect_format(&bytes[..bytes.len().min(SNIFF_LENGTH)], hinted_format)?;
+        let quads = quads_from_reader(bytes, format, lenient, graph_iri)
+            .map_err(|e| Self::parse_failure(format, e))?;
+        Ok((Self::with_parse_failures(quads, format), format))
+    }
+
+    /// Reports errors of `quads` as failures to parse the input as `format`.
+    fn with_parse_failures(quads: QuadIter<'_>, format: DataType) -> QuadIter<'_> {
+        Box::new(quads.map(move |quad| quad.map_err(|e| Self::parse_failure(format, e))))
+    }
 
-        for format in formats {
-            let result =
-                std::panic::catch_unwind(|| parser_from_bytes(bytes, format, lenient, graph_iri));
-            if let Ok(Ok(quads)) = result {
-                info!("Parsed import as {format:?}");
-                return Ok((quads, format));
+    /// Picks the format of an input from its first bytes, falling back to `hint`.
+    ///
+    /// # Errors
+    /// Returns an error if neither the content nor the hint name a supported format.
+    fn detect_format(head: &[u8], hint: DataType) -> Result<DataType, VOWLGrapherStoreError> {
+        match sniff_format(head, hint) {
+            DataType::UNKNOWN
+            | DataType::SPARQLJSON
+            | DataType::SPARQLXML
+            | DataType::SPARQLCSV
+            | DataType::SPARQLTSV => Err(VOWLGrapherStoreErrorKind::InvalidFileType(
+                "Could not detect the format of the input. Supported formats are RDF/XML, OWL/XML, \
+                OWL Functional, Turtle, N-Triples, N-Quads, TriG, JSON-LD and N3"
+                    .to_string(),
+            )
+            .into()),
+            format => {
+                debug!("Detected format {format:?} (hint: {hint:?})");
+                Ok(format)
             }
         }
+    }
 
-        Err(VOWLGrapherStoreErrorKind::InvalidFileType(
-            "Could not parse imported ontology".to_string(),
-        )
-        .into())
+    /// Reports that the input failed to parse as `format`, keeping the parser's message and its location.
+    fn parse_failure(format: DataType, error: VOWLGrapherStoreError) -> VOWLGrapherStoreError {
+        VOWLGrapherStoreErrorKind::InvalidFileType(format!(
+            "Failed to parse the input as {format}: {}",
+            ErrorRecord::from(error).message
+        ))
+        .into()
     }
 
     /// Serializes the store into a stream of the specified resource type.
     ///
+    /// `graph_name` is either the name of the graph or its full IRI.
+    ///
     /// # Errors
     /// Returns an error if the store fails to serialize its content.
     pub async fn serialize_stream(
@@ -240,12 +891,79 @@ impl VOWLGrapherStore {
         graph_name: &str,
     ) -> Result<BoxStream<'static, Result<Vec<u8>, VOWLGrapherStoreError>>, VOWLGrapherStoreError>
     {
+        self.maintain().await?;
         debug!(
             "Store size before export: {}",
             self.session.len().await.unwrap_or(0)
         );
+        let graph_name = self.resolve_graph_name(graph_name);
+        lifecycle::touch_graph(&graph_name);
+        self.serialize_graph(resource_type, &graph_name).await
+    }
+
+    /// Serializes a graph merged with all of its imports into a stream of the specified resource type.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails to serialize its content.
+    pub async fn serialize_stream_with_imports(
+        &self,
+        resource_type: DataType,
+        graph_name: &str,
+    ) -> Result<BoxStream<'static, Result<Vec<u8>, VOWLGrapherStoreError>>, VOWLGrapherStoreError>
+    {
+        self.maintain().await?;
         let graph_name = self.get_graph_name(graph_name);
-        let graph_ref = NamedNodeRef::new(&graph_name)?;
+        lifecycle::touch_graph(&graph_name);
+
+        let import_graphs = self.ontology_imports(&graph_name).await?;
+        if import_graphs.is_empty() {
+            return self.serialize_graph(resource_type, &graph_name).await;
+        }
+
+        let mut triples = Vec::new();
+        for source_graph in std::iter::once(&graph_name).chain(&import_graphs) {
+            let mut quads_stream = self
+                .session
+                .quads_for_pattern(
+                    None,
+                    None,
+                    None,
+                    Some(NamedNodeRef::new(source_graph)?.into()),
+                )
+                .await?;
+            while let Some(quad) = quads_stream.next().await {
+                triples.push(Ok::<_, VOWLGrapherStoreError>(Triple::from(quad?)));
+            }
+        }
+        info!(
+            "Merging graph '{graph_name}' with {} imports for export",
+            import_graphs.len()
+        );
+
+        // Serialization completes before the stream is returned, so the merged graph can be removed right away.
+        let merged_graph = self
+            .load_temporary_graph(futures::stream::iter(triples))
+            .await?;
+        let result = self
+            .serialize_graph(resource_type, merged_graph.as_str())
+            .await;
+        self.session
+            .remove_named_graph(merged_graph.as_ref())
+            .await?;
+        result
+    }
+
+    /// Serializes a single named graph into a stream of the specified resource type.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails to serialize the graph.
+    async fn serialize_graph(
+        &self,
+        resource_type: DataType,
+        graph_name: &str,
+    ) -> Result<BoxStream<'static, Result<Vec<u8>, VOWLGrapherStoreError>>, VOWLGrapherStoreError>
+    {
+        let graph_ref = NamedNodeRef::new(graph_name)?;
 
         if matches!(resource_type, DataType::OWL | DataType::OFN | DataType::OWX) {
             info!("Exporting graph '{graph_name}' as {resource_type:?}...");
@@ -273,6 +991,9 @@ impl VOWLGrapherStore {
 
     /// Create a temporary file on the server to upload user input into.
     ///
+    /// Files uploaded before are kept until the upload is completed, so several files
+    /// can be loaded into the same graph.
+    ///
     /// # Errors
     /// Returns an error if the file cannot be created.
     pub fn start_upload(&mut self, filename: &str) -> Result<(), VOWLGrapherStoreError> {
@@ -283,16 +1004,16 @@ impl VOWLGrapherStore {
         let file = tempfile::Builder::new()
             .suffix(&format!(".{extension}"))
             .tempfile()?;
-        self.upload_handle = Some(file);
+        self.uploads.push((filename.to_string(), file));
         Ok(())
     }
 
-    /// Insert a chunk of data into the file currently in use.
+    /// Insert a chunk of data into the file started last.
     ///
     /// # Errors
     /// Returns an error if the data cannot ve written to the file.
     pub fn upload_chunk(&mut self, data: &[u8]) -> Result<(), VOWLGrapherStoreError> {
-        if let Some(file) = &mut self.upload_handle {
+        if let Some((_, file)) = self.uploads.last_mut() {
             std::io::Write::write_all(file, data)?;
         } else {
             warn!("upload_chunk called without start_upload");
@@ -300,245 +1021,486 @@ impl VOWLGrapherStore {
         Ok(())
     }
 
-    /// Inserts a file into the store.
+    /// Inserts the uploaded files into the store, merged into the graph named after `filename`.
+    ///
+    /// The format of each file is detected from the file, with `hinted_format` deciding between formats
+    /// the file cannot tell apart. If it is [`DataType::UNKNOWN`], the extension of the file's name decides.
     ///
-    /// Files are automatically parsed.
+    /// Compressed files are decompressed. Of a zip archive, the ontology the other files are bundled
+    /// for is loaded, and its imports are resolved against the other files.
+    /// Imports of one uploaded file by another are not loaded again.
+    ///
+    /// Returns the format each file was parsed as.
     ///
     /// # Errors
-    /// Returns an error if the file fails to parse or
-    /// the store fails to load the triples of the file.
+    /// Returns an error if no file was uploaded, a file fails to unpack or parse, or
+    /// the store fails to load the triples of the files.
     pub async fn complete_upload(
         &mut self,
         filename: &str,
-    ) -> Result<(DataType, Option<VOWLGrapherError>), VOWLGrapherStoreError> {
+        hinted_format: DataType,
+    ) -> Result<(Vec<DataType>, Option<VOWLGrapherError>), VOWLGrapherStoreError> {
+        self.maintain().await?;
         let graph_name = self.get_graph_name(filename);
-        let path = if let Some(file) = &mut self.upload_handle {
-            std::io::Write::flush(file)?;
-            file.path().to_path_buf()
-        } else {
+        // The uploads are removed once loaded or failed, and must live until then.
+        let mut uploads = std::mem::take(&mut self.uploads);
+        if uploads.is_empty() {
             return Err(VOWLGrapherStoreErrorKind::InvalidFileType(
                 "No upload handle found".to_string(),
             )
             .into());
-        };
+        }
+
+        let mut unpacked = Vec::with_capacity(uploads.len());
+        for (name, file) in &mut uploads {
+            std::io::Write::flush(file)?;
+            unpacked.push(archive::unpack(file.path(), name, hinted_format)?);
+        }
 
-        let (root_quads, loaded_format) = Self::load_file(&path, false, &graph_name)?;
-        let (quads, warnings) = self
-            .flatten_import_closure(
-                root_quads,
+        let mut inputs = Vec::with_capacity(unpacked.len());
+        let mut loaded_formats = Vec::with_capacity(unpacked.len());
+        let mut byte_size = 0;
+        for input in &unpacked {
+            let (quads, format) = Self::open_file(&input.path, input.hint, false, &graph_name)?;
+            inputs.push(quads);
+            loaded_formats.push(format);
+            byte_size += std::fs::metadata(&input.path)?.len();
+        }
+
+        let base = match unpacked.as_slice() {
+            [input] if input.archived => ImportBase::from_path(&input.path),
+            _ => ImportBase::from_user_input(filename),
+        };
+        let merged_files = uploads
+            .iter()
+            .zip(&unpacked)
+            .map(|((name, _), input)| {
+                if input.archived {
+                    input.path.clone()
+                } else {
+                    decompressed_name(Path::new(name)).to_path_buf()
+                }
+            })
+            .collect();
+        let bundled_files = unpacked
+            .iter_mut()
+            .flat_map(|input| std::mem::take(&mut input.bundled_files))
+            .collect();
+        let warnings = self
+            .ingest(
                 &graph_name,
+                inputs,
+                byte_size,
+                ImportResolver::new(base, false)
+                    .with_bundled_files(bundled_files)
+                    .with_merged_files(merged_files),
                 false,
-                ImportBase::from_user_input(filename),
             )
             .await?;
 
+        Ok((loaded_formats, warnings))
+    }
+
+    /// Loads the quads parsed from `inputs` into a named graph, followed by the ontologies they import.
+    ///
+    /// Quads are inserted in batches while they are parsed, so the inputs are never held in memory.
+    /// The graph and its import graphs are replaced unless `append` is set, in which case
+    /// the imports of the whole graph are loaded again.
+    /// `byte_size` is the size of the inputs the quads are parsed from.
+    /// Imports are resolved and parsed as configured by `resolver`.
+    /// Finally, the graph and its imports are indexed for search.
+    ///
+    /// An existing graph is left untouched until every input has been parsed: the quads are
+    /// loaded into a staging graph first, which replaces or extends the graph on success.
+    /// If an input fails to parse or the user would exceed their quota, the quads loaded
+    /// so far are removed again.
+    ///
+    /// Returns the imports which could not be loaded.
+    ///
+    /// # Errors
+    /// Returns an error if an input fails to parse, the user would exceed their quota
+    /// or the store fails to load the quads.
+    async fn ingest(
+        &self,
+        graph_name: &str,
+        inputs: Vec<QuadIter<'_>>,
+        mut byte_size: u64,
+        resolver: ImportResolver,
+        append: bool,
+    ) -> Result<Option<VOWLGrapherError>, VOWLGrapherStoreError> {
+        let existing_quads =
+            usize::try_from(self.graph_len(graph_name).await?).unwrap_or(usize::MAX);
+        let mut quad_count = 0;
+        if append {
+            quad_count = existing_quads;
+            byte_size += lifecycle::graph_usage(graph_name).map_or(0, |usage| usage.byte_size);
+        }
+        self.check_quota(graph_name, quad_count, byte_size)?;
+
+        let staging_graph = if existing_quads > 0 {
+            Some(NamedNode::new(format!(
+                "{STAGING_GRAPH_PREFIX}{}",
+                STAGING_GRAPH_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ))?)
+        } else {
+            if !append {
+                // Leftovers of an earlier load, such as its imports, are removed before loading anew.
+                self.clear_graph(graph_name).await?;
+            }
+            None
+        };
+        let target_graph = NamedNode::new(
+            staging_graph
+                .as_ref()
+                .map_or(graph_name, |staging_graph| staging_graph.as_str()),
+        )?;
+
         info!("Loading graph '{graph_name}' into database...");
         let start_time = Instant::now();
+        let mut loaded = 0;
+        for quads in inputs {
+            let quads = match &staging_graph {
+                Some(staging_graph) => Self::into_graph(quads, staging_graph.clone()),
+                None => quads,
+            };
+            match self
+                .extend_in_batches(quads, graph_name, quad_count + loaded, byte_size)
+                .await
+            {
+                Ok(inserted) => loaded += inserted,
+                Err(e) => {
+                    self.session
+                        .remove_named_graph(target_graph.as_ref())
+                        .await?;
+                    return Err(e);
+                }
+            }
+        }
 
-        self.session.extend(quads).await?;
+        if let Some(staging_graph) = &staging_graph {
+            if append {
+                for import_graph in self.import_graphs(graph_name).await? {
+                    self.remove_graph(&import_graph).await?;
+                }
+            } else {
+                info!("Replacing existing graph '{graph_name}'");
+                self.clear_graph(graph_name).await?;
+            }
+            self.move_graph(staging_graph, graph_name).await?;
+        }
         info!(
-            "Loaded {} quads in {} s",
-            self.session.len().await?,
+            "Loaded {loaded} quads in {} s",
             Instant::now()
                 .checked_duration_since(start_time)
                 .unwrap_or(Duration::new(0, 0))
                 .as_secs_f32()
         );
+        self.register_graph(graph_name, byte_size).await?;
+        self.persist_graph(graph_name).await?;
 
-        self.upload_handle = None;
-        Ok((loaded_format, warnings))
+        let import_errors = self
+            .load_import_closure(graph_name, quad_count + loaded, byte_size, resolver)
+            .await?;
+        search::index_graph(self, graph_name).await?;
+        Ok(import_errors)
+    }
+
+    /// Redirects parsed quads into the named graph `graph`.
+    fn into_graph(quads: QuadIter<'_>, graph: NamedNode) -> QuadIter<'_> {
+        Box::new(quads.map(move |quad| {
+            quad.map(|quad| Quad::new(quad.subject, quad.predicate, quad.object, graph.clone()))
+        }))
+    }
+
+    /// Moves every quad of the graph `source` into the graph `target` and removes `source`.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails to read, insert or remove the quads.
+    async fn move_graph(
+        &self,
+        source: &NamedNode,
+        target: &str,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let target = NamedNode::new(target)?;
+        self.extend_from_graph(source.as_ref(), |quad| {
+            Quad::new(quad.subject, quad.predicate, quad.object, target.clone())
+        })
+        .await?;
+        self.session.remove_named_graph(source.as_ref()).await?;
+        Ok(())
+    }
+
+    /// Inserts every quad of the graph `source`, mapped by `map`, into the store
+    /// in batches of [`INSERT_BATCH_SIZE`], so the graph is never held in memory at once.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails to read or insert the quads.
+    /// Batches inserted before the error are kept.
+    async fn extend_from_graph(
+        &self,
+        source: NamedNodeRef<'_>,
+        map: impl Fn(Quad) -> Quad,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let mut batches = self
+            .session
+            .quads_for_pattern(None, None, None, Some(source.into()))
+            .await?
+            .chunks(INSERT_BATCH_SIZE);
+        while let Some(batch) = batches.next().await {
+            let batch = batch
+                .into_iter()
+                .map(|quad| quad.map(&map))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.session.extend(batch).await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts parsed quads into the store in batches of [`INSERT_BATCH_SIZE`].
+    ///
+    /// Before each batch, the quota is checked for `quota_graph` holding `loaded_quads`
+    /// quads besides the inserted ones, parsed from `byte_size` bytes of input.
+    ///
+    /// Returns the number of inserted quads.
+    ///
+    /// # Errors
+    /// Returns an error if the input fails to parse, the user would exceed their quota
+    /// or the store fails to insert a batch. Batches inserted before the error are kept.
+    async fn extend_in_batches(
+        &self,
+        mut quads: QuadIter<'_>,
+        quota_graph: &str,
+        loaded_quads: usize,
+        byte_size: u64,
+    ) -> Result<usize, VOWLGrapherStoreError> {
+        let mut inserted = 0;
+        loop {
+            let batch = quads
+                .by_ref()
+                .take(INSERT_BATCH_SIZE)
+                .collect::<Result<Vec<_>, _>>()?;
+            if batch.is_empty() {
+                return Ok(inserted);
+            }
+            inserted += batch.len();
+            self.check_quota(quota_graph, loaded_quads + inserted, byte_size)?;
+            self.session.extend(batch).await?;
+        }
     }
 
-    async fn flatten_import_closure(
+    /// Loads every ontology transitively imported by the graph `graph_iri`.
+    ///
+    /// Each import is parsed into its own graph next to `graph_iri`, and its provenance
+    /// is recorded in the provenance graph of `graph_iri`.
+    /// Imports are loaded breadth-first, fetching all imports at the same depth concurrently.
+    /// Imports which fail to resolve, fetch or parse, which exceed the limits of `resolver`
+    /// or the user's quota, are skipped and reported as warnings.
+    ///
+    /// `quad_count` and `byte_size` are the size of `graph_iri` and its input, which count
+    /// towards the quota together with the imports.
+    ///
+    /// Returns the imports which could not be loaded.
+    ///
+    /// # Errors
+    /// Returns an error if the imports of `graph_iri` cannot be inspected
+    /// or the store fails to load their provenance.
+    async fn load_import_closure(
         &self,
-        root_quads: Vec<Quad>,
         graph_iri: &str,
-        lenient: bool,
-        root_base: ImportBase,
-    ) -> Result<(Vec<Quad>, Option<VOWLGrapherError>), VOWLGrapherStoreError> {
+        mut quad_count: usize,
+        mut byte_size: u64,
+        mut resolver: ImportResolver,
+    ) -> Result<Option<VOWLGrapherError>, VOWLGrapherStoreError> {
         if !VOWLGRAPHER_ENVIRONMENT.resolve_imports {
             debug!("Import resolution disabled via VOWLGRAPHER_RESOLVE_IMPORTS");
-            return Ok((root_quads, None));
+            return Ok(None);
         }
+        let ImportLimits {
+            max_depth,
+            max_imports,
+            timeout,
+        } = resolver.limits;
 
-        let client = Client::new();
-        let mut all_quads = root_quads.clone();
+        let mut provenance = Vec::new();
+        let mut import_graphs = Vec::<(String, u64)>::new();
         let mut visited = HashSet::<String>::new();
-        let mut queue = VecDeque::<(String, ImportBase)>::new();
         let mut warnings = Vec::<ErrorRecord>::new();
+        let mut depth = 0;
 
-        for import in extract_import_iris(&root_quads).await? {
-            queue.push_back((import, root_base.clone()));
-        }
+        // Ontologies loaded into the graph itself, e.g. from several uploaded files, are not imported again.
+        let local_ontologies = declared_ontology_iris(&self.session, graph_iri).await?;
+
+        // The imports found at the current depth, with the base and graph of their importing ontology.
+        let mut level = extract_import_iris(&self.session, graph_iri)
+            .await?
+            .into_iter()
+            .map(|import| (import, resolver.root_base.clone(), graph_iri.to_string()))
+            .collect::<Vec<_>>();
+
+        while !level.is_empty() {
+            depth += 1;
+            if depth > max_depth {
+                let msg = format!(
+                    "Import depth limit of {max_depth} reached. Skipping {} nested imports",
+                    level.len()
+                );
+                warn!("{msg}");
+                warnings.push(
+                    VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::ImportLimitExceeded(
+                        msg,
+                    ))
+                    .into(),
+                );
+                break;
+            }
 
-        while let Some((raw_import, parent_base)) = queue.pop_front() {
-            let resolved = match parent_base.resolve(&raw_import) {
-                Ok(url) => url,
-                Err(err) => {
-                    warn!("Skipping unresolved import '{raw_import}': {err}");
-                    warnings.push(err.into());
+            // Resolve the whole level first, so every import is only fetched once.
+            let mut pending = Vec::new();
+            let mut skipped = 0_usize;
+            for (raw_import, parent_base, parent_graph) in level.drain(..) {
+                if local_ontologies.contains(&raw_import) || resolver.is_merged(&raw_import) {
+                    debug!("Import '{raw_import}' is part of '{graph_iri}'");
                     continue;
                 }
-            };
-            let resolved_key = resolved.to_string();
+                let resolved = match parent_base.resolve(&raw_import, &mut resolver) {
+                    Ok(resolved) => resolved,
+                    Err(err) => {
+                        warn!("Skipping unresolved import '{raw_import}': {err}");
+                        warnings.push(err.into());
+                        continue;
+                    }
+                };
+                let import_graph = import_graph_name(graph_iri, resolved.iri.as_str());
 
-            if !visited.insert(resolved_key.clone()) {
-                continue;
+                if visited.contains(resolved.iri.as_str()) {
+                    // Already loaded on behalf of another ontology, so only record who imports it.
+                    provenance.extend(provenance_quads(
+                        graph_iri,
+                        &import_graph,
+                        None,
+                        &parent_graph,
+                    )?);
+                    continue;
+                }
+                if visited.len() >= max_imports {
+                    skipped += 1;
+                    continue;
+                }
+                visited.insert(resolved.iri.to_string());
+                pending.push((resolved, import_graph, parent_graph));
+            }
+            if skipped > 0 {
+                let msg = format!(
+                    "Import limit of {max_imports} reached. Skipping {skipped} further imports"
+                );
+                warn!("{msg}");
+                warnings.push(
+                    VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::ImportLimitExceeded(
+                        msg,
+                    ))
+                    .into(),
+                );
             }
 
-            let (bytes, hinted_format, next_base) =
-                match fetch_import_source(&client, &resolved).await {
+            let sources = futures::future::join_all(
+                pending
+                    .iter()
+                    .map(|(resolved, _, _)| fetch_import_source(resolved, timeout)),
+            )
+            .await;
+
+            for ((resolved, import_graph, parent_graph), source) in pending.into_iter().zip(sources)
+            {
+                let (bytes, hinted_format, next_base) = match source {
                     Ok(source) => source,
                     Err(err) => {
-                        warn!("Skipping failed import fetch '{resolved}': {err}");
+                        warn!("Skipping failed import fetch '{}': {err}", resolved.iri);
                         warnings.push(err.into());
                         continue;
                     }
                 };
+                let import_bytes = u64::try_from(bytes.len()).unwrap_or(u64::MAX);
 
-            let (quads, _) =
-                match Self::load_bytes_with_fallback(&bytes, hinted_format, lenient, graph_iri) {
-                    Ok(parsed) => parsed,
+                let loaded = match Self::open_bytes(
+                    &bytes,
+                    hinted_format,
+                    resolver.lenient,
+                    &import_graph,
+                ) {
+                    Ok((quads, _)) => {
+                        self.extend_in_batches(
+                            quads,
+                            graph_iri,
+                            quad_count,
+                            byte_size + import_bytes,
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err),
+                };
+                match loaded {
+                    Ok(loaded) => {
+                        quad_count += loaded;
+                        byte_size += import_bytes;
+                    }
                     Err(err) => {
-                        warn!("Skipping unparsable import '{resolved}': {err}");
+                        warn!("Skipping import '{}': {err}", resolved.iri);
                         warnings.push(err.into());
+                        self.session
+                            .remove_named_graph(NamedNodeRef::new(&import_graph)?)
+                            .await?;
                         continue;
                     }
-                };
+                }
 
-            match extract_import_iris(&quads).await {
-                Ok(nested_imports) => {
-                    for nested in nested_imports {
-                        queue.push_back((nested, next_base.clone()));
+                match extract_import_iris(&self.session, &import_graph).await {
+                    Ok(nested_imports) => {
+                        for nested in nested_imports {
+                            level.push((nested, next_base.clone(), import_graph.clone()));
+                        }
+                    }
+                    Err(err) => {
+                        warn!(
+                            "Failed to inspect nested imports for '{}': {err}",
+                            resolved.iri
+                        );
+                        warnings.push(err.into());
                     }
                 }
-                Err(err) => {
-                    warn!("Failed to inspect nested imports for '{resolved}': {err}");
-                    warnings.push(err.into());
-                }
+
+                provenance.extend(provenance_quads(
+                    graph_iri,
+                    &import_graph,
+                    Some(&resolved),
+                    &parent_graph,
+                )?);
+                import_graphs.push((import_graph, import_bytes));
             }
+        }
 
-            all_quads.extend(quads);
+        if !import_graphs.is_empty() {
+            self.session.extend(provenance).await?;
+            import_graphs.push((provenance_graph_name(graph_iri), 0));
+        }
+        for (import_graph, import_bytes) in &import_graphs {
+            self.register_graph(import_graph, *import_bytes).await?;
+            self.persist_graph(import_graph).await?;
         }
 
-        let warnings = if warnings.is_empty() {
+        Ok(if warnings.is_empty() {
             None
         } else {
             Some(warnings.into())
-        };
-
-        Ok((all_quads, warnings))
+        })
     }
 }
 
 impl Default for VOWLGrapherStore {
     fn default() -> Self {
         let session = GLOBAL_STORE.get_or_init(Store::default).clone();
-        Self::new(session)
-    }
-}
-
-async fn extract_import_iris(quads: &[Quad]) -> Result<Vec<String>, VOWLGrapherStoreError> {
-    let tmp = Store::default();
-    tmp.extend(quads.iter().cloned()).await?;
-
-    let results = tmp
-        .query(
-            r"
-        PREFIX owl: <http://www.w3.org/2002/07/owl#>
-        SELECT DISTINCT ?import
-        WHERE {
-            GRAPH ?g {
-                ?ontology owl:imports ?import .
-                FILTER(isIRI(?import))
-            }
-        }
-        ",
-        )
-        .await?;
-
-    let mut imports = Vec::new();
-    if let QueryResults::Solutions(mut solutions) = results {
-        while let Some(solution) = solutions.next().await {
-            let solution = solution?;
-            if let Some(term) = solution.get("import") {
-                imports.push(
-                    term.to_string()
-                        .trim_start_matches('<')
-                        .trim_end_matches('>')
-                        .to_string(),
-                );
-            }
-        }
-    }
-
-    Ok(imports)
-}
-
-async fn fetch_import_source(
-    client: &Client,
-    url: &Url,
-) -> Result<(Vec<u8>, DataType, ImportBase), VOWLGrapherStoreError> {
-    let format = DataType::from(Path::new(url.path()));
-
-    match url.scheme() {
-        "file" => {
-            let path = url.to_file_path().map_err(|()| {
-                VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
-                    "Could not convert file URL to path: {url}"
-                ))
-            })?;
-            let bytes = std::fs::read(&path)?;
-            Ok((bytes, format, ImportBase::from_path(&path)))
-        }
-        "http" | "https" => {
-            let response = client.get(url.clone()).send().await.map_err(|e| {
-                VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
-                    "Failed to fetch import {url}: {e}"
-                ))
-            })?;
-
-            let bytes = response.bytes().await.map_err(|e| {
-                VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
-                    "Failed to read import body {url}: {e}"
-                ))
-            })?;
-
-            Ok((bytes.to_vec(), format, ImportBase::Url(url.clone())))
-        }
-        scheme => Err(VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
-            "Unsupported import scheme '{scheme}' for {url}"
-        ))
-        .into()),
-    }
-}
-
-#[derive(Clone, Debug)]
-enum ImportBase {
-    Url(Url),
-    Unknown,
-}
-
-impl ImportBase {
-    fn from_path(path: &Path) -> Self {
-        Url::from_file_path(path).map_or(Self::Unknown, Self::Url)
-    }
-
-    fn from_user_input(input: &str) -> Self {
-        Url::parse(input).map_or(Self::Unknown, Self::Url)
-    }
-
-    fn resolve(&self, import_iri: &str) -> Result<Url, VOWLGrapherStoreError> {
-        match self {
-            Self::Url(base) => base
-                .join(import_iri)
-                .or_else(|_| Url::parse(import_iri))
-                .map_err(|e| {
-                    VOWLGrapherStoreErrorKind::ImportResolutionError(e.to_string()).into()
-                }),
-            Self::Unknown => Url::parse(import_iri).map_err(|e| {
-                VOWLGrapherStoreErrorKind::ImportResolutionError(e.to_string()).into()
-            }),
+        Self {
+            persistent: true,
+            ..Self::new(session)
         }
     }
 }
@@ -626,6 +1588,231 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_construct_query() -> Result<(), VOWLGrapherError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .session
+            .extend(vec![Quad::new(
+                NamedNode::new_unchecked("http://example.com/Person"),
+                NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                NamedNode::new_unchecked("http://www.w3.org/2002/07/owl#Class"),
+                NamedNode::new_unchecked(store.get_graph_name("construct")),
+            )])
+            .await
+            .map_err(VOWLGrapherStoreError::from)?;
+
+        let (data, _) = store
+            .query(
+                "CONSTRUCT { ?s ?p ?o } WHERE { GRAPH <{GRAPH_IRI}> { ?s ?p ?o } }".to_string(),
+                Some("construct".to_string()),
+            )
+            .await?;
+        assert!(
+            !data.elements.is_empty(),
+            "Expected the class to be visualized"
+        );
+        assert_eq!(
+            store
+                .session
+                .len()
+                .await
+                .map_err(VOWLGrapherStoreError::from)?,
+            1,
+            "Expected the temporary graph to be removed"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_stays_in_user_graphs() -> Result<(), VOWLGrapherError> {
+        let session = Store::default();
+        let owner = VOWLGrapherStore {
+            user_id: Some("query-owner".to_string()),
+            ..VOWLGrapherStore::new(session.clone())
+        };
+        let other = VOWLGrapherStore {
+            user_id: Some("query-other".to_string()),
+            ..VOWLGrapherStore::new(session)
+        };
+        owner
+            .insert_bytes(
+                b"<http://example.com/Secret> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/2002/07/owl#Class> .",
+                "private",
+                DataType::NTriples,
+                false,
+            )
+            .await?;
+
+        let (data, _) = owner
+            .query(
+                "CONSTRUCT { ?s ?p ?o } WHERE { GRAPH ?g { ?s ?p ?o } }".to_string(),
+                Some("private".to_string()),
+            )
+            .await?;
+        assert!(
+            !data.elements.is_empty(),
+            "the owner should see their graph"
+        );
+
+        for query in [
+            "CONSTRUCT { ?s ?p ?o } WHERE { GRAPH ?g { ?s ?p ?o } }".to_string(),
+            format!(
+                "CONSTRUCT {{ ?s ?p ?o }} WHERE {{ GRAPH <{}> {{ ?s ?p ?o }} }}",
+                owner.get_graph_name("private")
+            ),
+        ] {
+            let (data, _) = other.query(query.clone(), Some("own".to_string())).await?;
+            assert!(
+                data.elements.is_empty(),
+                "another user's graph was visible to {query}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_construct_query_highlights() -> Result<(), VOWLGrapherError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(
+                b"<http://example.com/Person> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/2002/07/owl#Class> .\n\
+                  <http://example.com/Place> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/2002/07/owl#Class> .",
+                "highlight",
+                DataType::NTriples,
+                false,
+            )
+            .await?;
+
+        for query in [
+            DEFAULT_QUERY.as_str(),
+            "CONSTRUCT { ?s ?p ?o } WHERE { GRAPH <{GRAPH_IRI}> { ?s ?p ?o } }",
+        ] {
+            let (_, annotations, _) = store
+                .query_with_imports(
+                    query.to_string(),
+                    Some("highlight".to_string()),
+                    &[],
+                    false,
+                    Some("http://example.com/Person".to_string()),
+                )
+                .await?;
+            assert_eq!(
+                annotations
+                    .elements
+                    .values()
+                    .filter(|annotations| annotations.contains(&Annotation::Highlighted))
+                    .count(),
+                1,
+                "the searched element was not highlighted by {query}"
+            );
+        }
+        Ok(())
+    }
+
+    const VALID: &[u8] = b"<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+    const APPENDED: &[u8] =
+        b"<http://example.com/b> <http://example.com/p> <http://example.com/c> .";
+    const INVALID: &[u8] = b"<http://example.com/a> <http://example.com/p> .";
+
+    #[test]
+    fn test_quads_from_reader() -> Result<(), VOWLGrapherStoreError> {
+        let graph_name = "urn:vowlgrapher:graph:reader";
+        let quads = quads_from_reader(VALID, DataType::NTriples, false, graph_name)?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(quads.len(), 1);
+        assert_eq!(
+            quads[0].graph_name,
+            rdf_fusion::model::GraphName::from(NamedNode::new_unchecked(graph_name))
+        );
+
+        assert!(
+            quads_from_reader(INVALID, DataType::NTriples, false, graph_name)?
+                .any(|quad| quad.is_err()),
+            "invalid input should yield an error"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extend_in_batches() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let graph_name = store.get_graph_name("batches");
+
+        let quads = quads_from_reader(VALID, DataType::NTriples, false, &graph_name)?;
+        assert_eq!(store.extend_in_batches(quads, &graph_name, 0, 0).await?, 1);
+        assert_eq!(store.graph_len(&graph_name).await?, 1);
+
+        let quads = quads_from_reader(
+            [APPENDED, b"\n".as_slice(), INVALID].concat().as_slice(),
+            DataType::NTriples,
+            false,
+            &graph_name,
+        )?
+        .collect::<Vec<_>>();
+        assert!(
+            store
+                .extend_in_batches(Box::new(quads.into_iter()), &graph_name, 1, 0)
+                .await
+                .is_err(),
+            "a batch with an invalid quad should fail"
+        );
+        assert_eq!(
+            store.graph_len(&graph_name).await?,
+            1,
+            "a failed batch should not be inserted"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_keeps_graph() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let graph_name = store.get_graph_name("kept");
+        store
+            .insert_bytes(VALID, "kept", DataType::NTriples, false)
+            .await?;
+
+        for append in [false, true] {
+            assert!(
+                store
+                    .insert_bytes(INVALID, "kept", DataType::NTriples, append)
+                    .await
+                    .is_err(),
+                "invalid input should fail to load"
+            );
+            assert_eq!(
+                store.graph_len(&graph_name).await?,
+                1,
+                "a failed load should leave the graph untouched"
+            );
+            assert_eq!(
+                store.session.len().await?,
+                1,
+                "a failed load should leave no quads behind"
+            );
+        }
+
+        store
+            .insert_bytes(APPENDED, "kept", DataType::NTriples, true)
+            .await?;
+        assert_eq!(store.graph_len(&graph_name).await?, 2);
+        store
+            .insert_bytes(APPENDED, "kept", DataType::NTriples, false)
+            .await?;
+        assert_eq!(store.graph_len(&graph_name).await?, 1);
+
+        assert!(
+            store
+                .insert_bytes(INVALID, "new", DataType::NTriples, false)
+                .await
+                .is_err(),
+            "invalid input should fail to load"
+        );
+        assert!(!store.contains_graph("new").await?);
+        Ok(())
+    }
+
     #[test_resources("crates/database/data/owl-functional/*.ofn")]
     async fn test_ofn_parser_stream(resource: &str) -> Result<(), VOWLGrapherStoreError> {
         let mut out = vec![];
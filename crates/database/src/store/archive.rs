@@ -0,0 +1,316 @@
+//! Unpacking of compressed and archived input.
+//!
+//! Documents compressed with gzip, bzip2, xz or zstd are decompressed into a temporary file.
+//! Zip archives, which bundle an ontology with its imports, are extracted into a temporary
+//! directory, and the ontology the others are bundled for is loaded.
+//!
+//! Compressions are recognised by their magic bytes, so the extension of the input does not matter.
+//! Unpacking stops once the unpacked files exceed `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+
+use log::{info, warn};
+use std::cmp::Reverse;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{DataType, VOWLGRAPHER_ENVIRONMENT, decompressed_name};
+
+/// How many leading bytes identify a compression.
+const MAGIC_LENGTH: u64 = 6;
+
+/// A compression of single documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the compression of an input starting with `head`, if any.
+    fn sniff(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if head.starts_with(b"BZh") {
+            Some(Self::Bzip2)
+        } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::Xz)
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reader decompressing `file`.
+    ///
+    /// # Errors
+    /// Returns an error if the decoder cannot be initialised.
+    fn decoder(self, file: File) -> Result<Box<dyn Read>, VOWLGrapherStoreError> {
+        let file = BufReader::new(file);
+        Ok(match self {
+            Self::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(file)),
+            Self::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(file)),
+            Self::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(file)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        })
+    }
+}
+
+/// Returns whether an input starting with `head` is a zip archive.
+fn is_zip(head: &[u8]) -> bool {
+    head.starts_with(b"PK\x03\x04")
+}
+
+/// An input ready to be parsed.
+#[derive(Debug)]
+pub struct UnpackedInput {
+    /// The file holding the ontology to load.
+    pub path: PathBuf,
+    /// The format the name of the input, or of the ontology picked from an archive, points to.
+    pub hint: DataType,
+    /// Whether the input was an archive, whose other files the imports are resolved against.
+    pub archived: bool,
+    /// The other ontologies of an archive.
+    pub bundled_files: Vec<PathBuf>,
+    /// The directory the input was unpacked into, removed once the input is dropped.
+    _unpacked: Option<TempDir>,
+}
+
+/// Unpacks the input at `path` if it is compressed or archived.
+///
+/// `name` is the name the input was uploaded under, such as its file name or URL.
+/// `hinted_format` is kept as the hint for compressed documents, unless it is [`DataType::UNKNOWN`].
+///
+/// # Errors
+/// Returns an error if the input cannot be read, is a corrupt archive,
+/// or unpacks to more than `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+pub fn unpack(
+    path: &Path,
+    name: &str,
+    hinted_format: DataType,
+) -> Result<UnpackedInput, VOWLGrapherStoreError> {
+    let mut head = Vec::new();
+    File::open(path)?
+        .take(MAGIC_LENGTH)
+        .read_to_end(&mut head)?;
+    let hint = if hinted_format == DataType::UNKNOWN {
+        DataType::from(Path::new(name))
+    } else {
+        hinted_format
+    };
+
+    if is_zip(&head) {
+        return extract_zip(path, name);
+    }
+    let Some(compression) = Compression::sniff(&head) else {
+        return Ok(UnpackedInput {
+            path: path.to_path_buf(),
+            hint,
+            archived: false,
+            bundled_files: Vec::new(),
+            _unpacked: None,
+        });
+    };
+
+    let directory = tempfile::tempdir()?;
+    let file_name = decompressed_name(Path::new(name))
+        .file_name()
+        .map_or_else(|| PathBuf::from("input"), PathBuf::from);
+    let target = directory.path().join(file_name);
+    let mut budget = VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes.0;
+    let size = copy_limited(
+        &mut compression.decoder(File::open(path)?)?,
+        &target,
+        &mut budget,
+        name,
+    )?;
+    info!("Decompressed {compression:?} input '{name}' to {size} bytes");
+
+    Ok(UnpackedInput {
+        path: target,
+        hint,
+        archived: false,
+        bundled_files: Vec::new(),
+        _unpacked: Some(directory),
+    })
+}
+
+/// Extracts the zip archive at `path` and picks its root ontology with [`root_ontology`].
+///
+/// # Errors
+/// Returns an error if the archive is corrupt, contains no ontology,
+/// or unpacks to more than `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+fn extract_zip(path: &Path, name: &str) -> Result<UnpackedInput, VOWLGrapherStoreError> {
+    let invalid_archive = |e: zip::result::ZipError| {
+        VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::InvalidArchive(format!(
+            "Failed to read zip archive '{name}': {e}"
+        )))
+    };
+    let directory = tempfile::tempdir()?;
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(invalid_archive)?;
+    let mut budget = VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes.0;
+
+    let mut ontologies = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(invalid_archive)?;
+        let Some(relative) = entry.enclosed_name() else {
+            warn!(
+                "Skipping entry '{}' of zip archive '{name}', which points outside the archive",
+                entry.name()
+            );
+            continue;
+        };
+        let target = directory.path().join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        copy_limited(&mut entry, &target, &mut budget, name)?;
+
+        if is_ontology(&target) {
+            ontologies.push(target);
+        }
+    }
+
+    let root = root_ontology(&ontologies)?.ok_or_else(|| {
+        VOWLGrapherStoreErrorKind::InvalidArchive(format!(
+            "Zip archive '{name}' contains no ontology"
+        ))
+    })?;
+    info!(
+        "Loading '{}' from zip archive '{name}' with {} bundled ontologies",
+        root.display(),
+        ontologies.len() - 1
+    );
+    let hint = DataType::from(root.as_path());
+    ontologies.retain(|ontology| *ontology != root);
+
+    Ok(UnpackedInput {
+        path: root,
+        hint,
+        archived: true,
+        bundled_files: ontologies,
+        _unpacked: Some(directory),
+    })
+}
+
+/// Returns whether the extension of `path` names an ontology format.
+fn is_ontology(path: &Path) -> bool {
+    !matches!(
+        DataType::from(path),
+        DataType::UNKNOWN
+            | DataType::SPARQLJSON
+            | DataType::SPARQLXML
+            | DataType::SPARQLCSV
+            | DataType::SPARQLTSV
+    )
+}
+
+/// Picks the ontology of an archive which the other ontologies are bundled for.
+///
+/// Ontologies import each other by IRIs ending in their file name, so the root is an
+/// ontology whose name no other ontology mentions. Among several, the one nearest
+/// the top of the archive wins, then the largest.
+///
+/// # Errors
+/// Returns an error if an ontology cannot be read.
+fn root_ontology(ontologies: &[PathBuf]) -> Result<Option<PathBuf>, VOWLGrapherStoreError> {
+    let mut mentioned = vec![false; ontologies.len()];
+    for ontology in ontologies {
+        let content = std::fs::read(ontology)?;
+        for (index, other) in ontologies.iter().enumerate() {
+            if other != ontology && mentions(&content, other) {
+                mentioned[index] = true;
+            }
+        }
+    }
+
+    let rank = |ontology: &&PathBuf| {
+        let size = std::fs::metadata(ontology).map_or(0, |metadata| metadata.len());
+        (ontology.components().count(), Reverse(size))
+    };
+    let unmentioned = ontologies
+        .iter()
+        .zip(&mentioned)
+        .filter(|(_, mentioned)| !**mentioned)
+        .map(|(ontology, _)| ontology)
+        .min_by_key(rank);
+    Ok(unmentioned
+        .or_else(|| ontologies.iter().min_by_key(rank))
+        .cloned())
+}
+
+/// Returns whether `content` contains an IRI ending in the file name or stem of `ontology`.
+fn mentions(content: &[u8], ontology: &Path) -> bool {
+    [ontology.file_name(), ontology.file_stem()]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.to_str())
+        .any(|name| {
+            let needle = format!("/{name}");
+            memchr::memmem::find_iter(content, needle.as_bytes()).any(|start| {
+                content
+                    .get(start + needle.len())
+                    .is_none_or(|next| !(next.is_ascii_alphanumeric() || b"_-".contains(next)))
+            })
+        })
+}
+
+/// Copies `reader` into a new file at `target`, allowing at most `budget` bytes.
+///
+/// Returns the number of copied bytes, which are subtracted from `budget`.
+///
+/// # Errors
+/// Returns an error if the data cannot be copied or exceeds the budget.
+fn copy_limited(
+    reader: &mut impl Read,
+    target: &Path,
+    budget: &mut u64,
+    name: &str,
+) -> Result<u64, VOWLGrapherStoreError> {
+    let mut file = File::create(target)?;
+    let size = std::io::copy(&mut reader.take(budget.saturating_add(1)), &mut file)?;
+    if size > *budget {
+        return Err(VOWLGrapherStoreErrorKind::InvalidArchive(format!(
+            "'{name}' decompresses to more than the maximum allowed size of {}",
+            VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes.display().si()
+        ))
+        .into());
+    }
+    *budget -= size;
+    Ok(size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_root_ontology() -> Result<(), VOWLGrapherStoreError> {
+        let directory = tempfile::tempdir()?;
+        std::fs::create_dir(directory.path().join("imports"))?;
+        let root = directory.path().join("a.owl");
+        let import = directory.path().join("imports").join("b.ttl");
+        let nested = directory.path().join("imports").join("c.owl");
+        std::fs::write(
+            &root,
+            r#"<owl:imports rdf:resource="http://example.com/b"/>"#,
+        )?;
+        std::fs::write(
+            &import,
+            "<http://example.com/b> owl:imports <http://example.com/c.owl> .",
+        )?;
+        std::fs::write(&nested, "<owl:Ontology/>")?;
+
+        let ontologies = vec![import, nested, root.clone()];
+        assert_eq!(root_ontology(&ontologies)?, Some(root));
+        assert!(!mentions(b"http://example.com/bc", Path::new("b.ttl")));
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! Classification of the ontology in a named graph and its imports with the OWL 2 EL classifier.
+//!
+//! The inferred taxonomy can be serialized in place of the graph. It holds the named classes,
+//! their direct subclass relations and equivalences, and the labels of the classes.
+
+use grapher::prelude::GraphDisplayData;
+use log::info;
+use rdf_fusion::model::{GraphName, NamedNode, NamedOrBlankNode, Quad, Triple};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use vowlgrapher_parser::classifier::{Taxonomy, classify};
+use vowlgrapher_parser::errors::VOWLGrapherStoreError;
+use vowlgrapher_parser::parser_util::ontology_from_quads;
+use vowlgrapher_util::prelude::{
+    Annotation, ClassificationReport, ErrorRecord, ErrorSeverity, ErrorType, GraphAnnotations,
+    VOWLGrapherError, get_timestamp,
+};
+
+use crate::store::{RDFS_LABEL, VOWLGrapherStore};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_SUB_CLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+const OWL_EQUIVALENT_CLASS: &str = "http://www.w3.org/2002/07/owl#equivalentClass";
+
+impl VOWLGrapherStore {
+    /// Classifies the ontology in a graph of the user and its imports.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or could not be mapped to an ontology.
+    pub async fn classify(
+        &self,
+        graph_name: &str,
+    ) -> Result<ClassificationReport, VOWLGrapherStoreError> {
+        let (taxonomy, _) = self.taxonomy(graph_name).await?;
+        Ok(report(graph_name, &taxonomy))
+    }
+
+    /// Classifies the ontology in a graph of the user and its imports and serializes the
+    /// inferred taxonomy, with its unsatisfiable classes annotated.
+    ///
+    /// This method tries to continue serializing despite errors.
+    /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or could not be classified or serialized.
+    pub async fn taxonomy_display_data(
+        &self,
+        graph_name: &str,
+    ) -> Result<
+        (
+            GraphDisplayData,
+            GraphAnnotations,
+            ClassificationReport,
+            Option<VOWLGrapherError>,
+        ),
+        VOWLGrapherError,
+    > {
+        let (taxonomy, labels) = self.taxonomy(graph_name).await?;
+
+        let mut triples = Vec::new();
+        let statement = |subject: &str, predicate: &str, object: &str| {
+            Ok::<_, VOWLGrapherStoreError>(Triple::new(
+                NamedNode::new(subject)?,
+                NamedNode::new_unchecked(predicate),
+                NamedNode::new(object)?,
+            ))
+        };
+        for node in &taxonomy.nodes {
+            let Some(first) = node.classes.first() else {
+                continue;
+            };
+            for class in &node.classes {
+                triples.push(statement(class, RDF_TYPE, OWL_CLASS)?);
+                if class != first {
+                    triples.push(statement(first, OWL_EQUIVALENT_CLASS, class)?);
+                }
+            }
+            for parent in &node.parents {
+                if let Some(parent) = taxonomy.nodes[*parent].classes.first() {
+                    triples.push(statement(first, RDFS_SUB_CLASS_OF, parent)?);
+                }
+            }
+        }
+        for class in &taxonomy.unsatisfiable {
+            triples.push(statement(class, RDF_TYPE, OWL_CLASS)?);
+        }
+
+        let classes = taxonomy
+            .nodes
+            .iter()
+            .flat_map(|node| &node.classes)
+            .chain(&taxonomy.unsatisfiable)
+            .map(String::as_str)
+            .collect::<HashSet<_>>();
+        triples.extend(labels.into_iter().filter(|triple| {
+            matches!(&triple.subject, NamedOrBlankNode::NamedNode(subject) if classes.contains(subject.as_str()))
+        }));
+        let unsatisfiable = taxonomy
+            .unsatisfiable
+            .iter()
+            .map(|class| (class.clone(), BTreeSet::from([Annotation::Unsatisfiable])))
+            .collect::<HashMap<_, _>>();
+
+        let (display_data, annotations, errors) =
+            self.display_triples(triples, unsatisfiable).await?;
+        Ok((
+            display_data,
+            annotations,
+            report(graph_name, &taxonomy),
+            errors,
+        ))
+    }
+
+    /// Classifies the ontology in a graph of the user and its imports.
+    ///
+    /// Returns the taxonomy, and the labels of the graph and its imports.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or could not be mapped to an ontology.
+    async fn taxonomy(
+        &self,
+        graph_name: &str,
+    ) -> Result<(Taxonomy, Vec<Triple>), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let mut triples = self.graph_triples(graph_name).await?;
+        for import_graph in self
+            .ontology_imports(&self.get_graph_name(graph_name))
+            .await?
+        {
+            triples.extend(self.named_graph_triples(&import_graph).await?);
+        }
+        let labels = triples
+            .iter()
+            .filter(|triple| triple.predicate.as_str() == RDFS_LABEL)
+            .cloned()
+            .collect();
+
+        let taxonomy = tokio::task::spawn_blocking(move || {
+            let (ontology, _) = ontology_from_quads(triples.into_iter().map(|triple| {
+                Quad::new(
+                    triple.subject,
+                    triple.predicate,
+                    triple.object,
+                    GraphName::DefaultGraph,
+                )
+            }))?;
+            Ok::<_, VOWLGrapherStoreError>(classify(&ontology))
+        })
+        .await??;
+
+        info!(
+            "Classified graph '{graph_name}': {} classes, {} unsatisfiable, {} axioms outside OWL 2 EL",
+            taxonomy.nodes.len(),
+            taxonomy.unsatisfiable.len(),
+            taxonomy.ignored_axioms
+        );
+        Ok((taxonomy, labels))
+    }
+}
+
+/// Summarizes a taxonomy, with an error for every unsatisfiable class.
+fn report(graph_name: &str, taxonomy: &Taxonomy) -> ClassificationReport {
+    ClassificationReport {
+        graph_name: graph_name.to_string(),
+        class_count: taxonomy.nodes.len() as u64,
+        subsumption_count: taxonomy
+            .nodes
+            .iter()
+            .map(|node| node.parents.len() as u64)
+            .sum(),
+        ignored_axiom_count: taxonomy.ignored_axioms as u64,
+        unsatisfiable: taxonomy
+            .unsatisfiable
+            .iter()
+            .map(|class| {
+                ErrorRecord::new(
+                    get_timestamp(),
+                    ErrorSeverity::Error,
+                    ErrorType::Inference,
+                    format!(
+                        "The class <{class}> is unsatisfiable, so it is equivalent to owl:Nothing"
+                    ),
+                    #[cfg(debug_assertions)]
+                    None,
+                )
+            })
+            .collect(),
+        unsatisfiable_classes: taxonomy.unsatisfiable.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    #[tokio::test]
+    async fn test_classify() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let ontology = br"@prefix : <http://example.com/> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            :part a owl:ObjectProperty .
+            :Cell a owl:Class .
+            :Organ a owl:Class .
+            :Tissue a owl:Class ;
+                rdfs:subClassOf [ a owl:Restriction ; owl:onProperty :part ; owl:someValuesFrom :Cell ] .
+            :Composite a owl:Class ;
+                owl:equivalentClass [ a owl:Restriction ; owl:onProperty :part ; owl:someValuesFrom :Cell ] .
+            :Broken a owl:Class ;
+                rdfs:subClassOf :Cell , :Organ .
+            :Cell owl:disjointWith :Organ .";
+        store
+            .insert_bytes(ontology, "ontology", DataType::TTL, false)
+            .await?;
+
+        let report = store.classify("ontology").await?;
+        assert_eq!(report.class_count, 4);
+        assert_eq!(report.subsumption_count, 1);
+        assert_eq!(report.unsatisfiable_classes, ["http://example.com/Broken"]);
+        assert!(matches!(
+            report.unsatisfiable[0].severity,
+            ErrorSeverity::Error
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+//! Restricting user-supplied SPARQL queries to a set of named graphs.
+
+use rdf_fusion::model::NamedNode;
+use spargebra::{Query, QueryDataset, SparqlParser};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+
+/// Stands in for an empty list of graphs in a scoped query.
+///
+/// A query without `FROM NAMED` clauses may name any graph of the store,
+/// so an empty list is replaced by this graph, which never holds any data.
+const EMPTY_GRAPH: &str = "urn:vowlgrapher:empty";
+
+/// Parses a SPARQL query and replaces its dataset with `graph_names`.
+///
+/// The graphs become both the default graph (merged) and the named graphs of the query,
+/// so triple patterns outside of a `GRAPH` clause match them and `GRAPH ?g` cannot
+/// reach any other graph. `FROM` and `FROM NAMED` clauses of the query are discarded.
+///
+/// # Errors
+/// Returns an error if the query or one of the graph names is invalid, or no graph is given.
+pub fn scope_query(query: &str, graph_names: &[String]) -> Result<String, VOWLGrapherStoreError> {
+    scope_query_to(query, graph_names, graph_names)
+}
+
+/// Parses a SPARQL query and replaces its dataset with the merged `default_graphs`
+/// and the `named_graphs`, like the `default-graph-uri` and `named-graph-uri`
+/// parameters of the SPARQL 1.1 Protocol.
+///
+/// Graph patterns outside of a `GRAPH` clause only match `default_graphs`, while
+/// `GRAPH ?g` only matches `named_graphs`. Either list may be empty, but not both.
+///
+/// # Errors
+/// Returns an error if the query or one of the graph names is invalid, or no graph is given.
+pub fn scope_query_to(
+    query: &str,
+    default_graphs: &[String],
+    named_graphs: &[String],
+) -> Result<String, VOWLGrapherStoreError> {
+    if default_graphs.is_empty() && named_graphs.is_empty() {
+        // Without any FROM clause, the query would run against the whole store.
+        return Err(VOWLGrapherStoreErrorKind::GraphNotFound(
+            "The query is not restricted to any graph".to_string(),
+        )
+        .into());
+    }
+    let mut query = SparqlParser::new()
+        .parse_query(query)
+        .map_err(|e| VOWLGrapherStoreErrorKind::InvalidQuery(e.to_string()))?;
+
+    let to_nodes = |graph_names: &[String]| {
+        if graph_names.is_empty() {
+            return Ok(vec![NamedNode::new_unchecked(EMPTY_GRAPH)]);
+        }
+        graph_names
+            .iter()
+            .map(|graph_name| NamedNode::new(graph_name.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let scoped_dataset = QueryDataset {
+        default: to_nodes(default_graphs)?,
+        named: Some(to_nodes(named_graphs)?),
+    };
+
+    match &mut query {
+        Query::Select { dataset, .. }
+        | Query::Construct { dataset, .. }
+        | Query::Describe { dataset, .. }
+        | Query::Ask { dataset, .. } => *dataset = Some(scoped_dataset),
+    }
+    Ok(query.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scope_query() -> Result<(), VOWLGrapherStoreError> {
+        let scoped = scope_query(
+            "SELECT * FROM <urn:other> WHERE { ?s ?p ?o }",
+            &["urn:vowlgrapher:graph:a".to_string()],
+        )?;
+        assert!(
+            scoped.contains("FROM <urn:vowlgrapher:graph:a>"),
+            "missing default graph: {scoped}"
+        );
+        assert!(
+            scoped.contains("FROM NAMED <urn:vowlgrapher:graph:a>"),
+            "missing named graph: {scoped}"
+        );
+        assert!(
+            !scoped.contains("urn:other"),
+            "kept foreign graph: {scoped}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scope_query_without_graphs() {
+        assert!(
+            scope_query("SELECT * WHERE { GRAPH ?g { ?s ?p ?o } }", &[]).is_err(),
+            "an unscoped query would see every graph of the store"
+        );
+    }
+
+    #[test]
+    fn test_scope_query_to_default_graphs() -> Result<(), VOWLGrapherStoreError> {
+        let scoped = scope_query_to(
+            "SELECT * WHERE { GRAPH ?g { ?s ?p ?o } }",
+            &["urn:vowlgrapher:graph:a".to_string()],
+            &[],
+        )?;
+        assert!(
+            scoped.contains("FROM <urn:vowlgrapher:graph:a>"),
+            "missing default graph: {scoped}"
+        );
+        assert!(
+            !scoped.contains("FROM NAMED <urn:vowlgrapher:graph:a>"),
+            "default graph became a named graph: {scoped}"
+        );
+        assert!(
+            scoped.contains(&format!("FROM NAMED <{EMPTY_GRAPH}>")),
+            "named graphs are not restricted: {scoped}"
+        );
+        Ok(())
+    }
+}
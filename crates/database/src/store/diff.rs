@@ -0,0 +1,708 @@
+//! Comparison of two graphs holding versions of an ontology.
+//!
+//! Blank nodes are labelled anew whenever a document is parsed, so they are compared by
+//! content: every blank node is replaced by a hash of the statements it is the subject of.
+//! Restrictions and other anonymous class expressions thus match across versions while they
+//! are unchanged, and a changed one changes the statement of the named element it belongs to.
+
+use grapher::prelude::GraphDisplayData;
+use log::info;
+use rdf_fusion::io::{RdfFormat, RdfSerializer};
+use rdf_fusion::model::{BlankNode, Literal, NamedNode, NamedOrBlankNode, Term, Triple};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{
+    Annotation, ChangeKind, ElementChange, ElementKind, GraphAnnotations, OntologyDiff,
+    ReportFormat, VOWLGrapherError,
+};
+
+use crate::store::VOWLGrapherStore;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_STATEMENT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement";
+const RDF_SUBJECT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#subject";
+const RDF_PREDICATE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate";
+const RDF_OBJECT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#object";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const OWL_RESTRICTION: &str = "http://www.w3.org/2002/07/owl#Restriction";
+const OWL_ON_PROPERTY: &str = "http://www.w3.org/2002/07/owl#onProperty";
+
+/// The namespace of the Changeset vocabulary the Turtle report is written in.
+const CHANGESET: &str = "http://purl.org/vocab/changeset/schema#";
+
+/// A statement whose blank nodes are replaced by their canonical labels.
+type CanonicalTriple = (String, String, String);
+
+/// One version of an ontology, indexed for comparison.
+struct Version {
+    /// The statements of the version.
+    triples: Vec<Triple>,
+    /// The index into `triples` of every canonical statement.
+    canonical: HashMap<CanonicalTriple, usize>,
+    /// The IRIs which are the subject of a statement.
+    subjects: HashSet<String>,
+    /// The types of every resource.
+    types: HashMap<String, Vec<String>>,
+    /// The first label of every resource.
+    labels: HashMap<String, String>,
+    /// The property of every blank node with an `owl:onProperty` statement.
+    on_properties: HashMap<String, String>,
+}
+
+impl Version {
+    /// Indexes the statements of a version.
+    fn new(triples: Vec<Triple>) -> Self {
+        let mut statements = HashMap::<String, Vec<usize>>::new();
+        for (index, triple) in triples.iter().enumerate() {
+            if let NamedOrBlankNode::BlankNode(node) = &triple.subject {
+                statements
+                    .entry(node.as_str().to_string())
+                    .or_default()
+                    .push(index);
+            }
+        }
+        let mut canonicalizer = Canonicalizer {
+            triples: &triples,
+            statements,
+            labels: HashMap::new(),
+        };
+
+        let mut canonical = HashMap::with_capacity(triples.len());
+        let mut subjects = HashSet::new();
+        let mut types = HashMap::<String, Vec<String>>::new();
+        let mut labels = HashMap::new();
+        let mut on_properties = HashMap::new();
+        for (index, triple) in triples.iter().enumerate() {
+            let subject = canonicalizer.subject(&triple.subject);
+            let object = canonicalizer.term(&triple.object);
+            canonical.insert((subject, triple.predicate.to_string(), object), index);
+
+            let subject = match &triple.subject {
+                NamedOrBlankNode::NamedNode(subject) => subject.as_str(),
+                NamedOrBlankNode::BlankNode(node) => node.as_str(),
+            };
+            subjects.insert(subject.to_string());
+            match (triple.predicate.as_str(), &triple.object) {
+                (RDF_TYPE, Term::NamedNode(class)) => types
+                    .entry(subject.to_string())
+                    .or_default()
+                    .push(class.as_str().to_string()),
+                (RDFS_LABEL, Term::Literal(label)) => {
+                    labels
+                        .entry(subject.to_string())
+                        .or_insert_with(|| label.value().to_string());
+                }
+                (OWL_ON_PROPERTY, Term::NamedNode(property)) => {
+                    on_properties.insert(subject.to_string(), property.as_str().to_string());
+                }
+                _ => (),
+            }
+        }
+
+        Self {
+            triples,
+            canonical,
+            subjects,
+            types,
+            labels,
+            on_properties,
+        }
+    }
+
+    /// Returns the statements of this version missing from `other`.
+    fn missing_from(&self, other: &Self) -> Vec<&Triple> {
+        let mut indices = self
+            .canonical
+            .iter()
+            .filter(|(statement, _)| !other.canonical.contains_key(*statement))
+            .map(|(_, index)| *index)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.iter().map(|index| &self.triples[*index]).collect()
+    }
+
+    /// Returns the IRI of the property restricted by the blank node `node`, if it is a restriction.
+    fn restricted_property(&self, node: &str) -> Option<&str> {
+        self.types
+            .get(node)?
+            .iter()
+            .any(|class| class == OWL_RESTRICTION)
+            .then(|| self.on_properties.get(node).map(String::as_str))
+            .flatten()
+    }
+
+    /// Returns the kind of the element `iri`.
+    fn kind(&self, iri: &str) -> Option<ElementKind> {
+        self.types
+            .get(iri)?
+            .iter()
+            .map(|class| element_kind(class))
+            .min()
+    }
+}
+
+/// Replaces blank nodes by labels derived from their statements.
+struct Canonicalizer<'t> {
+    /// The statements being canonicalized.
+    triples: &'t [Triple],
+    /// The indices of the statements of every blank node.
+    statements: HashMap<String, Vec<usize>>,
+    /// The canonical labels computed so far.
+    labels: HashMap<String, String>,
+}
+
+impl Canonicalizer<'_> {
+    /// Returns the canonical form of a subject.
+    fn subject(&mut self, subject: &NamedOrBlankNode) -> String {
+        match subject {
+            NamedOrBlankNode::BlankNode(node) => self.label(node.as_str()),
+            NamedOrBlankNode::NamedNode(_) => subject.to_string(),
+        }
+    }
+
+    /// Returns the canonical form of an object.
+    fn term(&mut self, term: &Term) -> String {
+        match term {
+            Term::BlankNode(node) => self.label(node.as_str()),
+            _ => term.to_string(),
+        }
+    }
+
+    /// Returns the canonical label of the blank node `node`.
+    fn label(&mut self, node: &str) -> String {
+        if let Some(label) = self.labels.get(node) {
+            return label.clone();
+        }
+        // Well-formed OWL never contains cycles of blank nodes, but other RDF may.
+        self.labels.insert(node.to_string(), "_:cycle".to_string());
+
+        let mut lines = Vec::new();
+        for index in self.statements.get(node).cloned().unwrap_or_default() {
+            let triple = &self.triples[index];
+            lines.push(format!(
+                "{} {}",
+                triple.predicate,
+                self.term(&triple.object)
+            ));
+        }
+        lines.sort_unstable();
+        let hash = Sha256::digest(lines.join("\n").as_bytes());
+        let label = hash
+            .iter()
+            .take(16)
+            .fold(String::from("_:"), |mut label, byte| {
+                let _ = write!(label, "{byte:02x}");
+                label
+            });
+        self.labels.insert(node.to_string(), label.clone());
+        label
+    }
+}
+
+/// Returns the kind of the elements of type `class`.
+fn element_kind(class: &str) -> ElementKind {
+    match class.rsplit(['#', '/']).next().unwrap_or_default() {
+        "Class" => ElementKind::Class,
+        "Restriction" => ElementKind::Restriction,
+        "ObjectProperty"
+        | "DatatypeProperty"
+        | "AnnotationProperty"
+        | "Property"
+        | "FunctionalProperty"
+        | "InverseFunctionalProperty"
+        | "TransitiveProperty"
+        | "SymmetricProperty"
+        | "AsymmetricProperty"
+        | "ReflexiveProperty"
+        | "IrreflexiveProperty" => ElementKind::Property,
+        "NamedIndividual" => ElementKind::Individual,
+        _ => ElementKind::Other,
+    }
+}
+
+/// Returns the local name of `iri`.
+fn local_name(iri: &str) -> &str {
+    iri.rsplit(['#', '/'])
+        .find(|name| !name.is_empty())
+        .unwrap_or(iri)
+}
+
+/// The comparison of two versions of an ontology.
+struct Comparison {
+    /// The old version.
+    old: Version,
+    /// The new version.
+    new: Version,
+    /// The summary of the differences.
+    diff: OntologyDiff,
+}
+
+impl Comparison {
+    /// Compares the statements of two versions.
+    fn new(old_graph: &str, new_graph: &str, old: Version, new: Version) -> Self {
+        let added = new.missing_from(&old);
+        let removed = old.missing_from(&new);
+
+        let label = |iri: &str| {
+            new.labels
+                .get(iri)
+                .or_else(|| old.labels.get(iri))
+                .cloned()
+                .unwrap_or_else(|| local_name(iri).to_string())
+        };
+
+        // A restriction is identified by the class it restricts and its label.
+        let mut elements = BTreeMap::<(String, ElementKind, String), ChangeKind>::new();
+        let mut record = |iri: &str, kind: ElementKind, change: ChangeKind, label: String| {
+            elements
+                .entry((iri.to_string(), kind, label))
+                .and_modify(|existing| {
+                    if *existing != change {
+                        *existing = ChangeKind::Changed;
+                    }
+                })
+                .or_insert(change);
+        };
+
+        for (triples, version, change) in [
+            (&added, &new, ChangeKind::Added),
+            (&removed, &old, ChangeKind::Removed),
+        ] {
+            for triple in triples {
+                let NamedOrBlankNode::NamedNode(subject) = &triple.subject else {
+                    // Statements about blank nodes change the statement referring to them.
+                    continue;
+                };
+                let subject = subject.as_str();
+                let element_change = if !old.subjects.contains(subject) {
+                    ChangeKind::Added
+                } else if new.subjects.contains(subject) {
+                    ChangeKind::Changed
+                } else {
+                    ChangeKind::Removed
+                };
+                let kind = new
+                    .kind(subject)
+                    .or_else(|| old.kind(subject))
+                    .unwrap_or(ElementKind::Other);
+                record(subject, kind, element_change, label(subject));
+
+                if let Term::BlankNode(node) = &triple.object
+                    && let Some(property) = version.restricted_property(node.as_str())
+                {
+                    record(
+                        subject,
+                        ElementKind::Restriction,
+                        change,
+                        format!("{} on {}", label(subject), label(property)),
+                    );
+                }
+            }
+        }
+
+        let mut elements = elements
+            .into_iter()
+            .map(|((iri, kind, label), change)| ElementChange {
+                iri,
+                label,
+                kind,
+                change,
+            })
+            .collect::<Vec<_>>();
+        elements.sort_by(|a, b| (a.kind, &a.label, &a.iri).cmp(&(b.kind, &b.label, &b.iri)));
+
+        let diff = OntologyDiff {
+            old_graph: old_graph.to_string(),
+            new_graph: new_graph.to_string(),
+            added_quads: added.len() as u64,
+            removed_quads: removed.len() as u64,
+            elements,
+        };
+        Self { old, new, diff }
+    }
+
+    /// Returns the statements only the new version contains.
+    fn added(&self) -> Vec<&Triple> {
+        self.new.missing_from(&self.old)
+    }
+
+    /// Returns the statements only the old version contains.
+    fn removed(&self) -> Vec<&Triple> {
+        self.old.missing_from(&self.new)
+    }
+
+    /// Returns the new version merged with the statements removed from the old version.
+    fn overlay(&self) -> Vec<Triple> {
+        self.new
+            .triples
+            .iter()
+            .chain(self.removed())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the change of every added, removed and changed element, by its IRI.
+    fn annotated_terms(&self) -> HashMap<String, BTreeSet<Annotation>> {
+        let mut annotated_terms = HashMap::<String, BTreeSet<Annotation>>::new();
+        for element in &self.diff.elements {
+            // Restrictions are annotated through the class they belong to.
+            if element.kind != ElementKind::Restriction {
+                annotated_terms
+                    .entry(element.iri.clone())
+                    .or_default()
+                    .insert(Annotation::Change(element.change));
+            }
+        }
+        annotated_terms
+    }
+
+    /// Writes the differences as a Changeset in Turtle.
+    fn turtle_report(&self) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        let change_set = BlankNode::default();
+        let mut report = vec![
+            Triple::new(
+                change_set.clone(),
+                NamedNode::new(RDF_TYPE)?,
+                NamedNode::new(format!("{CHANGESET}ChangeSet"))?,
+            ),
+            Triple::new(
+                change_set.clone(),
+                NamedNode::new(format!("{CHANGESET}changeReason"))?,
+                Literal::new_simple_literal(format!(
+                    "Changes from graph '{}' to graph '{}'",
+                    self.diff.old_graph, self.diff.new_graph
+                )),
+            ),
+        ];
+        if let Some(ontology) = self.new.types.iter().find_map(|(iri, types)| {
+            types
+                .iter()
+                .any(|class| class == "http://www.w3.org/2002/07/owl#Ontology")
+                .then_some(iri)
+        }) && let Ok(ontology) = NamedNode::new(ontology)
+        {
+            report.push(Triple::new(
+                change_set.clone(),
+                NamedNode::new(format!("{CHANGESET}subjectOfChange"))?,
+                ontology,
+            ));
+        }
+
+        for (triples, relation) in [(self.added(), "addition"), (self.removed(), "removal")] {
+            for triple in triples {
+                let statement = BlankNode::default();
+                report.extend([
+                    Triple::new(
+                        change_set.clone(),
+                        NamedNode::new(format!("{CHANGESET}{relation}"))?,
+                        statement.clone(),
+                    ),
+                    Triple::new(
+                        statement.clone(),
+                        NamedNode::new(RDF_TYPE)?,
+                        NamedNode::new(RDF_STATEMENT)?,
+                    ),
+                    Triple::new(
+                        statement.clone(),
+                        NamedNode::new(RDF_SUBJECT)?,
+                        triple.subject.clone(),
+                    ),
+                    Triple::new(
+                        statement.clone(),
+                        NamedNode::new(RDF_PREDICATE)?,
+                        triple.predicate.clone(),
+                    ),
+                    Triple::new(
+                        statement,
+                        NamedNode::new(RDF_OBJECT)?,
+                        triple.object.clone(),
+                    ),
+                ]);
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut serializer = RdfSerializer::from_format(RdfFormat::Turtle)
+            .with_prefix("cs", CHANGESET)?
+            .with_prefix("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#")?
+            .for_writer(&mut buf);
+        for triple in &report {
+            serializer.serialize_triple(triple)?;
+        }
+        serializer.finish()?;
+        Ok(buf)
+    }
+
+    /// Writes the differences as a Markdown document.
+    fn markdown_report(&self) -> Vec<u8> {
+        let diff = &self.diff;
+        let mut report = format!(
+            "# Changes from `{}` to `{}`\n\n| Quads | Count |\n| --- | ---: |\n| Added | {} |\n| Removed | {} |\n",
+            diff.old_graph, diff.new_graph, diff.added_quads, diff.removed_quads
+        );
+
+        for kind in [
+            ElementKind::Class,
+            ElementKind::Property,
+            ElementKind::Restriction,
+            ElementKind::Individual,
+            ElementKind::Other,
+        ] {
+            let elements = diff
+                .elements
+                .iter()
+                .filter(|element| element.kind == kind)
+                .collect::<Vec<_>>();
+            if elements.is_empty() {
+                continue;
+            }
+            let _ = write!(report, "\n## {kind} changes\n\n");
+            for element in elements {
+                let _ = writeln!(
+                    report,
+                    "- {} {} {} <{}>",
+                    element.change.marker(),
+                    element.change,
+                    element.label.replace('\n', " "),
+                    element.iri
+                );
+            }
+        }
+
+        for (title, triples) in [
+            ("Added statements", self.added()),
+            ("Removed statements", self.removed()),
+        ] {
+            if triples.is_empty() {
+                continue;
+            }
+            let _ = write!(report, "\n## {title}\n\n```ntriples\n");
+            for triple in triples {
+                let _ = writeln!(report, "{triple} .");
+            }
+            report.push_str("```\n");
+        }
+        report.into_bytes()
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Compares two graphs of the user, holding an old and a new version of an ontology.
+    ///
+    /// Only the graphs themselves are compared, not their imports.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist or the store could not be queried.
+    pub async fn diff_graphs(
+        &self,
+        old_graph: &str,
+        new_graph: &str,
+    ) -> Result<OntologyDiff, VOWLGrapherStoreError> {
+        Ok(self.compare(old_graph, new_graph).await?.diff)
+    }
+
+    /// Compares two graphs of the user and serializes the new version merged with the
+    /// statements removed from the old one.
+    ///
+    /// Added, removed and changed elements are annotated with their change.
+    ///
+    /// This method tries to continue serializing despite errors.
+    /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist or the comparison could not be serialized.
+    pub async fn diff_display_data(
+        &self,
+        old_graph: &str,
+        new_graph: &str,
+    ) -> Result<
+        (
+            GraphDisplayData,
+            GraphAnnotations,
+            OntologyDiff,
+            Option<VOWLGrapherError>,
+        ),
+        VOWLGrapherError,
+    > {
+        let comparison = self.compare(old_graph, new_graph).await?;
+        let (display_data, annotations, errors) = self
+            .display_triples(comparison.overlay(), comparison.annotated_terms())
+            .await?;
+        Ok((display_data, annotations, comparison.diff, errors))
+    }
+
+    /// Compares two graphs of the user and writes the differences as a report.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist or the report could not be written.
+    pub async fn diff_report(
+        &self,
+        old_graph: &str,
+        new_graph: &str,
+        format: ReportFormat,
+    ) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        let comparison = self.compare(old_graph, new_graph).await?;
+        match format {
+            ReportFormat::Turtle => comparison.turtle_report(),
+            ReportFormat::Markdown => Ok(comparison.markdown_report()),
+        }
+    }
+
+    /// Reads and compares two graphs of the user.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist or the store could not be queried.
+    async fn compare(
+        &self,
+        old_graph: &str,
+        new_graph: &str,
+    ) -> Result<Comparison, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let old = Version::new(self.graph_triples(old_graph).await?);
+        let new = Version::new(self.graph_triples(new_graph).await?);
+        let comparison = Comparison::new(old_graph, new_graph, old, new);
+        info!(
+            "Compared graph '{old_graph}' to '{new_graph}': {} quads added, {} removed, {} elements changed",
+            comparison.diff.added_quads,
+            comparison.diff.removed_quads,
+            comparison.diff.elements.len()
+        );
+        Ok(comparison)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::model::Quad;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    /// Inserts `triples` into the graph `graph_name`.
+    ///
+    /// Terms starting with `_:` are blank nodes, labelled differently in every graph.
+    async fn insert(
+        store: &VOWLGrapherStore,
+        graph_name: &str,
+        triples: &[(&str, &str, &str)],
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let graph = NamedNode::new(store.get_graph_name(graph_name))?;
+        let node = |value: &str| -> Result<NamedOrBlankNode, VOWLGrapherStoreError> {
+            Ok(match value.strip_prefix("_:") {
+                Some(node) => BlankNode::new_unchecked(format!("{graph_name}{node}")).into(),
+                None => NamedNode::new(value)?.into(),
+            })
+        };
+        let mut quads = Vec::new();
+        for (subject, predicate, object) in triples {
+            quads.push(Quad::new(
+                node(subject)?,
+                NamedNode::new(*predicate)?,
+                Term::from(node(object)?),
+                graph.clone(),
+            ));
+        }
+        store.session.extend(quads).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_graphs() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let owl = "http://www.w3.org/2002/07/owl#";
+        let class = &format!("{owl}Class");
+        let restriction = &format!("{owl}Restriction");
+        let some_values = &format!("{owl}someValuesFrom");
+        let sub_class = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+        let shared = [
+            ("http://example.com/A", RDF_TYPE, class.as_str()),
+            ("http://example.com/A", sub_class, "_:r"),
+            ("_:r", RDF_TYPE, restriction.as_str()),
+            ("_:r", OWL_ON_PROPERTY, "http://example.com/p"),
+            ("_:r", some_values.as_str(), "http://example.com/B"),
+        ];
+        insert(&store, "old", &shared).await?;
+        insert(&store, "old", &[("http://example.com/B", RDF_TYPE, class)]).await?;
+        insert(&store, "new", &shared).await?;
+        insert(&store, "new", &[("http://example.com/C", RDF_TYPE, class)]).await?;
+
+        let diff = store.diff_graphs("old", "new").await?;
+        assert_eq!((diff.added_quads, diff.removed_quads), (1, 1));
+        assert_eq!(
+            diff.elements
+                .iter()
+                .map(|element| (element.label.as_str(), element.change))
+                .collect::<Vec<_>>(),
+            vec![("B", ChangeKind::Removed), ("C", ChangeKind::Added)]
+        );
+
+        let annotated_terms = store.compare("old", "new").await?.annotated_terms();
+        assert_eq!(
+            annotated_terms.get("http://example.com/B"),
+            Some(&BTreeSet::from([Annotation::Change(ChangeKind::Removed)]))
+        );
+        assert_eq!(
+            annotated_terms.get("http://example.com/C"),
+            Some(&BTreeSet::from([Annotation::Change(ChangeKind::Added)]))
+        );
+        assert!(!annotated_terms.contains_key("http://example.com/A"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_display_data() -> Result<(), VOWLGrapherError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let class = |name: &str| {
+            format!(
+                "<http://example.com/{name}> <{RDF_TYPE}> <http://www.w3.org/2002/07/owl#Class> .\n\
+                 <http://example.com/{name}> <{RDFS_LABEL}> \"{name}\" .\n"
+            )
+        };
+        let comment =
+            "<http://example.com/A> <http://www.w3.org/2000/01/rdf-schema#comment> \"Changed\" .\n";
+        for (graph_name, data) in [
+            ("old", format!("{}{}", class("A"), class("B"))),
+            ("new", format!("{}{comment}{}", class("A"), class("C"))),
+        ] {
+            store
+                .insert_bytes(data.as_bytes(), graph_name, DataType::NTriples, false)
+                .await?;
+        }
+
+        let (mut display_data, annotations, _, _) = store.diff_display_data("old", "new").await?;
+        let annotations_of = |label: &str| {
+            display_data
+                .labels
+                .iter()
+                .position(|element_label| element_label.as_deref() == Some(label))
+                .map(|index| annotations.element(index).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            annotations_of("A"),
+            Some(vec![Annotation::Change(ChangeKind::Changed)])
+        );
+        assert_eq!(
+            annotations_of("B"),
+            Some(vec![Annotation::Change(ChangeKind::Removed)])
+        );
+        assert_eq!(
+            annotations_of("C"),
+            Some(vec![Annotation::Change(ChangeKind::Added)])
+        );
+
+        annotations.mark_labels(&mut display_data);
+        for (label, marker) in [("A", "~"), ("B", "−"), ("C", "+")] {
+            let marked_label = format!("{marker} {label}");
+            assert!(
+                display_data
+                    .labels
+                    .iter()
+                    .any(|element_label| element_label.as_deref() == Some(marked_label.as_str())),
+                "the label of {label} is not marked"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+//! Loading the responses of remote SPARQL endpoints.
+//!
+//! The results of CONSTRUCT and DESCRIBE queries are RDF documents, loaded like any other upload.
+//! Solutions of SELECT queries binding `?s`, `?p` and `?o` are loaded as triples, while
+//! other solutions and the results of ASK queries are returned to be shown as a table.
+//!
+//! SPARQL CSV results do not tell IRIs and literals apart. A value is read as an IRI if it
+//! is an absolute IRI, as a blank node if it starts with `_:`, and as a plain literal otherwise.
+
+use log::info;
+use rdf_fusion::model::{BlankNode, Literal, NamedNode, NamedOrBlankNode, Quad, Term};
+use sparesults::{QueryResultsFormat, QueryResultsParser, ReaderQueryResultsParserOutput};
+use std::io::BufReader;
+use std::path::Path;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{
+    DataType, ErrorRecord, ErrorSeverity, ErrorType, QuerySolutionRow, VOWLGrapherError,
+    get_timestamp,
+};
+
+use crate::store::VOWLGrapherStore;
+use crate::store::imports::{ImportBase, ImportResolver};
+use crate::store::query_results::results_format_from_resource_type;
+
+/// The number of solutions returned to be shown as a table.
+const MAX_TABLE_ROWS: usize = 1000;
+
+/// The variable the result of an ASK query is shown as.
+const BOOLEAN_VARIABLE: &str = "result";
+
+/// Solutions read from SPARQL query results.
+struct Solutions {
+    /// The variables of the query.
+    variables: Vec<String>,
+    /// The value bound to each variable, in the order of `variables`.
+    rows: Vec<Vec<Option<Term>>>,
+}
+
+impl VOWLGrapherStore {
+    /// Inserts the uploaded response of a SPARQL endpoint, which is in `format`,
+    /// into the graph named after `filename`.
+    ///
+    /// RDF documents are loaded by [`Self::complete_upload`]. Solutions binding the variables
+    /// `?s`, `?p` and `?o` are loaded as triples, skipping the solutions which bind no valid triple.
+    /// Other solutions and the results of ASK queries are not loaded, but returned as rows of a table.
+    ///
+    /// Returns the format the response was parsed as, the rows of the table if the response
+    /// was not loaded, and warnings about solutions which were skipped.
+    ///
+    /// # Errors
+    /// Returns an error if no response was uploaded, the response fails to parse,
+    /// or the store fails to load the triples.
+    pub async fn complete_endpoint_upload(
+        &mut self,
+        filename: &str,
+        format: DataType,
+    ) -> Result<
+        (
+            DataType,
+            Option<Vec<QuerySolutionRow>>,
+            Option<VOWLGrapherError>,
+        ),
+        VOWLGrapherStoreError,
+    > {
+        let Some(results_format) = results_format_from_resource_type(format) else {
+            let (loaded_formats, warnings) = self.complete_upload(filename, format).await?;
+            return Ok((
+                loaded_formats.first().copied().unwrap_or(format),
+                None,
+                warnings,
+            ));
+        };
+
+        self.maintain().await?;
+        // The upload is removed once read, and must live until then.
+        let mut uploads = std::mem::take(&mut self.uploads);
+        let Some((_, file)) = uploads.last_mut() else {
+            return Err(VOWLGrapherStoreErrorKind::InvalidFileType(
+                "No upload handle found".to_string(),
+            )
+            .into());
+        };
+        std::io::Write::flush(file)?;
+        let byte_size = std::fs::metadata(file.path())?.len();
+        let Solutions { variables, rows } = read_solutions(file.path(), format, results_format)?;
+
+        let position = |name: &str| variables.iter().position(|variable| variable == name);
+        let (Some(s), Some(p), Some(o)) = (position("s"), position("p"), position("o")) else {
+            info!(
+                "Returning {} solutions binding {variables:?} as a table",
+                rows.len()
+            );
+            let mut warnings = Vec::new();
+            if rows.len() > MAX_TABLE_ROWS {
+                warnings.push(warning(format!(
+                    "The query returned {} solutions, of which the first {MAX_TABLE_ROWS} are shown",
+                    rows.len()
+                )));
+            }
+            let table = rows
+                .into_iter()
+                .take(MAX_TABLE_ROWS)
+                .map(|row| QuerySolutionRow {
+                    variables: variables.clone(),
+                    values: row
+                        .iter()
+                        .map(|term| term.as_ref().map(display_term))
+                        .collect(),
+                })
+                .collect();
+            return Ok((format, Some(table), warnings_of(warnings, None)));
+        };
+
+        let graph_name = self.get_graph_name(filename);
+        let graph = NamedNode::new(graph_name.as_str())?;
+        let solution_count = rows.len();
+        let quads = rows
+            .into_iter()
+            .filter_map(|row| {
+                let value = |index: usize| row.get(index).cloned().flatten();
+                solution_quad(value(s), value(p), value(o), &graph)
+            })
+            .collect::<Vec<_>>();
+        let mut warnings = Vec::new();
+        if quads.len() < solution_count {
+            warnings.push(warning(format!(
+                "{} of {solution_count} solutions did not bind ?s, ?p and ?o to a valid triple and were skipped",
+                solution_count - quads.len()
+            )));
+        }
+
+        info!(
+            "Loading {} triples bound by the solutions into graph '{graph_name}'",
+            quads.len()
+        );
+        let import_warnings = self
+            .ingest(
+                &graph_name,
+                vec![Box::new(quads.into_iter().map(Ok))],
+                byte_size,
+                ImportResolver::new(ImportBase::Unknown, false),
+                false,
+            )
+            .await?;
+        Ok((format, None, warnings_of(warnings, import_warnings)))
+    }
+}
+
+/// Reads the solutions of SPARQL query results in `format`, from the file at `path`.
+///
+/// The result of an ASK query is read as a single solution binding [`BOOLEAN_VARIABLE`].
+///
+/// # Errors
+/// Returns an error if the file cannot be read or fails to parse.
+fn read_solutions(
+    path: &Path,
+    format: DataType,
+    results_format: QueryResultsFormat,
+) -> Result<Solutions, VOWLGrapherStoreError> {
+    let parse_failure = |error: &dyn std::fmt::Display| {
+        VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::InvalidFileType(format!(
+            "Failed to parse the input as {format}: {error}"
+        )))
+    };
+
+    // The parser refuses CSV, as it is lossy.
+    if matches!(results_format, QueryResultsFormat::Csv) {
+        return Ok(csv_solutions(&std::fs::read_to_string(path)?));
+    }
+
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    match QueryResultsParser::from_format(results_format)
+        .for_reader(reader)
+        .map_err(|e| parse_failure(&e))?
+    {
+        ReaderQueryResultsParserOutput::Solutions(solutions) => {
+            let variables = solutions
+                .variables()
+                .iter()
+                .map(|variable| variable.as_str().to_string())
+                .collect();
+            let rows = solutions
+                .map(|solution| solution.map(|solution| solution.values().to_vec()))
+                .collect::<Result<_, _>>()
+                .map_err(|e| parse_failure(&e))?;
+            Ok(Solutions { variables, rows })
+        }
+        ReaderQueryResultsParserOutput::Boolean(result) => Ok(Solutions {
+            variables: vec![BOOLEAN_VARIABLE.to_string()],
+            rows: vec![vec![Some(Literal::from(result).into())]],
+        }),
+    }
+}
+
+/// Reads the solutions of SPARQL CSV results.
+///
+/// The first record names the variables. Empty values are unbound.
+fn csv_solutions(text: &str) -> Solutions {
+    let mut records = csv_records(text).into_iter();
+    let variables = records.next().unwrap_or_default();
+    let rows = records
+        .filter(|record| record.iter().any(|value| !value.is_empty()))
+        .map(|record| {
+            let mut row = record.into_iter().map(csv_term).collect::<Vec<_>>();
+            row.resize(variables.len(), None);
+            row
+        })
+        .collect();
+    Solutions { variables, rows }
+}
+
+/// Splits CSV into records of fields, as specified by RFC 4180.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Returns the term a value of SPARQL CSV results is read as, or `None` if the value is unbound.
+fn csv_term(value: String) -> Option<Term> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Some(id) = value.strip_prefix("_:")
+        && let Ok(node) = BlankNode::new(id)
+    {
+        return Some(node.into());
+    }
+    Some(
+        NamedNode::new(value.as_str())
+            .map_or_else(|_| Literal::new_simple_literal(value).into(), Term::from),
+    )
+}
+
+/// Returns the quad in `graph` of the triple a solution binds to `?s`, `?p` and `?o`,
+/// if they form a valid triple.
+fn solution_quad(
+    subject: Option<Term>,
+    predicate: Option<Term>,
+    object: Option<Term>,
+    graph: &NamedNode,
+) -> Option<Quad> {
+    let subject: NamedOrBlankNode = match subject? {
+        Term::NamedNode(node) => node.into(),
+        Term::BlankNode(node) => node.into(),
+        _ => return None,
+    };
+    let Term::NamedNode(predicate) = predicate? else {
+        return None;
+    };
+    Some(Quad::new(subject, predicate, object?, graph.clone()))
+}
+
+/// Returns how a term is shown in a table: literals by their lexical value,
+/// and other terms as in N-Triples.
+fn display_term(term: &Term) -> String {
+    if let Term::Literal(literal) = term {
+        literal.value().to_string()
+    } else {
+        term.to_string()
+    }
+}
+
+/// Returns a warning about loading the response of an endpoint.
+fn warning(message: String) -> ErrorRecord {
+    ErrorRecord::new(
+        get_timestamp(),
+        ErrorSeverity::Warning,
+        ErrorType::Parser,
+        message,
+        #[cfg(debug_assertions)]
+        None,
+    )
+}
+
+/// Merges warnings into the warnings of loading imports, if there are any.
+fn warnings_of(
+    mut warnings: Vec<ErrorRecord>,
+    import_warnings: Option<VOWLGrapherError>,
+) -> Option<VOWLGrapherError> {
+    if let Some(import_warnings) = import_warnings {
+        warnings.extend(import_warnings.records);
+    }
+    (!warnings.is_empty()).then(|| warnings.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+
+    #[tokio::test]
+    async fn test_complete_endpoint_upload() -> Result<(), VOWLGrapherStoreError> {
+        let mut store = VOWLGrapherStore::new(Store::default());
+
+        let triples = "s,p,o\r\n\
+            http://example.com/a,http://example.com/knows,_:b\r\n\
+            _:b,http://example.com/name,\"Bob, \"\"the builder\"\"\"\r\n\
+            \"not an IRI\",http://example.com/p,http://example.com/c\r\n";
+        store.start_upload("endpoint.csv")?;
+        store.upload_chunk(triples.as_bytes())?;
+        let (format, table, warnings) = store
+            .complete_endpoint_upload("endpoint.csv", DataType::SPARQLCSV)
+            .await?;
+        assert_eq!(format, DataType::SPARQLCSV);
+        assert!(table.is_none());
+        assert_eq!(warnings.map(|w| w.records.len()), Some(1));
+        assert_eq!(
+            store
+                .graph_len(&store.get_graph_name("endpoint.csv"))
+                .await?,
+            2
+        );
+
+        let solutions = "?name\t?age\n\"Alice\"@en\t42\n<http://example.com/c>\t\n";
+        store.start_upload("names.tsv")?;
+        store.upload_chunk(solutions.as_bytes())?;
+        let (_, table, _) = store
+            .complete_endpoint_upload("names.tsv", DataType::SPARQLTSV)
+            .await?;
+        assert_eq!(
+            table,
+            Some(vec![
+                QuerySolutionRow {
+                    variables: vec!["name".to_string(), "age".to_string()],
+                    values: vec![Some("Alice".to_string()), Some("42".to_string())],
+                },
+                QuerySolutionRow {
+                    variables: vec!["name".to_string(), "age".to_string()],
+                    values: vec![Some("<http://example.com/c>".to_string()), None],
+                },
+            ])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,557 @@
+//! Harvesting large SPARQL endpoints into a named graph, one page at a time.
+//!
+//! A single CONSTRUCT query against a large endpoint times out or is truncated. A harvest sends
+//! the query once per page, sliced by LIMIT and OFFSET, and appends every page to the graph.
+//! Unless the query orders its solutions itself, they are ordered by the variables of its
+//! template, so the pages neither overlap nor skip solutions.
+//! By default, the schema of the endpoint is harvested: its classes and properties with their
+//! subclass, subproperty, equivalence, domain and range triples.
+//!
+//! Blank nodes are not shared between pages.
+//!
+//! The cursor of a harvest is kept until its graph is replaced or removed, so a harvest which
+//! failed, e.g. as a page timed out, resumes with the page that failed. It is persisted along
+//! with the graph after every page, so a harvest also resumes after a restart. Pages into the
+//! same graph are harvested one at a time.
+
+use dashmap::DashMap;
+use log::info;
+use reqwest::Url;
+use sparesults::{QueryResultsFormat, QueryResultsParser, ReaderQueryResultsParserOutput};
+use spargebra::algebra::{Expression, GraphPattern, OrderExpression};
+use spargebra::term::{NamedNodePattern, TermPattern, TriplePattern, Variable};
+use spargebra::{Query, SparqlParser};
+use std::sync::{Arc, LazyLock};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{DataType, HarvestReport, VOWLGRAPHER_ENVIRONMENT};
+
+use crate::remote::query_endpoint;
+use crate::store::query_results::results_format_from_resource_type;
+use crate::store::{VOWLGrapherStore, search};
+
+/// Harvests the classes and properties of an endpoint and the triples relating them.
+pub const SCHEMA_HARVEST_QUERY: &str = r"PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>
+PREFIX owl: <http://www.w3.org/2002/07/owl#>
+CONSTRUCT { ?s ?p ?o }
+WHERE {
+    {
+        ?s ?p ?o .
+        VALUES ?p {
+            rdfs:subClassOf rdfs:subPropertyOf rdfs:domain rdfs:range
+            owl:equivalentClass owl:equivalentProperty owl:inverseOf owl:disjointWith
+        }
+    }
+    UNION
+    {
+        ?s ?p ?o .
+        VALUES (?p ?o) {
+            (rdf:type owl:Class) (rdf:type rdfs:Class) (rdf:type rdf:Property)
+            (rdf:type owl:ObjectProperty) (rdf:type owl:DatatypeProperty)
+            (rdf:type owl:AnnotationProperty)
+        }
+    }
+}";
+
+/// The cursor of every harvest, keyed by the name of the graph harvested into.
+static HARVESTS: LazyLock<DashMap<String, HarvestCursor>> = LazyLock::new(DashMap::new);
+
+/// Serializes the pages of every harvest, keyed by the name of the graph harvested into.
+///
+/// The lock is held from fetching a page until the cursor moved past it,
+/// so concurrent requests neither harvest the same page twice nor skip one.
+/// A lock is dropped from the map once it is released and nobody waits for it.
+static HARVEST_LOCKS: LazyLock<DashMap<String, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+/// The position of a harvest.
+#[derive(Debug, Clone)]
+struct HarvestCursor {
+    /// The harvested endpoint.
+    endpoint: Url,
+    /// The CONSTRUCT query harvested, with its solutions in a stable order.
+    query: Query,
+    /// The number of solutions requested per page.
+    page_size: usize,
+    /// The number of solutions harvested, which is the offset of the next page.
+    offset: usize,
+    /// The number of pages harvested.
+    page_count: u64,
+    /// The size of the harvested pages, in bytes.
+    byte_size: u64,
+    /// Whether the endpoint returned every page.
+    complete: bool,
+}
+
+impl HarvestCursor {
+    /// Writes the cursor as a record to persist: the endpoint, then the numbers
+    /// and the completion of the harvest, then the query.
+    fn to_record(&self) -> String {
+        format!(
+            "{}\n{} {} {} {} {}\n{}",
+            self.endpoint,
+            self.page_size,
+            self.offset,
+            self.page_count,
+            self.byte_size,
+            self.complete,
+            self.query
+        )
+    }
+
+    /// Reads a cursor from a record written by [`Self::to_record`].
+    ///
+    /// Returns `None` if the record is malformed.
+    fn from_record(record: &str) -> Option<Self> {
+        let mut lines = record.splitn(3, '\n');
+        let endpoint = Url::parse(lines.next()?).ok()?;
+        let mut numbers = lines.next()?.split(' ');
+        let page_size = numbers.next()?.parse().ok()?;
+        let offset = numbers.next()?.parse().ok()?;
+        let page_count = numbers.next()?.parse().ok()?;
+        let byte_size = numbers.next()?.parse().ok()?;
+        let complete = numbers.next()?.parse().ok()?;
+        let query = SparqlParser::new().parse_query(lines.next()?).ok()?;
+        Some(Self {
+            endpoint,
+            query,
+            page_size,
+            offset,
+            page_count,
+            byte_size,
+            complete,
+        })
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Starts harvesting `endpoint` into a graph of the user, replacing the graph.
+    ///
+    /// `query` is a CONSTRUCT query without LIMIT or OFFSET, [`SCHEMA_HARVEST_QUERY`] by default.
+    /// Each page holds the triples of `page_size` of its solutions.
+    ///
+    /// # Errors
+    /// Returns an error if the query is invalid, is not a CONSTRUCT query or has a LIMIT
+    /// or OFFSET, or if the graph could not be replaced.
+    pub async fn start_harvest(
+        &self,
+        graph_name: &str,
+        endpoint: Url,
+        query: Option<&str>,
+        page_size: usize,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.get_graph_name(graph_name);
+        let query = ordered_query(query.unwrap_or(SCHEMA_HARVEST_QUERY))?;
+
+        self.check_quota(&graph_name, 0, 0)?;
+        let _harvesting = lock_harvest(&graph_name).await;
+        self.clear_graph(&graph_name).await?;
+        info!("Harvesting {endpoint} into graph '{graph_name}' in pages of {page_size} solutions");
+        let cursor = HarvestCursor {
+            endpoint,
+            query,
+            page_size: page_size.max(1),
+            offset: 0,
+            page_count: 0,
+            byte_size: 0,
+            complete: false,
+        };
+        HARVESTS.insert(graph_name.clone(), cursor.clone());
+        self.persist_graph(&graph_name).await?;
+        self.persist_cursor(&graph_name, &cursor)
+    }
+
+    /// Returns whether a harvest into a graph of the user has pages left to harvest.
+    pub fn can_resume_harvest(&self, graph_name: &str) -> bool {
+        self.harvest_cursor(&self.get_graph_name(graph_name))
+            .is_some_and(|cursor| !cursor.complete)
+    }
+
+    /// Harvests the next page of the harvest into a graph of the user and appends it to the graph.
+    ///
+    /// Returns the size of the page in bytes, or `None` if the harvest is complete.
+    /// The harvest completes once the endpoint returns a page without solutions.
+    /// The graph and the cursor are persisted after every page.
+    ///
+    /// # Errors
+    /// Returns an error if no harvest was started, the page fails to fetch or parse, the harvest
+    /// exceeds `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES` or the user would exceed their quota.
+    /// The harvest can be resumed with the failed page, unless the graph was removed.
+    pub async fn harvest_page(
+        &self,
+        graph_name: &str,
+    ) -> Result<Option<u64>, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.get_graph_name(graph_name);
+        let _harvesting = lock_harvest(&graph_name).await;
+        let Some(cursor) = self.harvest_cursor(&graph_name) else {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "No harvest into graph '{graph_name}' was started"
+            ))
+            .into());
+        };
+        if cursor.complete {
+            return Ok(None);
+        }
+
+        let (mut response, format) = query_endpoint(
+            &cursor.endpoint,
+            &page_query(&cursor.query, cursor.offset, cursor.page_size).to_string(),
+            DataType::SPARQLJSON,
+        )
+        .await?;
+        let max_size = VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes;
+        // The announced length may be missing or wrong, so the body is checked while reading it.
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| {
+            VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
+                "Failed to read page {} of {}: {e}",
+                cursor.page_count + 1,
+                cursor.endpoint
+            ))
+        })? {
+            bytes.extend_from_slice(&chunk);
+            if cursor
+                .byte_size
+                .saturating_add(u64::try_from(bytes.len()).unwrap_or(u64::MAX))
+                > max_size.0
+            {
+                return Err(VOWLGrapherStoreErrorKind::QuotaExceeded(format!(
+                    "Harvesting {} exceeds the maximum allowed size of {} after {} pages",
+                    cursor.endpoint,
+                    max_size.display().si(),
+                    cursor.page_count
+                ))
+                .into());
+            }
+        }
+
+        let page_bytes = u64::try_from(bytes.len()).unwrap_or(u64::MAX);
+        let byte_size = cursor.byte_size + page_bytes;
+        let loaded_quads =
+            usize::try_from(self.graph_len(&graph_name).await?).unwrap_or(usize::MAX);
+        let (quads, _) = Self::open_bytes(&bytes, format, false, &graph_name)?;
+        let inserted = self
+            .extend_in_batches(quads, &graph_name, loaded_quads, byte_size)
+            .await?;
+        self.register_graph(&graph_name, byte_size).await?;
+        search::forget_index(&graph_name);
+
+        // A page of solutions may yield no new triples, e.g. if they repeat earlier ones,
+        // so only a page without solutions ends the harvest.
+        let complete = inserted == 0 && page_is_empty(&cursor).await?;
+        let mut next = cursor.clone();
+        next.byte_size = byte_size;
+        if complete {
+            next.complete = true;
+        } else {
+            next.offset += next.page_size;
+            next.page_count += 1;
+        }
+        if let Some(mut current) = HARVESTS.get_mut(&graph_name) {
+            current.clone_from(&next);
+        } else {
+            // The graph was replaced or removed while the page was harvested.
+            return Ok(None);
+        }
+        self.persist_graph(&graph_name).await?;
+        self.persist_cursor(&graph_name, &next)?;
+
+        if complete {
+            info!(
+                "Harvested {} pages of {} into graph '{graph_name}'",
+                cursor.page_count, cursor.endpoint
+            );
+            search::index_graph(self, &graph_name).await?;
+            return Ok(None);
+        }
+        Ok(Some(page_bytes))
+    }
+
+    /// Returns the progress of the harvest into a graph of the user, if one was started.
+    ///
+    /// # Errors
+    /// Returns an error if the size of the graph could not be determined.
+    pub async fn harvest_report(
+        &self,
+        graph_name: &str,
+    ) -> Result<Option<HarvestReport>, VOWLGrapherStoreError> {
+        let graph_name = self.get_graph_name(graph_name);
+        let Some(cursor) = self.harvest_cursor(&graph_name) else {
+            return Ok(None);
+        };
+        Ok(Some(HarvestReport {
+            endpoint: cursor.endpoint.to_string(),
+            page_count: cursor.page_count,
+            quad_count: self.graph_len(&graph_name).await?,
+            byte_size: cursor.byte_size,
+            complete: cursor.complete,
+        }))
+    }
+
+    /// Returns the cursor of the harvest into a named graph, reading it from disk
+    /// if it is not in memory, e.g. after a restart.
+    fn harvest_cursor(&self, graph_name: &str) -> Option<HarvestCursor> {
+        if let Some(cursor) = HARVESTS.get(graph_name) {
+            return Some(cursor.clone());
+        }
+        let cursor = HarvestCursor::from_record(&self.backing()?.read_harvest(graph_name)?)?;
+        HARVESTS.insert(graph_name.to_string(), cursor.clone());
+        Some(cursor)
+    }
+
+    /// Writes the cursor of the harvest into a named graph to disk, if persistence is enabled.
+    ///
+    /// # Errors
+    /// Returns an error if the cursor could not be written to disk.
+    fn persist_cursor(
+        &self,
+        graph_name: &str,
+        cursor: &HarvestCursor,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        if let Some(backing) = self.backing() {
+            backing.persist_harvest(graph_name, &cursor.to_record())?;
+        }
+        Ok(())
+    }
+}
+
+/// Forgets the harvest into a graph, as the graph was replaced or removed.
+pub fn forget_harvest(graph_name: &str) {
+    HARVESTS.remove(graph_name);
+}
+
+/// The held lock of the harvest into a graph.
+struct HarvestGuard {
+    graph_name: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for HarvestGuard {
+    fn drop(&mut self) {
+        // Releasing the guard drops its reference to the lock, so only the map and the
+        // requests waiting for the lock still refer to it.
+        drop(self.guard.take());
+        HARVEST_LOCKS.remove_if(&self.graph_name, |_, lock| Arc::strong_count(lock) == 1);
+    }
+}
+
+/// Waits for and takes the lock serializing the pages of the harvest into a graph.
+async fn lock_harvest(graph_name: &str) -> HarvestGuard {
+    let lock = Arc::clone(
+        HARVEST_LOCKS
+            .entry(graph_name.to_string())
+            .or_default()
+            .value(),
+    );
+    HarvestGuard {
+        graph_name: graph_name.to_string(),
+        guard: Some(lock.lock_owned().await),
+    }
+}
+
+/// Returns whether the page at the cursor of a harvest holds no solutions.
+///
+/// # Errors
+/// Returns an error if the endpoint could not be queried or its results fail to parse.
+async fn page_is_empty(cursor: &HarvestCursor) -> Result<bool, VOWLGrapherStoreError> {
+    let (response, format) = query_endpoint(
+        &cursor.endpoint,
+        &probe_query(&cursor.query, cursor.offset).to_string(),
+        DataType::SPARQLJSON,
+    )
+    .await?;
+    let bytes = response.bytes().await.map_err(|e| {
+        VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
+            "Failed to read page {} of {}: {e}",
+            cursor.page_count + 1,
+            cursor.endpoint
+        ))
+    })?;
+    let parse_failure = |error: &dyn std::fmt::Display| {
+        VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::InvalidFileType(format!(
+            "Failed to parse the solutions of page {} of {} as {format}: {error}",
+            cursor.page_count + 1,
+            cursor.endpoint
+        )))
+    };
+    match QueryResultsParser::from_format(
+        results_format_from_resource_type(format).unwrap_or(QueryResultsFormat::Json),
+    )
+    .for_reader(bytes.as_ref())
+    .map_err(|e| parse_failure(&e))?
+    {
+        ReaderQueryResultsParserOutput::Solutions(mut solutions) => Ok(solutions
+            .next()
+            .transpose()
+            .map_err(|e| parse_failure(&e))?
+            .is_none()),
+        ReaderQueryResultsParserOutput::Boolean(result) => Ok(!result),
+    }
+}
+
+/// Parses a CONSTRUCT query to harvest, ordering its solutions by the variables of its template
+/// unless it orders them itself.
+///
+/// # Errors
+/// Returns an error if the query is invalid, is not a CONSTRUCT query or has a LIMIT or OFFSET.
+fn ordered_query(query: &str) -> Result<Query, VOWLGrapherStoreError> {
+    let mut query = SparqlParser::new()
+        .parse_query(query)
+        .map_err(|e| VOWLGrapherStoreErrorKind::InvalidQuery(e.to_string()))?;
+    let Query::Construct {
+        template, pattern, ..
+    } = &mut query
+    else {
+        return Err(VOWLGrapherStoreErrorKind::UnsupportedQueryType(
+            "Only CONSTRUCT queries can be harvested".to_string(),
+        )
+        .into());
+    };
+
+    match pattern {
+        GraphPattern::Slice { .. } => {
+            return Err(VOWLGrapherStoreErrorKind::InvalidQuery(
+                "A harvested query must not have a LIMIT or OFFSET, as it is paged through"
+                    .to_string(),
+            )
+            .into());
+        }
+        GraphPattern::OrderBy { .. } => {}
+        _ => {
+            let variables = template_variables(template);
+            if !variables.is_empty() {
+                let inner = std::mem::replace(pattern, GraphPattern::Bgp { patterns: vec![] });
+                *pattern = GraphPattern::OrderBy {
+                    inner: Box::new(inner),
+                    expression: variables
+                        .into_iter()
+                        .map(|variable| OrderExpression::Asc(Expression::Variable(variable)))
+                        .collect(),
+                };
+            }
+        }
+    }
+    Ok(query)
+}
+
+/// Returns the variables of a CONSTRUCT template, in the order they first appear.
+fn template_variables(template: &[TriplePattern]) -> Vec<Variable> {
+    let mut variables = Vec::<Variable>::new();
+    for triple in template {
+        let subject = match &triple.subject {
+            TermPattern::Variable(variable) => Some(variable),
+            _ => None,
+        };
+        let predicate = match &triple.predicate {
+            NamedNodePattern::Variable(variable) => Some(variable),
+            NamedNodePattern::NamedNode(_) => None,
+        };
+        let object = match &triple.object {
+            TermPattern::Variable(variable) => Some(variable),
+            _ => None,
+        };
+        for variable in [subject, predicate, object].into_iter().flatten() {
+            if !variables.contains(variable) {
+                variables.push(variable.clone());
+            }
+        }
+    }
+    variables
+}
+
+/// Returns the page of a harvested query holding `page_size` solutions from `offset` on.
+fn page_query(query: &Query, offset: usize, page_size: usize) -> Query {
+    let mut page = query.clone();
+    if let Query::Construct { pattern, .. } = &mut page {
+        let inner = std::mem::replace(pattern, GraphPattern::Bgp { patterns: vec![] });
+        *pattern = GraphPattern::Slice {
+            inner: Box::new(inner),
+            start: offset,
+            length: Some(page_size),
+        };
+    }
+    page
+}
+
+/// Returns a SELECT query for the first solution of the page of a harvested query from `offset` on.
+fn probe_query(query: &Query, offset: usize) -> Query {
+    let Query::Construct {
+        dataset,
+        pattern,
+        base_iri,
+        ..
+    } = query
+    else {
+        return query.clone();
+    };
+    Query::Select {
+        dataset: dataset.clone(),
+        pattern: GraphPattern::Slice {
+            inner: Box::new(pattern.clone()),
+            start: offset,
+            length: Some(1),
+        },
+        base_iri: base_iri.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_page_query() -> Result<(), VOWLGrapherStoreError> {
+        let query = ordered_query(SCHEMA_HARVEST_QUERY)?;
+        let page = page_query(&query, 200, 100).to_string();
+        assert!(page.contains("ORDER BY"), "unordered: {page}");
+        assert!(page.contains("LIMIT 100"), "missing limit: {page}");
+        assert!(page.contains("OFFSET 200"), "missing offset: {page}");
+        assert!(SparqlParser::new().parse_query(&page).is_ok());
+
+        let probe = probe_query(&query, 200).to_string();
+        assert!(probe.contains("SELECT"), "not a SELECT query: {probe}");
+        assert!(probe.contains("LIMIT 1"), "missing limit: {probe}");
+        assert!(probe.contains("OFFSET 200"), "missing offset: {probe}");
+        assert!(SparqlParser::new().parse_query(&probe).is_ok());
+
+        assert!(ordered_query("CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o } LIMIT 10").is_err());
+        assert!(ordered_query("SELECT * WHERE { ?s ?p ?o }").is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_cursor_record() -> Result<(), Box<dyn std::error::Error>> {
+        let cursor = HarvestCursor {
+            endpoint: Url::parse("https://example.com/sparql")?,
+            query: ordered_query(SCHEMA_HARVEST_QUERY)?,
+            page_size: 100,
+            offset: 300,
+            page_count: 3,
+            byte_size: 4096,
+            complete: false,
+        };
+        let restored = HarvestCursor::from_record(&cursor.to_record())
+            .ok_or("the record of the cursor failed to parse")?;
+        assert_eq!(restored.to_record(), cursor.to_record());
+        assert_eq!(restored.offset, 300);
+        assert!(!restored.complete);
+        assert!(HarvestCursor::from_record("https://example.com/sparql\n100").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_harvest_locks_released() -> Result<(), tokio::task::JoinError> {
+        let graph_name = "urn:vowlgrapher:test:harvest-locks";
+        let first = lock_harvest(graph_name).await;
+        let waiting = tokio::spawn(lock_harvest(graph_name));
+        tokio::task::yield_now().await;
+        drop(first);
+        // The lock stays while another request waits for it.
+        assert!(HARVEST_LOCKS.contains_key(graph_name));
+
+        drop(waiting.await?);
+        assert!(!HARVEST_LOCKS.contains_key(graph_name));
+        Ok(())
+    }
+}
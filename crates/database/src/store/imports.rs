@@ -0,0 +1,751 @@
+//! Resolution of the `owl:imports` closure of an ontology.
+//!
+//! Imports are looked up in XML catalogs and in the archive the ontology was bundled in first,
+//! and fetched over the network if none has them. The import cache only stands in for the
+//! network if fetching fails, or in offline mode, where the network is never used.
+//!
+//! Every import is loaded into its own named graph next to the graph of the importing
+//! ontology. A provenance graph records where each import came from and which
+//! ontologies imported it.
+
+mod cache;
+mod catalog;
+
+use futures::StreamExt;
+use log::{debug, info, warn};
+use rdf_fusion::execution::results::QueryResults;
+use rdf_fusion::model::{NamedNode, Quad, Term};
+use rdf_fusion::store::Store;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{DataType, VOWLGRAPHER_ENVIRONMENT};
+
+use crate::remote::{fetch_ontology, response_format};
+use crate::store::imports::cache::{CachedImport, ImportCache};
+use crate::store::imports::catalog::{CATALOG_FILE_NAME, Catalog};
+
+/// The cache of fetched imports, if configured.
+static IMPORT_CACHE: LazyLock<Option<ImportCache>> = LazyLock::new(ImportCache::from_environment);
+
+/// The catalog configured by `VOWLGRAPHER_IMPORT_CATALOG`, consulted for every import.
+static GLOBAL_CATALOG: LazyLock<Option<Catalog>> = LazyLock::new(|| {
+    let path = VOWLGRAPHER_ENVIRONMENT.import_catalog_path.as_ref()?;
+    match Catalog::from_path(Path::new(path)) {
+        Ok(catalog) => {
+            info!("Resolving imports through catalog '{path}'");
+            Some(catalog)
+        }
+        Err(e) => {
+            warn!("Could not read import catalog '{path}': {e}");
+            None
+        }
+    }
+});
+
+/// Separates the name of a graph from the names of the graphs holding its imports.
+pub const IMPORT_GRAPH_INFIX: &str = ":import:";
+
+/// The suffix, after [`IMPORT_GRAPH_INFIX`], of the graph recording the provenance of imports.
+const PROVENANCE_GRAPH_SUFFIX: &str = "provenance";
+
+/// The suffix, after [`IMPORT_GRAPH_INFIX`], of the graph holding the statements inferred by a reasoner.
+const INFERRED_GRAPH_SUFFIX: &str = "inferred";
+
+/// Links an import graph to the IRI of the ontology it was loaded from.
+pub const PROV_WAS_DERIVED_FROM: &str = "http://www.w3.org/ns/prov#wasDerivedFrom";
+
+/// Links an import graph to the location its document was read from, if it differs from its IRI.
+pub const PROV_AT_LOCATION: &str = "http://www.w3.org/ns/prov#atLocation";
+
+/// Links an import graph to the graph of an ontology importing it.
+pub const IMPORTED_BY: &str = "urn:vowlgrapher:vocab:importedBy";
+
+/// Returns the name of the graph holding the import `import_iri` of the graph `graph_name`.
+pub fn import_graph_name(graph_name: &str, import_iri: &str) -> String {
+    let hash = Sha256::digest(import_iri.as_bytes()).iter().take(8).fold(
+        String::with_capacity(16),
+        |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        },
+    );
+    format!("{graph_name}{IMPORT_GRAPH_INFIX}{hash}")
+}
+
+/// Returns the name of the graph recording the provenance of the imports of `graph_name`.
+pub fn provenance_graph_name(graph_name: &str) -> String {
+    format!("{graph_name}{IMPORT_GRAPH_INFIX}{PROVENANCE_GRAPH_SUFFIX}")
+}
+
+/// Returns the name of the graph holding the statements inferred from `graph_name` and its imports.
+///
+/// Like the provenance graph, it is named like an import graph,
+/// so it is hidden, counted and removed together with `graph_name`.
+pub fn inferred_graph_name(graph_name: &str) -> String {
+    format!("{graph_name}{IMPORT_GRAPH_INFIX}{INFERRED_GRAPH_SUFFIX}")
+}
+
+/// Returns whether `candidate` holds imports, or their provenance, of `graph_name`.
+pub fn is_import_graph_of(candidate: &str, graph_name: &str) -> bool {
+    candidate
+        .strip_prefix(graph_name)
+        .is_some_and(|rest| rest.starts_with(IMPORT_GRAPH_INFIX))
+}
+
+/// Returns whether `graph_name` holds imports, or their provenance, of another graph.
+pub fn is_import_graph(graph_name: &str) -> bool {
+    graph_name.contains(IMPORT_GRAPH_INFIX)
+}
+
+/// Returns the quads recording that `import_graph` was loaded from `resolved`
+/// on behalf of the ontology in `parent_graph`.
+///
+/// # Errors
+/// Returns an error if one of the graph names is not a valid IRI.
+pub fn provenance_quads(
+    graph_name: &str,
+    import_graph: &str,
+    resolved: Option<&ResolvedImport>,
+    parent_graph: &str,
+) -> Result<Vec<Quad>, VOWLGrapherStoreError> {
+    let provenance_graph = NamedNode::new(provenance_graph_name(graph_name))?;
+    let import_graph = NamedNode::new(import_graph)?;
+
+    let mut quads = vec![Quad::new(
+        import_graph.clone(),
+        NamedNode::new_unchecked(IMPORTED_BY),
+        NamedNode::new(parent_graph)?,
+        provenance_graph.clone(),
+    )];
+    if let Some(resolved) = resolved {
+        quads.push(Quad::new(
+            import_graph.clone(),
+            NamedNode::new_unchecked(PROV_WAS_DERIVED_FROM),
+            NamedNode::new(resolved.iri.as_str())?,
+            provenance_graph.clone(),
+        ));
+        if resolved.location != resolved.iri {
+            quads.push(Quad::new(
+                import_graph,
+                NamedNode::new_unchecked(PROV_AT_LOCATION),
+                NamedNode::new(resolved.location.as_str())?,
+                provenance_graph,
+            ));
+        }
+    }
+    Ok(quads)
+}
+
+/// Returns the IRIs imported by the ontologies in the graph `graph_name` of `store`.
+///
+/// # Errors
+/// Returns an error if the store cannot be queried.
+pub async fn extract_import_iris(
+    store: &Store,
+    graph_name: &str,
+) -> Result<Vec<String>, VOWLGrapherStoreError> {
+    select_iris(
+        store,
+        &format!(
+            r"
+        PREFIX owl: <http://www.w3.org/2002/07/owl#>
+        SELECT DISTINCT ?iri
+        WHERE {{
+            GRAPH <{graph_name}> {{
+                ?ontology owl:imports ?iri .
+                FILTER(isIRI(?iri))
+            }}
+        }}
+        "
+        ),
+    )
+    .await
+}
+
+/// Returns the IRIs and version IRIs of the ontologies in the graph `graph_name` of `store`.
+///
+/// # Errors
+/// Returns an error if the store cannot be queried.
+pub async fn declared_ontology_iris(
+    store: &Store,
+    graph_name: &str,
+) -> Result<HashSet<String>, VOWLGrapherStoreError> {
+    Ok(select_iris(
+        store,
+        &format!(
+            r"
+        PREFIX owl: <http://www.w3.org/2002/07/owl#>
+        SELECT DISTINCT ?iri
+        WHERE {{
+            GRAPH <{graph_name}> {{
+                {{ ?iri a owl:Ontology }} UNION {{ ?ontology owl:versionIRI ?iri }}
+                FILTER(isIRI(?iri))
+            }}
+        }}
+        "
+        ),
+    )
+    .await?
+    .into_iter()
+    .collect())
+}
+
+/// Returns the IRIs bound to `?iri` by a SELECT query.
+///
+/// # Errors
+/// Returns an error if the store cannot be queried.
+async fn select_iris(store: &Store, query: &str) -> Result<Vec<String>, VOWLGrapherStoreError> {
+    let mut iris = Vec::new();
+    if let QueryResults::Solutions(mut solutions) = store.query(query).await? {
+        while let Some(solution) = solutions.next().await {
+            if let Some(Term::NamedNode(iri)) = solution?.get("iri") {
+                iris.push(iri.as_str().to_string());
+            }
+        }
+    }
+    Ok(iris)
+}
+
+/// Returns whether `iri` names the document `file`, as `http://example.com/b.owl`
+/// and `http://example.com/b` name `b.owl`.
+fn names_file(iri: &str, file: &Path) -> bool {
+    let name = iri
+        .trim_end_matches(['/', '#'])
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    file.file_name().is_some_and(|file_name| file_name == name)
+        || file.file_stem().is_some_and(|stem| stem == name)
+}
+
+/// Reads the document of a resolved import.
+///
+/// Returns the document, its format as announced by the server or told by its location,
+/// and the base its own imports are resolved against.
+///
+/// Remote documents are always fetched anew, unless in offline mode. If fetching fails,
+/// the cached copy is read instead, if there is one.
+///
+/// Fetching is aborted if it takes longer than `timeout` or the document is larger
+/// than `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+///
+/// # Errors
+/// Returns an error if the document cannot be read or fetched, or exceeds a limit.
+pub async fn fetch_import_source(
+    resolved: &ResolvedImport,
+    timeout: Duration,
+) -> Result<(Vec<u8>, DataType, ImportBase), VOWLGrapherStoreError> {
+    if VOWLGRAPHER_ENVIRONMENT.offline
+        && let Some(cached) = &resolved.cached
+    {
+        return read_cached_import(resolved, cached);
+    }
+
+    let fetched = tokio::time::timeout(timeout, read_import_source(resolved))
+        .await
+        .unwrap_or_else(|_| {
+            Err(VOWLGrapherStoreErrorKind::ImportLimitExceeded(format!(
+                "Fetching import {} took longer than {} s. Skipping it",
+                resolved.iri,
+                timeout.as_secs_f32()
+            ))
+            .into())
+        });
+    match (fetched, &resolved.cached) {
+        (Err(e), Some(cached)) => {
+            warn!(
+                "Could not fetch import {}: {e}. Using the cached copy",
+                resolved.iri
+            );
+            read_cached_import(resolved, cached)
+        }
+        (fetched, _) => fetched,
+    }
+}
+
+/// Reads the cached copy of a resolved import.
+///
+/// # Errors
+/// Returns an error if the copy cannot be read or is too large.
+fn read_cached_import(
+    resolved: &ResolvedImport,
+    cached: &CachedImport,
+) -> Result<(Vec<u8>, DataType, ImportBase), VOWLGrapherStoreError> {
+    debug!("Reading import {} from cache", resolved.iri);
+    check_import_size(resolved, std::fs::metadata(&cached.path)?.len())?;
+    let format = if cached.format == DataType::UNKNOWN {
+        DataType::from(Path::new(resolved.location.path()))
+    } else {
+        cached.format
+    };
+    Ok((
+        std::fs::read(&cached.path)?,
+        format,
+        ImportBase::Url(resolved.location.clone()),
+    ))
+}
+
+/// Reads the document of a resolved import from its location, without a time limit.
+///
+/// # Errors
+/// Returns an error if the document cannot be read or fetched, or is too large.
+async fn read_import_source(
+    resolved: &ResolvedImport,
+) -> Result<(Vec<u8>, DataType, ImportBase), VOWLGrapherStoreError> {
+    let location = &resolved.location;
+    let location_format = DataType::from(Path::new(location.path()));
+
+    match location.scheme() {
+        "file" => {
+            let path = location.to_file_path().map_err(|()| {
+                VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
+                    "Could not convert file URL to path: {location}"
+                ))
+            })?;
+            check_import_size(resolved, std::fs::metadata(&path)?.len())?;
+            let bytes = std::fs::read(&path)?;
+            Ok((bytes, location_format, ImportBase::from_path(&path)))
+        }
+        "http" | "https" => {
+            let mut response = fetch_ontology(location).await?;
+            let format = response_format(&response);
+            // Nested imports are resolved against the location the import was finally served from.
+            let next_base = ImportBase::Url(response.url().clone());
+            if let Some(length) = response.content_length() {
+                check_import_size(resolved, length)?;
+            }
+
+            // The announced length may be missing or wrong, so the body is checked while reading it.
+            let mut bytes = Vec::new();
+            while let Some(chunk) = response.chunk().await.map_err(|e| {
+                VOWLGrapherStoreErrorKind::RemoteFetchError(format!(
+                    "Failed to read import body {location}: {e}"
+                ))
+            })? {
+                bytes.extend_from_slice(&chunk);
+                check_import_size(resolved, u64::try_from(bytes.len()).unwrap_or(u64::MAX))?;
+            }
+
+            if let Some(cache) = IMPORT_CACHE.as_ref()
+                && let Err(e) = cache.store(location, &bytes, format)
+            {
+                warn!("Could not cache import {location}: {e}");
+            }
+            Ok((bytes, format, next_base))
+        }
+        scheme => Err(VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
+            "Unsupported import scheme '{scheme}' for {location}"
+        ))
+        .into()),
+    }
+}
+
+/// Checks that an import of `byte_size` bytes does not exceed `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+///
+/// # Errors
+/// Returns an error if the import is too large.
+fn check_import_size(
+    resolved: &ResolvedImport,
+    byte_size: u64,
+) -> Result<(), VOWLGrapherStoreError> {
+    let max_size = VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes;
+    if byte_size > max_size.0 {
+        return Err(VOWLGrapherStoreErrorKind::ImportLimitExceeded(format!(
+            "Import {} exceeds the maximum allowed size of {}. Skipping it",
+            resolved.iri,
+            max_size.display().si()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// The limits on loading the imports of a single ontology.
+#[derive(Clone, Copy, Debug)]
+pub struct ImportLimits {
+    /// How many levels of nested imports are loaded.
+    pub max_depth: u32,
+    /// The maximum number of imports loaded.
+    pub max_imports: usize,
+    /// How long fetching a single import may take.
+    pub timeout: Duration,
+}
+
+impl ImportLimits {
+    /// Returns the limits configured by the environment.
+    pub fn from_environment() -> Self {
+        Self {
+            max_depth: VOWLGRAPHER_ENVIRONMENT.max_import_depth,
+            max_imports: usize::try_from(VOWLGRAPHER_ENVIRONMENT.max_imports).unwrap_or(usize::MAX),
+            timeout: Duration::from_secs(VOWLGRAPHER_ENVIRONMENT.import_timeout_secs),
+        }
+    }
+}
+
+/// The state shared by all import resolutions of a single load.
+#[derive(Debug)]
+pub struct ImportResolver {
+    /// The base the imports of the loaded ontology are resolved against.
+    pub root_base: ImportBase,
+    /// Whether imports are parsed leniently.
+    pub lenient: bool,
+    /// The limits on loading the imports.
+    pub limits: ImportLimits,
+    /// The catalogs found next to local ontologies, keyed by their directory.
+    directory_catalogs: HashMap<PathBuf, Option<Catalog>>,
+    /// The other ontologies of the archive the loaded ontology was bundled in.
+    bundled_files: Vec<PathBuf>,
+    /// The files merged into the graph of the loaded ontology.
+    merged_files: Vec<PathBuf>,
+}
+
+impl ImportResolver {
+    /// Returns a resolver for the imports of an ontology read from `root_base`.
+    pub fn new(root_base: ImportBase, lenient: bool) -> Self {
+        Self {
+            root_base,
+            lenient,
+            limits: ImportLimits::from_environment(),
+            directory_catalogs: HashMap::new(),
+            bundled_files: Vec::new(),
+            merged_files: Vec::new(),
+        }
+    }
+
+    /// Resolves imports to the given files if their IRIs name them, e.g. `http://example.com/b.owl`
+    /// or `http://example.com/b` to `b.owl`.
+    #[must_use]
+    pub fn with_bundled_files(mut self, bundled_files: Vec<PathBuf>) -> Self {
+        self.bundled_files = bundled_files;
+        self
+    }
+
+    /// Loads imports within `limits` instead of the limits configured by the environment.
+    #[must_use]
+    pub const fn with_limits(mut self, limits: ImportLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Skips imports whose IRIs name one of the given files, which are loaded into the graph itself.
+    #[must_use]
+    pub fn with_merged_files(mut self, merged_files: Vec<PathBuf>) -> Self {
+        self.merged_files = merged_files;
+        self
+    }
+
+    /// Returns whether the import `iri` names a file merged into the graph of the loaded ontology.
+    pub fn is_merged(&self, iri: &str) -> bool {
+        self.merged_files.iter().any(|file| names_file(iri, file))
+    }
+
+    /// Returns the location the catalogs or the bundled files map `iri` to, if any.
+    ///
+    /// The catalog next to the importing ontology takes precedence over the bundled files,
+    /// which take precedence over the global catalog.
+    fn lookup_catalogs(&mut self, base: &ImportBase, iri: &str) -> Option<Url> {
+        let directory = match base {
+            ImportBase::Url(url) if url.scheme() == "file" => url
+                .to_file_path()
+                .ok()
+                .and_then(|path| path.parent().map(Path::to_path_buf)),
+            _ => None,
+        };
+
+        if let Some(directory) = directory {
+            let catalog =
+                self.directory_catalogs
+                    .entry(directory)
+                    .or_insert_with_key(|directory| {
+                        let path = directory.join(CATALOG_FILE_NAME);
+                        if !path.is_file() {
+                            return None;
+                        }
+                        Catalog::from_path(&path)
+                            .inspect_err(|e| warn!("Ignoring catalog '{}': {e}", path.display()))
+                            .ok()
+                    });
+            if let Some(location) = catalog.as_ref().and_then(|catalog| catalog.lookup(iri)) {
+                return Some(location);
+            }
+        }
+
+        let bundled = self.bundled_files.iter().find(|file| names_file(iri, file));
+        if let Some(location) = bundled.and_then(|file| Url::from_file_path(file).ok()) {
+            return Some(location);
+        }
+
+        GLOBAL_CATALOG
+            .as_ref()
+            .and_then(|catalog| catalog.lookup(iri))
+    }
+}
+
+/// An import resolved to the location its document is read from.
+#[derive(Debug)]
+pub struct ResolvedImport {
+    /// The absolute IRI of the import.
+    pub iri: Url,
+    /// The location of the document, which differs from `iri` if a catalog maps it elsewhere.
+    location: Url,
+    /// The cached copy of the document, if any, read if it cannot be fetched.
+    cached: Option<CachedImport>,
+}
+
+/// The location imports are resolved against.
+#[derive(Clone, Debug)]
+pub enum ImportBase {
+    /// Imports are resolved against a URL.
+    Url(Url),
+    /// Only absolute imports can be resolved.
+    Unknown,
+}
+
+impl ImportBase {
+    /// Returns the base of an ontology read from a local file.
+    pub fn from_path(path: &Path) -> Self {
+        Url::from_file_path(path).map_or(Self::Unknown, Self::Url)
+    }
+
+    /// Returns the base of an ontology identified by user input, if it is a URL.
+    pub fn from_user_input(input: &str) -> Self {
+        Url::parse(input).map_or(Self::Unknown, Self::Url)
+    }
+
+    /// Resolves an import against this base.
+    ///
+    /// The import is looked up in the XML catalogs before it is left to be fetched
+    /// over the network. Its cached copy is kept in case fetching fails.
+    ///
+    /// # Errors
+    /// Returns an error if the import is not a valid IRI, or if it would have to be fetched
+    /// over the network in offline mode.
+    pub fn resolve(
+        &self,
+        import_iri: &str,
+        resolver: &mut ImportResolver,
+    ) -> Result<ResolvedImport, VOWLGrapherStoreError> {
+        let iri = match self {
+            Self::Url(base) => base
+                .join(import_iri)
+                .or_else(|_| Url::parse(import_iri))
+                .map_err(|e| VOWLGrapherStoreErrorKind::ImportResolutionError(e.to_string()))?,
+            Self::Unknown => Url::parse(import_iri)
+                .map_err(|e| VOWLGrapherStoreErrorKind::ImportResolutionError(e.to_string()))?,
+        };
+
+        let location = resolver
+            .lookup_catalogs(self, iri.as_str())
+            .unwrap_or_else(|| iri.clone());
+        if location.scheme() == "file" {
+            return Ok(ResolvedImport {
+                iri,
+                location,
+                cached: None,
+            });
+        }
+
+        let cached = IMPORT_CACHE
+            .as_ref()
+            .and_then(|cache| cache.lookup(&location));
+        if cached.is_none() && VOWLGRAPHER_ENVIRONMENT.offline {
+            return Err(VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
+                "Import {iri} is neither mapped by a catalog nor cached, and cannot be fetched in offline mode"
+            ))
+            .into());
+        }
+        Ok(ResolvedImport {
+            iri,
+            location,
+            cached,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::VOWLGrapherStore;
+
+    #[test]
+    fn test_import_graph_names() {
+        let graph_name = "urn:vowlgrapher:user:1:graph:a";
+        let import_graph = import_graph_name(graph_name, "http://example.com/b");
+
+        assert_eq!(
+            import_graph,
+            import_graph_name(graph_name, "http://example.com/b")
+        );
+        assert_ne!(
+            import_graph,
+            import_graph_name(graph_name, "http://example.com/c")
+        );
+        assert!(is_import_graph_of(&import_graph, graph_name));
+        assert!(is_import_graph_of(
+            &provenance_graph_name(graph_name),
+            graph_name
+        ));
+        assert!(!is_import_graph_of(
+            &import_graph,
+            "urn:vowlgrapher:user:1:graph:b"
+        ));
+        assert!(!is_import_graph(graph_name));
+    }
+
+    #[test]
+    fn test_names_file() {
+        let file = Path::new("modules/b.owl");
+        assert!(names_file("http://example.com/b.owl", file));
+        assert!(names_file("http://example.com/b/", file));
+        assert!(!names_file("http://example.com/bc", file));
+    }
+
+    /// The limits used by the tests unless they test a specific one.
+    const LIMITS: ImportLimits = ImportLimits {
+        max_depth: 8,
+        max_imports: 64,
+        timeout: Duration::from_secs(30),
+    };
+
+    /// Writes an ontology importing `imports` as `name` to `dir` and returns its location.
+    fn write_ontology(
+        dir: &Path,
+        name: &str,
+        imports: &[Url],
+    ) -> Result<Url, Box<dyn std::error::Error>> {
+        let path = dir.join(name);
+        let location = Url::from_file_path(&path).map_err(|()| "path should be absolute")?;
+        let mut ontology = format!(
+            "<{location}> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/2002/07/owl#Ontology> .\n"
+        );
+        for import in imports {
+            let _ = writeln!(
+                ontology,
+                "<{location}> <http://www.w3.org/2002/07/owl#imports> <{import}> ."
+            );
+        }
+        std::fs::write(path, ontology)?;
+        Ok(location)
+    }
+
+    /// Loads the closure of `imports` into a new store within `limits`.
+    ///
+    /// Returns the number of loaded imports and the messages of the warnings.
+    async fn load_imports(
+        imports: &[Url],
+        limits: ImportLimits,
+    ) -> Result<(usize, Vec<String>), Box<dyn std::error::Error>> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let graph_name = store.get_graph_name("imports");
+        let graph = NamedNode::new(&graph_name)?;
+        store
+            .session
+            .extend(imports.iter().map(|import| {
+                Quad::new(
+                    NamedNode::new_unchecked("urn:root"),
+                    NamedNode::new_unchecked("http://www.w3.org/2002/07/owl#imports"),
+                    NamedNode::new_unchecked(import.as_str()),
+                    graph.clone(),
+                )
+            }))
+            .await?;
+
+        let warnings = store
+            .load_import_closure(
+                &graph_name,
+                0,
+                0,
+                ImportResolver::new(ImportBase::Unknown, false).with_limits(limits),
+            )
+            .await?;
+        Ok((
+            store.ontology_imports(&graph_name).await?.len(),
+            warnings
+                .map(|warnings| {
+                    warnings
+                        .records
+                        .into_iter()
+                        .map(|record| record.message)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_max_import_depth() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let d = write_ontology(dir.path(), "d.nt", &[])?;
+        let c = write_ontology(dir.path(), "c.nt", &[d])?;
+        let b = write_ontology(dir.path(), "b.nt", &[c])?;
+
+        let (loaded, warnings) = load_imports(&[b.clone()], LIMITS).await?;
+        assert_eq!(loaded, 3);
+        assert!(warnings.is_empty(), "{warnings:?}");
+
+        let limits = ImportLimits {
+            max_depth: 2,
+            ..LIMITS
+        };
+        let (loaded, warnings) = load_imports(&[b], limits).await?;
+        assert_eq!(loaded, 2, "only b and c should be loaded");
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("depth limit of 2")),
+            "{warnings:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_imports() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let imports = ["a.nt", "b.nt", "c.nt", "d.nt"]
+            .into_iter()
+            .map(|name| write_ontology(dir.path(), name, &[]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let limits = ImportLimits {
+            max_imports: 2,
+            ..LIMITS
+        };
+        let (loaded, warnings) = load_imports(&imports, limits).await?;
+        assert_eq!(loaded, 2);
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("Skipping 2 further imports")),
+            "{warnings:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        // The connection is accepted by the OS, but the request is never answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let slow = Url::parse(&format!("http://{}/slow.ttl", listener.local_addr()?))?;
+
+        let limits = ImportLimits {
+            timeout: Duration::from_millis(200),
+            ..LIMITS
+        };
+        let (loaded, warnings) = load_imports(&[slow], limits).await?;
+        assert_eq!(loaded, 0);
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("took longer than")),
+            "{warnings:?}"
+        );
+        drop(listener);
+        Ok(())
+    }
+}
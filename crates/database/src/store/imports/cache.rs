@@ -0,0 +1,152 @@
+//! Content-addressed on-disk cache of fetched imports.
+//!
+//! Every fetched document is stored once under `objects/`, named by the SHA-256 hash of its
+//! content. Files under `index/`, named by the hash of the location the document was
+//! fetched from, contain the hash of the document last fetched from that location on their
+//! first line and the media type it was served as on their second line.
+//! Identical imports published under several IRIs are thus only stored once.
+
+use log::{info, warn};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use vowlgrapher_parser::errors::VOWLGrapherStoreError;
+use vowlgrapher_util::prelude::{DataType, VOWLGRAPHER_ENVIRONMENT};
+
+/// The directory containing the cached documents.
+const OBJECTS_DIR: &str = "objects";
+
+/// The directory mapping locations to cached documents.
+const INDEX_DIR: &str = "index";
+
+/// A document found in the import cache.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CachedImport {
+    /// The path of the cached document.
+    pub path: PathBuf,
+    /// The type the document was served as, or [`DataType::UNKNOWN`] if the server did not tell.
+    pub format: DataType,
+}
+
+/// A directory on disk caching fetched imports.
+#[derive(Debug)]
+pub struct ImportCache {
+    /// The directory containing the cache.
+    root: PathBuf,
+}
+
+impl ImportCache {
+    /// Returns the cache configured by `VOWLGRAPHER_IMPORT_CACHE_PATH`, if any.
+    ///
+    /// The directory is created if it does not exist.
+    pub fn from_environment() -> Option<Self> {
+        let root = PathBuf::from(VOWLGRAPHER_ENVIRONMENT.import_cache_path.as_ref()?);
+        match std::fs::create_dir_all(root.join(OBJECTS_DIR))
+            .and_then(|()| std::fs::create_dir_all(root.join(INDEX_DIR)))
+        {
+            Ok(()) => {
+                info!("Caching imports in '{}'", root.display());
+                Some(Self { root })
+            }
+            Err(e) => {
+                warn!(
+                    "Could not create import cache directory '{}': {e}. Imports are not cached",
+                    root.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the document cached for `location`, if any.
+    pub fn lookup(&self, location: &Url) -> Option<CachedImport> {
+        let entry = std::fs::read_to_string(self.index_path(location)).ok()?;
+        let mut lines = entry.lines();
+        let path = self.object_path(lines.next()?.trim());
+        let format = lines.next().map_or(DataType::UNKNOWN, |mime_type| {
+            DataType::from_mime_type(mime_type.trim())
+        });
+        path.is_file().then_some(CachedImport { path, format })
+    }
+
+    /// Caches the document fetched from `location` as `format`, replacing any previous version.
+    ///
+    /// # Errors
+    /// Returns an error if the document cannot be written to disk.
+    pub fn store(
+        &self,
+        location: &Url,
+        bytes: &[u8],
+        format: DataType,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let hash = hash_hex(bytes);
+        let object_path = self.object_path(&hash);
+        if !object_path.is_file() {
+            // Write to a temporary file first so a crash never leaves a half-written document behind.
+            let mut file = tempfile::NamedTempFile::new_in(self.root.join(OBJECTS_DIR))?;
+            file.write_all(bytes)?;
+            file.persist(object_path).map_err(|e| e.error)?;
+        }
+
+        let mut file = tempfile::NamedTempFile::new_in(self.root.join(INDEX_DIR))?;
+        writeln!(file, "{hash}")?;
+        if format != DataType::UNKNOWN {
+            writeln!(file, "{}", format.mime_type())?;
+        }
+        file.persist(self.index_path(location))
+            .map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Returns the path of the document with the content hash `hash`.
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join(OBJECTS_DIR).join(hash)
+    }
+
+    /// Returns the path of the index entry of `location`.
+    fn index_path(&self, location: &Url) -> PathBuf {
+        self.root
+            .join(INDEX_DIR)
+            .join(hash_hex(location.as_str().as_bytes()))
+    }
+}
+
+/// Returns the SHA-256 hash of `bytes` as a lowercase hex string.
+fn hash_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_import_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let root = tempfile::tempdir()?;
+        std::fs::create_dir_all(root.path().join(OBJECTS_DIR))?;
+        std::fs::create_dir_all(root.path().join(INDEX_DIR))?;
+        let cache = ImportCache {
+            root: root.path().to_path_buf(),
+        };
+        let a = Url::parse("http://example.com/a")?;
+        let b = Url::parse("http://example.com/b.ttl")?;
+
+        assert_eq!(cache.lookup(&a), None);
+        cache.store(&a, b"<urn:s> <urn:p> <urn:o> .", DataType::NTriples)?;
+        cache.store(&b, b"<urn:s> <urn:p> <urn:o> .", DataType::UNKNOWN)?;
+        let cached_a = cache.lookup(&a).ok_or("import should be cached")?;
+        let cached_b = cache.lookup(&b).ok_or("import should be cached")?;
+        assert_eq!(cached_a.path, cached_b.path);
+        assert_eq!(cached_a.format, DataType::NTriples);
+        assert_eq!(cached_b.format, DataType::UNKNOWN);
+        assert_eq!(std::fs::read(cached_a.path)?, b"<urn:s> <urn:p> <urn:o> .");
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! OASIS XML catalogs mapping import IRIs to other locations.
+//!
+//! Supports the subset of <https://www.oasis-open.org/committees/download.php/14809/xml-catalogs.html>
+//! written by Protégé into `catalog-v001.xml`: `uri` and `rewriteURI` entries, `group`s,
+//! `xml:base` and `nextCatalog`.
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use reqwest::Url;
+use std::path::Path;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+
+/// The file name Protégé stores the catalog of an ontology under.
+pub const CATALOG_FILE_NAME: &str = "catalog-v001.xml";
+
+/// How deep `nextCatalog` entries are followed.
+const MAX_CATALOG_DEPTH: usize = 8;
+
+/// The mappings of an XML catalog, including the catalogs it delegates to.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// `uri` entries, mapping an IRI to a location.
+    uris: Vec<(String, Url)>,
+    /// `rewriteURI` entries, mapping an IRI prefix to a location prefix.
+    rewrites: Vec<(String, Url)>,
+    /// Catalogs consulted if no entry of this catalog matches.
+    next_catalogs: Vec<Self>,
+}
+
+impl Catalog {
+    /// Reads the catalog at `path`, following its `nextCatalog` entries.
+    ///
+    /// # Errors
+    /// Returns an error if the catalog cannot be read or is not a valid XML catalog.
+    pub fn from_path(path: &Path) -> Result<Self, VOWLGrapherStoreError> {
+        Self::from_path_with_depth(path, 0)
+    }
+
+    /// Reads the catalog at `path`, following `nextCatalog` entries up to [`MAX_CATALOG_DEPTH`].
+    fn from_path_with_depth(path: &Path, depth: usize) -> Result<Self, VOWLGrapherStoreError> {
+        let base = Url::from_file_path(path.canonicalize()?).map_err(|()| {
+            VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
+                "Invalid catalog path '{}'",
+                path.display()
+            ))
+        })?;
+        let (mut catalog, next_catalogs) = Self::parse(&std::fs::read_to_string(path)?, &base)
+            .map_err(|e| {
+                VOWLGrapherStoreErrorKind::ImportResolutionError(format!(
+                    "Invalid catalog '{}': {e}",
+                    path.display()
+                ))
+            })?;
+
+        if depth < MAX_CATALOG_DEPTH {
+            for next in next_catalogs {
+                if let Ok(next_path) = next.to_file_path() {
+                    catalog
+                        .next_catalogs
+                        .push(Self::from_path_with_depth(&next_path, depth + 1)?);
+                }
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Parses a catalog, resolving relative locations against `base`.
+    ///
+    /// Returns the catalog and the locations of its `nextCatalog` entries.
+    fn parse(xml: &str, base: &Url) -> Result<(Self, Vec<Url>), quick_xml::Error> {
+        let mut catalog = Self::default();
+        let mut next_catalogs = Vec::new();
+        let mut bases = vec![base.clone()];
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => {
+                    let base = element_base(&element, bases.last().unwrap_or(base))?;
+                    catalog.add_entry(&element, &base, &mut next_catalogs)?;
+                    bases.push(base);
+                }
+                Event::Empty(element) => {
+                    let base = element_base(&element, bases.last().unwrap_or(base))?;
+                    catalog.add_entry(&element, &base, &mut next_catalogs)?;
+                }
+                Event::End(_) => {
+                    bases.pop();
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Ok((catalog, next_catalogs))
+    }
+
+    /// Adds the entry described by `element`, if it is one.
+    fn add_entry(
+        &mut self,
+        element: &BytesStart,
+        base: &Url,
+        next_catalogs: &mut Vec<Url>,
+    ) -> Result<(), quick_xml::Error> {
+        match element.local_name().as_ref() {
+            b"uri" => {
+                if let (Some(name), Some(uri)) =
+                    (attribute(element, b"name")?, attribute(element, b"uri")?)
+                    && let Ok(location) = base.join(&uri)
+                {
+                    self.uris.push((name, location));
+                }
+            }
+            b"rewriteURI" => {
+                if let (Some(prefix), Some(rewrite)) = (
+                    attribute(element, b"uriStartString")?,
+                    attribute(element, b"rewritePrefix")?,
+                ) && let Ok(location) = base.join(&rewrite)
+                {
+                    self.rewrites.push((prefix, location));
+                }
+            }
+            b"nextCatalog" => {
+                if let Some(catalog) = attribute(element, b"catalog")?
+                    && let Ok(location) = base.join(&catalog)
+                {
+                    next_catalogs.push(location);
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Returns the location `iri` is mapped to, if any.
+    ///
+    /// `uri` entries take precedence over `rewriteURI` entries, of which the longest
+    /// matching prefix wins.
+    pub fn lookup(&self, iri: &str) -> Option<Url> {
+        if let Some((_, location)) = self.uris.iter().find(|(name, _)| name == iri) {
+            return Some(location.clone());
+        }
+
+        let rewritten = self
+            .rewrites
+            .iter()
+            .filter(|(prefix, _)| iri.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .and_then(|(prefix, location)| {
+                let mut location = location.to_string();
+                location.push_str(&iri[prefix.len()..]);
+                Url::parse(&location).ok()
+            });
+        if rewritten.is_some() {
+            return rewritten;
+        }
+
+        self.next_catalogs
+            .iter()
+            .find_map(|catalog| catalog.lookup(iri))
+    }
+}
+
+/// Returns the base of `element`, which is its `xml:base` resolved against `parent`.
+fn element_base(element: &BytesStart, parent: &Url) -> Result<Url, quick_xml::Error> {
+    Ok(attribute(element, b"xml:base")?
+        .and_then(|base| parent.join(&base).ok())
+        .unwrap_or_else(|| parent.clone()))
+}
+
+/// Returns the unescaped value of the attribute `key` of `element`.
+fn attribute(element: &BytesStart, key: &[u8]) -> Result<Option<String>, quick_xml::Error> {
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        if attribute.key.as_ref() == key {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_catalog_lookup() -> Result<(), Box<dyn std::error::Error>> {
+        let base = Url::parse("file:///ontologies/catalog-v001.xml")?;
+        let (catalog, next_catalogs) = Catalog::parse(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+            <catalog prefer="public" xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+                <group id="Folder Repository" prefer="public" xml:base="imports/">
+                    <uri id="Imports Wizard Entry" name="http://example.com/a" uri="a.owl"/>
+                </group>
+                <rewriteURI uriStartString="http://example.com/lib/" rewritePrefix="lib/"/>
+                <nextCatalog catalog="shared/catalog-v001.xml"/>
+            </catalog>"#,
+            &base,
+        )?;
+
+        assert_eq!(
+            catalog.lookup("http://example.com/a").map(String::from),
+            Some("file:///ontologies/imports/a.owl".to_string())
+        );
+        assert_eq!(
+            catalog
+                .lookup("http://example.com/lib/b.ttl")
+                .map(String::from),
+            Some("file:///ontologies/lib/b.ttl".to_string())
+        );
+        assert_eq!(catalog.lookup("http://example.com/c"), None);
+        assert_eq!(next_catalogs.len(), 1);
+        Ok(())
+    }
+}
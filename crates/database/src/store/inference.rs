@@ -0,0 +1,363 @@
+//! Materialization of the statements entailed by the ontology in a named graph.
+//!
+//! The rules of RDFS and
+//! [OWL 2 RL](https://www.w3.org/TR/owl2-profiles/#Reasoning_in_OWL_2_RL_and_RDF_Graphs_using_Rules)
+//! are applied by forward chaining over the store: in every round, each rule is run as a query
+//! over the ontology, its imports and the statements inferred so far, and the new conclusions
+//! are added to a sibling graph of the ontology. Reasoning stops once a round concludes nothing new.
+//! The inferred graph is named like the import graphs, so it is removed whenever the ontology
+//! is reloaded or removed, and it is dropped whenever the ontology is updated.
+//!
+//! The rules on datatypes (`dt-*`) are not applied. Neither are the rules concluding
+//! statements which hold for every resource, such as `?x owl:sameAs ?x` or `?c rdfs:subClassOf ?c`.
+
+mod rules;
+
+use futures::StreamExt;
+use log::info;
+use rdf_fusion::execution::results::QueryResults;
+use rdf_fusion::model::NamedNode;
+use std::collections::{BTreeSet, HashSet};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{
+    ErrorRecord, ErrorSeverity, ErrorType, InferenceReport, VOWLGRAPHER_ENVIRONMENT, get_timestamp,
+};
+
+use crate::store::imports::inferred_graph_name;
+use crate::store::{VOWLGrapherStore, dataset, lifecycle};
+
+/// The most statements inferred from a single graph, whatever the user's quota.
+const MAX_INFERRED_STATEMENTS: usize = 1_000_000;
+
+/// Returns the most statements which may be inferred from a single graph.
+fn inference_limit() -> usize {
+    let max_user_quads =
+        usize::try_from(VOWLGRAPHER_ENVIRONMENT.max_user_quads).unwrap_or(usize::MAX);
+    if max_user_quads == 0 {
+        MAX_INFERRED_STATEMENTS
+    } else {
+        max_user_quads.min(MAX_INFERRED_STATEMENTS)
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Infers the statements entailed by a graph of the user and its imports,
+    /// replacing the statements inferred before.
+    ///
+    /// Inconsistencies do not stop the inference, but are returned in the report.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist, the inferred statements would exceed
+    /// the user's quota or the limit of inferred statements, or the store could not be
+    /// queried or updated.
+    pub async fn materialize_inferences(
+        &self,
+        graph_name: &str,
+    ) -> Result<InferenceReport, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_iri = self.get_graph_name(graph_name);
+        let asserted_len = self.graph_len(&graph_iri).await?;
+        if asserted_len == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{graph_name}' does not exist"
+            ))
+            .into());
+        }
+        lifecycle::touch_graph(&graph_iri);
+        self.forget_inferences(&graph_iri).await?;
+
+        let mut asserted_count = asserted_len;
+        let mut graphs = vec![graph_iri.clone()];
+        for import_graph in self.ontology_imports(&graph_iri).await? {
+            asserted_count += self.graph_len(&import_graph).await?;
+            graphs.push(import_graph);
+        }
+        let inferred_graph = inferred_graph_name(&graph_iri);
+        graphs.push(inferred_graph.clone());
+
+        let inferred_count = match self
+            .infer(&graphs, &inferred_graph, inference_limit())
+            .await
+        {
+            Ok(inferred_count) => inferred_count,
+            Err(e) => {
+                // Leave no partial inference behind.
+                self.session
+                    .remove_named_graph(NamedNode::new(&inferred_graph)?.as_ref())
+                    .await?;
+                return Err(e);
+            }
+        };
+        let inconsistencies = self.inconsistencies(&graphs).await?;
+        if inferred_count > 0 {
+            self.register_graph(&inferred_graph, 0).await?;
+            self.persist_graph(&inferred_graph).await?;
+        }
+
+        info!(
+            "Inferred {inferred_count} statements from the {asserted_count} statements of graph '{graph_name}', finding {} inconsistencies",
+            inconsistencies.len()
+        );
+        Ok(InferenceReport {
+            graph_name: graph_name.to_string(),
+            asserted_count,
+            inferred_count: inferred_count as u64,
+            inconsistencies: inconsistencies
+                .into_iter()
+                .map(|message| {
+                    ErrorRecord::new(
+                        get_timestamp(),
+                        ErrorSeverity::Warning,
+                        ErrorType::Inference,
+                        message,
+                        #[cfg(debug_assertions)]
+                        None,
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Applies the rules to the merged `graphs` until no new statement follows,
+    /// adding the inferred statements to `inferred_graph`, which must be among `graphs`.
+    ///
+    /// Returns the number of inferred statements.
+    ///
+    /// # Errors
+    /// Returns an error if more than `limit` statements are inferred, the user's quota
+    /// would be exceeded, or the store could not be queried or updated.
+    async fn infer(
+        &self,
+        graphs: &[String],
+        inferred_graph: &str,
+        limit: usize,
+    ) -> Result<usize, VOWLGrapherStoreError> {
+        let mut chains = Vec::new();
+        if let QueryResults::Solutions(mut solutions) = self
+            .session
+            .query(&dataset::scope_query(rules::CHAINS_QUERY, graphs)?)
+            .await?
+        {
+            while let Some(solution) = solutions.next().await {
+                chains.push(solution?);
+            }
+        }
+        let queries = rules::RULES
+            .iter()
+            .cloned()
+            .chain(rules::chain_rules(&chains))
+            .map(|rule| dataset::scope_query(&rule.query(), graphs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let graph = NamedNode::new(inferred_graph)?;
+        let mut inferred_count = 0;
+        loop {
+            let mut conclusions = HashSet::new();
+            for query in &queries {
+                if let QueryResults::Graph(mut triples) = self.session.query(query).await? {
+                    while let Some(triple) = triples.next().await {
+                        let triple = triple?;
+                        if rules::admits(&triple) {
+                            conclusions.insert(triple);
+                        }
+                    }
+                }
+            }
+            if conclusions.is_empty() {
+                return Ok(inferred_count);
+            }
+
+            inferred_count += conclusions.len();
+            if inferred_count > limit {
+                return Err(VOWLGrapherStoreErrorKind::QuotaExceeded(format!(
+                    "The ontology entails more than {limit} statements, exceeding the limit of inferred statements"
+                ))
+                .into());
+            }
+            self.check_quota(inferred_graph, inferred_count, 0)?;
+            self.session
+                .extend(
+                    conclusions
+                        .into_iter()
+                        .map(|triple| triple.in_graph(graph.clone())),
+                )
+                .await?;
+        }
+    }
+
+    /// Returns a description of every inconsistency of the merged `graphs`, naming the rule
+    /// which found it.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn inconsistencies(
+        &self,
+        graphs: &[String],
+    ) -> Result<BTreeSet<String>, VOWLGrapherStoreError> {
+        let mut inconsistencies = BTreeSet::new();
+        for check in rules::CHECKS {
+            let query = dataset::scope_query(&check.query(), graphs)?;
+            if let QueryResults::Solutions(mut solutions) = self.session.query(&query).await? {
+                while let Some(solution) = solutions.next().await {
+                    inconsistencies.insert(check.message(&solution?));
+                }
+            }
+        }
+        Ok(inconsistencies)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_sparql_queries::prelude::DEFAULT_QUERY;
+    use vowlgrapher_util::prelude::{Annotation, DataType, VOWLGrapherError};
+
+    const ONTOLOGY: &[u8] = br"@prefix : <http://example.com/> .
+        @prefix owl: <http://www.w3.org/2002/07/owl#> .
+        @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+        :Student rdfs:subClassOf :Person .
+        :Person owl:disjointWith :Course .
+        :attends rdfs:domain :Student .
+        :alice :attends :logic .
+        :alice a :Course .";
+
+    #[tokio::test]
+    async fn test_materialize_inferences() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(ONTOLOGY, "ontology", DataType::TTL, false)
+            .await?;
+
+        let report = store.materialize_inferences("ontology").await?;
+        assert_eq!(report.asserted_count, 5);
+        assert_eq!(report.inferred_count, 3);
+        assert_eq!(
+            report.inconsistencies.len(),
+            1,
+            "{:?}",
+            report.inconsistencies
+        );
+        assert!(report.inconsistencies[0].message.ends_with("(cax-dw)"));
+
+        // Inferring again replaces the statements inferred before.
+        let report = store.materialize_inferences("ontology").await?;
+        assert_eq!(report.inferred_count, 3);
+        let inferred_graph = inferred_graph_name(&store.get_graph_name("ontology"));
+        let inferred = store.named_graph_triples(&inferred_graph).await?;
+        assert_eq!(inferred.len(), 3);
+        for expected in [
+            "<http://example.com/alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Student>",
+            "<http://example.com/alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Person>",
+            "<http://example.com/attends> <http://www.w3.org/2000/01/rdf-schema#domain> <http://example.com/Person>",
+        ] {
+            assert!(
+                inferred.iter().any(|triple| triple.to_string() == expected),
+                "missing inferred statement {expected}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inference_limit() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(ONTOLOGY, "ontology", DataType::TTL, false)
+            .await?;
+        let graph_iri = store.get_graph_name("ontology");
+        let inferred_graph = inferred_graph_name(&graph_iri);
+
+        let graphs = [graph_iri, inferred_graph.clone()];
+        assert!(
+            store.infer(&graphs, &inferred_graph, 2).await.is_err(),
+            "limit was not enforced"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_changes_drop_inferences() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(ONTOLOGY, "ontology", DataType::TTL, false)
+            .await?;
+        let inferred_graph = inferred_graph_name(&store.get_graph_name("ontology"));
+
+        store.materialize_inferences("ontology").await?;
+        store
+            .update(
+                "DELETE DATA { <http://example.com/alice> a <http://example.com/Course> }",
+                "ontology",
+            )
+            .await?;
+        assert_eq!(store.graph_len(&inferred_graph).await?, 0);
+
+        store.materialize_inferences("ontology").await?;
+        store
+            .insert_bytes(
+                b"<http://example.com/bob> <http://example.com/attends> <http://example.com/logic> .",
+                "ontology",
+                DataType::NTriples,
+                true,
+            )
+            .await?;
+        assert_eq!(store.graph_len(&inferred_graph).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inferred_edges_annotated() -> Result<(), VOWLGrapherError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(
+                br#"@prefix : <http://example.com/> .
+                @prefix owl: <http://www.w3.org/2002/07/owl#> .
+                @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+                :A a owl:Class ; rdfs:label "A" ; rdfs:subClassOf :B .
+                :B a owl:Class ; rdfs:label "B" ; rdfs:subClassOf :C .
+                :C a owl:Class ; rdfs:label "C" ."#,
+                "taxonomy",
+                DataType::TTL,
+                false,
+            )
+            .await?;
+        store.materialize_inferences("taxonomy").await?;
+
+        let (display_data, annotations, _) = store
+            .query_with_imports(
+                DEFAULT_QUERY.to_string(),
+                Some("taxonomy".to_string()),
+                &[],
+                true,
+                None,
+            )
+            .await?;
+        let edge_annotations = |subject: &str, object: &str| {
+            let index_of = |label: &str| {
+                display_data
+                    .labels
+                    .iter()
+                    .position(|element_label| element_label.as_deref() == Some(label))
+            };
+            let (subject, object) = (index_of(subject)?, index_of(object)?);
+            display_data
+                .edges
+                .iter()
+                .position(|edge| edge[0] == subject && edge[2] == object)
+                .map(|index| annotations.edge(index).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            edge_annotations("A", "C"),
+            Some(vec![Annotation::Inferred]),
+            "the inferred edge is not annotated"
+        );
+        assert_eq!(
+            edge_annotations("A", "B"),
+            Some(vec![]),
+            "the asserted edge is annotated"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,538 @@
+//! The entailment rules of RDFS and OWL 2 RL, written as SPARQL queries over the store.
+//!
+//! Every [`Rule`] is a CONSTRUCT query concluding a single statement from its premises,
+//! leaving out the conclusions which are already known. Every [`Check`] is a SELECT query
+//! finding the solutions of premises which contradict each other.
+//! The names of the rules are those of the
+//! [OWL 2 RL/RDF rules](https://www.w3.org/TR/owl2-profiles/#OWL_2_RL_RDF_rules)
+//! and the [RDFS entailment rules](https://www.w3.org/TR/rdf11-mt/#patterns-of-rdfs-entailment-informative).
+
+use rdf_fusion::execution::results::QuerySolution;
+use rdf_fusion::model::{NamedNode, Term, Triple};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// The prefixes the rules and checks are written with.
+const PREFIXES: &str = "PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> \
+    PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#> \
+    PREFIX owl: <http://www.w3.org/2002/07/owl#> ";
+
+/// A rule concluding a single statement from its premises.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// The name of the rule.
+    pub name: &'static str,
+    /// The graph pattern the premises must match.
+    premises: Cow<'static, str>,
+    /// The triple pattern of the concluded statement.
+    conclusion: Cow<'static, str>,
+}
+
+impl Rule {
+    const fn new(name: &'static str, premises: &'static str, conclusion: &'static str) -> Self {
+        Self {
+            name,
+            premises: Cow::Borrowed(premises),
+            conclusion: Cow::Borrowed(conclusion),
+        }
+    }
+
+    /// Returns the CONSTRUCT query of the statements the rule concludes which are not yet known.
+    pub fn query(&self) -> String {
+        format!(
+            "{PREFIXES}CONSTRUCT {{ {conclusion} }} WHERE {{ {premises} FILTER NOT EXISTS {{ {conclusion} }} }}",
+            premises = self.premises,
+            conclusion = self.conclusion
+        )
+    }
+}
+
+/// The rules which are the same for every ontology.
+///
+/// Rules following from the others, such as `cax-eqc1`, are left out.
+pub const RULES: &[Rule] = &[
+    Rule::new("eq-sym", "?x owl:sameAs ?y .", "?y owl:sameAs ?x"),
+    Rule::new(
+        "eq-trans",
+        "?x owl:sameAs ?y . ?y owl:sameAs ?z .",
+        "?x owl:sameAs ?z",
+    ),
+    Rule::new("eq-rep-s", "?s owl:sameAs ?s2 . ?s ?p ?o .", "?s2 ?p ?o"),
+    Rule::new("eq-rep-p", "?p owl:sameAs ?p2 . ?s ?p ?o .", "?s ?p2 ?o"),
+    Rule::new("eq-rep-o", "?o owl:sameAs ?o2 . ?s ?p ?o .", "?s ?p ?o2"),
+    Rule::new("prp-dom", "?p rdfs:domain ?c . ?x ?p ?y .", "?x a ?c"),
+    Rule::new("prp-rng", "?p rdfs:range ?c . ?x ?p ?y .", "?y a ?c"),
+    Rule::new(
+        "prp-fp",
+        "?p a owl:FunctionalProperty . ?x ?p ?y1 , ?y2 . FILTER(!sameTerm(?y1, ?y2))",
+        "?y1 owl:sameAs ?y2",
+    ),
+    Rule::new(
+        "prp-ifp",
+        "?p a owl:InverseFunctionalProperty . ?x1 ?p ?y . ?x2 ?p ?y . FILTER(!sameTerm(?x1, ?x2))",
+        "?x1 owl:sameAs ?x2",
+    ),
+    Rule::new(
+        "prp-symp",
+        "?p a owl:SymmetricProperty . ?x ?p ?y .",
+        "?y ?p ?x",
+    ),
+    Rule::new(
+        "prp-trp",
+        "?p a owl:TransitiveProperty . ?x ?p ?y . ?y ?p ?z .",
+        "?x ?p ?z",
+    ),
+    Rule::new(
+        "prp-spo1",
+        "?p1 rdfs:subPropertyOf ?p2 . ?x ?p1 ?y .",
+        "?x ?p2 ?y",
+    ),
+    Rule::new(
+        "prp-inv1",
+        "?p1 owl:inverseOf ?p2 . ?x ?p1 ?y .",
+        "?y ?p2 ?x",
+    ),
+    Rule::new(
+        "prp-inv2",
+        "?p1 owl:inverseOf ?p2 . ?x ?p2 ?y .",
+        "?y ?p1 ?x",
+    ),
+    Rule::new(
+        "prp-key",
+        "?c owl:hasKey ?list . ?list rdf:first ?p1 . ?x a ?c ; ?p1 ?z1 . ?y a ?c ; ?p1 ?z1 . \
+         FILTER(!sameTerm(?x, ?y)) \
+         FILTER NOT EXISTS { ?list rdf:rest*/rdf:first ?p . FILTER NOT EXISTS { ?x ?p ?z . ?y ?p ?z } }",
+        "?x owl:sameAs ?y",
+    ),
+    Rule::new(
+        "cls-int1",
+        "?c owl:intersectionOf ?list . ?list rdf:first ?first . ?y a ?first . \
+         FILTER NOT EXISTS { ?list rdf:rest*/rdf:first ?member . FILTER NOT EXISTS { ?y a ?member } }",
+        "?y a ?c",
+    ),
+    Rule::new(
+        "cls-int2",
+        "?c owl:intersectionOf ?list . ?list rdf:rest*/rdf:first ?member . ?y a ?c .",
+        "?y a ?member",
+    ),
+    Rule::new(
+        "cls-uni",
+        "?c owl:unionOf ?list . ?list rdf:rest*/rdf:first ?member . ?y a ?member .",
+        "?y a ?c",
+    ),
+    Rule::new(
+        "cls-svf1",
+        "?x owl:someValuesFrom ?y ; owl:onProperty ?p . ?u ?p ?v . ?v a ?y .",
+        "?u a ?x",
+    ),
+    Rule::new(
+        "cls-svf2",
+        "?x owl:someValuesFrom owl:Thing ; owl:onProperty ?p . ?u ?p ?v .",
+        "?u a ?x",
+    ),
+    Rule::new(
+        "cls-avf",
+        "?x owl:allValuesFrom ?y ; owl:onProperty ?p . ?u a ?x ; ?p ?v .",
+        "?v a ?y",
+    ),
+    Rule::new(
+        "cls-hv1",
+        "?x owl:hasValue ?y ; owl:onProperty ?p . ?u a ?x .",
+        "?u ?p ?y",
+    ),
+    Rule::new(
+        "cls-hv2",
+        "?x owl:hasValue ?y ; owl:onProperty ?p . ?u ?p ?y .",
+        "?u a ?x",
+    ),
+    Rule::new(
+        "cls-maxc2",
+        "?x owl:maxCardinality ?n ; owl:onProperty ?p . FILTER(STR(?n) = \"1\") \
+         ?u a ?x ; ?p ?y1 , ?y2 . FILTER(!sameTerm(?y1, ?y2))",
+        "?y1 owl:sameAs ?y2",
+    ),
+    Rule::new(
+        "cls-maxqc3",
+        "?x owl:maxQualifiedCardinality ?n ; owl:onProperty ?p ; owl:onClass ?c . \
+         FILTER(STR(?n) = \"1\") ?u a ?x ; ?p ?y1 , ?y2 . ?y1 a ?c . ?y2 a ?c . \
+         FILTER(!sameTerm(?y1, ?y2))",
+        "?y1 owl:sameAs ?y2",
+    ),
+    Rule::new(
+        "cls-maxqc4",
+        "?x owl:maxQualifiedCardinality ?n ; owl:onProperty ?p ; owl:onClass owl:Thing . \
+         FILTER(STR(?n) = \"1\") ?u a ?x ; ?p ?y1 , ?y2 . FILTER(!sameTerm(?y1, ?y2))",
+        "?y1 owl:sameAs ?y2",
+    ),
+    Rule::new(
+        "cls-oo",
+        "?c owl:oneOf ?list . ?list rdf:rest*/rdf:first ?y .",
+        "?y a ?c",
+    ),
+    Rule::new(
+        "cax-sco",
+        "?c1 rdfs:subClassOf ?c2 . ?x a ?c1 .",
+        "?x a ?c2",
+    ),
+    Rule::new(
+        "rdfs12",
+        "?p a rdfs:ContainerMembershipProperty .",
+        "?p rdfs:subPropertyOf rdfs:member",
+    ),
+    Rule::new(
+        "scm-sco",
+        "?c1 rdfs:subClassOf ?c2 . ?c2 rdfs:subClassOf ?c3 .",
+        "?c1 rdfs:subClassOf ?c3",
+    ),
+    Rule::new(
+        "scm-eqc1",
+        "?c1 owl:equivalentClass ?c2 .",
+        "?c1 rdfs:subClassOf ?c2",
+    ),
+    Rule::new(
+        "scm-eqc1",
+        "?c1 owl:equivalentClass ?c2 .",
+        "?c2 rdfs:subClassOf ?c1",
+    ),
+    Rule::new(
+        "scm-eqc2",
+        "?c1 rdfs:subClassOf ?c2 . ?c2 rdfs:subClassOf ?c1 .",
+        "?c1 owl:equivalentClass ?c2",
+    ),
+    Rule::new(
+        "scm-spo",
+        "?p1 rdfs:subPropertyOf ?p2 . ?p2 rdfs:subPropertyOf ?p3 .",
+        "?p1 rdfs:subPropertyOf ?p3",
+    ),
+    Rule::new(
+        "scm-eqp1",
+        "?p1 owl:equivalentProperty ?p2 .",
+        "?p1 rdfs:subPropertyOf ?p2",
+    ),
+    Rule::new(
+        "scm-eqp1",
+        "?p1 owl:equivalentProperty ?p2 .",
+        "?p2 rdfs:subPropertyOf ?p1",
+    ),
+    Rule::new(
+        "scm-eqp2",
+        "?p1 rdfs:subPropertyOf ?p2 . ?p2 rdfs:subPropertyOf ?p1 .",
+        "?p1 owl:equivalentProperty ?p2",
+    ),
+    Rule::new(
+        "scm-dom1",
+        "?p rdfs:domain ?c1 . ?c1 rdfs:subClassOf ?c2 .",
+        "?p rdfs:domain ?c2",
+    ),
+    Rule::new(
+        "scm-dom2",
+        "?p2 rdfs:domain ?c . ?p1 rdfs:subPropertyOf ?p2 .",
+        "?p1 rdfs:domain ?c",
+    ),
+    Rule::new(
+        "scm-rng1",
+        "?p rdfs:range ?c1 . ?c1 rdfs:subClassOf ?c2 .",
+        "?p rdfs:range ?c2",
+    ),
+    Rule::new(
+        "scm-rng2",
+        "?p2 rdfs:range ?c . ?p1 rdfs:subPropertyOf ?p2 .",
+        "?p1 rdfs:range ?c",
+    ),
+    Rule::new(
+        "scm-hv",
+        "?c1 owl:hasValue ?i ; owl:onProperty ?p1 . ?c2 owl:hasValue ?i ; owl:onProperty ?p2 . \
+         ?p1 rdfs:subPropertyOf ?p2 .",
+        "?c1 rdfs:subClassOf ?c2",
+    ),
+    Rule::new(
+        "scm-svf1",
+        "?c1 owl:someValuesFrom ?y1 ; owl:onProperty ?p . \
+         ?c2 owl:someValuesFrom ?y2 ; owl:onProperty ?p . ?y1 rdfs:subClassOf ?y2 .",
+        "?c1 rdfs:subClassOf ?c2",
+    ),
+    Rule::new(
+        "scm-svf2",
+        "?c1 owl:someValuesFrom ?y ; owl:onProperty ?p1 . \
+         ?c2 owl:someValuesFrom ?y ; owl:onProperty ?p2 . ?p1 rdfs:subPropertyOf ?p2 .",
+        "?c1 rdfs:subClassOf ?c2",
+    ),
+    Rule::new(
+        "scm-avf1",
+        "?c1 owl:allValuesFrom ?y1 ; owl:onProperty ?p . \
+         ?c2 owl:allValuesFrom ?y2 ; owl:onProperty ?p . ?y1 rdfs:subClassOf ?y2 .",
+        "?c1 rdfs:subClassOf ?c2",
+    ),
+    Rule::new(
+        "scm-avf2",
+        "?c1 owl:allValuesFrom ?y ; owl:onProperty ?p1 . \
+         ?c2 owl:allValuesFrom ?y ; owl:onProperty ?p2 . ?p1 rdfs:subPropertyOf ?p2 .",
+        "?c2 rdfs:subClassOf ?c1",
+    ),
+    Rule::new(
+        "scm-int",
+        "?c owl:intersectionOf ?list . ?list rdf:rest*/rdf:first ?member .",
+        "?c rdfs:subClassOf ?member",
+    ),
+    Rule::new(
+        "scm-uni",
+        "?c owl:unionOf ?list . ?list rdf:rest*/rdf:first ?member .",
+        "?member rdfs:subClassOf ?c",
+    ),
+];
+
+/// Lists the nodes of the property chains of the ontology, with the property they imply.
+pub const CHAINS_QUERY: &str = "PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> \
+    PREFIX owl: <http://www.w3.org/2002/07/owl#> \
+    SELECT DISTINCT ?property ?head ?node ?first ?rest WHERE { \
+    ?property owl:propertyChainAxiom ?head . ?head rdf:rest* ?node . \
+    ?node rdf:first ?first ; rdf:rest ?rest }";
+
+/// Returns a `prp-spo2` rule for every property chain listed by the solutions of [`CHAINS_QUERY`].
+///
+/// Chains which are not well-formed lists of named properties are left out.
+pub fn chain_rules(solutions: &[QuerySolution]) -> Vec<Rule> {
+    let mut chains: HashMap<(&Term, &Term), HashMap<&Term, (&Term, &Term)>> = HashMap::new();
+    for solution in solutions {
+        if let (Some(property), Some(head), Some(node), Some(first), Some(rest)) = (
+            solution.get("property"),
+            solution.get("head"),
+            solution.get("node"),
+            solution.get("first"),
+            solution.get("rest"),
+        ) {
+            chains
+                .entry((property, head))
+                .or_default()
+                .insert(node, (first, rest));
+        }
+    }
+
+    let nil = Term::from(NamedNode::new_unchecked(format!("{RDF}nil")));
+    let mut rules = Vec::new();
+    for ((property, head), nodes) in chains {
+        let Term::NamedNode(property) = property else {
+            continue;
+        };
+        let mut links = Vec::new();
+        let mut visited = HashSet::new();
+        let mut node = head;
+        while *node != nil {
+            let Some(&(first, rest)) = nodes.get(node) else {
+                break;
+            };
+            let Term::NamedNode(link) = first else {
+                break;
+            };
+            if !visited.insert(node) {
+                break;
+            }
+            links.push(link);
+            node = rest;
+        }
+        if *node != nil || links.is_empty() {
+            continue;
+        }
+
+        let premises = links
+            .iter()
+            .enumerate()
+            .map(|(position, link)| format!("?u{position} {link} ?u{} .", position + 1))
+            .collect::<Vec<_>>()
+            .join(" ");
+        rules.push(Rule {
+            name: "prp-spo2",
+            premises: Cow::Owned(premises),
+            conclusion: Cow::Owned(format!("?u0 {property} ?u{}", links.len())),
+        });
+    }
+    rules
+}
+
+/// A rule finding an inconsistency.
+#[derive(Debug, Clone, Copy)]
+pub struct Check {
+    /// The name of the rule.
+    pub name: &'static str,
+    /// The graph pattern matching the inconsistent statements.
+    premises: &'static str,
+    /// The description of an inconsistency, naming the variables of the premises in braces.
+    message: &'static str,
+}
+
+impl Check {
+    const fn new(name: &'static str, premises: &'static str, message: &'static str) -> Self {
+        Self {
+            name,
+            premises,
+            message,
+        }
+    }
+
+    /// Returns the SELECT query of the inconsistencies the rule finds.
+    pub fn query(&self) -> String {
+        format!("{PREFIXES}SELECT DISTINCT * WHERE {{ {} }}", self.premises)
+    }
+
+    /// Describes the inconsistency found by a solution of the query, naming the rule.
+    pub fn message(&self, solution: &QuerySolution) -> String {
+        let mut message = self.message.to_string();
+        for (variable, term) in solution.iter() {
+            message = message.replace(&format!("{{{}}}", variable.as_str()), &term.to_string());
+        }
+        format!("{message} ({})", self.name)
+    }
+}
+
+/// The rules finding inconsistencies.
+///
+/// Inconsistencies between two equal resources are only found once, by ordering them.
+pub const CHECKS: &[Check] = &[
+    Check::new(
+        "eq-diff1",
+        "?x owl:sameAs ?y . { ?x owl:differentFrom ?y } UNION { ?y owl:differentFrom ?x } \
+         FILTER(!sameTerm(?x, ?y) && (isBlank(?x) || isBlank(?y) || STR(?x) < STR(?y)))",
+        "{x} and {y} are both the same and different",
+    ),
+    Check::new(
+        "eq-diff1",
+        "?x owl:differentFrom ?y . FILTER(sameTerm(?x, ?y))",
+        "{x} and {y} are both the same and different",
+    ),
+    Check::new(
+        "eq-diff2",
+        "?a a owl:AllDifferent ; owl:members|owl:distinctMembers ?list . \
+         ?list rdf:rest*/rdf:first ?x . ?list rdf:rest*/rdf:first ?y . ?x owl:sameAs ?y . \
+         FILTER(!sameTerm(?x, ?y) && (isBlank(?x) || isBlank(?y) || STR(?x) < STR(?y)))",
+        "{x} and {y} are the same, but members of {a}",
+    ),
+    Check::new(
+        "prp-irp",
+        "?p a owl:IrreflexiveProperty . ?x ?p ?x .",
+        "{x} is related to itself by the irreflexive property {p}",
+    ),
+    Check::new(
+        "prp-asyp",
+        "?p a owl:AsymmetricProperty . ?x ?p ?y . ?y ?p ?x . \
+         FILTER(!sameTerm(?x, ?y) && (isBlank(?x) || isBlank(?y) || STR(?x) < STR(?y)))",
+        "{x} and {y} are related in both directions by the asymmetric property {p}",
+    ),
+    Check::new(
+        "prp-pdw",
+        "?p1 owl:propertyDisjointWith ?p2 . ?x ?p1 ?y . ?x ?p2 ?y .",
+        "{x} and {y} are related by the disjoint properties {p1} and {p2}",
+    ),
+    Check::new(
+        "prp-adp",
+        "?a a owl:AllDisjointProperties ; owl:members ?list . \
+         ?list rdf:rest*/rdf:first ?p1 . ?list rdf:rest*/rdf:first ?p2 . \
+         FILTER(!sameTerm(?p1, ?p2) && (isBlank(?p1) || isBlank(?p2) || STR(?p1) < STR(?p2))) \
+         ?x ?p1 ?y . ?x ?p2 ?y .",
+        "{x} and {y} are related by the disjoint properties {p1} and {p2}",
+    ),
+    Check::new(
+        "prp-npa1",
+        "?n owl:sourceIndividual ?i1 ; owl:assertionProperty ?p ; owl:targetIndividual ?i2 . \
+         ?i1 ?p ?i2 .",
+        "{i1} is related to {i2} by {p}, contradicting the negative property assertion {n}",
+    ),
+    Check::new(
+        "prp-npa2",
+        "?n owl:sourceIndividual ?i1 ; owl:assertionProperty ?p ; owl:targetValue ?i2 . \
+         ?i1 ?p ?i2 .",
+        "{i1} is related to {i2} by {p}, contradicting the negative property assertion {n}",
+    ),
+    Check::new(
+        "cls-nothing2",
+        "?x a owl:Nothing .",
+        "{x} is an instance of owl:Nothing",
+    ),
+    Check::new(
+        "cls-com",
+        "?c1 owl:complementOf ?c2 . ?x a ?c1 , ?c2 .",
+        "{x} is an instance of the complementary classes {c1} and {c2}",
+    ),
+    Check::new(
+        "cax-dw",
+        "?c1 owl:disjointWith ?c2 . ?x a ?c1 , ?c2 .",
+        "{x} is an instance of the disjoint classes {c1} and {c2}",
+    ),
+    Check::new(
+        "cax-adc",
+        "?a a owl:AllDisjointClasses ; owl:members ?list . \
+         ?list rdf:rest*/rdf:first ?c1 . ?list rdf:rest*/rdf:first ?c2 . \
+         FILTER(!sameTerm(?c1, ?c2) && (isBlank(?c1) || isBlank(?c2) || STR(?c1) < STR(?c2))) \
+         ?x a ?c1 , ?c2 .",
+        "{x} is an instance of the disjoint classes {c1} and {c2}",
+    ),
+    Check::new(
+        "cls-maxc1",
+        "?c owl:maxCardinality ?n ; owl:onProperty ?p . FILTER(STR(?n) = \"0\") ?x a ?c ; ?p ?y .",
+        "{x} has a value for {p}, but is an instance of {c}, which allows none",
+    ),
+    Check::new(
+        "cls-maxqc1",
+        "?c owl:maxQualifiedCardinality ?n ; owl:onProperty ?p ; owl:onClass ?d . \
+         FILTER(STR(?n) = \"0\") ?x a ?c ; ?p ?y . ?y a ?d .",
+        "{x} has a value for {p}, but is an instance of {c}, which allows none",
+    ),
+    Check::new(
+        "cls-maxqc2",
+        "?c owl:maxQualifiedCardinality ?n ; owl:onProperty ?p ; owl:onClass owl:Thing . \
+         FILTER(STR(?n) = \"0\") ?x a ?c ; ?p ?y .",
+        "{x} has a value for {p}, but is an instance of {c}, which allows none",
+    ),
+];
+
+/// The predicates of the statements which hold for every resource, relating it to itself.
+const TRIVIAL_PREDICATES: &[&str] = &[
+    "http://www.w3.org/2002/07/owl#sameAs",
+    "http://www.w3.org/2002/07/owl#equivalentClass",
+    "http://www.w3.org/2002/07/owl#equivalentProperty",
+    "http://www.w3.org/2000/01/rdf-schema#subClassOf",
+    "http://www.w3.org/2000/01/rdf-schema#subPropertyOf",
+];
+
+/// Returns whether a conclusion is a statement worth adding.
+///
+/// Statements which hold for every resource, such as `?x owl:sameAs ?x`, are left out.
+pub fn admits(triple: &Triple) -> bool {
+    Term::from(triple.subject.clone()) != triple.object
+        || !TRIVIAL_PREDICATES.contains(&triple.predicate.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use spargebra::SparqlParser;
+
+    #[test]
+    fn test_queries_parse() -> Result<(), Box<dyn std::error::Error>> {
+        for rule in RULES {
+            SparqlParser::new().parse_query(&rule.query())?;
+        }
+        for check in CHECKS {
+            SparqlParser::new().parse_query(&check.query())?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_admits() {
+        let node = |iri: &str| NamedNode::new_unchecked(format!("http://example.com#{iri}"));
+        let sub_class_of = NamedNode::new_unchecked(TRIVIAL_PREDICATES[3]);
+        assert!(!admits(&Triple::new(
+            node("A"),
+            sub_class_of.clone(),
+            node("A")
+        )));
+        assert!(admits(&Triple::new(node("A"), sub_class_of, node("B"))));
+        assert!(admits(&Triple::new(
+            node("p"),
+            NamedNode::new_unchecked(format!("{RDF}type")),
+            node("p")
+        )));
+    }
+}
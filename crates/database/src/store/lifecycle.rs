@@ -0,0 +1,270 @@
+//! Lifecycle management of user graphs in the global store.
+//!
+//! Every user graph loaded into the store is registered here along with its size
+//! and when it was last used. This lets the store evict graphs that have been idle
+//! for longer than `VOWLGRAPHER_GRAPH_IDLE_TTL_SECS` and enforce per-user quotas.
+
+use dashmap::DashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::VOWLGRAPHER_ENVIRONMENT;
+
+use crate::store::imports::{is_import_graph, is_import_graph_of};
+
+/// The prefix of all graph names owned by a user.
+const USER_GRAPH_PREFIX: &str = "urn:vowlgrapher:user:";
+
+/// Bookkeeping of every registered graph, keyed by the graph name.
+static GRAPH_REGISTRY: LazyLock<DashMap<String, GraphUsage>> = LazyLock::new(DashMap::new);
+
+/// Usage statistics of a single named graph.
+#[derive(Debug, Clone)]
+pub struct GraphUsage {
+    /// The user owning the graph.
+    pub user_id: Option<String>,
+    /// The number of quads in the graph.
+    pub quad_count: u64,
+    /// The size, in bytes, of the input the graph was loaded from.
+    pub byte_size: u64,
+    /// When the graph was loaded into the store.
+    pub loaded_at: SystemTime,
+    /// When the graph was last read or written.
+    pub last_access: Instant,
+}
+
+/// Registers a graph loaded at `loaded_at`, replacing any previous registration.
+pub fn register_graph(graph_name: &str, quad_count: u64, byte_size: u64, loaded_at: SystemTime) {
+    GRAPH_REGISTRY.insert(
+        graph_name.to_string(),
+        GraphUsage {
+            user_id: user_of_graph(graph_name),
+            quad_count,
+            byte_size,
+            loaded_at,
+            last_access: Instant::now(),
+        },
+    );
+}
+
+/// Marks a graph as used right now.
+pub fn touch_graph(graph_name: &str) {
+    if let Some(mut usage) = GRAPH_REGISTRY.get_mut(graph_name) {
+        usage.last_access = Instant::now();
+    }
+}
+
+/// Removes a graph from the registry.
+pub fn unregister_graph(graph_name: &str) {
+    GRAPH_REGISTRY.remove(graph_name);
+}
+
+/// Returns the usage statistics of a graph, if it is registered.
+pub fn graph_usage(graph_name: &str) -> Option<GraphUsage> {
+    GRAPH_REGISTRY.get(graph_name).map(|usage| usage.clone())
+}
+
+/// Returns the registered graphs whose names start with `prefix`, including import graphs,
+/// along with their usage statistics.
+pub fn graph_usages_with_prefix(prefix: &str) -> Vec<(String, GraphUsage)> {
+    GRAPH_REGISTRY
+        .iter()
+        .filter(|entry| entry.key().starts_with(prefix))
+        .map(|entry| (entry.key().clone(), entry.value().clone()))
+        .collect()
+}
+
+/// Returns the user graphs which have been idle for longer than the configured TTL.
+///
+/// Import graphs are never returned, since they are evicted along with the graph importing them.
+pub fn idle_graphs() -> Vec<String> {
+    match VOWLGRAPHER_ENVIRONMENT.graph_idle_ttl_secs {
+        0 => Vec::new(),
+        ttl => graphs_idle_for(Duration::from_secs(ttl)),
+    }
+}
+
+/// Returns the user graphs which have been idle for longer than `ttl`, except import graphs.
+fn graphs_idle_for(ttl: Duration) -> Vec<String> {
+    GRAPH_REGISTRY
+        .iter()
+        .filter(|entry| {
+            entry.user_id.is_some()
+                && !is_import_graph(entry.key())
+                && entry.last_access.elapsed() > ttl
+        })
+        .map(|entry| entry.key().clone())
+        .collect()
+}
+
+/// Checks whether a user may store `quad_count` quads loaded from `byte_size` bytes
+/// in the graph named `graph_name`.
+///
+/// The current content of `graph_name` and its imports is not counted towards the quota,
+/// since it is replaced by the new quads.
+///
+/// # Errors
+/// Returns [`VOWLGrapherStoreErrorKind::QuotaExceeded`] if the user would exceed
+/// either the quad or byte quota.
+pub fn check_quota(
+    graph_name: &str,
+    quad_count: u64,
+    byte_size: u64,
+) -> Result<(), VOWLGrapherStoreError> {
+    check_quota_within(
+        graph_name,
+        quad_count,
+        byte_size,
+        VOWLGRAPHER_ENVIRONMENT.max_user_quads,
+        VOWLGRAPHER_ENVIRONMENT.max_user_bytes,
+    )
+}
+
+/// Checks the quota like [`check_quota`], with a limit of `max_quads` quads and `max_bytes` bytes.
+///
+/// # Errors
+/// Returns [`VOWLGrapherStoreErrorKind::QuotaExceeded`] if the user would exceed either limit.
+fn check_quota_within(
+    graph_name: &str,
+    quad_count: u64,
+    byte_size: u64,
+    max_quads: u64,
+    max_bytes: bytesize::ByteSize,
+) -> Result<(), VOWLGrapherStoreError> {
+    let Some(user_id) = user_of_graph(graph_name) else {
+        return Ok(());
+    };
+
+    let (used_quads, used_bytes) = GRAPH_REGISTRY
+        .iter()
+        .filter(|entry| {
+            entry.key() != graph_name
+                && !is_import_graph_of(entry.key(), graph_name)
+                && entry.user_id.as_deref() == Some(user_id.as_str())
+        })
+        .fold((0, 0), |(quads, bytes), entry| {
+            (quads + entry.quad_count, bytes + entry.byte_size)
+        });
+
+    if max_quads > 0 && used_quads + quad_count > max_quads {
+        return Err(VOWLGrapherStoreErrorKind::QuotaExceeded(format!(
+            "Loading '{graph_name}' would store {} quads, exceeding the limit of {max_quads} quads per user. Remove a loaded ontology and try again",
+            used_quads + quad_count
+        ))
+        .into());
+    }
+
+    if max_bytes.0 > 0 && used_bytes + byte_size > max_bytes.0 {
+        return Err(VOWLGrapherStoreErrorKind::QuotaExceeded(format!(
+            "Loading '{graph_name}' would store {} of input, exceeding the limit of {} per user. Remove a loaded ontology and try again",
+            bytesize::ByteSize(used_bytes + byte_size).display().si(),
+            max_bytes.display().si()
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Returns the user owning a graph, if it is a user graph.
+pub fn user_of_graph(graph_name: &str) -> Option<String> {
+    graph_name
+        .strip_prefix(USER_GRAPH_PREFIX)?
+        .split_once(':')
+        .map(|(user_id, _)| user_id.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytesize::ByteSize;
+
+    /// Marks a registered graph as last used `idle` ago.
+    fn set_idle(graph_name: &str, idle: Duration) {
+        if let Some(mut usage) = GRAPH_REGISTRY.get_mut(graph_name)
+            && let Some(last_access) = Instant::now().checked_sub(idle)
+        {
+            usage.last_access = last_access;
+        }
+    }
+
+    #[test]
+    fn test_quota() {
+        let graph = "urn:vowlgrapher:user:quota:graph:a.ttl";
+        let import = "urn:vowlgrapher:user:quota:graph:a.ttl:import:http%3A%2F%2Fexample.com";
+        let other = "urn:vowlgrapher:user:quota:graph:b.ttl";
+        register_graph(graph, 60, 600, SystemTime::now());
+        register_graph(import, 30, 300, SystemTime::now());
+        register_graph(other, 50, 500, SystemTime::now());
+
+        assert!(
+            check_quota_within(graph, 50, 500, 100, ByteSize(1000)).is_ok(),
+            "the replaced graph and its imports should not count"
+        );
+        assert!(
+            check_quota_within(graph, 51, 0, 100, ByteSize(0)).is_err(),
+            "the quad quota should be enforced"
+        );
+        assert!(
+            check_quota_within(graph, 0, 501, 0, ByteSize(1000)).is_err(),
+            "the byte quota should be enforced"
+        );
+        assert!(
+            check_quota_within(graph, u64::MAX / 2, u64::MAX / 2, 0, ByteSize(0)).is_ok(),
+            "a limit of 0 should disable the quota"
+        );
+        assert!(
+            check_quota_within(
+                "urn:vowlgrapher:user:quota-other:graph:a.ttl",
+                100,
+                0,
+                100,
+                ByteSize(0)
+            )
+            .is_ok(),
+            "graphs of other users should not count"
+        );
+
+        for graph_name in [graph, import, other] {
+            unregister_graph(graph_name);
+        }
+    }
+
+    #[test]
+    fn test_idle_graphs() {
+        let idle = "urn:vowlgrapher:user:idle:graph:a.ttl";
+        let idle_import = "urn:vowlgrapher:user:idle:graph:a.ttl:import:http%3A%2F%2Fexample.com";
+        let active = "urn:vowlgrapher:user:idle:graph:b.ttl";
+        let shared = "urn:vowlgrapher:graph:idle.ttl";
+        for graph_name in [idle, idle_import, active, shared] {
+            register_graph(graph_name, 1, 1, SystemTime::now());
+        }
+        set_idle(idle, Duration::from_secs(120));
+        set_idle(idle_import, Duration::from_secs(120));
+        set_idle(shared, Duration::from_secs(120));
+
+        let evicted = graphs_idle_for(Duration::from_secs(60));
+        assert!(evicted.iter().any(|graph_name| graph_name == idle));
+        assert!(
+            !evicted.iter().any(|graph_name| graph_name == idle_import),
+            "import graphs are evicted along with their graph"
+        );
+        assert!(!evicted.iter().any(|graph_name| graph_name == active));
+        assert!(
+            !evicted.iter().any(|graph_name| graph_name == shared),
+            "graphs without a user are never evicted"
+        );
+
+        touch_graph(idle);
+        assert!(
+            !graphs_idle_for(Duration::from_secs(60))
+                .iter()
+                .any(|graph_name| graph_name == idle),
+            "a touched graph is no longer idle"
+        );
+
+        for graph_name in [idle, idle_import, active, shared] {
+            unregister_graph(graph_name);
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! Statistics about the ontology in a named graph, computed with SPARQL.
+//!
+//! Only the graph itself is measured. Its imports are counted, but not inspected.
+
+use futures::StreamExt;
+use log::info;
+use rdf_fusion::execution::results::{QueryResults, QuerySolution};
+use rdf_fusion::model::Term;
+use std::collections::HashMap;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{AxiomCount, MetricsFormat, OntologyMetrics};
+
+use crate::store::{VOWLGrapherStore, lifecycle};
+
+/// The prefixes used by the metric queries.
+const PREFIXES: &str = "PREFIX owl: <http://www.w3.org/2002/07/owl#>
+PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>
+";
+
+/// The types of the entities whose labels, comments and deprecation are measured.
+const ENTITY_TYPES: &str = "owl:Class rdfs:Class owl:ObjectProperty owl:DatatypeProperty owl:AnnotationProperty rdf:Property owl:NamedIndividual";
+
+/// The predicates stating an axiom, with the name of the axiom in OWL 2 Functional Syntax.
+const AXIOM_PREDICATES: &str = r#"(rdfs:subClassOf "SubClassOf")
+(owl:equivalentClass "EquivalentClasses")
+(owl:disjointWith "DisjointClasses")
+(owl:disjointUnionOf "DisjointUnion")
+(rdfs:subPropertyOf "SubPropertyOf")
+(owl:propertyChainAxiom "SubPropertyChainOf")
+(owl:equivalentProperty "EquivalentProperties")
+(owl:propertyDisjointWith "DisjointProperties")
+(owl:inverseOf "InverseObjectProperties")
+(rdfs:domain "PropertyDomain")
+(rdfs:range "PropertyRange")
+(owl:hasKey "HasKey")
+(owl:sameAs "SameIndividual")
+(owl:differentFrom "DifferentIndividuals")"#;
+
+/// The types stating an axiom, with the name of the axiom in OWL 2 Functional Syntax.
+const AXIOM_TYPES: &str = r#"(owl:Class "Declaration")
+(owl:ObjectProperty "Declaration")
+(owl:DatatypeProperty "Declaration")
+(owl:AnnotationProperty "Declaration")
+(owl:NamedIndividual "Declaration")
+(rdfs:Datatype "Declaration")
+(owl:FunctionalProperty "FunctionalProperty")
+(owl:InverseFunctionalProperty "InverseFunctionalObjectProperty")
+(owl:TransitiveProperty "TransitiveObjectProperty")
+(owl:SymmetricProperty "SymmetricObjectProperty")
+(owl:AsymmetricProperty "AsymmetricObjectProperty")
+(owl:ReflexiveProperty "ReflexiveObjectProperty")
+(owl:IrreflexiveProperty "IrreflexiveObjectProperty")
+(owl:AllDisjointClasses "DisjointClasses")
+(owl:AllDisjointProperties "DisjointProperties")
+(owl:AllDifferent "DifferentIndividuals")
+(owl:NegativePropertyAssertion "NegativePropertyAssertion")"#;
+
+/// The built-in annotation properties.
+const ANNOTATION_PREDICATES: &str = "rdfs:label rdfs:comment rdfs:seeAlso rdfs:isDefinedBy owl:deprecated owl:versionInfo owl:priorVersion owl:backwardCompatibleWith owl:incompatibleWith";
+
+impl VOWLGrapherStore {
+    /// Computes statistics about the ontology in a graph of the user.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or the store could not be queried.
+    pub async fn metrics(
+        &self,
+        graph_name: &str,
+    ) -> Result<OntologyMetrics, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph = self.get_graph_name(graph_name);
+        if self.graph_len(&graph).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{graph_name}' does not exist"
+            ))
+            .into());
+        }
+        lifecycle::touch_graph(&graph);
+
+        let count_entities = |types: &str| {
+            format!(
+                "SELECT (COUNT(DISTINCT ?entity) AS ?count) WHERE {{ GRAPH <{graph}> {{ ?entity a ?type FILTER(isIRI(?entity)) VALUES ?type {{ {types} }} }} }}"
+            )
+        };
+        let class_count = self.count(&count_entities("owl:Class rdfs:Class")).await?;
+        let object_property_count = self.count(&count_entities("owl:ObjectProperty")).await?;
+        let datatype_property_count = self.count(&count_entities("owl:DatatypeProperty")).await?;
+        let annotation_property_count = self
+            .count(&count_entities("owl:AnnotationProperty"))
+            .await?;
+        let individual_count = self
+            .count(&format!(
+                "SELECT (COUNT(DISTINCT ?individual) AS ?count) WHERE {{ GRAPH <{graph}> {{
+                    {{ ?individual a owl:NamedIndividual }}
+                    UNION {{ ?individual a ?class . ?class a owl:Class }}
+                    FILTER(isIRI(?individual))
+                }} }}"
+            ))
+            .await?;
+
+        let mut axiom_counts = Vec::new();
+        for solution in self
+            .solutions(&format!(
+                "SELECT ?axiom (COUNT(*) AS ?count) WHERE {{ GRAPH <{graph}> {{
+                    {{ ?s ?p ?o VALUES (?p ?axiom) {{ {AXIOM_PREDICATES} }} }}
+                    UNION {{ ?s a ?o VALUES (?o ?axiom) {{ {AXIOM_TYPES} }} }}
+                    UNION {{ ?s a ?o . ?o a owl:Class FILTER(isIRI(?s)) BIND(\"ClassAssertion\" AS ?axiom) }}
+                    UNION {{ ?s ?p ?o VALUES ?p {{ {ANNOTATION_PREDICATES} }} BIND(\"AnnotationAssertion\" AS ?axiom) }}
+                }} }} GROUP BY ?axiom"
+            ))
+            .await?
+        {
+            if let Some(Term::Literal(axiom)) = solution.get("axiom") {
+                axiom_counts.push(AxiomCount {
+                    axiom: axiom.value().to_string(),
+                    count: count_value(solution.get("count")),
+                });
+            }
+        }
+        axiom_counts.sort_by(|a, b| a.axiom.cmp(&b.axiom));
+
+        let mut superclasses = HashMap::<String, Vec<String>>::new();
+        for solution in self
+            .solutions(&format!(
+                "SELECT ?class ?superclass WHERE {{ GRAPH <{graph}> {{
+                    ?class a ?type FILTER(isIRI(?class)) VALUES ?type {{ owl:Class rdfs:Class }}
+                    OPTIONAL {{
+                        ?class rdfs:subClassOf ?superclass
+                        FILTER(isIRI(?superclass) && ?superclass != owl:Thing && ?superclass != ?class)
+                    }}
+                }} }}"
+            ))
+            .await?
+        {
+            let Some(Term::NamedNode(class)) = solution.get("class") else {
+                continue;
+            };
+            let entry = superclasses.entry(class.as_str().to_string()).or_default();
+            if let Some(Term::NamedNode(superclass)) = solution.get("superclass") {
+                entry.push(superclass.as_str().to_string());
+            }
+        }
+        let hierarchy = Hierarchy::new(&superclasses);
+
+        let mut entity_shares = (0.0, 0.0, 0.0);
+        if let Some(solution) = self
+            .solutions(&format!(
+                "SELECT (COUNT(DISTINCT ?entity) AS ?entities) (COUNT(DISTINCT ?labelled) AS ?labels)
+                    (COUNT(DISTINCT ?commented) AS ?comments) (COUNT(DISTINCT ?deprecated) AS ?deprecations)
+                WHERE {{ GRAPH <{graph}> {{
+                    ?entity a ?type FILTER(isIRI(?entity)) VALUES ?type {{ {ENTITY_TYPES} }}
+                    OPTIONAL {{ ?entity rdfs:label ?label BIND(?entity AS ?labelled) }}
+                    OPTIONAL {{ ?entity rdfs:comment ?comment BIND(?entity AS ?commented) }}
+                    OPTIONAL {{
+                        ?entity owl:deprecated ?flag FILTER(STR(?flag) IN (\"true\", \"1\"))
+                        BIND(?entity AS ?deprecated)
+                    }}
+                }} }}"
+            ))
+            .await?
+            .first()
+        {
+            let entities = count_value(solution.get("entities"));
+            entity_shares = (
+                share(count_value(solution.get("labels")), entities),
+                share(count_value(solution.get("comments")), entities),
+                share(count_value(solution.get("deprecations")), entities),
+            );
+        }
+
+        let import_count = self.ontology_imports(&graph).await?.len() as u64;
+
+        info!("Computed metrics of graph '{graph}'");
+        Ok(OntologyMetrics {
+            graph_name: graph_name.to_string(),
+            class_count,
+            object_property_count,
+            datatype_property_count,
+            annotation_property_count,
+            individual_count,
+            axiom_counts,
+            hierarchy_depth: hierarchy.depth,
+            hierarchy_breadth: hierarchy.breadth,
+            max_subclasses: hierarchy.max_subclasses,
+            labelled_share: entity_shares.0,
+            commented_share: entity_shares.1,
+            deprecated_share: entity_shares.2,
+            import_count,
+        })
+    }
+
+    /// Computes statistics about the ontology in a graph of the user and serializes them.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or the store could not be queried.
+    pub async fn serialize_metrics(
+        &self,
+        graph_name: &str,
+        format: MetricsFormat,
+    ) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        let metrics = self.metrics(graph_name).await?;
+        Ok(match format {
+            MetricsFormat::Json => {
+                serde_json::to_vec_pretty(&metrics).map_err(std::io::Error::from)?
+            }
+            MetricsFormat::Csv => metrics.to_csv().into_bytes(),
+        })
+    }
+
+    /// Returns the solutions of a SELECT query using the metric prefixes.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn solutions(&self, query: &str) -> Result<Vec<QuerySolution>, VOWLGrapherStoreError> {
+        let mut solutions = Vec::new();
+        if let QueryResults::Solutions(mut stream) =
+            self.session.query(&format!("{PREFIXES}{query}")).await?
+        {
+            while let Some(solution) = stream.next().await {
+                solutions.push(solution?);
+            }
+        }
+        Ok(solutions)
+    }
+
+    /// Returns the `?count` of the first solution of a SELECT query using the metric prefixes.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn count(&self, query: &str) -> Result<u64, VOWLGrapherStoreError> {
+        Ok(self
+            .solutions(query)
+            .await?
+            .first()
+            .map_or(0, |solution| count_value(solution.get("count"))))
+    }
+}
+
+/// Returns the value of a count bound in a solution, or 0 if it is not bound to a number.
+fn count_value(term: Option<&Term>) -> u64 {
+    match term {
+        Some(Term::Literal(count)) => count.value().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Returns `part` as a share of `total`, or 0 if `total` is 0.
+#[expect(clippy::cast_precision_loss)]
+fn share(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// The shape of a subclass hierarchy.
+#[derive(Debug)]
+struct Hierarchy {
+    /// The number of levels of the hierarchy.
+    depth: u64,
+    /// The largest number of classes on one level.
+    breadth: u64,
+    /// The largest number of direct subclasses of a single class.
+    max_subclasses: u64,
+}
+
+impl Hierarchy {
+    /// Measures the hierarchy given by the named superclasses of every class.
+    ///
+    /// Classes without superclasses are on the first level, and every other class is one
+    /// level below its lowest superclass.
+    fn new(superclasses: &HashMap<String, Vec<String>>) -> Self {
+        let levels = class_levels(superclasses);
+
+        let mut classes_per_level = HashMap::<u64, u64>::new();
+        for level in levels.values() {
+            *classes_per_level.entry(*level).or_default() += 1;
+        }
+        let mut subclass_counts = HashMap::<&str, u64>::new();
+        for parents in superclasses.values() {
+            for parent in parents {
+                *subclass_counts.entry(parent.as_str()).or_default() += 1;
+            }
+        }
+
+        Self {
+            depth: levels.values().copied().max().unwrap_or(0),
+            breadth: classes_per_level.values().copied().max().unwrap_or(0),
+            max_subclasses: subclass_counts.values().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+/// Returns the level of every class in the hierarchy given by the named superclasses of every class.
+///
+/// The classes on a cycle are equivalent and share a level. Levels are computed without recursion
+/// over the strongly connected components of the hierarchy, so long chains of subclasses
+/// cannot overflow the stack.
+fn class_levels(superclasses: &HashMap<String, Vec<String>>) -> HashMap<&str, u64> {
+    let mut classes = Vec::<&str>::new();
+    let mut indices = HashMap::<&str, usize>::new();
+    for class in superclasses.keys().chain(superclasses.values().flatten()) {
+        indices.entry(class.as_str()).or_insert_with(|| {
+            classes.push(class.as_str());
+            classes.len() - 1
+        });
+    }
+    let mut parents = vec![Vec::new(); classes.len()];
+    let mut children = vec![Vec::new(); classes.len()];
+    for (class, class_superclasses) in superclasses {
+        let class = indices[class.as_str()];
+        for superclass in class_superclasses {
+            let superclass = indices[superclass.as_str()];
+            parents[class].push(superclass);
+            children[superclass].push(class);
+        }
+    }
+
+    // Orders the classes by when the depth-first search through their superclasses finishes.
+    let mut finished = Vec::with_capacity(classes.len());
+    let mut visited = vec![false; classes.len()];
+    for &start in indices.values() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![(start, 0)];
+        while let Some((class, next)) = stack.pop() {
+            if let Some(&parent) = parents[class].get(next) {
+                stack.push((class, next + 1));
+                if !visited[parent] {
+                    visited[parent] = true;
+                    stack.push((parent, 0));
+                }
+            } else {
+                finished.push(class);
+            }
+        }
+    }
+
+    // Searching through the subclasses in reverse finishing order finds one component at a
+    // time, every component before the components of its superclasses.
+    let mut component = vec![None; classes.len()];
+    let mut components = Vec::<Vec<usize>>::new();
+    for &start in finished.iter().rev() {
+        if component[start].is_some() {
+            continue;
+        }
+        let id = components.len();
+        component[start] = Some(id);
+        let mut members = Vec::new();
+        let mut stack = vec![start];
+        while let Some(class) = stack.pop() {
+            members.push(class);
+            for &child in &children[class] {
+                if component[child].is_none() {
+                    component[child] = Some(id);
+                    stack.push(child);
+                }
+            }
+        }
+        components.push(members);
+    }
+
+    // Walking the components backwards reaches every superclass before its subclasses.
+    let mut component_levels = vec![1_u64; components.len()];
+    for (id, members) in components.iter().enumerate().rev() {
+        let level = members
+            .iter()
+            .flat_map(|&class| &parents[class])
+            .filter_map(|&parent| component[parent].filter(|&other| other != id))
+            .map(|other| component_levels[other] + 1)
+            .max()
+            .unwrap_or(1);
+        component_levels[id] = level;
+    }
+
+    classes
+        .iter()
+        .zip(component)
+        .filter_map(|(class, id)| Some((*class, component_levels[id?])))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::imports::import_graph_name;
+    use rdf_fusion::model::{NamedNode, Quad};
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    #[test]
+    fn test_hierarchy() {
+        let superclasses = [
+            ("A", vec![]),
+            ("B", vec!["A"]),
+            ("C", vec!["A"]),
+            ("D", vec!["B", "C"]),
+            ("E", vec!["D", "A"]),
+            ("F", vec!["F2"]),
+            ("F2", vec!["F"]),
+        ]
+        .into_iter()
+        .map(|(class, parents)| {
+            (
+                class.to_string(),
+                parents.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+        let hierarchy = Hierarchy::new(&superclasses);
+        assert_eq!(hierarchy.depth, 4);
+        assert_eq!(hierarchy.max_subclasses, 3);
+        assert_eq!(hierarchy.breadth, 3);
+    }
+
+    #[test]
+    fn test_long_hierarchy() {
+        let superclasses = (1..100_000)
+            .map(|class| (format!("C{class}"), vec![format!("C{}", class - 1)]))
+            .collect::<HashMap<_, _>>();
+
+        let hierarchy = Hierarchy::new(&superclasses);
+        assert_eq!(hierarchy.depth, 100_000);
+        assert_eq!(hierarchy.breadth, 1);
+        assert_eq!(hierarchy.max_subclasses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_metrics() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let ontology = br#"@prefix : <http://example.com/> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            <http://example.com/a> a owl:Ontology ; owl:imports <http://example.com/b> .
+            :Animal a owl:Class ; rdfs:label "Animal" ; rdfs:comment "A living being" .
+            :Dog a owl:Class ; rdfs:label "Dog" ; rdfs:subClassOf :Animal .
+            :Cat a owl:Class ; rdfs:subClassOf :Animal ; owl:deprecated true .
+            :owns a owl:ObjectProperty ; rdfs:domain :Animal .
+            :rex a :Dog ."#;
+        store
+            .insert_bytes(ontology, "animals", DataType::TTL, false)
+            .await?;
+        let graph = store.get_graph_name("animals");
+        store
+            .session
+            .extend([Quad::new(
+                NamedNode::new("http://example.com/b")?,
+                NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type")?,
+                NamedNode::new("http://www.w3.org/2002/07/owl#Ontology")?,
+                NamedNode::new(import_graph_name(&graph, "http://example.com/b"))?,
+            )])
+            .await?;
+
+        let metrics = store.metrics("animals").await?;
+        assert_eq!(metrics.class_count, 3);
+        assert_eq!(metrics.object_property_count, 1);
+        assert_eq!(metrics.datatype_property_count, 0);
+        assert_eq!(metrics.individual_count, 1);
+        assert_eq!(
+            metrics
+                .axiom_counts
+                .iter()
+                .map(|axiom| (axiom.axiom.as_str(), axiom.count))
+                .collect::<Vec<_>>(),
+            [
+                ("AnnotationAssertion", 4),
+                ("ClassAssertion", 1),
+                ("Declaration", 4),
+                ("PropertyDomain", 1),
+                ("SubClassOf", 2),
+            ]
+        );
+        assert_eq!(
+            (
+                metrics.hierarchy_depth,
+                metrics.hierarchy_breadth,
+                metrics.max_subclasses
+            ),
+            (2, 2, 2)
+        );
+        // Of the three classes and the property, two are labelled, one commented and one deprecated.
+        for (share, expected) in [
+            (metrics.labelled_share, 0.5),
+            (metrics.commented_share, 0.25),
+            (metrics.deprecated_share, 0.25),
+        ] {
+            assert!(
+                (share - expected).abs() < f64::EPSILON,
+                "{share} != {expected}"
+            );
+        }
+        assert_eq!(metrics.import_count, 1);
+
+        let json = store
+            .serialize_metrics("animals", MetricsFormat::Json)
+            .await?;
+        assert_eq!(
+            serde_json::from_slice::<OntologyMetrics>(&json).map_err(std::io::Error::from)?,
+            metrics
+        );
+        let csv = String::from_utf8(
+            store
+                .serialize_metrics("animals", MetricsFormat::Csv)
+                .await?,
+        )
+        .map_err(|e| VOWLGrapherStoreError::from(e.to_string()))?;
+        let rows = csv.split_terminator("\r\n").collect::<Vec<_>>();
+        assert_eq!(rows.first(), Some(&"metric,value"));
+        for row in [
+            "graph_name,animals",
+            "class_count,3",
+            "individual_count,1",
+            "labelled_share,0.5",
+            "deprecated_share,0.25",
+            "import_count,1",
+            "axioms.SubClassOf,2",
+        ] {
+            assert!(rows.contains(&row), "missing row {row}");
+        }
+        Ok(())
+    }
+}
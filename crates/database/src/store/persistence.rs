@@ -0,0 +1,486 @@
+//! On-disk persistence of the graph database.
+//!
+//! The underlying store only lives in memory. To let graphs survive a server restart,
+//! every named graph is mirrored to its own N-Triples file in the directory given by
+//! `VOWLGRAPHER_STORE_PATH`, next to a small metadata file recording when it was loaded
+//! and, for a graph being harvested, a file recording the cursor of the harvest.
+//! The graphs of a user are read back into memory when the user first returns,
+//! so graphs of users who never return do not occupy memory and are eventually swept from disk.
+
+use log::{info, warn};
+use rdf_fusion::io::RdfFormat;
+use rdf_fusion::model::NamedNodeRef;
+use rdf_fusion::store::Store;
+use std::fmt::Write as _;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use vowlgrapher_parser::errors::VOWLGrapherStoreError;
+use vowlgrapher_parser::parser_util::parser_from_path;
+use vowlgrapher_util::prelude::{DataType, VOWLGRAPHER_ENVIRONMENT};
+
+/// The file extension of persisted graphs.
+const GRAPH_FILE_EXTENSION: &str = "nt";
+
+/// The file extension of the metadata of persisted graphs.
+const META_FILE_EXTENSION: &str = "meta";
+
+/// The file extension of the cursors of harvests into persisted graphs.
+const HARVEST_FILE_EXTENSION: &str = "harvest";
+
+/// The name of the file holding the key which signs the session cookies.
+const SESSION_KEY_FILE: &str = "session.key";
+
+/// A graph read back from disk.
+#[derive(Debug)]
+pub struct PersistedGraph {
+    /// The name of the graph.
+    pub graph_name: String,
+    /// The number of quads in the graph.
+    pub quad_count: u64,
+    /// The size, in bytes, of the input the graph was originally loaded from.
+    pub byte_size: u64,
+    /// When the graph was originally loaded into the store.
+    pub loaded_at: SystemTime,
+}
+
+/// A directory on disk mirroring the named graphs of the store.
+#[derive(Debug)]
+pub struct StoreBacking {
+    /// The directory containing one file per named graph.
+    root: PathBuf,
+}
+
+impl StoreBacking {
+    /// Returns the backing configured by `VOWLGRAPHER_STORE_PATH`, if any.
+    ///
+    /// The directory is created if it does not exist.
+    pub fn from_environment() -> Option<Self> {
+        let root = PathBuf::from(VOWLGRAPHER_ENVIRONMENT.store_path.as_ref()?);
+        match std::fs::create_dir_all(&root) {
+            Ok(()) => {
+                info!("Persisting graph database in '{}'", root.display());
+                Some(Self { root })
+            }
+            Err(e) => {
+                warn!(
+                    "Could not create store directory '{}': {e}. Falling back to an in-memory store",
+                    root.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Loads the persisted graphs whose names start with `prefix` into the store.
+    ///
+    /// Returns the restored graphs.
+    ///
+    /// # Errors
+    /// Returns an error if the store directory cannot be read or the store fails to load a graph.
+    pub async fn restore(
+        &self,
+        store: &Store,
+        prefix: &str,
+    ) -> Result<Vec<PersistedGraph>, VOWLGrapherStoreError> {
+        let start_time = Instant::now();
+        let mut restored = Vec::new();
+        let mut quad_total = 0;
+
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            let Some(graph_name) = Self::graph_name_of(&path) else {
+                continue;
+            };
+            if !graph_name.starts_with(prefix) {
+                continue;
+            }
+
+            match parser_from_path(&path, DataType::NTriples, false, &graph_name) {
+                Ok(quads) => {
+                    let quad_count = u64::try_from(quads.len()).unwrap_or(u64::MAX);
+                    store.extend(quads).await?;
+                    let (loaded_at, byte_size) = self.read_meta(&graph_name).unwrap_or_else(|| {
+                        (
+                            SystemTime::now(),
+                            std::fs::metadata(&path).map_or(0, |m| m.len()),
+                        )
+                    });
+                    // Restoring counts as use, so the graph is not swept while its user is active.
+                    self.touch_graph(&graph_name);
+                    quad_total += quad_count;
+                    restored.push(PersistedGraph {
+                        graph_name,
+                        quad_count,
+                        byte_size,
+                        loaded_at,
+                    });
+                }
+                Err(e) => {
+                    warn!("Skipping corrupt graph file '{}': {e}", path.display());
+                }
+            }
+        }
+
+        if !restored.is_empty() {
+            info!(
+                "Restored {} graph{} ({quad_total} quads) of '{prefix}' from disk in {} s",
+                restored.len(),
+                if restored.len() == 1 { "" } else { "s" },
+                Instant::now()
+                    .checked_duration_since(start_time)
+                    .unwrap_or(Duration::new(0, 0))
+                    .as_secs_f32()
+            );
+        }
+        Ok(restored)
+    }
+
+    /// Returns the key signing the session cookies, creating it with `generate` on first use.
+    ///
+    /// The key is kept next to the graphs, so the sessions of users, and with them their graphs,
+    /// survive a server restart.
+    ///
+    /// # Errors
+    /// Returns an error if the key file exists but cannot be read, or cannot be created.
+    pub fn session_key(
+        &self,
+        generate: impl FnOnce() -> Vec<u8>,
+    ) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        let path = self.root.join(SESSION_KEY_FILE);
+        match std::fs::read(&path) {
+            Ok(key) => return Ok(key),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            Err(_) => {}
+        }
+
+        // The temporary file is only readable by the server, and so is the key once persisted.
+        let key = generate();
+        let file = tempfile::NamedTempFile::new_in(&self.root)?;
+        std::fs::write(file.path(), &key)?;
+        file.persist_noclobber(&path).map_err(|e| e.error)?;
+        info!("Created session key in '{}'", path.display());
+        Ok(key)
+    }
+
+    /// Removes the persisted graphs which have not been used for at least `ttl`.
+    ///
+    /// A graph counts as used when it is written or its user's graphs are restored or
+    /// marked by [`Self::touch_graph`]. Returns the names of the removed graphs.
+    ///
+    /// # Errors
+    /// Returns an error if the store directory cannot be read or a graph cannot be removed.
+    pub fn remove_expired(&self, ttl: Duration) -> Result<Vec<String>, VOWLGrapherStoreError> {
+        let mut removed = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            let Some(graph_name) = Self::graph_name_of(&path) else {
+                continue;
+            };
+            let last_used = std::fs::metadata(self.meta_path(&graph_name))
+                .or_else(|_| std::fs::metadata(&path))
+                .and_then(|metadata| metadata.modified())?;
+            if last_used.elapsed().is_ok_and(|idle| idle >= ttl) {
+                self.remove_graph(&graph_name)?;
+                removed.push(graph_name);
+            }
+        }
+        if !removed.is_empty() {
+            info!("Removed {} expired graphs from disk", removed.len());
+        }
+        Ok(removed)
+    }
+
+    /// Marks a persisted graph as used right now, so [`Self::remove_expired`] keeps it.
+    pub fn touch_graph(&self, graph_name: &str) {
+        let touched = std::fs::File::options()
+            .write(true)
+            .open(self.meta_path(graph_name))
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = touched {
+            warn!("Could not mark graph '{graph_name}' as used on disk: {e}");
+        }
+    }
+
+    /// Writes the current content of a named graph to disk, replacing any previous version.
+    ///
+    /// `loaded_at` and `byte_size` record when and from how large an input the graph
+    /// was originally loaded, and are restored along with the graph.
+    ///
+    /// # Errors
+    /// Returns an error if the graph cannot be serialized or written to disk.
+    pub async fn persist_graph(
+        &self,
+        store: &Store,
+        graph_name: &str,
+        loaded_at: SystemTime,
+        byte_size: u64,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let graph_ref = NamedNodeRef::new(graph_name)?;
+
+        // Write to a temporary file first so a crash never leaves a half-written graph behind.
+        let file = tempfile::NamedTempFile::new_in(&self.root)?;
+        let writer = store
+            .dump_graph_to_writer(graph_ref, RdfFormat::NTriples, BufWriter::new(file))
+            .await?;
+        let file = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        file.persist(self.graph_path(graph_name))
+            .map_err(|e| e.error)?;
+
+        let loaded_at = loaded_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let meta = tempfile::NamedTempFile::new_in(&self.root)?;
+        std::fs::write(meta.path(), format!("{loaded_at} {byte_size}\n"))?;
+        meta.persist(self.meta_path(graph_name))
+            .map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Reads when a persisted graph was loaded and the size of its input.
+    ///
+    /// Returns `None` if the graph has no metadata, e.g. because it was persisted by an older version.
+    fn read_meta(&self, graph_name: &str) -> Option<(SystemTime, u64)> {
+        let meta = std::fs::read_to_string(self.meta_path(graph_name)).ok()?;
+        let (loaded_at, byte_size) = meta.trim().split_once(' ')?;
+        Some((
+            UNIX_EPOCH + Duration::from_secs(loaded_at.parse().ok()?),
+            byte_size.parse().ok()?,
+        ))
+    }
+
+    /// Writes the cursor of the harvest into a named graph to disk, replacing any previous one.
+    ///
+    /// # Errors
+    /// Returns an error if the cursor cannot be written to disk.
+    pub fn persist_harvest(
+        &self,
+        graph_name: &str,
+        cursor: &str,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let file = tempfile::NamedTempFile::new_in(&self.root)?;
+        std::fs::write(file.path(), cursor)?;
+        file.persist(self.harvest_path(graph_name))
+            .map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Reads the cursor of the harvest into a named graph, if one was persisted.
+    pub fn read_harvest(&self, graph_name: &str) -> Option<String> {
+        std::fs::read_to_string(self.harvest_path(graph_name)).ok()
+    }
+
+    /// Removes the cursor of the harvest into a named graph from disk.
+    ///
+    /// # Errors
+    /// Returns an error if the cursor file exists but cannot be removed.
+    pub fn remove_harvest(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        Self::remove_file(self.harvest_path(graph_name))
+    }
+
+    /// Removes a named graph from disk.
+    ///
+    /// # Errors
+    /// Returns an error if the graph file exists but cannot be removed.
+    pub fn remove_graph(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        for path in [
+            self.graph_path(graph_name),
+            self.meta_path(graph_name),
+            self.harvest_path(graph_name),
+        ] {
+            Self::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Removes a file, which may not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be removed.
+    fn remove_file(path: PathBuf) -> Result<(), VOWLGrapherStoreError> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the path of the file persisting a named graph.
+    fn graph_path(&self, graph_name: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}.{GRAPH_FILE_EXTENSION}",
+            encode_graph_name(graph_name)
+        ))
+    }
+
+    /// Returns the path of the file recording the metadata of a persisted graph.
+    fn meta_path(&self, graph_name: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}.{META_FILE_EXTENSION}",
+            encode_graph_name(graph_name)
+        ))
+    }
+
+    /// Returns the path of the file recording the cursor of the harvest into a persisted graph.
+    fn harvest_path(&self, graph_name: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}.{HARVEST_FILE_EXTENSION}",
+            encode_graph_name(graph_name)
+        ))
+    }
+
+    /// Returns the name of the graph persisted at `path`, if it is a graph file.
+    fn graph_name_of(path: &Path) -> Option<String> {
+        if path.extension().and_then(|e| e.to_str()) != Some(GRAPH_FILE_EXTENSION) {
+            return None;
+        }
+        decode_graph_name(path.file_stem()?.to_str()?)
+    }
+}
+
+/// Percent-encodes a graph name so it is safe to use as a file name.
+fn encode_graph_name(graph_name: &str) -> String {
+    let mut encoded = String::with_capacity(graph_name.len());
+    for byte in graph_name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Reverses [`encode_graph_name`].
+fn decode_graph_name(file_stem: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(file_stem.len());
+    let mut iter = file_stem.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::model::{NamedNode, Quad};
+
+    #[test]
+    fn test_graph_name_roundtrip() {
+        let graph_name = "urn:vowlgrapher:user:1234-abcd:graph:my_ontology.owl";
+        let encoded = encode_graph_name(graph_name);
+        assert!(!encoded.contains(':'), "encoded name contains ':'");
+        assert_eq!(decode_graph_name(&encoded).as_deref(), Some(graph_name));
+    }
+
+    #[tokio::test]
+    async fn test_restore_per_user() -> Result<(), VOWLGrapherStoreError> {
+        let root = tempfile::tempdir()?;
+        let backing = StoreBacking {
+            root: root.path().to_path_buf(),
+        };
+        let store = Store::default();
+        let loaded_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for graph_name in [
+            "urn:vowlgrapher:user:a:graph:one.ttl",
+            "urn:vowlgrapher:user:b:graph:two.ttl",
+        ] {
+            store
+                .extend(vec![Quad::new(
+                    NamedNode::new("http://example.com/s")?,
+                    NamedNode::new("http://example.com/p")?,
+                    NamedNode::new("http://example.com/o")?,
+                    NamedNode::new(graph_name)?,
+                )])
+                .await?;
+            backing
+                .persist_graph(&store, graph_name, loaded_at, 42)
+                .await?;
+        }
+
+        let restored_store = Store::default();
+        let restored = backing
+            .restore(&restored_store, "urn:vowlgrapher:user:a:graph:")
+            .await?;
+        assert_eq!(restored.len(), 1, "restored graphs of other users");
+        assert_eq!(
+            restored[0].graph_name,
+            "urn:vowlgrapher:user:a:graph:one.ttl"
+        );
+        assert_eq!(restored[0].quad_count, 1);
+        assert_eq!(restored[0].byte_size, 42);
+        assert_eq!(restored[0].loaded_at, loaded_at, "lost the load time");
+        assert_eq!(restored_store.len().await?, 1);
+
+        assert!(
+            backing
+                .remove_expired(Duration::from_secs(3600))?
+                .is_empty()
+        );
+        assert_eq!(backing.remove_expired(Duration::ZERO)?.len(), 2);
+        assert!(
+            backing
+                .restore(&restored_store, "urn:vowlgrapher:user:b:graph:")
+                .await?
+                .is_empty(),
+            "expired graph was restored"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reopen_keeps_sessions() -> Result<(), VOWLGrapherStoreError> {
+        let root = tempfile::tempdir()?;
+        let backing = StoreBacking {
+            root: root.path().to_path_buf(),
+        };
+        let session_key = backing.session_key(|| vec![7; 64])?;
+        let graph_name = "urn:vowlgrapher:user:returning:graph:one.ttl";
+        let store = Store::default();
+        store
+            .extend(vec![Quad::new(
+                NamedNode::new("http://example.com/s")?,
+                NamedNode::new("http://example.com/p")?,
+                NamedNode::new("http://example.com/o")?,
+                NamedNode::new(graph_name)?,
+            )])
+            .await?;
+        backing
+            .persist_graph(&store, graph_name, SystemTime::now(), 42)
+            .await?;
+        drop(backing);
+
+        // Re-opening the store after a restart signs cookies with the same key,
+        // so the user keeps their id and gets their graph back.
+        let reopened = StoreBacking {
+            root: root.path().to_path_buf(),
+        };
+        assert_eq!(
+            reopened.session_key(|| vec![8; 64])?,
+            session_key,
+            "the session key changed"
+        );
+        let restored_store = Store::default();
+        let restored = reopened
+            .restore(&restored_store, "urn:vowlgrapher:user:returning:graph:")
+            .await?;
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].graph_name, graph_name);
+        assert_eq!(restored_store.len().await?, 1);
+        assert_eq!(reopened.remove_expired(Duration::ZERO)?.len(), 1);
+        assert_eq!(
+            reopened.session_key(|| vec![8; 64])?,
+            session_key,
+            "sweeping graphs removed the session key"
+        );
+        Ok(())
+    }
+}
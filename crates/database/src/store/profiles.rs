@@ -0,0 +1,100 @@
+//! Checks of the ontology in a named graph against the profiles of OWL 2.
+//!
+//! The graph is mapped to axioms by horned-owl, which needs the whole graph in memory.
+//! Imports are not part of the check.
+
+use grapher::prelude::GraphDisplayData;
+use log::info;
+use rdf_fusion::model::{GraphName, Quad};
+use std::collections::{BTreeSet, HashMap};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_parser::parser_util::ontology_from_quads;
+use vowlgrapher_parser::profiles::check_profiles;
+use vowlgrapher_util::prelude::{
+    Annotation, GraphAnnotations, OwlProfile, ProfileReport, VOWLGrapherError,
+};
+
+use crate::store::VOWLGrapherStore;
+
+impl VOWLGrapherStore {
+    /// Checks the ontology in a graph of the user against every profile of OWL 2.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or could not be mapped to an ontology.
+    pub async fn check_profiles(
+        &self,
+        graph_name: &str,
+    ) -> Result<Vec<ProfileReport>, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let triples = self.graph_triples(graph_name).await?;
+        let reports = tokio::task::spawn_blocking(move || {
+            let (ontology, complete) = ontology_from_quads(triples.into_iter().map(|triple| {
+                Quad::new(
+                    triple.subject,
+                    triple.predicate,
+                    triple.object,
+                    GraphName::DefaultGraph,
+                )
+            }))?;
+            Ok::<_, VOWLGrapherStoreError>(check_profiles(&ontology, complete))
+        })
+        .await??;
+
+        for report in &reports {
+            info!(
+                "Checked graph '{graph_name}' against {}: {} violations",
+                report.profile,
+                report.violations.len()
+            );
+        }
+        Ok(reports)
+    }
+
+    /// Checks the ontology in a graph of the user against a profile of OWL 2 and serializes it,
+    /// with the entities mentioned by violating axioms annotated as violations.
+    ///
+    /// This method tries to continue serializing despite errors.
+    /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or could not be checked or serialized.
+    pub async fn profile_display_data(
+        &self,
+        graph_name: &str,
+        profile: OwlProfile,
+    ) -> Result<
+        (
+            GraphDisplayData,
+            GraphAnnotations,
+            ProfileReport,
+            Option<VOWLGrapherError>,
+        ),
+        VOWLGrapherError,
+    > {
+        let report = self
+            .check_profiles(graph_name)
+            .await?
+            .into_iter()
+            .find(|report| report.profile == profile)
+            .ok_or_else(|| {
+                VOWLGrapherStoreError::from(VOWLGrapherStoreErrorKind::InvalidQuery(format!(
+                    "{profile} was not checked"
+                )))
+            })?;
+
+        let violations = report
+            .entities
+            .iter()
+            .map(|entity| {
+                (
+                    entity.clone(),
+                    BTreeSet::from([Annotation::ProfileViolation]),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let triples = self.graph_triples(graph_name).await?;
+
+        let (display_data, annotations, errors) = self.display_triples(triples, violations).await?;
+        Ok((display_data, annotations, report, errors))
+    }
+}
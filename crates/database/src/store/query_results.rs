@@ -0,0 +1,270 @@
+//! Export of raw SPARQL query results.
+
+use futures::stream::{BoxStream, StreamExt};
+use log::{debug, info};
+use rdf_fusion::execution::results::QueryResults;
+use rdf_fusion::io::RdfSerializer;
+use sparesults::{QueryResultsFormat, QueryResultsSerializer};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_parser::parser_util::format_from_resource_type;
+use vowlgrapher_util::prelude::DataType;
+
+use crate::store::dataset::{scope_query, scope_query_to};
+use crate::store::{VOWLGrapherStore, lifecycle};
+
+/// Returns the SPARQL query results format of a resource type, if it is one.
+pub const fn results_format_from_resource_type(
+    resource_type: DataType,
+) -> Option<QueryResultsFormat> {
+    match resource_type {
+        DataType::SPARQLJSON => Some(QueryResultsFormat::Json),
+        DataType::SPARQLXML => Some(QueryResultsFormat::Xml),
+        DataType::SPARQLCSV => Some(QueryResultsFormat::Csv),
+        DataType::SPARQLTSV => Some(QueryResultsFormat::Tsv),
+        _ => None,
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Executes a SELECT or ASK query against a graph and serializes the raw results
+    /// into a stream of the specified SPARQL query results type.
+    ///
+    /// The query only sees the graph `graph_name`.
+    ///
+    /// # Errors
+    /// Returns an error if the query is invalid, is not a SELECT or ASK query
+    /// or its results fail to serialize.
+    pub async fn serialize_query_results(
+        &self,
+        query: &str,
+        graph_name: &str,
+        resource_type: DataType,
+    ) -> Result<BoxStream<'static, Result<Vec<u8>, VOWLGrapherStoreError>>, VOWLGrapherStoreError>
+    {
+        self.maintain().await?;
+        let graph_name = self.get_graph_name(graph_name);
+        lifecycle::touch_graph(&graph_name);
+        let query = scope_query(
+            &query.replace("{GRAPH_IRI}", &graph_name),
+            std::slice::from_ref(&graph_name),
+        )?;
+
+        info!("Exporting query results of graph '{graph_name}' as {resource_type:?}...");
+        let (_, buf) = self.serialize_results(&query, resource_type, None).await?;
+        Ok(futures::stream::once(async move { Ok(buf) }).boxed())
+    }
+
+    /// Executes a query received through the SPARQL 1.1 Protocol and serializes its results.
+    ///
+    /// The default graph of the query merges `default_graph_names` and `GRAPH` clauses only
+    /// see `named_graph_names`. If neither are given, the query sees every graph of the user
+    /// as both. Graph names are either the names given by the user when loading a graph
+    /// or full graph IRIs.
+    ///
+    /// The results of SELECT and ASK queries are serialized as `results_type`,
+    /// the results of CONSTRUCT and DESCRIBE queries as `graph_type`.
+    /// Returns the type the results were serialized as along with the serialized results.
+    ///
+    /// # Errors
+    /// Returns an error if the query is invalid, one of the graphs does not exist,
+    /// the user has no graphs or the results fail to serialize.
+    pub async fn serialize_protocol_query(
+        &self,
+        query: &str,
+        default_graph_names: &[String],
+        named_graph_names: &[String],
+        results_type: DataType,
+        graph_type: DataType,
+    ) -> Result<(DataType, Vec<u8>), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let (default_graphs, named_graphs) =
+            if default_graph_names.is_empty() && named_graph_names.is_empty() {
+                let graphs = self
+                    .list_graphs()
+                    .await?
+                    .iter()
+                    .map(|graph| self.get_graph_name(&graph.name))
+                    .collect::<Vec<_>>();
+                if graphs.is_empty() {
+                    return Err(VOWLGrapherStoreErrorKind::GraphNotFound(
+                        "No graphs have been loaded in this session".to_string(),
+                    )
+                    .into());
+                }
+                (graphs.clone(), graphs)
+            } else {
+                (
+                    self.existing_graphs(default_graph_names).await?,
+                    self.existing_graphs(named_graph_names).await?,
+                )
+            };
+        for graph_name in default_graphs.iter().chain(&named_graphs) {
+            lifecycle::touch_graph(graph_name);
+        }
+
+        let query = scope_query_to(query, &default_graphs, &named_graphs)?;
+        debug!(
+            "Executing protocol query on {} default and {} named graphs",
+            default_graphs.len(),
+            named_graphs.len()
+        );
+        self.serialize_results(&query, results_type, Some(graph_type))
+            .await
+    }
+
+    /// Resolves the names of graphs of the user to their IRIs.
+    ///
+    /// # Errors
+    /// Returns an error if one of the graphs does not exist.
+    async fn existing_graphs(
+        &self,
+        graph_names: &[String],
+    ) -> Result<Vec<String>, VOWLGrapherStoreError> {
+        let mut graphs = Vec::with_capacity(graph_names.len());
+        for graph_name in graph_names {
+            let graph_name = self.resolve_graph_name(graph_name);
+            if self.graph_len(&graph_name).await? == 0 {
+                return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                    "Graph '{graph_name}' does not exist"
+                ))
+                .into());
+            }
+            graphs.push(graph_name);
+        }
+        Ok(graphs)
+    }
+
+    /// Executes a query and serializes its raw results.
+    ///
+    /// The results of SELECT and ASK queries are serialized as `results_type`,
+    /// the results of CONSTRUCT and DESCRIBE queries as `graph_type`, if given.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails, the output type does not fit the query
+    /// or the results fail to serialize.
+    async fn serialize_results(
+        &self,
+        query: &str,
+        results_type: DataType,
+        graph_type: Option<DataType>,
+    ) -> Result<(DataType, Vec<u8>), VOWLGrapherStoreError> {
+        let unsupported_type = |resource_type: DataType| {
+            VOWLGrapherStoreErrorKind::InvalidFileType(format!(
+                "Unsupported query results type: {resource_type:?}"
+            ))
+        };
+
+        match self.session.query(query).await? {
+            QueryResults::Solutions(mut solutions) => {
+                let format = results_format_from_resource_type(results_type)
+                    .ok_or_else(|| unsupported_type(results_type))?;
+                let mut writer = QueryResultsSerializer::from_format(format)
+                    .serialize_solutions_to_writer(Vec::new(), solutions.variables().to_vec())?;
+                while let Some(solution) = solutions.next().await {
+                    let solution = solution?;
+                    writer.serialize(
+                        solution
+                            .iter()
+                            .map(|(variable, term)| (variable.as_ref(), term.as_ref())),
+                    )?;
+                }
+                Ok((results_type, writer.finish()?))
+            }
+            QueryResults::Boolean(result) => {
+                let format = results_format_from_resource_type(results_type)
+                    .ok_or_else(|| unsupported_type(results_type))?;
+                let buf = QueryResultsSerializer::from_format(format)
+                    .serialize_boolean_to_writer(Vec::new(), result)?;
+                Ok((results_type, buf))
+            }
+            QueryResults::Graph(mut triples) => {
+                let Some(graph_type) = graph_type else {
+                    return Err(VOWLGrapherStoreErrorKind::UnsupportedQueryType(
+                        "Only the results of SELECT and ASK queries can be exported".to_string(),
+                    )
+                    .into());
+                };
+                let format = format_from_resource_type(&graph_type)
+                    .ok_or_else(|| unsupported_type(graph_type))?;
+                let mut buf = Vec::new();
+                let mut serializer = RdfSerializer::from_format(format).for_writer(&mut buf);
+                while let Some(triple) = triples.next().await {
+                    serializer.serialize_triple(&triple?)?;
+                }
+                serializer.finish()?;
+                Ok((graph_type, buf))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::model::{NamedNode, Quad};
+    use rdf_fusion::store::Store;
+
+    #[tokio::test]
+    async fn test_export_csv() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .session
+            .extend(vec![Quad::new(
+                NamedNode::new("http://example.com/s")?,
+                NamedNode::new("http://example.com/p")?,
+                NamedNode::new("http://example.com/o")?,
+                NamedNode::new(store.get_graph_name("results"))?,
+            )])
+            .await?;
+
+        let mut out = vec![];
+        let mut stream = store
+            .serialize_query_results(
+                "SELECT ?s WHERE { ?s ?p ?o }",
+                "results",
+                DataType::SPARQLCSV,
+            )
+            .await?;
+        while let Some(result) = stream.next().await {
+            out.extend(result?);
+        }
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "s\r\nhttp://example.com/s\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_protocol_query_is_scoped_to_user() -> Result<(), VOWLGrapherStoreError> {
+        let owner = VOWLGrapherStore::new_for_user("protocol-owner".to_string());
+        let stranger = VOWLGrapherStore::new_for_user("protocol-stranger".to_string());
+        owner
+            .insert_bytes(
+                b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+                "owned.nt",
+                DataType::NTriples,
+                false,
+            )
+            .await?;
+
+        let query = "SELECT ?g ?s WHERE { GRAPH ?g { ?s ?p ?o } }";
+        let (_, results) = owner
+            .serialize_protocol_query(query, &[], &[], DataType::SPARQLCSV, DataType::TTL)
+            .await?;
+        assert!(
+            String::from_utf8_lossy(&results).contains("http://example.com/s"),
+            "the owner should see their graph"
+        );
+        assert!(
+            stranger
+                .serialize_protocol_query(query, &[], &[], DataType::SPARQLCSV, DataType::TTL)
+                .await
+                .is_err(),
+            "a user without graphs should not query the whole store"
+        );
+
+        owner.delete_graph("owned.nt").await?;
+        Ok(())
+    }
+}
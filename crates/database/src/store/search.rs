@@ -0,0 +1,520 @@
+//! Full-text search over the elements of a named graph and its imports.
+//!
+//! A graph is indexed whenever it is loaded, and its index is dropped when the graph is
+//! replaced, updated or removed. Graphs without an index, e.g. after a restart, are indexed
+//! on their first search.
+//!
+//! Every named resource which is drawn as an element is indexed by its `rdfs:label` and
+//! `skos:altLabel` in every language, its `rdfs:comment` and the local name of its IRI.
+//! The words of a search match the words of these texts exactly, as a prefix or,
+//! if they are long enough, despite a typo or two.
+
+use dashmap::DashMap;
+use grapher::prelude::{
+    ElementType, OwlEdge, OwlNode, OwlType, RdfEdge, RdfType, RdfsNode, RdfsType,
+};
+use log::info;
+use rdf_fusion::model::{NamedOrBlankNode, Term, Triple};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+use std::sync::{Arc, LazyLock};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{SearchField, SearchHit};
+
+use crate::store::imports::is_import_graph_of;
+use crate::store::{RDFS_LABEL, VOWLGrapherStore};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const RDFS_DATATYPE: &str = "http://www.w3.org/2000/01/rdf-schema#Datatype";
+const RDFS_COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+const SKOS_ALT_LABEL: &str = "http://www.w3.org/2004/02/skos/core#altLabel";
+const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+const OWL_THING: &str = "http://www.w3.org/2002/07/owl#Thing";
+const OWL_OBJECT_PROPERTY: &str = "http://www.w3.org/2002/07/owl#ObjectProperty";
+const OWL_DATATYPE_PROPERTY: &str = "http://www.w3.org/2002/07/owl#DatatypeProperty";
+const OWL_DEPRECATED: &str = "http://www.w3.org/2002/07/owl#deprecated";
+
+/// The search index of every indexed graph, keyed by the graph name.
+static SEARCH_INDEXES: LazyLock<DashMap<String, Arc<SearchIndex>>> = LazyLock::new(DashMap::new);
+
+/// The score of a search word equal to a word of a text.
+const EXACT_SCORE: f32 = 1.0;
+
+/// The score of a search word which begins a word of a text, if it was as long as that word.
+const PREFIX_SCORE: f32 = 0.8;
+
+/// The score of a search word one typo away from a word of a text.
+const FUZZY_SCORE: f32 = 0.5;
+
+/// Added to the score of a text equal to the whole search.
+const WHOLE_TEXT_BONUS: f32 = 0.5;
+
+/// An element of a graph which can be found by a search.
+struct Element {
+    /// The IRI of the element.
+    iri: String,
+    /// The label the element is shown with, if it has one.
+    label: Option<String>,
+    /// The type the element is drawn as.
+    element_type: ElementType,
+}
+
+/// A text of an element.
+struct Text {
+    /// The index of the element in [`SearchIndex::elements`].
+    element: usize,
+    /// Which text of the element this is.
+    field: SearchField,
+    /// The text itself.
+    text: String,
+}
+
+/// The full-text index of a graph and its imports.
+#[derive(Default)]
+pub struct SearchIndex {
+    /// The elements which can be found.
+    elements: Vec<Element>,
+    /// The texts of the elements.
+    texts: Vec<Text>,
+    /// The indices into `texts` of the texts containing a word, keyed by the word.
+    words: BTreeMap<String, Vec<usize>>,
+}
+
+impl SearchIndex {
+    /// Indexes the elements of a graph holding `triples`, which imports `imported`.
+    ///
+    /// Classes and properties which are only described by the imports are indexed as external elements.
+    fn new(triples: &[Triple], imported: &[Triple]) -> Self {
+        let local = triples
+            .iter()
+            .filter_map(|triple| named_subject(triple))
+            .collect::<HashSet<_>>();
+
+        let mut types = HashMap::<&str, Vec<&str>>::new();
+        let mut deprecated = HashSet::new();
+        let mut texts = HashMap::<&str, Vec<(SearchField, &str, Option<&str>)>>::new();
+        for triple in triples.iter().chain(imported) {
+            let Some(subject) = named_subject(triple) else {
+                continue;
+            };
+            match (triple.predicate.as_str(), &triple.object) {
+                (RDF_TYPE, Term::NamedNode(class)) => {
+                    types.entry(subject).or_default().push(class.as_str());
+                }
+                (OWL_DEPRECATED, Term::Literal(value)) if matches!(value.value(), "true" | "1") => {
+                    deprecated.insert(subject);
+                }
+                (predicate, Term::Literal(text)) => {
+                    let field = match predicate {
+                        RDFS_LABEL => SearchField::Label,
+                        SKOS_ALT_LABEL => SearchField::AltLabel,
+                        RDFS_COMMENT => SearchField::Comment,
+                        _ => continue,
+                    };
+                    texts
+                        .entry(subject)
+                        .or_default()
+                        .push((field, text.value(), text.language()));
+                }
+                _ => {}
+            }
+        }
+
+        let mut index = Self::default();
+        for (iri, types) in types {
+            let Some(element_type) =
+                element_type(iri, &types, !local.contains(iri), deprecated.contains(iri))
+            else {
+                continue;
+            };
+            let texts = texts.remove(iri).unwrap_or_default();
+            let element = index.elements.len();
+            index.elements.push(Element {
+                iri: iri.to_string(),
+                label: shown_label(&texts),
+                element_type,
+            });
+            let local_name = iri.rsplit(['#', '/']).next().unwrap_or(iri);
+            for (field, text) in texts
+                .into_iter()
+                .map(|(field, text, _)| (field, text))
+                .chain(std::iter::once((SearchField::LocalName, local_name)))
+            {
+                index.insert(element, field, text);
+            }
+        }
+        index
+    }
+
+    /// Adds a text of an element to the index.
+    fn insert(&mut self, element: usize, field: SearchField, text: &str) {
+        let words = words(text);
+        if words.is_empty() {
+            return;
+        }
+        let id = self.texts.len();
+        self.texts.push(Text {
+            element,
+            field,
+            text: text.to_string(),
+        });
+        for word in words {
+            self.words.entry(word).or_default().push(id);
+        }
+    }
+
+    /// Returns the `limit` elements matching `query` best, the best one first.
+    ///
+    /// An element matches if one of its texts matches every word of the query.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query_words = words(query);
+        if query_words.is_empty() {
+            return Vec::new();
+        }
+
+        // The summed score of every text matching all words so far.
+        let mut scores: Option<HashMap<usize, f32>> = None;
+        for word in &query_words {
+            let matches = self.matches(word);
+            scores = Some(match scores {
+                None => matches,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(text, score)| {
+                        matches.get(&text).map(|other| (text, score + other))
+                    })
+                    .collect(),
+            });
+        }
+
+        let query = query_words.join(" ");
+        let mut best = HashMap::<usize, (usize, f32)>::new();
+        for (text_id, score) in scores.unwrap_or_default() {
+            let text = &self.texts[text_id];
+            #[expect(clippy::cast_precision_loss, reason = "a query has few words")]
+            let mut score = score / query_words.len() as f32 * field_weight(text.field);
+            if words(&text.text).join(" ") == query {
+                score += WHOLE_TEXT_BONUS;
+            }
+            let entry = best.entry(text.element).or_insert((text_id, score));
+            if score > entry.1 {
+                *entry = (text_id, score);
+            }
+        }
+
+        let mut hits = best
+            .into_values()
+            .map(|(text_id, score)| {
+                let text = &self.texts[text_id];
+                let element = &self.elements[text.element];
+                SearchHit {
+                    iri: element.iri.clone(),
+                    label: element.label.clone(),
+                    element_type: element.element_type,
+                    field: text.field,
+                    matched_text: text.text.clone(),
+                    score,
+                }
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.label.cmp(&b.label))
+                .then_with(|| a.iri.cmp(&b.iri))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    /// Returns the texts with a word matching `word`, along with how well it matches.
+    fn matches(&self, word: &str) -> HashMap<usize, f32> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let max_distance = match chars.len() {
+            0..4 => 0,
+            4..8 => 1,
+            _ => 2,
+        };
+
+        let mut matches = HashMap::new();
+        let mut add = |texts: &[usize], score: f32| {
+            for text in texts {
+                let best = matches.entry(*text).or_insert(score);
+                *best = best.max(score);
+            }
+        };
+
+        for (candidate, texts) in self
+            .words
+            .range::<str, _>((Bound::Included(word), Bound::Unbounded))
+            .take_while(|(candidate, _)| candidate.starts_with(word))
+        {
+            if candidate == word {
+                add(texts, EXACT_SCORE);
+            } else {
+                #[expect(clippy::cast_precision_loss, reason = "words are short")]
+                let coverage = chars.len() as f32 / candidate.chars().count() as f32;
+                add(texts, PREFIX_SCORE * coverage.max(0.5));
+            }
+        }
+
+        if max_distance > 0 {
+            for (candidate, texts) in &self.words {
+                let candidate = candidate.chars().collect::<Vec<_>>();
+                if candidate.len().abs_diff(chars.len()) > max_distance {
+                    continue;
+                }
+                if let Some(distance @ 1..) = edit_distance(&chars, &candidate, max_distance) {
+                    #[expect(clippy::cast_precision_loss, reason = "the distance is at most 2")]
+                    let score = FUZZY_SCORE / distance as f32;
+                    add(texts, score);
+                }
+            }
+        }
+        matches
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Searches the elements of a graph of the user and its imports by their labels,
+    /// comments and IRIs.
+    ///
+    /// Returns at most `limit` hits, the best one first.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or the store could not be queried.
+    pub async fn search(
+        &self,
+        graph_name: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_iri = self.get_graph_name(graph_name);
+        if self.graph_len(&graph_iri).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{graph_name}' does not exist"
+            ))
+            .into());
+        }
+        let cached = SEARCH_INDEXES
+            .get(&graph_iri)
+            .map(|index| Arc::clone(&index));
+        let index = match cached {
+            Some(index) => index,
+            None => index_graph(self, &graph_iri).await?,
+        };
+        Ok(index.search(query, limit))
+    }
+}
+
+/// Builds the search index of a named graph and its imports, replacing the previous one.
+///
+/// # Errors
+/// Returns an error if the store could not be queried.
+pub async fn index_graph(
+    store: &VOWLGrapherStore,
+    graph_iri: &str,
+) -> Result<Arc<SearchIndex>, VOWLGrapherStoreError> {
+    let triples = store.named_graph_triples(graph_iri).await?;
+    let mut imported = Vec::new();
+    for import_graph in store.ontology_imports(graph_iri).await? {
+        imported.extend(store.named_graph_triples(&import_graph).await?);
+    }
+
+    let index =
+        Arc::new(tokio::task::spawn_blocking(move || SearchIndex::new(&triples, &imported)).await?);
+    info!(
+        "Indexed {} elements of graph '{graph_iri}' for search",
+        index.elements.len()
+    );
+    SEARCH_INDEXES.insert(graph_iri.to_string(), Arc::clone(&index));
+    Ok(index)
+}
+
+/// Drops the search index of a graph and of the graph importing it, if any.
+pub fn forget_index(graph_name: &str) {
+    SEARCH_INDEXES
+        .retain(|indexed, _| indexed != graph_name && !is_import_graph_of(graph_name, indexed));
+}
+
+/// Returns the subject of a statement, if it is an IRI.
+fn named_subject(triple: &Triple) -> Option<&str> {
+    match &triple.subject {
+        NamedOrBlankNode::NamedNode(subject) => Some(subject.as_str()),
+        NamedOrBlankNode::BlankNode(_) => None,
+    }
+}
+
+/// Returns the type a resource with the given types is drawn as, if it is drawn at all.
+fn element_type(
+    iri: &str,
+    types: &[&str],
+    external: bool,
+    deprecated: bool,
+) -> Option<ElementType> {
+    if iri == OWL_THING {
+        return Some(ElementType::Owl(OwlType::Node(OwlNode::Thing)));
+    }
+    if types.contains(&OWL_CLASS) {
+        let node = if deprecated {
+            OwlNode::DeprecatedClass
+        } else if external {
+            OwlNode::ExternalClass
+        } else {
+            OwlNode::Class
+        };
+        return Some(ElementType::Owl(OwlType::Node(node)));
+    }
+    if types.contains(&OWL_OBJECT_PROPERTY) || types.contains(&OWL_DATATYPE_PROPERTY) {
+        let edge = if deprecated {
+            OwlEdge::DeprecatedProperty
+        } else if external {
+            OwlEdge::ExternalProperty
+        } else if types.contains(&OWL_OBJECT_PROPERTY) {
+            OwlEdge::ObjectProperty
+        } else {
+            OwlEdge::DatatypeProperty
+        };
+        return Some(ElementType::Owl(OwlType::Edge(edge)));
+    }
+    if types.contains(&RDFS_CLASS) {
+        return Some(ElementType::Rdfs(RdfsType::Node(RdfsNode::Class)));
+    }
+    if types.contains(&RDFS_DATATYPE) {
+        return Some(ElementType::Rdfs(RdfsType::Node(RdfsNode::Datatype)));
+    }
+    if types.contains(&RDF_PROPERTY) {
+        return Some(ElementType::Rdf(RdfType::Edge(RdfEdge::RdfProperty)));
+    }
+    None
+}
+
+/// Returns the label an element is shown with, preferring labels without a language
+/// and then English ones.
+fn shown_label(texts: &[(SearchField, &str, Option<&str>)]) -> Option<String> {
+    texts
+        .iter()
+        .filter(|(field, ..)| *field == SearchField::Label)
+        .min_by_key(|(_, _, language)| match language {
+            None => 0,
+            Some(language) if language.starts_with("en") => 1,
+            Some(_) => 2,
+        })
+        .map(|(_, label, _)| (*label).to_string())
+}
+
+/// Splits a text into lowercase words, also at the humps of camel case,
+/// so `hasPart` consists of `has`, `part` and `haspart`.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut humps = 0;
+        let mut word = String::new();
+        let mut previous = None::<char>;
+        for c in token.chars() {
+            if c.is_uppercase() && previous.is_some_and(char::is_lowercase) {
+                words.push(std::mem::take(&mut word));
+                humps += 1;
+            }
+            word.extend(c.to_lowercase());
+            previous = Some(c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        if humps > 0 {
+            words.push(token.to_lowercase());
+        }
+    }
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+/// Returns how much a match of a text counts, relative to a match of a label.
+const fn field_weight(field: SearchField) -> f32 {
+    match field {
+        SearchField::Label => 1.0,
+        SearchField::AltLabel => 0.9,
+        SearchField::LocalName => 0.8,
+        SearchField::Comment => 0.4,
+    }
+}
+
+/// Returns the Levenshtein distance between two words, or `None` if it exceeds `max_distance`.
+fn edit_distance(a: &[char], b: &[char], max_distance: usize) -> Option<usize> {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(a_char != b_char))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|min| *min > max_distance) {
+            return None;
+        }
+        previous = current;
+    }
+    previous
+        .last()
+        .copied()
+        .filter(|distance| *distance <= max_distance)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    #[tokio::test]
+    async fn test_search() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let ontology = br#"@prefix : <http://example.com/> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix skos: <http://www.w3.org/2004/02/skos/core#> .
+            :Organism a owl:Class ;
+                rdfs:label "Organism"@en , "Organismus"@de ;
+                skos:altLabel "Living thing" .
+            :Cell a owl:Class ;
+                rdfs:label "Cell" ;
+                rdfs:comment "The smallest unit of an organism." .
+            :hasPart a owl:ObjectProperty ."#;
+        store
+            .insert_bytes(ontology, "searchable", DataType::TTL, false)
+            .await?;
+
+        // Labels in other languages and alternative labels are indexed.
+        let hits = store.search("searchable", "organismus", 10).await?;
+        assert_eq!(hits[0].iri, "http://example.com/Organism");
+        assert_eq!(hits[0].label.as_deref(), Some("Organism"));
+        assert_eq!(
+            hits[0].element_type,
+            ElementType::Owl(OwlType::Node(OwlNode::Class))
+        );
+        let hits = store.search("searchable", "living", 10).await?;
+        assert_eq!(hits[0].field, SearchField::AltLabel);
+
+        // Labels rank above comments, and words match by prefix and despite typos.
+        let hits = store.search("searchable", "organ", 10).await?;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].iri, "http://example.com/Organism");
+        assert_eq!(hits[1].field, SearchField::Comment);
+        let hits = store.search("searchable", "orgnism", 10).await?;
+        assert_eq!(hits.len(), 2);
+
+        // Local names are split at their humps.
+        let hits = store.search("searchable", "part", 10).await?;
+        assert_eq!(hits[0].field, SearchField::LocalName);
+        assert_eq!(
+            hits[0].element_type,
+            ElementType::Owl(OwlType::Edge(OwlEdge::ObjectProperty))
+        );
+        Ok(())
+    }
+}
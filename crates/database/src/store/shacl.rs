@@ -0,0 +1,1599 @@
+//! Validation of a graph against the SHACL Core shapes in another graph.
+//!
+//! Every constraint is compiled to a SPARQL query over the data graph, which selects the focus
+//! nodes and values violating it. The focus nodes of a shape are selected by a group pattern
+//! binding `?this`, so the shapes in `sh:node`, `sh:not`, `sh:and`, `sh:or`, `sh:xone` and
+//! `sh:qualifiedValueShape` are validated by the same queries, with the value nodes of the
+//! enclosing shape as focus nodes.
+//!
+//! SHACL-SPARQL constraints and `sh:qualifiedValueShapesDisjoint` are not supported.
+
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use grapher::prelude::GraphDisplayData;
+use log::info;
+use rdf_fusion::execution::results::{QueryResults, QuerySolution};
+use rdf_fusion::io::{RdfFormat, RdfSerializer};
+use rdf_fusion::model::{BlankNode, Literal, NamedNode, NamedOrBlankNode, Term, Triple};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{
+    Annotation, GraphAnnotations, ShaclSeverity, VOWLGrapherError, ValidationReport,
+    ValidationResult,
+};
+
+use crate::store::{VOWLGrapherStore, lifecycle};
+
+/// The namespace of SHACL.
+const SH: &str = "http://www.w3.org/ns/shacl#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const RDFS_SUB_CLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+
+/// How deeply shapes may be nested in each other.
+///
+/// SHACL leaves the validation of recursive shapes undefined, so they end here.
+const MAX_DEPTH: usize = 32;
+
+/// Returns the IRI of a term of the SHACL vocabulary.
+fn sh(name: &str) -> String {
+    format!("{SH}{name}")
+}
+
+/// Returns a term in SPARQL syntax, or `None` for blank nodes, which SPARQL cannot refer to.
+fn sparql_term(term: &Term) -> Option<String> {
+    match term {
+        Term::BlankNode(_) => None,
+        _ => Some(term.to_string()),
+    }
+}
+
+/// Returns a term which can be the subject of a statement as such.
+fn as_subject(term: &Term) -> Option<NamedOrBlankNode> {
+    match term {
+        Term::NamedNode(node) => Some(node.clone().into()),
+        Term::BlankNode(node) => Some(node.clone().into()),
+        _ => None,
+    }
+}
+
+/// Returns the IRI of a term, if it is a named node.
+fn as_iri(term: &Term) -> Option<&str> {
+    match term {
+        Term::NamedNode(node) => Some(node.as_str()),
+        _ => None,
+    }
+}
+
+/// Returns the lexical form of a literal term, if it is one.
+fn as_literal(term: &Term) -> Option<&str> {
+    match term {
+        Term::Literal(literal) => Some(literal.value()),
+        _ => None,
+    }
+}
+
+/// The statements of a shapes graph, indexed by subject.
+struct Shapes {
+    statements: HashMap<NamedOrBlankNode, Vec<(String, Term)>>,
+}
+
+impl Shapes {
+    /// Indexes the statements of a shapes graph.
+    fn new(triples: Vec<Triple>) -> Self {
+        let mut statements = HashMap::<_, Vec<_>>::new();
+        for triple in triples {
+            statements
+                .entry(triple.subject)
+                .or_default()
+                .push((triple.predicate.into_string(), triple.object));
+        }
+        Self { statements }
+    }
+
+    /// Returns the objects of the statements about `node` with the predicate `property`.
+    fn objects(&self, node: &NamedOrBlankNode, property: &str) -> Vec<&Term> {
+        self.statements
+            .get(node)
+            .into_iter()
+            .flatten()
+            .filter(|(predicate, _)| predicate == property)
+            .map(|(_, object)| object)
+            .collect()
+    }
+
+    /// Returns the first object of the statements about `node` with the predicate `property`.
+    fn object(&self, node: &NamedOrBlankNode, property: &str) -> Option<&Term> {
+        self.objects(node, property).into_iter().next()
+    }
+
+    /// Returns whether `node` has the type `class`.
+    fn has_type(&self, node: &NamedOrBlankNode, class: &str) -> bool {
+        self.objects(node, RDF_TYPE)
+            .into_iter()
+            .any(|object| as_iri(object) == Some(class))
+    }
+
+    /// Returns whether `node` has the boolean parameter `name` set to true.
+    fn flag(&self, node: &NamedOrBlankNode, name: &str) -> bool {
+        self.object(node, &sh(name))
+            .and_then(as_literal)
+            .is_some_and(|value| value == "true" || value == "1")
+    }
+
+    /// Returns the members of the RDF list starting at `head`.
+    fn list(&self, head: &Term) -> Vec<&Term> {
+        let mut members = Vec::new();
+        let mut seen = HashSet::new();
+        let mut node = as_subject(head);
+        while let Some(subject) = node
+            && seen.insert(subject.clone())
+            && let Some(first) = self.object(&subject, RDF_FIRST)
+        {
+            members.push(first);
+            node = self.object(&subject, RDF_REST).and_then(as_subject);
+        }
+        members
+    }
+
+    /// Returns a SHACL property path in SPARQL syntax.
+    fn path(&self, path: &Term, depth: usize) -> Option<String> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let node = match path {
+            Term::NamedNode(property) => return Some(format!("<{}>", property.as_str())),
+            Term::BlankNode(node) => NamedOrBlankNode::from(node.clone()),
+            _ => return None,
+        };
+        if let Some(inner) = self.object(&node, &sh("inversePath")) {
+            return Some(format!("^({})", self.path(inner, depth + 1)?));
+        }
+        for (name, operator) in [
+            ("zeroOrMorePath", "*"),
+            ("oneOrMorePath", "+"),
+            ("zeroOrOnePath", "?"),
+        ] {
+            if let Some(inner) = self.object(&node, &sh(name)) {
+                return Some(format!("({}){operator}", self.path(inner, depth + 1)?));
+            }
+        }
+        let (members, separator) = match self.object(&node, &sh("alternativePath")) {
+            Some(alternatives) => (self.list(alternatives), "|"),
+            None => (self.list(path), "/"),
+        };
+        if members.is_empty() {
+            return None;
+        }
+        let members = members
+            .into_iter()
+            .map(|member| self.path(member, depth + 1))
+            .collect::<Option<Vec<_>>>()?;
+        Some(format!("({})", members.join(separator)))
+    }
+
+    /// Appends the statements about a blank node, and the blank nodes it refers to, to `triples`.
+    fn describe(&self, term: &Term, triples: &mut Vec<Triple>, seen: &mut HashSet<BlankNode>) {
+        let Term::BlankNode(node) = term else {
+            return;
+        };
+        if !seen.insert(node.clone()) {
+            return;
+        }
+        let subject = NamedOrBlankNode::from(node.clone());
+        for (predicate, object) in self.statements.get(&subject).into_iter().flatten() {
+            triples.push(Triple::new(
+                subject.clone(),
+                NamedNode::new_unchecked(predicate.clone()),
+                object.clone(),
+            ));
+            self.describe(object, triples, seen);
+        }
+    }
+
+    /// Returns a group pattern binding the targets of a shape to `?this`,
+    /// or `None` if the shape has no targets.
+    fn targets(&self, shape: &NamedOrBlankNode) -> Option<String> {
+        let mut patterns = Vec::new();
+        let nodes = self
+            .objects(shape, &sh("targetNode"))
+            .into_iter()
+            .filter_map(sparql_term)
+            .collect::<Vec<_>>();
+        if !nodes.is_empty() {
+            patterns.push(format!("VALUES ?this {{ {} }}", nodes.join(" ")));
+        }
+
+        let mut classes = self
+            .objects(shape, &sh("targetClass"))
+            .into_iter()
+            .filter_map(as_iri)
+            .collect::<Vec<_>>();
+        // A shape which is also a class targets its instances.
+        if let NamedOrBlankNode::NamedNode(iri) = shape
+            && (self.has_type(shape, RDFS_CLASS) || self.has_type(shape, OWL_CLASS))
+            && (self.has_type(shape, &sh("NodeShape"))
+                || self.has_type(shape, &sh("PropertyShape")))
+        {
+            classes.push(iri.as_str());
+        }
+        for class in classes {
+            patterns.push(format!(
+                "?this <{RDF_TYPE}>/<{RDFS_SUB_CLASS_OF}>* <{class}>"
+            ));
+        }
+        for property in self
+            .objects(shape, &sh("targetSubjectsOf"))
+            .into_iter()
+            .filter_map(as_iri)
+        {
+            patterns.push(format!("?this <{property}> []"));
+        }
+        for property in self
+            .objects(shape, &sh("targetObjectsOf"))
+            .into_iter()
+            .filter_map(as_iri)
+        {
+            patterns.push(format!("[] <{property}> ?this"));
+        }
+
+        (!patterns.is_empty()).then(|| {
+            format!(
+                "{{ SELECT DISTINCT ?this WHERE {{ {{ {} }} }} }}",
+                patterns.join(" } UNION { ")
+            )
+        })
+    }
+}
+
+/// Returns whether a value node violates a constraint,
+/// given the nodes failing each shape the constraint refers to.
+type Check = fn(&Term, &[HashSet<Term>]) -> bool;
+
+/// A focus node or value violating a constraint.
+struct Violation {
+    focus: Term,
+    /// The path of the property shape, as a node of the shapes graph.
+    path: Option<Term>,
+    value: Option<Term>,
+    shape: NamedOrBlankNode,
+    /// The local name of the constraint component.
+    component: &'static str,
+    severity: ShaclSeverity,
+    message: String,
+}
+
+/// A shape being validated against a set of focus nodes.
+struct Scope<'s> {
+    shape: &'s NamedOrBlankNode,
+    /// A group pattern binding the focus nodes to `?this`.
+    focus: &'s str,
+    /// The path of a property shape, as a node of the shapes graph and in SPARQL syntax.
+    path: Option<(Term, String)>,
+    severity: ShaclSeverity,
+    message: Option<String>,
+}
+
+impl Scope<'_> {
+    /// Returns a pattern binding the value nodes of `?this` to `?{variable}`.
+    fn values(&self, variable: &str) -> String {
+        match &self.path {
+            Some((_, path)) => format!("?this {path} ?{variable} ."),
+            None => format!("BIND(?this AS ?{variable})"),
+        }
+    }
+
+    /// Returns an expression telling whether `term` is a value node of `?this`.
+    fn has_value(&self, term: &str) -> String {
+        match &self.path {
+            Some((_, path)) => format!("EXISTS {{ ?this {path} {term} }}"),
+            None => format!("sameTerm(?this, {term})"),
+        }
+    }
+
+    /// Returns a group pattern binding the value nodes of all focus nodes to `?this`.
+    fn value_focus(&self) -> String {
+        match &self.path {
+            Some((_, path)) => format!(
+                "{{ SELECT DISTINCT ?this WHERE {{ {{ SELECT (?this AS ?parent) WHERE {{ {} }} }} ?parent {path} ?this }} }}",
+                self.focus
+            ),
+            None => self.focus.to_string(),
+        }
+    }
+
+    /// Returns a violation of a constraint of the shape.
+    fn violation(
+        &self,
+        focus: Term,
+        value: Option<Term>,
+        component: &'static str,
+        message: String,
+    ) -> Violation {
+        Violation {
+            focus,
+            path: self.path.as_ref().map(|(path, _)| path.clone()),
+            value,
+            shape: self.shape.clone(),
+            component,
+            severity: self.severity,
+            message: self.message.clone().unwrap_or(message),
+        }
+    }
+}
+
+/// Validates a data graph against the shapes of a shapes graph.
+struct Validator<'v> {
+    store: &'v VOWLGrapherStore,
+    /// The IRI of the data graph.
+    graph: String,
+    shapes: Shapes,
+}
+
+impl Validator<'_> {
+    /// Returns the solutions of a SELECT DISTINCT query over the data graph.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn select(
+        &self,
+        projection: &str,
+        pattern: &str,
+        modifiers: &str,
+    ) -> Result<Vec<QuerySolution>, VOWLGrapherStoreError> {
+        let query = format!(
+            "SELECT DISTINCT {projection} WHERE {{ GRAPH <{}> {{ {pattern} }} }} {modifiers}",
+            self.graph
+        );
+        let mut solutions = Vec::new();
+        if let QueryResults::Solutions(mut stream) = self.store.session.query(&query).await? {
+            while let Some(solution) = stream.next().await {
+                solutions.push(solution?);
+            }
+        }
+        Ok(solutions)
+    }
+
+    /// Returns the value nodes of the focus nodes that satisfy `condition`, as violations.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn value_violations(
+        &self,
+        scope: &Scope<'_>,
+        condition: &str,
+        component: &'static str,
+        message: String,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let pattern = format!(
+            "{} {} FILTER({condition})",
+            scope.focus,
+            scope.values("value")
+        );
+        Ok(self
+            .select("?this ?value", &pattern, "")
+            .await?
+            .into_iter()
+            .filter_map(|solution| {
+                Some(scope.violation(
+                    solution.get("this")?.clone(),
+                    solution.get("value").cloned(),
+                    component,
+                    message.clone(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns the focus nodes matched by `pattern` and `modifiers`, as violations.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn focus_violations(
+        &self,
+        scope: &Scope<'_>,
+        pattern: &str,
+        modifiers: &str,
+        component: &'static str,
+        message: String,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        Ok(self
+            .select("?this", &format!("{} {pattern}", scope.focus), modifiers)
+            .await?
+            .into_iter()
+            .filter_map(|solution| {
+                Some(scope.violation(
+                    solution.get("this")?.clone(),
+                    None,
+                    component,
+                    message.clone(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns every focus node with each of its value nodes.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn value_pairs(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<(Term, Term)>, VOWLGrapherStoreError> {
+        Ok(self
+            .select(
+                "?this ?value",
+                &format!("{} {}", scope.focus, scope.values("value")),
+                "",
+            )
+            .await?
+            .into_iter()
+            .filter_map(|solution| {
+                Some((
+                    solution.get("this")?.clone(),
+                    solution.get("value")?.clone(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns the nodes bound by `focus` which do not conform to `shape`.
+    ///
+    /// # Errors
+    /// Returns an error if the shapes are nested too deeply or the store could not be queried.
+    async fn failing(
+        &self,
+        shape: &Term,
+        focus: &str,
+        depth: usize,
+    ) -> Result<HashSet<Term>, VOWLGrapherStoreError> {
+        let Some(shape) = as_subject(shape) else {
+            return Ok(HashSet::new());
+        };
+        Ok(self
+            .validate(&shape, focus, depth + 1)
+            .await?
+            .into_iter()
+            .map(|violation| violation.focus)
+            .collect())
+    }
+
+    /// Validates the focus nodes bound by `focus` against a shape.
+    ///
+    /// # Errors
+    /// Returns an error if the shapes are nested too deeply or the store could not be queried.
+    fn validate<'a>(
+        &'a self,
+        shape: &'a NamedOrBlankNode,
+        focus: &'a str,
+        depth: usize,
+    ) -> BoxFuture<'a, Result<Vec<Violation>, VOWLGrapherStoreError>> {
+        Box::pin(async move {
+            if depth > MAX_DEPTH {
+                return Err(VOWLGrapherStoreErrorKind::InvalidQuery(format!(
+                    "Shape {shape} is nested too deeply, or refers to itself"
+                ))
+                .into());
+            }
+            let shapes = &self.shapes;
+            if shapes.flag(shape, "deactivated") {
+                return Ok(Vec::new());
+            }
+
+            let path = match shapes.object(shape, &sh("path")) {
+                Some(path) => Some((
+                    path.clone(),
+                    shapes.path(path, 0).ok_or_else(|| {
+                        VOWLGrapherStoreErrorKind::InvalidQuery(format!(
+                            "Shape {shape} has an invalid path"
+                        ))
+                    })?,
+                )),
+                None => None,
+            };
+            let severity = match shapes.object(shape, &sh("severity")).and_then(as_iri) {
+                Some(iri) if iri == sh("Warning") => ShaclSeverity::Warning,
+                Some(iri) if iri == sh("Info") => ShaclSeverity::Info,
+                _ => ShaclSeverity::Violation,
+            };
+            let scope = Scope {
+                shape,
+                focus,
+                path,
+                severity,
+                message: shapes
+                    .object(shape, &sh("message"))
+                    .and_then(as_literal)
+                    .map(str::to_string),
+            };
+
+            let mut violations = self.value_type_constraints(&scope).await?;
+            violations.extend(self.value_range_constraints(&scope).await?);
+            violations.extend(self.cardinality_constraints(&scope).await?);
+            violations.extend(self.property_pair_constraints(&scope).await?);
+            violations.extend(self.closed_constraint(&scope).await?);
+            violations.extend(self.shape_constraints(&scope, depth).await?);
+
+            // Property shapes validate the same focus nodes.
+            for property in shapes
+                .objects(shape, &sh("property"))
+                .into_iter()
+                .filter_map(as_subject)
+            {
+                violations.extend(self.validate(&property, focus, depth + 1).await?);
+            }
+            Ok(violations)
+        })
+    }
+
+    /// Checks the constraints on the type of value nodes:
+    /// `sh:class`, `sh:datatype` and `sh:nodeKind`.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn value_type_constraints(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let parameter = |name: &str| self.shapes.objects(scope.shape, &sh(name));
+        let mut violations = Vec::new();
+        for class in parameter("class").into_iter().filter_map(as_iri) {
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!(
+                        "NOT EXISTS {{ ?value <{RDF_TYPE}>/<{RDFS_SUB_CLASS_OF}>* <{class}> }}"
+                    ),
+                    "ClassConstraintComponent",
+                    format!("Value is not an instance of <{class}>"),
+                )
+                .await?,
+            );
+        }
+        for datatype in parameter("datatype").into_iter().filter_map(as_iri) {
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("!isLiteral(?value) || datatype(?value) != <{datatype}>"),
+                    "DatatypeConstraintComponent",
+                    format!("Value does not have datatype <{datatype}>"),
+                )
+                .await?,
+            );
+        }
+        for kind in parameter("nodeKind").into_iter().filter_map(as_iri) {
+            let Some(condition) = kind.strip_prefix(SH).and_then(|kind| match kind {
+                "IRI" => Some("isIRI(?value)"),
+                "BlankNode" => Some("isBlank(?value)"),
+                "Literal" => Some("isLiteral(?value)"),
+                "BlankNodeOrIRI" => Some("isBlank(?value) || isIRI(?value)"),
+                "BlankNodeOrLiteral" => Some("isBlank(?value) || isLiteral(?value)"),
+                "IRIOrLiteral" => Some("isIRI(?value) || isLiteral(?value)"),
+                _ => None,
+            }) else {
+                continue;
+            };
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("!({condition})"),
+                    "NodeKindConstraintComponent",
+                    format!("Value is not of node kind <{kind}>"),
+                )
+                .await?,
+            );
+        }
+        Ok(violations)
+    }
+
+    /// Checks the constraints on the range, length and form of value nodes:
+    /// `sh:minExclusive`, `sh:minInclusive`, `sh:maxExclusive`, `sh:maxInclusive`,
+    /// `sh:minLength`, `sh:maxLength`, `sh:pattern`, `sh:languageIn`, `sh:in` and `sh:hasValue`.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn value_range_constraints(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let shapes = &self.shapes;
+        let parameter = |name: &str| shapes.objects(scope.shape, &sh(name));
+        let mut violations = Vec::new();
+        for (name, operator, component, relation) in [
+            (
+                "minExclusive",
+                ">",
+                "MinExclusiveConstraintComponent",
+                "greater than",
+            ),
+            (
+                "minInclusive",
+                ">=",
+                "MinInclusiveConstraintComponent",
+                "at least",
+            ),
+            (
+                "maxExclusive",
+                "<",
+                "MaxExclusiveConstraintComponent",
+                "less than",
+            ),
+            (
+                "maxInclusive",
+                "<=",
+                "MaxInclusiveConstraintComponent",
+                "at most",
+            ),
+        ] {
+            for bound in parameter(name).into_iter().filter_map(sparql_term) {
+                violations.extend(
+                    self.value_violations(
+                        scope,
+                        &format!("!COALESCE(?value {operator} {bound}, false)"),
+                        component,
+                        format!("Value is not {relation} {bound}"),
+                    )
+                    .await?,
+                );
+            }
+        }
+        for (name, operator, component, relation) in [
+            (
+                "minLength",
+                "<",
+                "MinLengthConstraintComponent",
+                "shorter than",
+            ),
+            (
+                "maxLength",
+                ">",
+                "MaxLengthConstraintComponent",
+                "longer than",
+            ),
+        ] {
+            for length in parameter(name)
+                .into_iter()
+                .filter_map(as_literal)
+                .filter_map(|length| length.parse::<u64>().ok())
+            {
+                violations.extend(
+                    self.value_violations(
+                        scope,
+                        &format!("isBlank(?value) || STRLEN(STR(?value)) {operator} {length}"),
+                        component,
+                        format!("Value is {relation} {length} characters"),
+                    )
+                    .await?,
+                );
+            }
+        }
+
+        let flags = shapes
+            .object(scope.shape, &sh("flags"))
+            .and_then(sparql_term)
+            .unwrap_or_else(|| "\"\"".to_string());
+        for pattern in parameter("pattern").into_iter().filter_map(sparql_term) {
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("isBlank(?value) || !REGEX(STR(?value), {pattern}, {flags})"),
+                    "PatternConstraintComponent",
+                    format!("Value does not match the pattern {pattern}"),
+                )
+                .await?,
+            );
+        }
+        for languages in parameter("languageIn") {
+            let languages = shapes
+                .list(languages)
+                .into_iter()
+                .filter_map(sparql_term)
+                .collect::<Vec<_>>();
+            let matches = if languages.is_empty() {
+                "false".to_string()
+            } else {
+                languages
+                    .iter()
+                    .map(|language| format!("langMatches(lang(?value), {language})"))
+                    .collect::<Vec<_>>()
+                    .join(" || ")
+            };
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("!isLiteral(?value) || !({matches})"),
+                    "LanguageInConstraintComponent",
+                    format!("Value is not in the languages {}", languages.join(", ")),
+                )
+                .await?,
+            );
+        }
+        for members in parameter("in") {
+            let members = shapes
+                .list(members)
+                .into_iter()
+                .filter_map(sparql_term)
+                .collect::<Vec<_>>()
+                .join(", ");
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("?value NOT IN ({members})"),
+                    "InConstraintComponent",
+                    format!("Value is not one of {members}"),
+                )
+                .await?,
+            );
+        }
+        for value in parameter("hasValue").into_iter().filter_map(sparql_term) {
+            violations.extend(
+                self.focus_violations(
+                    scope,
+                    &format!("FILTER(!({}))", scope.has_value(&value)),
+                    "",
+                    "HasValueConstraintComponent",
+                    format!("Missing the value {value}"),
+                )
+                .await?,
+            );
+        }
+        Ok(violations)
+    }
+
+    /// Checks the constraints on the number of value nodes of property shapes:
+    /// `sh:minCount`, `sh:maxCount` and `sh:uniqueLang`.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn cardinality_constraints(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let mut violations = Vec::new();
+        if scope.path.is_none() {
+            return Ok(violations);
+        }
+        for (name, operator, component, relation) in [
+            ("minCount", "<", "MinCountConstraintComponent", "Fewer"),
+            ("maxCount", ">", "MaxCountConstraintComponent", "More"),
+        ] {
+            for count in self
+                .shapes
+                .objects(scope.shape, &sh(name))
+                .into_iter()
+                .filter_map(as_literal)
+                .filter_map(|count| count.parse::<u64>().ok())
+            {
+                violations.extend(
+                    self.focus_violations(
+                        scope,
+                        &format!("OPTIONAL {{ {} }}", scope.values("value")),
+                        &format!(
+                            "GROUP BY ?this HAVING (COUNT(DISTINCT ?value) {operator} {count})"
+                        ),
+                        component,
+                        format!("{relation} than {count} values"),
+                    )
+                    .await?,
+                );
+            }
+        }
+        if self.shapes.flag(scope.shape, "uniqueLang") {
+            violations.extend(
+                self.focus_violations(
+                    scope,
+                    &format!(
+                        "{} {} FILTER(!sameTerm(?value, ?other) && lang(?value) != \"\" && lang(?value) = lang(?other))",
+                        scope.values("value"),
+                        scope.values("other")
+                    ),
+                    "",
+                    "UniqueLangConstraintComponent",
+                    "More than one value has the same language".to_string(),
+                )
+                .await?,
+            );
+        }
+        Ok(violations)
+    }
+
+    /// Checks the constraints comparing value nodes to the values of another property:
+    /// `sh:equals`, `sh:disjoint`, `sh:lessThan` and `sh:lessThanOrEquals`.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn property_pair_constraints(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let parameter = |name: &str| self.shapes.objects(scope.shape, &sh(name));
+        let mut violations = Vec::new();
+        for property in parameter("equals").into_iter().filter_map(as_iri) {
+            let message = format!("Values differ from the values of <{property}>");
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("NOT EXISTS {{ ?this <{property}> ?value }}"),
+                    "EqualsConstraintComponent",
+                    message.clone(),
+                )
+                .await?,
+            );
+            violations.extend(
+                self.select(
+                    "?this ?value",
+                    &format!(
+                        "{} ?this <{property}> ?value FILTER(!({}))",
+                        scope.focus,
+                        scope.has_value("?value")
+                    ),
+                    "",
+                )
+                .await?
+                .into_iter()
+                .filter_map(|solution| {
+                    Some(scope.violation(
+                        solution.get("this")?.clone(),
+                        solution.get("value").cloned(),
+                        "EqualsConstraintComponent",
+                        message.clone(),
+                    ))
+                }),
+            );
+        }
+        for property in parameter("disjoint").into_iter().filter_map(as_iri) {
+            violations.extend(
+                self.value_violations(
+                    scope,
+                    &format!("EXISTS {{ ?this <{property}> ?value }}"),
+                    "DisjointConstraintComponent",
+                    format!("Value is also a value of <{property}>"),
+                )
+                .await?,
+            );
+        }
+        for (name, operator, component, relation) in [
+            ("lessThan", "<", "LessThanConstraintComponent", "less than"),
+            (
+                "lessThanOrEquals",
+                "<=",
+                "LessThanOrEqualsConstraintComponent",
+                "at most",
+            ),
+        ] {
+            for property in parameter(name).into_iter().filter_map(as_iri) {
+                violations.extend(
+                    self.value_violations(
+                        scope,
+                        &format!(
+                            "EXISTS {{ ?this <{property}> ?other FILTER(!COALESCE(?value {operator} ?other, false)) }}"
+                        ),
+                        component,
+                        format!("Value is not {relation} the values of <{property}>"),
+                    )
+                    .await?,
+                );
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Checks `sh:closed` on node shapes, which then only allow the properties of their
+    /// property shapes and `sh:ignoredProperties`.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    async fn closed_constraint(
+        &self,
+        scope: &Scope<'_>,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let shapes = &self.shapes;
+        if scope.path.is_some() || !shapes.flag(scope.shape, "closed") {
+            return Ok(Vec::new());
+        }
+        let mut allowed = shapes
+            .objects(scope.shape, &sh("property"))
+            .into_iter()
+            .filter_map(as_subject)
+            .filter_map(|property| shapes.object(&property, &sh("path")).and_then(as_iri))
+            .map(|iri| format!("<{iri}>"))
+            .collect::<Vec<_>>();
+        for ignored in shapes.objects(scope.shape, &sh("ignoredProperties")) {
+            allowed.extend(
+                shapes
+                    .list(ignored)
+                    .into_iter()
+                    .filter_map(as_iri)
+                    .map(|iri| format!("<{iri}>")),
+            );
+        }
+
+        let mut violations = Vec::new();
+        for solution in self
+            .select(
+                "?this ?predicate ?value",
+                &format!(
+                    "{} ?this ?predicate ?value FILTER(?predicate NOT IN ({}))",
+                    scope.focus,
+                    allowed.join(", ")
+                ),
+                "",
+            )
+            .await?
+        {
+            if let (Some(this), Some(predicate)) = (solution.get("this"), solution.get("predicate"))
+            {
+                let mut violation = scope.violation(
+                    this.clone(),
+                    solution.get("value").cloned(),
+                    "ClosedConstraintComponent",
+                    format!("The shape does not allow the property {predicate}"),
+                );
+                violation.path = Some(predicate.clone());
+                violations.push(violation);
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Checks the constraints on the conformance of value nodes to other shapes:
+    /// `sh:node`, `sh:not`, `sh:and`, `sh:or`, `sh:xone` and `sh:qualifiedValueShape`.
+    ///
+    /// # Errors
+    /// Returns an error if the shapes are nested too deeply or the store could not be queried.
+    async fn shape_constraints(
+        &self,
+        scope: &Scope<'_>,
+        depth: usize,
+    ) -> Result<Vec<Violation>, VOWLGrapherStoreError> {
+        let shapes = &self.shapes;
+        let parameter = |name: &str| shapes.objects(scope.shape, &sh(name));
+        let value_focus = scope.value_focus();
+
+        let mut checks = Vec::new();
+        for (name, component, message, violates) in [
+            (
+                "node",
+                "NodeConstraintComponent",
+                "Value does not conform to the shape",
+                (|value: &Term, failing: &[HashSet<Term>]| {
+                    failing.iter().any(|failing| failing.contains(value))
+                }) as Check,
+            ),
+            (
+                "not",
+                "NotConstraintComponent",
+                "Value conforms to the shape",
+                |value: &Term, failing: &[HashSet<Term>]| {
+                    failing.iter().all(|failing| !failing.contains(value))
+                },
+            ),
+        ] {
+            for other in parameter(name) {
+                let failing = vec![self.failing(other, &value_focus, depth).await?];
+                checks.push((component, format!("{message} {other}"), failing, violates));
+            }
+        }
+        for (name, component, message, violates) in [
+            (
+                "and",
+                "AndConstraintComponent",
+                "Value does not conform to all of the shapes",
+                (|value: &Term, failing: &[HashSet<Term>]| {
+                    failing.iter().any(|failing| failing.contains(value))
+                }) as Check,
+            ),
+            (
+                "or",
+                "OrConstraintComponent",
+                "Value does not conform to any of the shapes",
+                |value: &Term, failing: &[HashSet<Term>]| {
+                    failing.iter().all(|failing| failing.contains(value))
+                },
+            ),
+            (
+                "xone",
+                "XoneConstraintComponent",
+                "Value does not conform to exactly one of the shapes",
+                |value: &Term, failing: &[HashSet<Term>]| {
+                    failing
+                        .iter()
+                        .filter(|failing| !failing.contains(value))
+                        .count()
+                        != 1
+                },
+            ),
+        ] {
+            for members in parameter(name) {
+                let mut failing = Vec::new();
+                for member in shapes.list(members) {
+                    failing.push(self.failing(member, &value_focus, depth).await?);
+                }
+                checks.push((component, message.to_string(), failing, violates));
+            }
+        }
+
+        let mut violations = Vec::new();
+        if !checks.is_empty() {
+            let pairs = self.value_pairs(scope).await?;
+            for (component, message, failing, violates) in checks {
+                violations.extend(
+                    pairs
+                        .iter()
+                        .filter(|(_, value)| violates(value, &failing))
+                        .map(|(focus, value)| {
+                            scope.violation(
+                                focus.clone(),
+                                Some(value.clone()),
+                                component,
+                                message.clone(),
+                            )
+                        }),
+                );
+            }
+        }
+
+        if scope.path.is_some()
+            && let Some(qualified) = shapes.object(scope.shape, &sh("qualifiedValueShape"))
+        {
+            let limit = |name: &str| {
+                shapes
+                    .object(scope.shape, &sh(name))
+                    .and_then(as_literal)
+                    .and_then(|count| count.parse::<usize>().ok())
+            };
+            let (min, max) = (limit("qualifiedMinCount"), limit("qualifiedMaxCount"));
+            let failing = self.failing(qualified, &value_focus, depth).await?;
+            let mut conforming = self
+                .select("?this", scope.focus, "")
+                .await?
+                .into_iter()
+                .filter_map(|solution| Some((solution.get("this")?.clone(), 0)))
+                .collect::<HashMap<_, _>>();
+            for (node, value) in self.value_pairs(scope).await? {
+                if !failing.contains(&value) {
+                    *conforming.entry(node).or_default() += 1;
+                }
+            }
+            for (node, count) in conforming {
+                if let Some(min) = min.filter(|min| count < *min) {
+                    violations.push(scope.violation(
+                        node.clone(),
+                        None,
+                        "QualifiedMinCountConstraintComponent",
+                        format!("Fewer than {min} values conform to the shape {qualified}"),
+                    ));
+                }
+                if let Some(max) = max.filter(|max| count > *max) {
+                    violations.push(scope.violation(
+                        node,
+                        None,
+                        "QualifiedMaxCountConstraintComponent",
+                        format!("More than {max} values conform to the shape {qualified}"),
+                    ));
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// The violations found by validating a data graph.
+struct Validation {
+    report: ValidationReport,
+    violations: Vec<Violation>,
+    shapes: Shapes,
+}
+
+impl Validation {
+    /// Writes the validation report as an `sh:ValidationReport` in Turtle.
+    fn turtle_report(&self) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        let report = BlankNode::default();
+        let mut triples = vec![
+            Triple::new(
+                report.clone(),
+                NamedNode::new(RDF_TYPE)?,
+                NamedNode::new(sh("ValidationReport"))?,
+            ),
+            Triple::new(
+                report.clone(),
+                NamedNode::new(sh("conforms"))?,
+                Literal::from(self.report.conforms),
+            ),
+        ];
+        let mut described = HashSet::new();
+        for violation in &self.violations {
+            let result = BlankNode::default();
+            let severity = match violation.severity {
+                ShaclSeverity::Violation => "Violation",
+                ShaclSeverity::Warning => "Warning",
+                ShaclSeverity::Info => "Info",
+            };
+            triples.extend([
+                Triple::new(
+                    report.clone(),
+                    NamedNode::new(sh("result"))?,
+                    result.clone(),
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(RDF_TYPE)?,
+                    NamedNode::new(sh("ValidationResult"))?,
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("focusNode"))?,
+                    violation.focus.clone(),
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("sourceShape"))?,
+                    violation.shape.clone(),
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("sourceConstraintComponent"))?,
+                    NamedNode::new(sh(violation.component))?,
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("resultSeverity"))?,
+                    NamedNode::new(sh(severity))?,
+                ),
+                Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("resultMessage"))?,
+                    Literal::new_simple_literal(&violation.message),
+                ),
+            ]);
+            if let Some(path) = &violation.path {
+                triples.push(Triple::new(
+                    result.clone(),
+                    NamedNode::new(sh("resultPath"))?,
+                    path.clone(),
+                ));
+                self.shapes.describe(path, &mut triples, &mut described);
+            }
+            if let Some(value) = &violation.value {
+                triples.push(Triple::new(
+                    result,
+                    NamedNode::new(sh("value"))?,
+                    value.clone(),
+                ));
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut serializer = RdfSerializer::from_format(RdfFormat::Turtle)
+            .with_prefix("sh", SH)?
+            .with_prefix("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#")?
+            .for_writer(&mut buf);
+        for triple in &triples {
+            serializer.serialize_triple(triple)?;
+        }
+        serializer.finish()?;
+        Ok(buf)
+    }
+}
+
+impl VOWLGrapherStore {
+    /// Validates a graph of the user against the SHACL shapes in another graph of the user.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist, the shapes are invalid,
+    /// or the store could not be queried.
+    pub async fn validate_shacl(
+        &self,
+        data_graph: &str,
+        shapes_graph: &str,
+    ) -> Result<ValidationReport, VOWLGrapherStoreError> {
+        Ok(self.shacl(data_graph, shapes_graph).await?.report)
+    }
+
+    /// Validates a graph of the user against the SHACL shapes in another graph of the user
+    /// and serializes it, with focus nodes annotated by the severities of their results.
+    ///
+    /// This method tries to continue serializing despite errors.
+    /// As such, the `Ok` value contains non-fatal errors encountered during serialization.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist, the shapes are invalid,
+    /// or the graph could not be validated or serialized.
+    pub async fn shacl_display_data(
+        &self,
+        data_graph: &str,
+        shapes_graph: &str,
+    ) -> Result<
+        (
+            GraphDisplayData,
+            GraphAnnotations,
+            ValidationReport,
+            Option<VOWLGrapherError>,
+        ),
+        VOWLGrapherError,
+    > {
+        let validation = self.shacl(data_graph, shapes_graph).await?;
+        let mut focus_nodes = HashMap::<String, BTreeSet<Annotation>>::new();
+        for violation in &validation.violations {
+            if let Some(focus) = as_iri(&violation.focus) {
+                focus_nodes
+                    .entry(focus.to_string())
+                    .or_default()
+                    .insert(Annotation::FocusNode(violation.severity));
+            }
+        }
+        let triples = self.graph_triples(data_graph).await?;
+
+        let (display_data, annotations, errors) =
+            self.display_triples(triples, focus_nodes).await?;
+        Ok((display_data, annotations, validation.report, errors))
+    }
+
+    /// Validates a graph of the user against the SHACL shapes in another graph of the user
+    /// and writes the results as an `sh:ValidationReport` in Turtle.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist, the shapes are invalid,
+    /// or the report could not be written.
+    pub async fn shacl_report(
+        &self,
+        data_graph: &str,
+        shapes_graph: &str,
+    ) -> Result<Vec<u8>, VOWLGrapherStoreError> {
+        self.shacl(data_graph, shapes_graph).await?.turtle_report()
+    }
+
+    /// Validates a graph of the user against the SHACL shapes in another graph of the user.
+    ///
+    /// # Errors
+    /// Returns an error if either graph does not exist, the shapes are invalid,
+    /// or the store could not be queried.
+    async fn shacl(
+        &self,
+        data_graph: &str,
+        shapes_graph: &str,
+    ) -> Result<Validation, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph = self.get_graph_name(data_graph);
+        if self.graph_len(&graph).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{data_graph}' does not exist"
+            ))
+            .into());
+        }
+        lifecycle::touch_graph(&graph);
+
+        let validator = Validator {
+            store: self,
+            graph,
+            shapes: Shapes::new(self.graph_triples(shapes_graph).await?),
+        };
+        let mut violations = Vec::new();
+        for (shape, focus) in validator
+            .shapes
+            .statements
+            .keys()
+            .filter_map(|shape| Some((shape, validator.shapes.targets(shape)?)))
+        {
+            violations.extend(validator.validate(shape, &focus, 0).await?);
+        }
+        violations.sort_by(|a, b| {
+            (a.severity, a.focus.to_string(), a.component).cmp(&(
+                b.severity,
+                b.focus.to_string(),
+                b.component,
+            ))
+        });
+
+        let shapes = validator.shapes;
+        let results = violations
+            .iter()
+            .map(|violation| ValidationResult {
+                focus_node: violation.focus.to_string(),
+                result_path: violation
+                    .path
+                    .as_ref()
+                    .and_then(|path| shapes.path(path, 0)),
+                value: violation.value.as_ref().map(ToString::to_string),
+                source_shape: violation.shape.to_string(),
+                constraint_component: violation.component.to_string(),
+                severity: violation.severity,
+                message: violation.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        info!(
+            "Validated graph '{data_graph}' against the shapes in '{shapes_graph}': {} results",
+            results.len()
+        );
+
+        Ok(Validation {
+            report: ValidationReport {
+                data_graph: data_graph.to_string(),
+                shapes_graph: shapes_graph.to_string(),
+                conforms: results.is_empty(),
+                results,
+            },
+            violations,
+            shapes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    #[tokio::test]
+    async fn test_validate_shacl() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let data = br#"@prefix : <http://example.com/> .
+            :alice a :Person ; :name "Alice" ; :age 30 .
+            :bob a :Person ; :age "old" .
+            :carol a :Person ; :name "Carol" ; :name "Caroline" ; :knows :dave .
+            :dave :name "Dave" ."#;
+        let shapes = br#"@prefix : <http://example.com/> .
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+            :PersonShape a sh:NodeShape ;
+                sh:targetClass :Person ;
+                sh:property [ sh:path :name ; sh:minCount 1 ; sh:maxCount 1 ] ;
+                sh:property [ sh:path :age ; sh:datatype xsd:integer ; sh:severity sh:Warning ] ;
+                sh:property [ sh:path :knows ; sh:class :Person ] ."#;
+        store
+            .insert_bytes(data, "data", DataType::TTL, false)
+            .await?;
+        store
+            .insert_bytes(shapes, "shapes", DataType::TTL, false)
+            .await?;
+
+        let report = store.validate_shacl("data", "shapes").await?;
+        assert!(!report.conforms);
+        assert_eq!(
+            report
+                .results
+                .iter()
+                .map(|result| (
+                    result.focus_node.as_str(),
+                    result.constraint_component.as_str(),
+                    result.severity,
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "<http://example.com/bob>",
+                    "MinCountConstraintComponent",
+                    ShaclSeverity::Violation
+                ),
+                (
+                    "<http://example.com/carol>",
+                    "ClassConstraintComponent",
+                    ShaclSeverity::Violation
+                ),
+                (
+                    "<http://example.com/carol>",
+                    "MaxCountConstraintComponent",
+                    ShaclSeverity::Violation
+                ),
+                (
+                    "<http://example.com/bob>",
+                    "DatatypeConstraintComponent",
+                    ShaclSeverity::Warning
+                ),
+            ]
+        );
+
+        // The Turtle report links one result per violation to a single report.
+        let turtle = store.shacl_report("data", "shapes").await?;
+        store
+            .insert_bytes(&turtle, "report", DataType::TTL, false)
+            .await?;
+        let query = format!(
+            "PREFIX sh: <{SH}>
+            SELECT ?focus ?component ?severity ?path WHERE {{ GRAPH <{}> {{
+                ?report a sh:ValidationReport ; sh:conforms false ; sh:result ?result .
+                ?result a sh:ValidationResult ; sh:focusNode ?focus ; sh:sourceShape ?shape ;
+                    sh:sourceConstraintComponent ?component ; sh:resultSeverity ?severity ;
+                    sh:resultMessage ?message ; sh:resultPath ?path .
+            }} }} ORDER BY ?focus ?component",
+            store.get_graph_name("report")
+        );
+        let mut reported = Vec::new();
+        if let QueryResults::Solutions(mut stream) = store.session.query(&query).await? {
+            while let Some(solution) = stream.next().await {
+                let solution = solution?;
+                reported.push(
+                    ["focus", "component", "severity", "path"]
+                        .map(|variable| solution.get(variable).map(ToString::to_string)),
+                );
+            }
+        }
+        let expected = |focus: &str, component: &str, severity: &str, path: &str| {
+            [
+                format!("<http://example.com/{focus}>"),
+                format!("<{SH}{component}>"),
+                format!("<{SH}{severity}>"),
+                format!("<http://example.com/{path}>"),
+            ]
+            .map(Some)
+        };
+        assert_eq!(
+            reported,
+            vec![
+                expected("bob", "DatatypeConstraintComponent", "Warning", "age"),
+                expected("bob", "MinCountConstraintComponent", "Violation", "name"),
+                expected("carol", "ClassConstraintComponent", "Violation", "knows"),
+                expected("carol", "MaxCountConstraintComponent", "Violation", "name"),
+            ]
+        );
+        Ok(())
+    }
+
+    /// Validates `data` against `shapes` and returns the focus nodes and components of the results.
+    async fn results(
+        data: &[u8],
+        shapes: &[u8],
+    ) -> Result<Vec<(String, String)>, VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .insert_bytes(data, "data", DataType::TTL, false)
+            .await?;
+        store
+            .insert_bytes(shapes, "shapes", DataType::TTL, false)
+            .await?;
+        Ok(store
+            .validate_shacl("data", "shapes")
+            .await?
+            .results
+            .into_iter()
+            .map(|result| (result.focus_node, result.constraint_component))
+            .collect())
+    }
+
+    /// Returns the expected focus node and component of a result.
+    fn result(focus: &str, component: &str) -> (String, String) {
+        (
+            format!("<http://example.com/{focus}>"),
+            component.to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_shape_constraints() -> Result<(), VOWLGrapherStoreError> {
+        let data = br#"@prefix : <http://example.com/> .
+            :alice a :Person ; :address :a1 ; :contact :c1 ; :id :i1 .
+            :bob a :Person ; :address :a2 ; :contact :c2 ; :id :i2 .
+            :a1 :city "Paris" .
+            :a2 :street "Main Street" .
+            :c1 :email "alice@example.com" .
+            :c2 :fax "555-0100" .
+            :i1 :ssn "123" .
+            :i2 :ssn "456" ; :passport "P789" ."#;
+        let shapes = br#"@prefix : <http://example.com/> .
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            :PersonShape a sh:NodeShape ;
+                sh:targetClass :Person ;
+                sh:property [ sh:path :address ; sh:node :AddressShape ] ;
+                sh:property [ sh:path :contact ; sh:or ( :EmailShape :PhoneShape ) ] ;
+                sh:property [ sh:path :id ; sh:xone ( :SsnShape :PassportShape ) ] .
+            :AddressShape a sh:NodeShape ; sh:property [ sh:path :city ; sh:minCount 1 ] .
+            :EmailShape a sh:NodeShape ; sh:property [ sh:path :email ; sh:minCount 1 ] .
+            :PhoneShape a sh:NodeShape ; sh:property [ sh:path :phone ; sh:minCount 1 ] .
+            :SsnShape a sh:NodeShape ; sh:property [ sh:path :ssn ; sh:minCount 1 ] .
+            :PassportShape a sh:NodeShape ; sh:property [ sh:path :passport ; sh:minCount 1 ] ."#;
+
+        // Only the results of the targeted shape are reported, not those of the shapes it refers to.
+        assert_eq!(
+            results(data, shapes).await?,
+            vec![
+                result("bob", "NodeConstraintComponent"),
+                result("bob", "OrConstraintComponent"),
+                result("bob", "XoneConstraintComponent"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_qualified_value_shape() -> Result<(), VOWLGrapherStoreError> {
+        let data = br"@prefix : <http://example.com/> .
+            :left a :Hand ; :digit :thumb1 , :finger1 .
+            :right a :Hand ; :digit :finger2 .
+            :odd a :Hand ; :digit :thumb2 , :thumb3 .
+            :thumb1 a :Thumb . :thumb2 a :Thumb . :thumb3 a :Thumb .
+            :finger1 a :Finger . :finger2 a :Finger .";
+        let shapes = br"@prefix : <http://example.com/> .
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            :HandShape a sh:NodeShape ;
+                sh:targetClass :Hand ;
+                sh:property [
+                    sh:path :digit ;
+                    sh:qualifiedValueShape [ sh:class :Thumb ] ;
+                    sh:qualifiedMinCount 1 ;
+                    sh:qualifiedMaxCount 1
+                ] .";
+
+        assert_eq!(
+            results(data, shapes).await?,
+            vec![
+                result("odd", "QualifiedMaxCountConstraintComponent"),
+                result("right", "QualifiedMinCountConstraintComponent"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_closed_constraint() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        let data = br"@prefix : <http://example.com/> .
+            :p1 a :Point ; :x 1 ; :y 2 .
+            :p2 a :Point ; :x 1 ; :z 3 .";
+        let shapes = br"@prefix : <http://example.com/> .
+            @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            :PointShape a sh:NodeShape ;
+                sh:targetClass :Point ;
+                sh:closed true ;
+                sh:ignoredProperties ( rdf:type ) ;
+                sh:property [ sh:path :x ] ;
+                sh:property [ sh:path :y ] .";
+        store
+            .insert_bytes(data, "data", DataType::TTL, false)
+            .await?;
+        store
+            .insert_bytes(shapes, "shapes", DataType::TTL, false)
+            .await?;
+
+        // The property which is not allowed is reported as the path of the result.
+        assert_eq!(
+            store
+                .validate_shacl("data", "shapes")
+                .await?
+                .results
+                .into_iter()
+                .map(|result| (
+                    result.focus_node,
+                    result.constraint_component,
+                    result.result_path
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "<http://example.com/p2>".to_string(),
+                "ClosedConstraintComponent".to_string(),
+                Some("<http://example.com/z>".to_string())
+            )]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_property_pair_constraints() -> Result<(), VOWLGrapherStoreError> {
+        let data = br#"@prefix : <http://example.com/> .
+            :ok a :Event ; :start 1 ; :end 2 ; :deadline 1 ; :name "A" ; :title "A" ; :alias "B" .
+            :bad a :Event ; :start 3 ; :end 2 ; :deadline 2 ; :name "C" ; :title "D" ; :alias "C" ."#;
+        let shapes = br"@prefix : <http://example.com/> .
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            :EventShape a sh:NodeShape ;
+                sh:targetClass :Event ;
+                sh:property [ sh:path :start ; sh:lessThan :end ; sh:lessThanOrEquals :deadline ] ;
+                sh:property [ sh:path :name ; sh:equals :title ; sh:disjoint :alias ] .";
+
+        // Values missing on either side of sh:equals are reported.
+        assert_eq!(
+            results(data, shapes).await?,
+            vec![
+                result("bad", "DisjointConstraintComponent"),
+                result("bad", "EqualsConstraintComponent"),
+                result("bad", "EqualsConstraintComponent"),
+                result("bad", "LessThanConstraintComponent"),
+                result("bad", "LessThanOrEqualsConstraintComponent"),
+            ]
+        );
+        Ok(())
+    }
+}
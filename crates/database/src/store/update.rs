@@ -0,0 +1,361 @@
+//! Guarded execution of SPARQL 1.1 Update requests against a single user graph.
+//!
+//! The underlying store only evaluates queries. Each update operation is therefore
+//! turned into CONSTRUCT queries computing the triples to delete and insert, which are
+//! then applied to the user's graph before the next operation runs. Operations may only
+//! touch the graph they are run on, which is also the default graph of the update.
+
+use futures::StreamExt;
+use log::info;
+use rdf_fusion::execution::results::QueryResults;
+use rdf_fusion::model::{GraphName, NamedNode, Quad};
+use spargebra::algebra::{GraphPattern, GraphTarget};
+use spargebra::term::{GraphNamePattern, TriplePattern};
+use spargebra::{GraphUpdateOperation, Query, QueryDataset, SparqlParser};
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+
+use crate::store::{VOWLGrapherStore, lifecycle, search};
+
+/// The changes computed for a single update operation.
+#[derive(Debug, Default)]
+struct Changes {
+    /// Quads to remove from the graph.
+    delete: Vec<Quad>,
+    /// Quads to add to the graph.
+    insert: Vec<Quad>,
+    /// Whether the whole graph is removed before anything is inserted.
+    clear: bool,
+}
+
+impl VOWLGrapherStore {
+    /// Executes a SPARQL 1.1 Update request against a graph of the user.
+    ///
+    /// The graph is the default graph of the update. Operations referring to any other graph
+    /// are rejected, as are `LOAD` operations. `USING` clauses are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the update is invalid, refers to another graph,
+    /// would exceed the user's quota or fails to execute.
+    pub async fn update(
+        &self,
+        update: &str,
+        graph_name: &str,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.get_graph_name(graph_name);
+        lifecycle::touch_graph(&graph_name);
+        let graph = NamedNode::new(&graph_name)?;
+
+        let update = SparqlParser::new()
+            .parse_update(&update.replace("{GRAPH_IRI}", &graph_name))
+            .map_err(|e| VOWLGrapherStoreErrorKind::InvalidQuery(e.to_string()))?;
+
+        // Check every operation before applying any, so a rejected update leaves the graph untouched.
+        for operation in &update.operations {
+            check_operation(operation, &graph)?;
+        }
+
+        let byte_size = lifecycle::graph_usage(&graph_name).map_or(0, |usage| usage.byte_size);
+        for operation in update.operations {
+            let changes = self.changes_of(operation, &graph).await?;
+
+            let quad_count = if changes.clear {
+                0
+            } else {
+                self.graph_len(&graph_name).await?
+            };
+            self.check_quota(
+                &graph_name,
+                usize::try_from(quad_count)
+                    .unwrap_or(usize::MAX)
+                    .saturating_sub(changes.delete.len())
+                    .saturating_add(changes.insert.len()),
+                byte_size,
+            )?;
+
+            if changes.clear {
+                self.session.remove_named_graph(graph.as_ref()).await?;
+            }
+            for quad in &changes.delete {
+                self.session.remove(quad.as_ref()).await?;
+            }
+            self.session.extend(changes.insert).await?;
+        }
+        info!("Updated graph '{graph_name}'");
+        search::forget_index(&graph_name);
+        self.forget_inferences(&graph_name).await?;
+
+        self.register_graph(&graph_name, byte_size).await?;
+        self.persist_graph(&graph_name).await?;
+        Ok(())
+    }
+
+    /// Computes the changes a single update operation makes to `graph`.
+    ///
+    /// The operation must have passed [`check_operation`].
+    ///
+    /// # Errors
+    /// Returns an error if the operation fails to evaluate.
+    async fn changes_of(
+        &self,
+        operation: GraphUpdateOperation,
+        graph: &NamedNode,
+    ) -> Result<Changes, VOWLGrapherStoreError> {
+        match operation {
+            GraphUpdateOperation::InsertData { data } => {
+                let mut insert = Vec::with_capacity(data.len());
+                for quad in data {
+                    insert.push(Quad::new(
+                        quad.subject,
+                        quad.predicate,
+                        quad.object,
+                        graph.clone(),
+                    ));
+                }
+                Ok(Changes {
+                    insert,
+                    ..Changes::default()
+                })
+            }
+            GraphUpdateOperation::DeleteData { data } => {
+                let mut template = Vec::with_capacity(data.len());
+                for quad in data {
+                    template.push(TriplePattern {
+                        subject: quad.subject.into(),
+                        predicate: quad.predicate.into(),
+                        object: quad.object.into(),
+                    });
+                }
+                Ok(Changes {
+                    delete: self
+                        .construct(template, GraphPattern::Bgp { patterns: vec![] }, graph)
+                        .await?,
+                    ..Changes::default()
+                })
+            }
+            GraphUpdateOperation::DeleteInsert {
+                delete,
+                insert,
+                pattern,
+                ..
+            } => {
+                let mut delete_template = Vec::with_capacity(delete.len());
+                for quad in delete {
+                    delete_template.push(TriplePattern {
+                        subject: quad.subject.into(),
+                        predicate: quad.predicate,
+                        object: quad.object.into(),
+                    });
+                }
+                let mut insert_template = Vec::with_capacity(insert.len());
+                for quad in insert {
+                    insert_template.push(TriplePattern {
+                        subject: quad.subject,
+                        predicate: quad.predicate,
+                        object: quad.object,
+                    });
+                }
+
+                Ok(Changes {
+                    delete: self
+                        .construct(delete_template, (*pattern).clone(), graph)
+                        .await?,
+                    insert: self.construct(insert_template, *pattern, graph).await?,
+                    clear: false,
+                })
+            }
+            GraphUpdateOperation::Clear { .. } | GraphUpdateOperation::Drop { .. } => Ok(Changes {
+                clear: true,
+                ..Changes::default()
+            }),
+            GraphUpdateOperation::Create { .. } | GraphUpdateOperation::Load { .. } => {
+                Ok(Changes::default())
+            }
+        }
+    }
+
+    /// Instantiates `template` for every solution of `pattern` evaluated on `graph`.
+    ///
+    /// Returns the resulting triples as quads in `graph`.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails to evaluate.
+    async fn construct(
+        &self,
+        template: Vec<TriplePattern>,
+        pattern: GraphPattern,
+        graph: &NamedNode,
+    ) -> Result<Vec<Quad>, VOWLGrapherStoreError> {
+        if template.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = Query::Construct {
+            template,
+            dataset: Some(QueryDataset {
+                default: vec![graph.clone()],
+                named: Some(vec![graph.clone()]),
+            }),
+            pattern,
+            base_iri: None,
+        };
+
+        let mut quads = Vec::new();
+        if let QueryResults::Graph(mut triples) = self.session.query(&query.to_string()).await? {
+            while let Some(triple) = triples.next().await {
+                quads.push(triple?.in_graph(graph.clone()));
+            }
+        }
+        Ok(quads)
+    }
+}
+
+/// Checks that an update operation only refers to `graph`.
+///
+/// # Errors
+/// Returns an error if the operation refers to another graph or is a `LOAD` operation.
+fn check_operation(
+    operation: &GraphUpdateOperation,
+    graph: &NamedNode,
+) -> Result<(), VOWLGrapherStoreError> {
+    match operation {
+        GraphUpdateOperation::InsertData { data } => data
+            .iter()
+            .try_for_each(|quad| check_graph_name(&quad.graph_name, graph)),
+        GraphUpdateOperation::DeleteData { data } => data
+            .iter()
+            .try_for_each(|quad| check_graph_name(&quad.graph_name, graph)),
+        GraphUpdateOperation::DeleteInsert { delete, insert, .. } => {
+            for quad in delete {
+                check_graph_name_pattern(&quad.graph_name, graph)?;
+            }
+            for quad in insert {
+                check_graph_name_pattern(&quad.graph_name, graph)?;
+            }
+            Ok(())
+        }
+        GraphUpdateOperation::Clear { graph: target, .. }
+        | GraphUpdateOperation::Drop { graph: target, .. } => match target {
+            GraphTarget::DefaultGraph => Ok(()),
+            GraphTarget::NamedNode(node) if node == graph => Ok(()),
+            _ => Err(forbidden_graph()),
+        },
+        GraphUpdateOperation::Create { graph: target, .. } => {
+            if target == graph {
+                Ok(())
+            } else {
+                Err(forbidden_graph())
+            }
+        }
+        GraphUpdateOperation::Load { .. } => Err(VOWLGrapherStoreErrorKind::ForbiddenOperation(
+            "LOAD is not supported. Upload the ontology instead".to_string(),
+        )
+        .into()),
+    }
+}
+
+/// Checks that a quad of an `INSERT DATA` or `DELETE DATA` operation belongs to `graph`.
+///
+/// # Errors
+/// Returns an error if the quad belongs to another graph.
+fn check_graph_name(
+    graph_name: &GraphName,
+    graph: &NamedNode,
+) -> Result<(), VOWLGrapherStoreError> {
+    match graph_name {
+        GraphName::DefaultGraph => Ok(()),
+        GraphName::NamedNode(node) if node == graph => Ok(()),
+        _ => Err(forbidden_graph()),
+    }
+}
+
+/// Checks that a template of a `DELETE`/`INSERT` operation writes to `graph`.
+///
+/// # Errors
+/// Returns an error if the template writes to another graph or to a variable graph.
+fn check_graph_name_pattern(
+    graph_name: &GraphNamePattern,
+    graph: &NamedNode,
+) -> Result<(), VOWLGrapherStoreError> {
+    match graph_name {
+        GraphNamePattern::DefaultGraph => Ok(()),
+        GraphNamePattern::NamedNode(node) if node == graph => Ok(()),
+        _ => Err(forbidden_graph()),
+    }
+}
+
+/// The error raised when an update refers to a graph other than the one it runs on.
+fn forbidden_graph() -> VOWLGrapherStoreError {
+    VOWLGrapherStoreErrorKind::ForbiddenOperation(
+        "Updates may only change the active graph. Remove GRAPH clauses referring to other graphs"
+            .to_string(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+
+    #[tokio::test]
+    async fn test_update() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .update(
+                "INSERT DATA { <http://example.com/a> <http://example.com/p> <http://example.com/b> }",
+                "update",
+            )
+            .await?;
+        store
+            .update(
+                "DELETE { ?s ?p ?o } INSERT { ?o ?p ?s } WHERE { ?s ?p ?o }",
+                "update",
+            )
+            .await?;
+        assert_eq!(store.graph_len(&store.get_graph_name("update")).await?, 1);
+
+        assert!(
+            store
+                .update(
+                    "INSERT DATA { GRAPH <urn:other> { <http://example.com/a> <http://example.com/p> <http://example.com/b> } }",
+                    "update",
+                )
+                .await
+                .is_err(),
+            "writing to another graph should fail"
+        );
+        assert_eq!(
+            store.graph_len("urn:other").await?,
+            0,
+            "another graph was written to"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_load_time() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new_for_user("update-load-time".to_string());
+        store
+            .update(
+                "INSERT DATA { <http://example.com/a> <http://example.com/p> <http://example.com/b> }",
+                "update",
+            )
+            .await?;
+        let graph_name = store.get_graph_name("update");
+        let loaded_at = lifecycle::graph_usage(&graph_name).map(|usage| usage.loaded_at);
+        assert!(loaded_at.is_some(), "the graph should be registered");
+
+        store
+            .update(
+                "INSERT DATA { <http://example.com/b> <http://example.com/p> <http://example.com/c> }",
+                "update",
+            )
+            .await?;
+        let usage = lifecycle::graph_usage(&graph_name);
+        assert_eq!(usage.as_ref().map(|usage| usage.loaded_at), loaded_at);
+        assert_eq!(usage.map(|usage| usage.quad_count), Some(2));
+
+        store.delete_graph("update").await?;
+        Ok(())
+    }
+}
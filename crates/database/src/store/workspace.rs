@@ -0,0 +1,392 @@
+//! Management of the named graphs a user has loaded into the store.
+
+use futures::StreamExt;
+use log::info;
+use rdf_fusion::execution::results::QueryResults;
+use rdf_fusion::model::{NamedNode, NamedNodeRef, NamedOrBlankNode, Quad, Term};
+use std::collections::HashMap;
+use std::time::UNIX_EPOCH;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{GraphSummary, ImportSummary};
+
+use crate::store::imports::{
+    IMPORT_GRAPH_INFIX, IMPORTED_BY, PROV_WAS_DERIVED_FROM, is_import_graph, is_import_graph_of,
+    provenance_graph_name,
+};
+use crate::store::{VOWLGrapherStore, lifecycle, search};
+
+/// Rejects the name of a new graph if it would be taken for an import graph.
+///
+/// # Errors
+/// Returns an error if `graph_name` contains [`IMPORT_GRAPH_INFIX`].
+fn check_new_graph_name(graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+    if is_import_graph(graph_name) {
+        return Err(VOWLGrapherStoreErrorKind::ForbiddenOperation(format!(
+            "Graph names may not contain '{IMPORT_GRAPH_INFIX}', which marks imported ontologies"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+impl VOWLGrapherStore {
+    /// Returns the graphs loaded by the user, sorted by name.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    pub async fn list_graphs(&self) -> Result<Vec<GraphSummary>, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let prefix = self.get_graph_name("");
+
+        let mut graphs = Vec::new();
+        if self.persistent {
+            // Every graph of the global store is registered, so other users' graphs are never scanned.
+            for (graph_name, usage) in lifecycle::graph_usages_with_prefix(&prefix) {
+                let name = &graph_name[prefix.len()..];
+                if is_import_graph(name) || usage.quad_count == 0 {
+                    continue;
+                }
+                graphs.push(GraphSummary {
+                    name: name.to_string(),
+                    quad_count: usage.quad_count,
+                    loaded_at: usage
+                        .loaded_at
+                        .duration_since(UNIX_EPOCH)
+                        .ok()
+                        .map(|since_epoch| since_epoch.as_secs()),
+                });
+            }
+        } else {
+            // A private store only holds the graphs of this instance.
+            let results = self
+                .session
+                .query("SELECT ?g (COUNT(*) AS ?count) WHERE { GRAPH ?g { ?s ?p ?o } } GROUP BY ?g")
+                .await?;
+            if let QueryResults::Solutions(mut solutions) = results {
+                while let Some(solution) = solutions.next().await {
+                    let solution = solution?;
+                    let Some(Term::NamedNode(graph)) = solution.get("g") else {
+                        continue;
+                    };
+                    let Some(name) = graph.as_str().strip_prefix(&prefix) else {
+                        continue;
+                    };
+                    if is_import_graph(name) {
+                        continue;
+                    }
+                    let quad_count = match solution.get("count") {
+                        Some(Term::Literal(count)) => count.value().parse().unwrap_or(0),
+                        _ => 0,
+                    };
+                    graphs.push(GraphSummary {
+                        name: name.to_string(),
+                        quad_count,
+                        loaded_at: None,
+                    });
+                }
+            }
+        }
+
+        graphs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(graphs)
+    }
+
+    /// Returns the ontologies imported by a graph of the user, sorted by IRI.
+    ///
+    /// # Errors
+    /// Returns an error if the store could not be queried.
+    pub async fn list_imports(
+        &self,
+        graph_name: &str,
+    ) -> Result<Vec<ImportSummary>, VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.get_graph_name(graph_name);
+        let results = self
+            .session
+            .query(&format!(
+                "SELECT ?import ?predicate ?object WHERE {{ GRAPH <{}> {{ ?import ?predicate ?object }} }}",
+                provenance_graph_name(&graph_name)
+            ))
+            .await?;
+
+        // The IRI of every import graph and the graphs importing it.
+        let mut imports = HashMap::<String, (String, Vec<String>)>::new();
+        if let QueryResults::Solutions(mut solutions) = results {
+            while let Some(solution) = solutions.next().await {
+                let solution = solution?;
+                let (
+                    Some(Term::NamedNode(import)),
+                    Some(Term::NamedNode(predicate)),
+                    Some(Term::NamedNode(object)),
+                ) = (
+                    solution.get("import"),
+                    solution.get("predicate"),
+                    solution.get("object"),
+                )
+                else {
+                    continue;
+                };
+                let entry = imports.entry(import.as_str().to_string()).or_default();
+                match predicate.as_str() {
+                    PROV_WAS_DERIVED_FROM => entry.0 = object.as_str().to_string(),
+                    IMPORTED_BY => entry.1.push(object.as_str().to_string()),
+                    _ => (),
+                }
+            }
+        }
+
+        let mut summaries = Vec::with_capacity(imports.len());
+        for (import_graph, (iri, importing_graphs)) in &imports {
+            let mut imported_by: Vec<String> = importing_graphs
+                .iter()
+                .filter_map(|importing_graph| {
+                    if *importing_graph == graph_name {
+                        None
+                    } else {
+                        imports.get(importing_graph).map(|(iri, _)| iri.clone())
+                    }
+                })
+                .collect();
+            imported_by.sort();
+            summaries.push(ImportSummary {
+                iri: iri.clone(),
+                imported_by,
+                quad_count: self.graph_len(import_graph).await?,
+            });
+        }
+
+        summaries.sort_by(|a, b| a.iri.cmp(&b.iri));
+        Ok(summaries)
+    }
+
+    /// Renames a graph of the user.
+    ///
+    /// # Errors
+    /// Returns an error if `from` does not exist, `to` already exists or is the name of an
+    /// import graph, or the store fails to move the quads.
+    pub async fn rename_graph(&self, from: &str, to: &str) -> Result<(), VOWLGrapherStoreError> {
+        check_new_graph_name(to)?;
+        self.maintain().await?;
+        let source = self.get_graph_name(from);
+        let target = self.get_graph_name(to);
+        if source == target {
+            return Ok(());
+        }
+
+        self.copy_graph(&source, &target).await?;
+        self.remove_graph(&source).await?;
+        search::forget_index(&source);
+        info!("Renamed graph '{source}' to '{target}'");
+        Ok(())
+    }
+
+    /// Copies a graph of the user into a new graph.
+    ///
+    /// # Errors
+    /// Returns an error if `from` does not exist, `to` already exists or is the name of an
+    /// import graph, the user would exceed their quota or the store fails to copy the quads.
+    pub async fn duplicate_graph(&self, from: &str, to: &str) -> Result<(), VOWLGrapherStoreError> {
+        check_new_graph_name(to)?;
+        self.maintain().await?;
+        let source = self.get_graph_name(from);
+        let target = self.get_graph_name(to);
+
+        // The imports are copied along with the graph, so they count towards the quota as well.
+        let mut source_len = 0;
+        let mut source_bytes = 0;
+        for source_graph in self
+            .import_graphs(&source)
+            .await?
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(source.as_str()))
+        {
+            source_len += self.graph_len(source_graph).await?;
+            source_bytes += lifecycle::graph_usage(source_graph).map_or(0, |usage| usage.byte_size);
+        }
+        self.check_quota(
+            &target,
+            usize::try_from(source_len).unwrap_or(usize::MAX),
+            source_bytes,
+        )?;
+
+        self.copy_graph(&source, &target).await?;
+        info!("Duplicated graph '{source}' as '{target}'");
+        Ok(())
+    }
+
+    /// Deletes a graph of the user.
+    ///
+    /// `graph_name` is either the name of the graph or its full IRI.
+    ///
+    /// # Errors
+    /// Returns an error if the graph does not exist or cannot be removed.
+    pub async fn delete_graph(&self, graph_name: &str) -> Result<(), VOWLGrapherStoreError> {
+        self.maintain().await?;
+        let graph_name = self.resolve_graph_name(graph_name);
+        if self.graph_len(&graph_name).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{graph_name}' does not exist"
+            ))
+            .into());
+        }
+
+        self.remove_graph(&graph_name).await?;
+        info!("Deleted graph '{graph_name}'");
+        Ok(())
+    }
+
+    /// Copies every quad of the graph `source` and its import graphs into the new graph `target`.
+    ///
+    /// # Errors
+    /// Returns an error if `source` does not exist, `target` already exists
+    /// or the store fails to copy the quads.
+    async fn copy_graph(&self, source: &str, target: &str) -> Result<(), VOWLGrapherStoreError> {
+        if self.graph_len(source).await? == 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphNotFound(format!(
+                "Graph '{source}' does not exist"
+            ))
+            .into());
+        }
+        if self.graph_len(target).await? > 0 {
+            return Err(VOWLGrapherStoreErrorKind::GraphAlreadyExists(format!(
+                "Graph '{target}' already exists"
+            ))
+            .into());
+        }
+
+        for source_graph in self
+            .import_graphs(source)
+            .await?
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(source))
+        {
+            let target_graph = format!("{target}{}", &source_graph[source.len()..]);
+            self.copy_quads(source, target, source_graph, &target_graph)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Copies every quad of `source_graph` into `target_graph`, which belongs to `target`.
+    ///
+    /// References to import graphs of `source` are rewritten to the import graphs of `target`,
+    /// so the provenance of imports stays intact.
+    ///
+    /// # Errors
+    /// Returns an error if the store fails to copy the quads.
+    async fn copy_quads(
+        &self,
+        source: &str,
+        target: &str,
+        source_graph: &str,
+        target_graph: &str,
+    ) -> Result<(), VOWLGrapherStoreError> {
+        let rename = |node: NamedNode| -> NamedNode {
+            if node.as_str() == source || is_import_graph_of(node.as_str(), source) {
+                NamedNode::new_unchecked(format!("{target}{}", &node.as_str()[source.len()..]))
+            } else {
+                node
+            }
+        };
+
+        let target_node = NamedNode::new(target_graph)?;
+        self.extend_from_graph(NamedNodeRef::new(source_graph)?, |quad| {
+            let subject = match quad.subject {
+                NamedOrBlankNode::NamedNode(node) => rename(node).into(),
+                subject => subject,
+            };
+            let object = match quad.object {
+                Term::NamedNode(node) => rename(node).into(),
+                object => object,
+            };
+            Quad::new(subject, quad.predicate, object, target_node.clone())
+        })
+        .await?;
+        let source_bytes = lifecycle::graph_usage(source_graph).map_or(0, |usage| usage.byte_size);
+        self.register_graph(target_graph, source_bytes).await?;
+        self.persist_graph(target_graph).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rdf_fusion::store::Store;
+    use vowlgrapher_util::prelude::DataType;
+
+    #[tokio::test]
+    async fn test_manage_graphs() -> Result<(), VOWLGrapherStoreError> {
+        let store = VOWLGrapherStore::new(Store::default());
+        store
+            .session
+            .extend(vec![Quad::new(
+                NamedNode::new("http://example.com/s")?,
+                NamedNode::new("http://example.com/p")?,
+                NamedNode::new("http://example.com/o")?,
+                NamedNode::new(store.get_graph_name("a.ttl"))?,
+            )])
+            .await?;
+
+        store.duplicate_graph("a.ttl", "b.ttl").await?;
+        store.rename_graph("a.ttl", "c.ttl").await?;
+        let names: Vec<String> = store
+            .list_graphs()
+            .await?
+            .into_iter()
+            .map(|graph| graph.name)
+            .collect();
+        assert_eq!(names, ["b.ttl", "c.ttl"]);
+
+        assert!(
+            store.rename_graph("b.ttl", "c.ttl").await.is_err(),
+            "renaming onto an existing graph should fail"
+        );
+        // Names marking import graphs would hide the graph and mix it up with the imports.
+        for name in ["b.ttl:import:x", "x:import:provenance"] {
+            let renamed = store.rename_graph("b.ttl", name).await.err();
+            let duplicated = store.duplicate_graph("b.ttl", name).await.err();
+            for error in [renamed, duplicated] {
+                assert!(
+                    matches!(
+                        error.as_ref().map(VOWLGrapherStoreError::kind),
+                        Some(VOWLGrapherStoreErrorKind::ForbiddenOperation(_))
+                    ),
+                    "'{name}' should be rejected"
+                );
+            }
+        }
+        store.delete_graph("b.ttl").await?;
+        assert_eq!(store.list_graphs().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_graphs_of_user() -> Result<(), VOWLGrapherStoreError> {
+        let alice = VOWLGrapherStore::new_for_user("workspace-alice".to_string());
+        let bob = VOWLGrapherStore::new_for_user("workspace-bob".to_string());
+        let data = b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .";
+        alice
+            .insert_bytes(data, "a.nt", DataType::NTriples, false)
+            .await?;
+        bob.insert_bytes(data, "b.nt", DataType::NTriples, false)
+            .await?;
+
+        let graphs = alice.list_graphs().await?;
+        assert_eq!(
+            graphs
+                .iter()
+                .map(|graph| graph.name.as_str())
+                .collect::<Vec<_>>(),
+            ["a.nt"]
+        );
+        assert_eq!(graphs[0].quad_count, 1);
+        assert!(graphs[0].loaded_at.is_some(), "missing load time");
+
+        alice.delete_graph("a.nt").await?;
+        bob.delete_graph("b.nt").await?;
+        Ok(())
+    }
+}
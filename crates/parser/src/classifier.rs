@@ -0,0 +1,1049 @@
+//! Classification of ontologies in OWL 2 EL.
+//!
+//! The classifier is consequence-based, following the completion rules of
+//! [EL++](https://www.ijcai.org/Proceedings/05/Papers/0372.pdf) as refined by ELK.
+//! Every axiom is normalized into subsumptions of the forms `A ⊑ B`, `A ⊓ B ⊑ C`, `A ⊑ ∃r.B`
+//! and `∃r.A ⊑ B` between atomic concepts, and inclusions `r ⊑ s` and `r ∘ s ⊑ t` between
+//! properties. The subsumers of every class are then saturated with the completion rules.
+//!
+//! Axioms outside of OWL 2 EL, or using inverse properties, are ignored in part or in whole,
+//! so the taxonomy of such ontologies is sound, but may be incomplete. Individuals, self
+//! restrictions and data property restrictions are treated as atomic concepts.
+
+use horned_owl::model::{
+    ClassExpression, Component, DeclareClass, DifferentIndividuals, DisjointClasses,
+    EquivalentClasses, EquivalentObjectProperties, Individual, ObjectPropertyExpression, RcStr,
+    SameIndividual, SubObjectPropertyExpression, TransitiveObjectProperty,
+};
+use horned_owl::ontology::component_mapped::RcComponentMappedOntology;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+const OWL_THING: &str = "http://www.w3.org/2002/07/owl#Thing";
+const OWL_NOTHING: &str = "http://www.w3.org/2002/07/owl#Nothing";
+
+type Ce = ClassExpression<RcStr>;
+type Ope = ObjectPropertyExpression<RcStr>;
+
+/// The id of an atomic concept, which is a class, an individual or a name for a class expression.
+type Concept = usize;
+
+/// The id of an object property, or a name for a part of a property chain.
+type Role = usize;
+
+/// The id of `owl:Thing`.
+const TOP: Concept = 0;
+
+/// The id of `owl:Nothing`.
+const BOTTOM: Concept = 1;
+
+/// A set of equivalent classes in an inferred taxonomy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaxonomyNode {
+    /// The IRIs of the equivalent classes, sorted.
+    pub classes: Vec<String>,
+    /// The indices of the nodes of the direct superclasses, which are none below `owl:Thing`.
+    pub parents: Vec<usize>,
+}
+
+/// The class hierarchy entailed by an ontology.
+#[derive(Debug, Clone, Default)]
+pub struct Taxonomy {
+    /// The satisfiable classes of the ontology, sorted by their first IRI.
+    ///
+    /// `owl:Thing` and the classes equivalent to it are left out.
+    pub nodes: Vec<TaxonomyNode>,
+    /// The IRIs of the classes equivalent to `owl:Nothing`, sorted.
+    pub unsatisfiable: Vec<String>,
+    /// The number of axioms which were ignored in part or in whole, as they are outside of OWL 2 EL.
+    pub ignored_axioms: usize,
+}
+
+/// Computes the taxonomy of the classes in an ontology.
+pub fn classify(ontology: &RcComponentMappedOntology) -> Taxonomy {
+    let mut axioms = Axioms::default();
+    for annotated in ontology {
+        if !axioms.add(&annotated.component) {
+            axioms.ignored += 1;
+        }
+    }
+    axioms.apply_ranges();
+
+    let mut saturation = Saturation::new(&axioms);
+    saturation.init(TOP);
+    for concept in axioms.classes.values() {
+        saturation.init(*concept);
+    }
+    saturation.run();
+    taxonomy(&axioms, &saturation)
+}
+
+/// The axioms of an ontology, normalized for the completion rules.
+struct Axioms {
+    /// The atomic concepts of class expressions which are not split up by normalization.
+    atoms: HashMap<Ce, Concept>,
+    /// The concepts of the named classes, by IRI.
+    classes: BTreeMap<String, Concept>,
+    concept_count: usize,
+    roles: HashMap<String, Role>,
+    role_count: usize,
+    /// `A ⊑ B`
+    told: HashMap<Concept, Vec<Concept>>,
+    /// `A ⊓ B ⊑ C`, stored for both `A` and `B`.
+    conjunctions: HashMap<Concept, Vec<(Concept, Concept)>>,
+    /// `A ⊑ ∃r.B`
+    existentials: HashMap<Concept, Vec<(Role, Concept)>>,
+    /// `∃r.A ⊑ B`
+    restrictions: HashMap<(Role, Concept), Vec<Concept>>,
+    /// `r ⊑ s`
+    sub_roles: Vec<(Role, Role)>,
+    /// `r ∘ s ⊑ t`
+    chains: Vec<(Role, Role, Role)>,
+    /// The concepts the ranges of properties are subsumed by.
+    ranges: HashMap<Role, Vec<Concept>>,
+    /// The number of ignored axioms.
+    ignored: usize,
+}
+
+impl Default for Axioms {
+    fn default() -> Self {
+        Self {
+            atoms: HashMap::new(),
+            classes: BTreeMap::new(),
+            concept_count: BOTTOM + 1,
+            roles: HashMap::new(),
+            role_count: 0,
+            told: HashMap::new(),
+            conjunctions: HashMap::new(),
+            existentials: HashMap::new(),
+            restrictions: HashMap::new(),
+            sub_roles: Vec::new(),
+            chains: Vec::new(),
+            ranges: HashMap::new(),
+            ignored: 0,
+        }
+    }
+}
+
+impl Axioms {
+    /// Returns a new concept, which names a class expression.
+    const fn fresh(&mut self) -> Concept {
+        self.concept_count += 1;
+        self.concept_count - 1
+    }
+
+    /// Returns a new role, which names a part of a property chain.
+    const fn fresh_role(&mut self) -> Role {
+        self.role_count += 1;
+        self.role_count - 1
+    }
+
+    /// Returns the concept of a class expression treated as atomic.
+    fn atom(&mut self, ce: &Ce) -> Concept {
+        if let ClassExpression::Class(class) = ce {
+            match class.0.to_string().as_str() {
+                OWL_THING => return TOP,
+                OWL_NOTHING => return BOTTOM,
+                _ => {}
+            }
+        }
+        if let Some(concept) = self.atoms.get(ce) {
+            return *concept;
+        }
+        let concept = self.fresh();
+        if let ClassExpression::Class(class) = ce {
+            self.classes.insert(class.0.to_string(), concept);
+        }
+        self.atoms.insert(ce.clone(), concept);
+        concept
+    }
+
+    /// Returns the concept of the class holding only an individual.
+    fn nominal(&mut self, individual: &Individual<RcStr>) -> Concept {
+        self.atom(&ClassExpression::ObjectOneOf(vec![individual.clone()]))
+    }
+
+    /// Returns the role of an object property, if it is not inverse.
+    fn role(&mut self, ope: &Ope) -> Option<Role> {
+        let ObjectPropertyExpression::ObjectProperty(property) = ope else {
+            return None;
+        };
+        let iri = property.0.to_string();
+        if let Some(role) = self.roles.get(&iri) {
+            return Some(*role);
+        }
+        let role = self.fresh_role();
+        self.roles.insert(iri, role);
+        Some(role)
+    }
+
+    fn subsumption(&mut self, sub: Concept, sup: Concept) {
+        self.told.entry(sub).or_default().push(sup);
+    }
+
+    fn conjunction(&mut self, first: Concept, second: Concept, sup: Concept) {
+        self.conjunctions
+            .entry(first)
+            .or_default()
+            .push((second, sup));
+        if first != second {
+            self.conjunctions
+                .entry(second)
+                .or_default()
+                .push((first, sup));
+        }
+    }
+
+    /// Returns a concept subsumed by everything a class expression on the left of a subsumption is,
+    /// if the expression is in OWL 2 EL.
+    fn sub(&mut self, ce: &Ce) -> Option<Concept> {
+        match ce {
+            ClassExpression::Class(_)
+            | ClassExpression::ObjectHasSelf(_)
+            | ClassExpression::DataSomeValuesFrom { .. }
+            | ClassExpression::DataHasValue { .. } => Some(self.atom(ce)),
+            ClassExpression::ObjectOneOf(individuals) if individuals.len() == 1 => {
+                Some(self.atom(ce))
+            }
+            ClassExpression::ObjectIntersectionOf(ces) => {
+                let mut conjuncts = ces
+                    .iter()
+                    .map(|ce| self.sub(ce))
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter();
+                let Some(mut concept) = conjuncts.next() else {
+                    return Some(TOP);
+                };
+                for conjunct in conjuncts {
+                    let conjunction = self.fresh();
+                    self.conjunction(concept, conjunct, conjunction);
+                    concept = conjunction;
+                }
+                Some(concept)
+            }
+            // Naming a union by a concept subsuming every member keeps subsumptions from it sound.
+            ClassExpression::ObjectUnionOf(ces) => {
+                let members = ces
+                    .iter()
+                    .map(|ce| self.sub(ce))
+                    .collect::<Option<Vec<_>>>()?;
+                let union = self.fresh();
+                for member in members {
+                    self.subsumption(member, union);
+                }
+                Some(union)
+            }
+            ClassExpression::ObjectOneOf(individuals) => {
+                let union = self.fresh();
+                for individual in individuals {
+                    let nominal = self.nominal(individual);
+                    self.subsumption(nominal, union);
+                }
+                Some(union)
+            }
+            ClassExpression::ObjectSomeValuesFrom { ope, bce } => {
+                let role = self.role(ope)?;
+                let filler = self.sub(bce)?;
+                let restriction = self.fresh();
+                self.restrictions
+                    .entry((role, filler))
+                    .or_default()
+                    .push(restriction);
+                Some(restriction)
+            }
+            ClassExpression::ObjectHasValue { ope, i } => {
+                let role = self.role(ope)?;
+                let filler = self.nominal(i);
+                let restriction = self.fresh();
+                self.restrictions
+                    .entry((role, filler))
+                    .or_default()
+                    .push(restriction);
+                Some(restriction)
+            }
+            _ => None,
+        }
+    }
+
+    /// Adds the subsumption of `sub` by a class expression,
+    /// returning whether the expression is in OWL 2 EL.
+    ///
+    /// The parts of an intersection in OWL 2 EL are added even if others are not.
+    fn sup(&mut self, sub: Concept, ce: &Ce) -> bool {
+        match ce {
+            ClassExpression::Class(_)
+            | ClassExpression::ObjectHasSelf(_)
+            | ClassExpression::DataSomeValuesFrom { .. }
+            | ClassExpression::DataHasValue { .. } => {
+                let sup = self.atom(ce);
+                self.subsumption(sub, sup);
+                true
+            }
+            ClassExpression::ObjectOneOf(individuals) if individuals.len() == 1 => {
+                let sup = self.atom(ce);
+                self.subsumption(sub, sup);
+                true
+            }
+            ClassExpression::ObjectIntersectionOf(ces) => ces
+                .iter()
+                .fold(true, |supported, ce| self.sup(sub, ce) && supported),
+            ClassExpression::ObjectSomeValuesFrom { ope, bce } => {
+                let Some(role) = self.role(ope) else {
+                    return false;
+                };
+                let filler = if matches!(**bce, ClassExpression::Class(_)) {
+                    self.atom(bce)
+                } else {
+                    let filler = self.fresh();
+                    if !self.sup(filler, bce) {
+                        return false;
+                    }
+                    filler
+                };
+                self.existentials
+                    .entry(sub)
+                    .or_default()
+                    .push((role, filler));
+                true
+            }
+            ClassExpression::ObjectHasValue { ope, i } => {
+                let Some(role) = self.role(ope) else {
+                    return false;
+                };
+                let filler = self.nominal(i);
+                self.existentials
+                    .entry(sub)
+                    .or_default()
+                    .push((role, filler));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds `sub ⊑ sup`, returning whether both class expressions are in OWL 2 EL.
+    fn subclass(&mut self, sub: &Ce, sup: &Ce) -> bool {
+        self.sub(sub).is_some_and(|sub| self.sup(sub, sup))
+    }
+
+    /// Adds the subsumptions of an axiom, returning whether it is in OWL 2 EL.
+    ///
+    /// Axioms which do not constrain classes, such as declarations, are accepted without effect.
+    fn add(&mut self, component: &Component<RcStr>) -> bool {
+        match component {
+            Component::DeclareClass(DeclareClass(class)) => {
+                self.atom(&ClassExpression::Class(class.clone()));
+                true
+            }
+            Component::SubClassOf(axiom) => self.subclass(&axiom.sub, &axiom.sup),
+            Component::EquivalentClasses(EquivalentClasses(ces)) => self.equivalent(ces),
+            Component::DisjointClasses(DisjointClasses(ces)) => self.disjoint(ces),
+            Component::DisjointUnion(axiom) => {
+                let class = ClassExpression::Class(axiom.0.clone());
+                let union = ClassExpression::ObjectUnionOf(axiom.1.clone());
+                let equivalent = self.equivalent(&[class, union]);
+                self.disjoint(&axiom.1) && equivalent
+            }
+            Component::SubObjectPropertyOf(axiom) => {
+                let Some(sup) = self.role(&axiom.sup) else {
+                    return false;
+                };
+                match &axiom.sub {
+                    SubObjectPropertyExpression::ObjectPropertyExpression(sub) => {
+                        self.role(sub).is_some_and(|sub| {
+                            self.sub_roles.push((sub, sup));
+                            true
+                        })
+                    }
+                    SubObjectPropertyExpression::ObjectPropertyChain(chain) => {
+                        let Some(chain) = chain
+                            .iter()
+                            .map(|ope| self.role(ope))
+                            .collect::<Option<Vec<_>>>()
+                        else {
+                            return false;
+                        };
+                        self.chain(&chain, sup);
+                        true
+                    }
+                }
+            }
+            Component::EquivalentObjectProperties(EquivalentObjectProperties(opes)) => {
+                let Some(roles) = opes
+                    .iter()
+                    .map(|ope| self.role(ope))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return false;
+                };
+                for pair in roles.windows(2) {
+                    self.sub_roles.push((pair[0], pair[1]));
+                    self.sub_roles.push((pair[1], pair[0]));
+                }
+                true
+            }
+            Component::TransitiveObjectProperty(TransitiveObjectProperty(ope)) => {
+                self.role(ope).is_some_and(|role| {
+                    self.chains.push((role, role, role));
+                    true
+                })
+            }
+            Component::ObjectPropertyDomain(axiom) => {
+                let Some(role) = self.role(&axiom.ope) else {
+                    return false;
+                };
+                let restriction = self.fresh();
+                self.restrictions
+                    .entry((role, TOP))
+                    .or_default()
+                    .push(restriction);
+                self.sup(restriction, &axiom.ce)
+            }
+            Component::ObjectPropertyRange(axiom) => {
+                let Some(role) = self.role(&axiom.ope) else {
+                    return false;
+                };
+                let range = self.fresh();
+                self.ranges.entry(role).or_default().push(range);
+                self.sup(range, &axiom.ce)
+            }
+            Component::ClassAssertion(axiom) => {
+                let individual = self.nominal(&axiom.i);
+                self.sup(individual, &axiom.ce)
+            }
+            Component::ObjectPropertyAssertion(axiom) => {
+                let Some(role) = self.role(&axiom.ope) else {
+                    return false;
+                };
+                let (from, to) = (self.nominal(&axiom.from), self.nominal(&axiom.to));
+                self.existentials.entry(from).or_default().push((role, to));
+                true
+            }
+            Component::SameIndividual(SameIndividual(individuals)) => {
+                let nominals = individuals
+                    .iter()
+                    .map(|individual| self.nominal(individual))
+                    .collect::<Vec<_>>();
+                for pair in nominals.windows(2) {
+                    self.subsumption(pair[0], pair[1]);
+                    self.subsumption(pair[1], pair[0]);
+                }
+                true
+            }
+            Component::DifferentIndividuals(DifferentIndividuals(individuals)) => {
+                let nominals = individuals
+                    .iter()
+                    .map(|individual| self.nominal(individual))
+                    .collect::<Vec<_>>();
+                for (i, first) in nominals.iter().enumerate() {
+                    for second in &nominals[i + 1..] {
+                        self.conjunction(*first, *second, BOTTOM);
+                    }
+                }
+                true
+            }
+            Component::InverseObjectProperties(_)
+            | Component::FunctionalObjectProperty(_)
+            | Component::InverseFunctionalObjectProperty(_)
+            | Component::SymmetricObjectProperty(_)
+            | Component::AsymmetricObjectProperty(_)
+            | Component::IrreflexiveObjectProperty(_)
+            | Component::ReflexiveObjectProperty(_)
+            | Component::DisjointObjectProperties(_)
+            | Component::NegativeObjectPropertyAssertion(_)
+            | Component::HasKey(_) => false,
+            _ => true,
+        }
+    }
+
+    /// Adds the subsumptions of an equivalent classes axiom, returning whether it is in OWL 2 EL.
+    fn equivalent(&mut self, ces: &[Ce]) -> bool {
+        let mut supported = true;
+        for (i, sub) in ces.iter().enumerate() {
+            for (j, sup) in ces.iter().enumerate() {
+                if i != j {
+                    supported &= self.subclass(sub, sup);
+                }
+            }
+        }
+        supported
+    }
+
+    /// Adds the subsumptions of a disjoint classes axiom, returning whether it is in OWL 2 EL.
+    fn disjoint(&mut self, ces: &[Ce]) -> bool {
+        let Some(concepts) = ces
+            .iter()
+            .map(|ce| self.sub(ce))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+        for (i, first) in concepts.iter().enumerate() {
+            for second in &concepts[i + 1..] {
+                self.conjunction(*first, *second, BOTTOM);
+            }
+        }
+        true
+    }
+
+    /// Adds the inclusion of a property chain in `sup`, splitting it into chains of two properties.
+    fn chain(&mut self, chain: &[Role], sup: Role) {
+        match chain {
+            [] => {}
+            [role] => self.sub_roles.push((*role, sup)),
+            [init @ .., last] => {
+                let mut head = init[0];
+                for role in &init[1..] {
+                    let part = self.fresh_role();
+                    self.chains.push((head, *role, part));
+                    head = part;
+                }
+                self.chains.push((head, *last, sup));
+            }
+        }
+    }
+
+    /// Makes the fillers of existential restrictions subsumed by the ranges of their properties.
+    ///
+    /// Must be called after every axiom was added, as ranges are inherited from super properties.
+    fn apply_ranges(&mut self) {
+        if self.ranges.is_empty() {
+            return;
+        }
+        let super_roles = super_roles(self.role_count, &self.sub_roles);
+        let mut ranged_fillers = HashMap::<(Role, Concept), Concept>::new();
+        let existentials = std::mem::take(&mut self.existentials);
+        for (sub, restrictions) in existentials {
+            for (role, filler) in restrictions {
+                let ranges = super_roles[role]
+                    .iter()
+                    .flat_map(|role| self.ranges.get(role).into_iter().flatten())
+                    .copied()
+                    .collect::<Vec<_>>();
+                let filler = if ranges.is_empty() {
+                    filler
+                } else if let Some(ranged) = ranged_fillers.get(&(role, filler)) {
+                    *ranged
+                } else {
+                    let ranged = self.fresh();
+                    self.subsumption(ranged, filler);
+                    for range in ranges {
+                        self.subsumption(ranged, range);
+                    }
+                    ranged_fillers.insert((role, filler), ranged);
+                    ranged
+                };
+                self.existentials
+                    .entry(sub)
+                    .or_default()
+                    .push((role, filler));
+            }
+        }
+    }
+}
+
+/// Returns the super properties of every property, including itself.
+fn super_roles(role_count: usize, sub_roles: &[(Role, Role)]) -> Vec<Vec<Role>> {
+    let mut direct = vec![Vec::new(); role_count];
+    for (sub, sup) in sub_roles {
+        direct[*sub].push(*sup);
+    }
+    (0..role_count)
+        .map(|role| {
+            let mut supers = vec![role];
+            let mut seen = HashSet::from([role]);
+            let mut pending = vec![role];
+            while let Some(role) = pending.pop() {
+                for sup in &direct[role] {
+                    if seen.insert(*sup) {
+                        supers.push(*sup);
+                        pending.push(*sup);
+                    }
+                }
+            }
+            supers
+        })
+        .collect()
+}
+
+/// A conclusion of the completion rules.
+enum Conclusion {
+    /// `A ⊑ B`
+    Subsumer(Concept, Concept),
+    /// `A ⊑ ∃r.B`
+    Link(Concept, Role, Concept),
+}
+
+/// The subsumers and links derived for the concepts of an ontology.
+struct Saturation<'a> {
+    axioms: &'a Axioms,
+    super_roles: Vec<Vec<Role>>,
+    /// For `r ∘ s ⊑ t`, `s` and `t` by `r`.
+    chains_by_first: HashMap<Role, Vec<(Role, Role)>>,
+    /// For `r ∘ s ⊑ t`, `r` and `t` by `s`.
+    chains_by_second: HashMap<Role, Vec<(Role, Role)>>,
+    subsumers: HashMap<Concept, HashSet<Concept>>,
+    links: HashSet<(Concept, Role, Concept)>,
+    successors: HashMap<Concept, Vec<(Role, Concept)>>,
+    predecessors: HashMap<Concept, Vec<(Role, Concept)>>,
+    queue: VecDeque<Conclusion>,
+}
+
+impl<'a> Saturation<'a> {
+    fn new(axioms: &'a Axioms) -> Self {
+        let mut chains_by_first = HashMap::<Role, Vec<(Role, Role)>>::new();
+        let mut chains_by_second = HashMap::<Role, Vec<(Role, Role)>>::new();
+        for (first, second, sup) in &axioms.chains {
+            chains_by_first
+                .entry(*first)
+                .or_default()
+                .push((*second, *sup));
+            chains_by_second
+                .entry(*second)
+                .or_default()
+                .push((*first, *sup));
+        }
+        Self {
+            axioms,
+            super_roles: super_roles(axioms.role_count, &axioms.sub_roles),
+            chains_by_first,
+            chains_by_second,
+            subsumers: HashMap::new(),
+            links: HashSet::new(),
+            successors: HashMap::new(),
+            predecessors: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Starts deriving the subsumers of a concept, unless that already happened.
+    fn init(&mut self, concept: Concept) {
+        if self.subsumers.contains_key(&concept) {
+            return;
+        }
+        self.subsumers.insert(concept, HashSet::new());
+        self.queue.push_back(Conclusion::Subsumer(concept, concept));
+        self.queue.push_back(Conclusion::Subsumer(concept, TOP));
+    }
+
+    /// Applies the completion rules until no new conclusion follows.
+    fn run(&mut self) {
+        while let Some(conclusion) = self.queue.pop_front() {
+            match conclusion {
+                Conclusion::Subsumer(concept, subsumer) => self.subsumer(concept, subsumer),
+                Conclusion::Link(from, role, to) => self.link(from, role, to),
+            }
+        }
+    }
+
+    /// Derives the conclusions of `concept ⊑ subsumer`.
+    fn subsumer(&mut self, concept: Concept, subsumer: Concept) {
+        let axioms = self.axioms;
+        let subsumers = self.subsumers.entry(concept).or_default();
+        if !subsumers.insert(subsumer) {
+            return;
+        }
+        for sup in axioms.told.get(&subsumer).into_iter().flatten() {
+            self.queue.push_back(Conclusion::Subsumer(concept, *sup));
+        }
+        for (other, sup) in axioms.conjunctions.get(&subsumer).into_iter().flatten() {
+            if subsumers.contains(other) {
+                self.queue.push_back(Conclusion::Subsumer(concept, *sup));
+            }
+        }
+        for (role, filler) in axioms.existentials.get(&subsumer).into_iter().flatten() {
+            self.queue
+                .push_back(Conclusion::Link(concept, *role, *filler));
+        }
+        let predecessors = self
+            .predecessors
+            .get(&concept)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        for (role, predecessor) in predecessors {
+            self.propagate(predecessor, role, subsumer);
+        }
+    }
+
+    /// Derives the conclusions of `from ⊑ ∃role.to`.
+    fn link(&mut self, from: Concept, role: Role, to: Concept) {
+        if !self.links.insert((from, role, to)) {
+            return;
+        }
+        self.successors.entry(from).or_default().push((role, to));
+        self.predecessors.entry(to).or_default().push((role, from));
+        self.init(to);
+
+        for sup in &self.super_roles[role] {
+            if *sup != role {
+                self.queue.push_back(Conclusion::Link(from, *sup, to));
+            }
+        }
+        let subsumers = self
+            .subsumers
+            .get(&to)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        for subsumer in subsumers {
+            self.propagate(from, role, subsumer);
+        }
+        for (second, sup) in self.chains_by_first.get(&role).into_iter().flatten() {
+            for (successor_role, successor) in self.successors.get(&to).into_iter().flatten() {
+                if successor_role == second {
+                    self.queue
+                        .push_back(Conclusion::Link(from, *sup, *successor));
+                }
+            }
+        }
+        for (first, sup) in self.chains_by_second.get(&role).into_iter().flatten() {
+            for (predecessor_role, predecessor) in
+                self.predecessors.get(&from).into_iter().flatten()
+            {
+                if predecessor_role == first {
+                    self.queue
+                        .push_back(Conclusion::Link(*predecessor, *sup, to));
+                }
+            }
+        }
+    }
+
+    /// Derives the subsumers of `from` which follow from `from ⊑ ∃role.subsumer`.
+    fn propagate(&mut self, from: Concept, role: Role, subsumer: Concept) {
+        if subsumer == BOTTOM {
+            self.queue.push_back(Conclusion::Subsumer(from, BOTTOM));
+        }
+        for sup in self
+            .axioms
+            .restrictions
+            .get(&(role, subsumer))
+            .into_iter()
+            .flatten()
+        {
+            self.queue.push_back(Conclusion::Subsumer(from, *sup));
+        }
+    }
+}
+
+/// Builds the taxonomy of the named classes from their saturated subsumers.
+fn taxonomy(axioms: &Axioms, saturation: &Saturation<'_>) -> Taxonomy {
+    let names = axioms
+        .classes
+        .iter()
+        .map(|(iri, concept)| (*concept, iri.as_str()))
+        .collect::<HashMap<_, _>>();
+    let subsumers = |concept: Concept| saturation.subsumers.get(&concept);
+    let top_subsumers = subsumers(TOP);
+
+    let mut unsatisfiable = Vec::new();
+    // The nodes by the first of their classes, and the node of every class.
+    let mut nodes = BTreeMap::<&str, BTreeSet<&str>>::new();
+    let mut node_of = HashMap::<Concept, &str>::new();
+    for (iri, concept) in &axioms.classes {
+        let Some(class_subsumers) = subsumers(*concept) else {
+            continue;
+        };
+        if class_subsumers.contains(&BOTTOM) {
+            unsatisfiable.push(iri.clone());
+            continue;
+        }
+        if top_subsumers.is_some_and(|top| top.contains(concept)) {
+            continue;
+        }
+        let equivalents = class_subsumers
+            .iter()
+            .filter(|sup| subsumers(**sup).is_some_and(|sups| sups.contains(concept)))
+            .filter_map(|sup| names.get(sup).copied())
+            .collect::<BTreeSet<_>>();
+        let Some(first) = equivalents.first().copied() else {
+            continue;
+        };
+        node_of.insert(*concept, first);
+        nodes.entry(first).or_insert(equivalents);
+    }
+
+    // The strict named subsumers of every node.
+    let strict = nodes
+        .keys()
+        .map(|first| {
+            let concept = axioms.classes[*first];
+            let supers = subsumers(concept)
+                .into_iter()
+                .flatten()
+                .filter_map(|sup| node_of.get(sup).copied())
+                .filter(|sup| sup != first)
+                .collect::<BTreeSet<_>>();
+            (*first, supers)
+        })
+        .collect::<HashMap<_, _>>();
+    let index = nodes
+        .keys()
+        .enumerate()
+        .map(|(i, first)| (*first, i))
+        .collect::<HashMap<_, _>>();
+
+    Taxonomy {
+        nodes: nodes
+            .iter()
+            .map(|(first, classes)| {
+                let supers = &strict[first];
+                // A superclass is direct if it is not a superclass of another superclass.
+                let parents = supers
+                    .iter()
+                    .filter(|sup| {
+                        !supers
+                            .iter()
+                            .any(|other| other != *sup && strict[other].contains(*sup))
+                    })
+                    .map(|sup| index[sup])
+                    .collect();
+                TaxonomyNode {
+                    classes: classes.iter().map(ToString::to_string).collect(),
+                    parents,
+                }
+            })
+            .collect(),
+        unsatisfiable,
+        ignored_axioms: axioms.ignored,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use horned_owl::io::ParserConfiguration;
+    use horned_owl::io::rdf::reader::ConcreteRDFOntology;
+    use horned_owl::io::{ofn, rdf};
+    use horned_owl::model::RcAnnotatedComponent;
+    use std::io::{BufReader, Cursor};
+
+    /// Classifies an ontology in OWL 2 Functional Syntax.
+    fn classify_ofn(ofn: &str) -> Result<Taxonomy, Box<dyn std::error::Error>> {
+        let (ontology, _): (RcComponentMappedOntology, _) =
+            ofn::reader::read(&mut Cursor::new(ofn), ParserConfiguration::default())?;
+        Ok(classify(&ontology))
+    }
+
+    /// Returns the classes of the direct superclass nodes of a class in `http://example.com/a#`.
+    fn parents(taxonomy: &Taxonomy, class: &str) -> Result<Vec<String>, String> {
+        let iri = format!("http://example.com/a#{class}");
+        let node = taxonomy
+            .nodes
+            .iter()
+            .find(|node| node.classes.contains(&iri))
+            .ok_or_else(|| format!("{iri} is not in the taxonomy"))?;
+        Ok(node
+            .parents
+            .iter()
+            .map(|parent| taxonomy.nodes[*parent].classes.join(" "))
+            .collect())
+    }
+
+    #[test]
+    fn test_classify() -> Result<(), Box<dyn std::error::Error>> {
+        let ofn = "Prefix(:=<http://example.com/a#>)
+Ontology(<http://example.com/a>
+    Declaration(Class(:A))
+    Declaration(Class(:B))
+    Declaration(Class(:C))
+    Declaration(Class(:D))
+    Declaration(Class(:E))
+    Declaration(Class(:F))
+    Declaration(Class(:G))
+    Declaration(Class(:H))
+    Declaration(Class(:I))
+    Declaration(Class(:R))
+    Declaration(ObjectProperty(:p))
+    Declaration(ObjectProperty(:part))
+    SubClassOf(:A ObjectSomeValuesFrom(:p :B))
+    SubClassOf(:B :C)
+    EquivalentClasses(:D ObjectSomeValuesFrom(:p :C))
+    EquivalentClasses(:E :F)
+    SubClassOf(:E :D)
+    ObjectPropertyRange(:p :R)
+    SubClassOf(ObjectSomeValuesFrom(:p ObjectIntersectionOf(:B :R)) :F)
+    SubClassOf(:G ObjectIntersectionOf(:B :H))
+    DisjointClasses(:C :H)
+    TransitiveObjectProperty(:part)
+    SubClassOf(:H ObjectSomeValuesFrom(:part ObjectSomeValuesFrom(:part :C)))
+    SubClassOf(ObjectSomeValuesFrom(:part :C) :I)
+    SubClassOf(:H ObjectAllValuesFrom(:p :A))
+)";
+        let taxonomy = classify_ofn(ofn)?;
+        let parents = |class: &str| parents(&taxonomy, class);
+        // A ⊑ ∃p.B ⊑ ∃p.C ≡ D, and B is in the range R of p, so A ⊑ ∃p.(B ⊓ R) ⊑ F ≡ E ⊑ D.
+        assert_eq!(
+            parents("A")?,
+            ["http://example.com/a#E http://example.com/a#F"]
+        );
+        assert_eq!(parents("B")?, ["http://example.com/a#C"]);
+        assert_eq!(parents("E")?, ["http://example.com/a#D"]);
+        assert_eq!(taxonomy.unsatisfiable, ["http://example.com/a#G"]);
+        // H ⊑ ∃part.∃part.C ⊑ ∃part.C ⊑ I, as part is transitive.
+        assert_eq!(parents("H")?, ["http://example.com/a#I"]);
+        assert_eq!(taxonomy.ignored_axioms, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_role_chains_and_transitivity() -> Result<(), Box<dyn std::error::Error>> {
+        let ofn = "Prefix(:=<http://example.com/a#>)
+Ontology(<http://example.com/a>
+    Declaration(Class(:Hand))
+    Declaration(Class(:Finger))
+    Declaration(Class(:Nail))
+    Declaration(Class(:HandPart))
+    Declaration(Class(:Tumour))
+    Declaration(Class(:FingerLesion))
+    Declaration(ObjectProperty(:partOf))
+    Declaration(ObjectProperty(:locatedIn))
+    TransitiveObjectProperty(:partOf)
+    SubObjectPropertyOf(ObjectPropertyChain(:locatedIn :partOf) :locatedIn)
+    SubClassOf(:Finger ObjectSomeValuesFrom(:partOf :Hand))
+    SubClassOf(:Nail ObjectSomeValuesFrom(:partOf :Finger))
+    EquivalentClasses(:HandPart ObjectSomeValuesFrom(:partOf :Hand))
+    SubClassOf(:Tumour ObjectSomeValuesFrom(:locatedIn :Nail))
+    EquivalentClasses(:FingerLesion ObjectSomeValuesFrom(:locatedIn :Finger))
+)";
+        let taxonomy = classify_ofn(ofn)?;
+        let parents = |class: &str| parents(&taxonomy, class);
+        // Nail ⊑ ∃partOf.Finger ⊑ ∃partOf.∃partOf.Hand ⊑ ∃partOf.Hand, as partOf is transitive.
+        assert_eq!(parents("Finger")?, ["http://example.com/a#HandPart"]);
+        assert_eq!(parents("Nail")?, ["http://example.com/a#HandPart"]);
+        // Tumour ⊑ ∃locatedIn.∃partOf.Finger ⊑ ∃locatedIn.Finger through the chain.
+        assert_eq!(parents("Tumour")?, ["http://example.com/a#FingerLesion"]);
+        assert!(taxonomy.unsatisfiable.is_empty());
+        assert_eq!(taxonomy.ignored_axioms, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bottom_through_existentials() -> Result<(), Box<dyn std::error::Error>> {
+        let ofn = "Prefix(:=<http://example.com/a#>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Ontology(<http://example.com/a>
+    Declaration(Class(:Cell))
+    Declaration(Class(:Organ))
+    Declaration(Class(:Broken))
+    Declaration(Class(:Holder))
+    Declaration(Class(:Outer))
+    Declaration(Class(:Empty))
+    Declaration(Class(:Fine))
+    Declaration(ObjectProperty(:p))
+    DisjointClasses(:Cell :Organ)
+    SubClassOf(:Broken ObjectIntersectionOf(:Cell :Organ))
+    SubClassOf(:Holder ObjectSomeValuesFrom(:p :Broken))
+    SubClassOf(:Outer ObjectSomeValuesFrom(:p :Holder))
+    SubClassOf(:Empty ObjectSomeValuesFrom(:p owl:Nothing))
+    SubClassOf(:Fine ObjectSomeValuesFrom(:p :Cell))
+)";
+        let taxonomy = classify_ofn(ofn)?;
+        // ⊥ propagates back along ∃p.⊥ to every class reaching an unsatisfiable one.
+        assert_eq!(
+            taxonomy.unsatisfiable,
+            [
+                "http://example.com/a#Broken",
+                "http://example.com/a#Empty",
+                "http://example.com/a#Holder",
+                "http://example.com/a#Outer"
+            ]
+        );
+        assert!(parents(&taxonomy, "Holder").is_err());
+        assert!(parents(&taxonomy, "Fine")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_classes_merged() -> Result<(), Box<dyn std::error::Error>> {
+        let ofn = "Prefix(:=<http://example.com/a#>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Ontology(<http://example.com/a>
+    Declaration(Class(:Human))
+    Declaration(Class(:Person))
+    Declaration(Class(:HomoSapiens))
+    Declaration(Class(:Mammal))
+    Declaration(Class(:Student))
+    Declaration(Class(:Parent))
+    Declaration(Class(:Guardian))
+    Declaration(Class(:Everything))
+    Declaration(ObjectProperty(:hasChild))
+    EquivalentClasses(:Human :Person :HomoSapiens)
+    SubClassOf(:Person :Mammal)
+    SubClassOf(:Student :Human)
+    EquivalentClasses(:Parent ObjectSomeValuesFrom(:hasChild :Person))
+    EquivalentClasses(:Guardian ObjectSomeValuesFrom(:hasChild :HomoSapiens))
+    EquivalentClasses(:Everything owl:Thing)
+)";
+        let taxonomy = classify_ofn(ofn)?;
+        assert_eq!(
+            taxonomy
+                .nodes
+                .iter()
+                .map(|node| node.classes.join(" "))
+                .collect::<Vec<_>>(),
+            [
+                "http://example.com/a#Guardian http://example.com/a#Parent",
+                "http://example.com/a#HomoSapiens http://example.com/a#Human http://example.com/a#Person",
+                "http://example.com/a#Mammal",
+                "http://example.com/a#Student",
+            ]
+        );
+        assert_eq!(
+            parents(&taxonomy, "Student")?,
+            [
+                "http://example.com/a#HomoSapiens http://example.com/a#Human http://example.com/a#Person"
+            ]
+        );
+        assert_eq!(
+            parents(&taxonomy, "Human")?,
+            ["http://example.com/a#Mammal"]
+        );
+        // Classes equivalent to owl:Thing are left out.
+        assert!(parents(&taxonomy, "Everything").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify_clinical_trial_ontology() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../src/assets/data/ClinicalTrialOntology-merged.owl");
+        let (ontology, _): (ConcreteRDFOntology<RcStr, RcAnnotatedComponent>, _) =
+            rdf::reader::read(
+                &mut BufReader::new(std::fs::File::open(path)?),
+                ParserConfiguration::default(),
+            )?;
+        let taxonomy = classify(&ontology.into());
+
+        assert!(taxonomy.unsatisfiable.is_empty());
+        assert!(taxonomy.nodes.len() > 200);
+        for (index, node) in taxonomy.nodes.iter().enumerate() {
+            assert!(
+                node.parents
+                    .iter()
+                    .all(|parent| *parent != index && *parent < taxonomy.nodes.len()),
+                "{:?} has invalid parents",
+                node.classes
+            );
+        }
+        // independent continuant ⊑ continuant ⊑ entity
+        let node_of = |iri: &str| {
+            taxonomy
+                .nodes
+                .iter()
+                .position(|node| node.classes.iter().any(|class| class == iri))
+                .ok_or_else(|| format!("{iri} is not in the taxonomy"))
+        };
+        let entity = node_of("http://purl.obolibrary.org/obo/BFO_0000001")?;
+        let mut ancestors = vec![node_of("http://purl.obolibrary.org/obo/BFO_0000004")?];
+        let mut seen = HashSet::new();
+        while let Some(node) = ancestors.pop() {
+            if seen.insert(node) {
+                ancestors.extend(&taxonomy.nodes[node].parents);
+            }
+        }
+        assert!(seen.contains(&entity));
+        Ok(())
+    }
+}
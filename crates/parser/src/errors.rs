@@ -32,6 +32,14 @@ pub enum VOWLGrapherStoreErrorKind {
     ImportResolutionError(String),
     /// Error on fetch of imported ontology
     RemoteFetchError(String),
+    /// An error raised if the imports of an ontology exceed a configured limit.
+    ///
+    /// Example: an import chain is nested deeper than `VOWLGRAPHER_MAX_IMPORT_DEPTH`.
+    ImportLimitExceeded(String),
+    /// An error raised if a compressed input or an archive cannot be unpacked.
+    ///
+    /// Example: a zip archive decompresses to more than `VOWLGRAPHER_MAX_INPUT_SIZE_BYTES`.
+    InvalidArchive(String),
     /// An error raised by Horned-OWL during parsing (of OWL files).
     HornedError(Box<HornedError>),
     /// Generic IO error.
@@ -52,6 +60,26 @@ pub enum VOWLGrapherStoreErrorKind {
     UnsupportedQueryType(String),
     /// Seralizer error
     SerializerError(Box<SerializerError>),
+    /// An error raised if a user exceeds their share of the database.
+    ///
+    /// Example: the user uploads a file while already holding the maximum allowed number of quads.
+    QuotaExceeded(String),
+    /// An error raised if a named graph does not exist.
+    ///
+    /// Example: the user tries to rename a graph which has already been deleted.
+    GraphNotFound(String),
+    /// An error raised if a named graph already exists.
+    ///
+    /// Example: the user tries to rename a graph to the name of another loaded graph.
+    GraphAlreadyExists(String),
+    /// An error raised if a user-supplied SPARQL query is invalid.
+    ///
+    /// Example: the query contains a syntax error.
+    InvalidQuery(String),
+    /// An error raised if a user tries to access data they are not allowed to.
+    ///
+    /// Example: a SPARQL update writes to a graph owned by another user.
+    ForbiddenOperation(String),
 }
 
 impl From<VOWLGrapherStoreErrorKind> for VOWLGrapherError {
@@ -183,6 +211,13 @@ impl From<StorageError> for VOWLGrapherStoreError {
     }
 }
 
+impl VOWLGrapherStoreError {
+    /// Returns the kind of the error.
+    pub const fn kind(&self) -> &VOWLGrapherStoreErrorKind {
+        &self.inner
+    }
+}
+
 impl std::fmt::Display for VOWLGrapherStoreError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.inner)
@@ -196,10 +231,17 @@ impl std::error::Error for VOWLGrapherStoreError {
             VOWLGrapherStoreErrorKind::IOError(e) => Some(e),
             VOWLGrapherStoreErrorKind::IriParseError(e) => Some(e),
             VOWLGrapherStoreErrorKind::InvalidFileType(_)
+            | VOWLGrapherStoreErrorKind::InvalidArchive(_)
             | VOWLGrapherStoreErrorKind::IncorrectFileExtension(_)
             | VOWLGrapherStoreErrorKind::ImportResolutionError(_)
             | VOWLGrapherStoreErrorKind::RemoteFetchError(_)
-            | VOWLGrapherStoreErrorKind::UnsupportedQueryType(_) => None,
+            | VOWLGrapherStoreErrorKind::ImportLimitExceeded(_)
+            | VOWLGrapherStoreErrorKind::UnsupportedQueryType(_)
+            | VOWLGrapherStoreErrorKind::QuotaExceeded(_)
+            | VOWLGrapherStoreErrorKind::GraphNotFound(_)
+            | VOWLGrapherStoreErrorKind::GraphAlreadyExists(_)
+            | VOWLGrapherStoreErrorKind::InvalidQuery(_)
+            | VOWLGrapherStoreErrorKind::ForbiddenOperation(_) => None,
             VOWLGrapherStoreErrorKind::LoaderError(e) => Some(e),
             VOWLGrapherStoreErrorKind::QueryEvaluationError(e) => Some(e),
             VOWLGrapherStoreErrorKind::JoinError(e) => Some(e),
@@ -212,12 +254,14 @@ impl std::error::Error for VOWLGrapherStoreError {
 impl From<VOWLGrapherStoreError> for ErrorRecord {
     fn from(value: VOWLGrapherStoreError) -> Self {
         let (message, severity, error_type) = match value.inner {
-            VOWLGrapherStoreErrorKind::InvalidFileType(e) => {
+            VOWLGrapherStoreErrorKind::InvalidFileType(e)
+            | VOWLGrapherStoreErrorKind::InvalidArchive(e) => {
                 (e, ErrorSeverity::Critical, ErrorType::Parser)
             }
             VOWLGrapherStoreErrorKind::IncorrectFileExtension(e)
             | VOWLGrapherStoreErrorKind::ImportResolutionError(e)
-            | VOWLGrapherStoreErrorKind::RemoteFetchError(e) => {
+            | VOWLGrapherStoreErrorKind::RemoteFetchError(e)
+            | VOWLGrapherStoreErrorKind::ImportLimitExceeded(e) => {
                 (e, ErrorSeverity::Warning, ErrorType::Parser)
             }
             VOWLGrapherStoreErrorKind::HornedError(horned_error) => (
@@ -263,6 +307,13 @@ impl From<VOWLGrapherStoreError> for ErrorRecord {
                 ErrorSeverity::Critical,
                 ErrorType::Parser,
             ),
+            VOWLGrapherStoreErrorKind::QuotaExceeded(e)
+            | VOWLGrapherStoreErrorKind::GraphNotFound(e)
+            | VOWLGrapherStoreErrorKind::GraphAlreadyExists(e)
+            | VOWLGrapherStoreErrorKind::InvalidQuery(e)
+            | VOWLGrapherStoreErrorKind::ForbiddenOperation(e) => {
+                (e, ErrorSeverity::Error, ErrorType::Database)
+            }
         };
 
         Self::new(
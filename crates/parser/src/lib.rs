@@ -1,4 +1,7 @@
 //! Parsers for various OWL and RDF formats
 
+pub mod classifier;
 pub mod errors;
 pub mod parser_util;
+pub mod profiles;
+pub mod sniff;
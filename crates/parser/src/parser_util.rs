@@ -15,7 +15,7 @@ use rdf_fusion::{
     model::{NamedNodeRef, Quad},
 };
 use std::io;
-use std::io::BufRead;
+use std::io::{BufRead, Read};
 use std::{
     io::{BufReader, Cursor, Write},
     path::Path,
@@ -23,7 +23,7 @@ use std::{
 };
 use tokio::sync::mpsc::{self, UnboundedSender};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use vowlgrapher_util::prelude::DataType;
+use vowlgrapher_util::prelude::{DataType, decompressed_name};
 
 fn to_default_graph_quad(quad: Quad) -> Quad {
     Quad::new(
@@ -35,8 +35,10 @@ fn to_default_graph_quad(quad: Quad) -> Quad {
 }
 
 /// Returns the datatype of the path, if it's supported by the parser.
+///
+/// Compressed documents are typed by the extension before the compression extension.
 pub fn path_type(path: &Path) -> Option<DataType> {
-    match path.extension().and_then(|s| s.to_str()) {
+    match decompressed_name(path).extension().and_then(|s| s.to_str()) {
         Some("ofn") => Some(DataType::OFN),
         Some("owx") => Some(DataType::OWX),
         Some("rdf") => Some(DataType::RDF),
@@ -155,6 +157,30 @@ where
     }
 }
 
+/// Maps quads to the axioms of a horned-owl ontology.
+///
+/// Returns the ontology, and whether every quad was mapped to an axiom.
+///
+/// # Errors
+/// Returns an error if the quads could not be serialized or mapped to an ontology.
+pub fn ontology_from_quads(
+    quads: impl IntoIterator<Item = Quad>,
+) -> Result<(RcComponentMappedOntology, bool), VOWLGrapherStoreError> {
+    let mut buf = Vec::new();
+    let mut serializer = RdfSerializer::from_format(RdfFormat::RdfXml).for_writer(&mut buf);
+    for quad in quads {
+        serializer.serialize_quad(&to_default_graph_quad(quad))?;
+    }
+    serializer.finish()?;
+
+    let (ontology, incomplete): (ConcreteRDFOntology<RcStr, RcAnnotatedComponent>, _) =
+        rdf::reader::read(
+            &mut BufReader::new(Cursor::new(buf)),
+            ParserConfiguration::default(),
+        )?;
+    Ok((ontology.into(), incomplete.is_complete()))
+}
+
 /// Returns the quads from parsing the file at the path.
 ///
 /// # Errors
@@ -175,11 +201,31 @@ pub fn parser_from_path(
 /// # Errors
 /// Returns an error if the parser fails to parse the input.
 pub fn parser_from_reader(
-    mut reader: impl BufRead,
+    reader: impl BufRead + Send,
     format: DataType,
     lenient: bool,
     graph_iri: &str,
 ) -> Result<Vec<Quad>, VOWLGrapherStoreError> {
+    quads_from_reader(reader, format, lenient, graph_iri)?.collect()
+}
+
+/// The quads parsed from an input, read while the iterator is consumed.
+pub type QuadIter<'r> = Box<dyn Iterator<Item = Result<Quad, VOWLGrapherStoreError>> + Send + 'r>;
+
+/// Returns an iterator over the quads parsed from the reader.
+///
+/// RDF inputs are parsed lazily, so neither the input nor its quads are held in memory as a whole.
+/// OFN and OWX inputs are converted to RDF/XML by horned-owl first, which needs the whole ontology.
+///
+/// # Errors
+/// Returns an error if the format is not supported, or if an OFN or OWX input fails to parse.
+/// Errors in RDF inputs are returned by the iterator.
+pub fn quads_from_reader<'r>(
+    mut reader: impl BufRead + Send + 'r,
+    format: DataType,
+    lenient: bool,
+    graph_iri: &str,
+) -> Result<QuadIter<'r>, VOWLGrapherStoreError> {
     let make_parser = |fmt| -> Result<RdfParser, VOWLGrapherStoreError> {
         let graph_node = NamedNodeRef::new(graph_iri)?;
         let parser = RdfParser::from_format(fmt).with_default_graph(graph_node);
@@ -190,15 +236,6 @@ pub fn parser_from_reader(
         }
     };
 
-    let collect_quads =
-        |parser: RdfParser, bytes: &[u8]| -> Result<Vec<Quad>, VOWLGrapherStoreError> {
-            parser
-                .rename_blank_nodes()
-                .for_reader(bytes)
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| VOWLGrapherStoreError::from(LoaderError::from(e)))
-        };
-
     match format {
         DataType::OFN => {
             info!("Parsing OFN input...");
@@ -229,7 +266,10 @@ pub fn parser_from_reader(
                     .as_secs_f32()
             );
 
-            collect_quads(make_parser(RdfFormat::RdfXml)?, &buf)
+            Ok(reader_quads(
+                make_parser(RdfFormat::RdfXml)?,
+                Cursor::new(buf),
+            ))
         }
         DataType::OWX => {
             info!("Parsing OWX input...");
@@ -263,27 +303,24 @@ pub fn parser_from_reader(
                     .as_secs_f32()
             );
 
-            collect_quads(make_parser(RdfFormat::RdfXml)?, &buf)
-        }
-        DataType::OWL | DataType::RDF => {
-            let mut input = Vec::new();
-            reader.read_to_end(&mut input)?;
-            collect_quads(make_parser(RdfFormat::RdfXml)?, &input)
+            Ok(reader_quads(
+                make_parser(RdfFormat::RdfXml)?,
+                Cursor::new(buf),
+            ))
         }
+        DataType::OWL | DataType::RDF => Ok(reader_quads(make_parser(RdfFormat::RdfXml)?, reader)),
         f @ (DataType::TTL
         | DataType::NTriples
         | DataType::NQuads
         | DataType::TriG
         | DataType::JsonLd
         | DataType::N3) => {
-            let mut input = Vec::new();
-            reader.read_to_end(&mut input)?;
             let format = format_from_resource_type(&f).ok_or_else(|| {
                 VOWLGrapherStoreErrorKind::InvalidFileType(format!(
                     "could not convert {f:?} to format"
                 ))
             })?;
-            collect_quads(make_parser(format)?, &input)
+            Ok(reader_quads(make_parser(format)?, reader))
         }
         _ => Err(VOWLGrapherStoreErrorKind::InvalidFileType(format!(
             "Unsupported parser: {}",
@@ -293,6 +330,16 @@ pub fn parser_from_reader(
     }
 }
 
+/// Returns the quads `parser` reads from `reader`, with blank nodes renamed to be unique in the store.
+fn reader_quads<'r>(parser: RdfParser, reader: impl Read + Send + 'r) -> QuadIter<'r> {
+    Box::new(
+        parser
+            .rename_blank_nodes()
+            .for_reader(reader)
+            .map(|quad| quad.map_err(|e| VOWLGrapherStoreError::from(LoaderError::from(e)))),
+    )
+}
+
 /// in-memory parsing
 ///
 /// # Errors
@@ -0,0 +1,595 @@
+//! Checks of an ontology against the profiles of OWL 2.
+//!
+//! The checks follow the grammars of the [OWL 2 Profiles](https://www.w3.org/TR/owl2-profiles/).
+//! Of the global restrictions of OWL 2 DL, only the use of non-simple object properties is
+//! checked, as the others concern ordering and typing that horned-owl already enforces while
+//! mapping RDF to axioms. Statements that do not map to any axiom also leave the ontology
+//! outside OWL 2 DL, and thus outside every profile.
+
+use horned_owl::io::ofn::writer::AsFunctional;
+use horned_owl::model::{
+    AnnotationProperty, Class, ClassExpression, Component, DataProperty, Datatype, DisjointClasses,
+    EquivalentClasses, IRI, Individual, NamedIndividual, ObjectProperty, ObjectPropertyExpression,
+    RcStr, SubObjectPropertyExpression,
+};
+use horned_owl::ontology::component_mapped::RcComponentMappedOntology;
+use horned_owl::visitor::immutable::{Visit, Walk};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use strum::IntoEnumIterator;
+use vowlgrapher_util::prelude::{
+    ErrorRecord, ErrorSeverity, ErrorType, OwlProfile, ProfileReport, get_timestamp,
+};
+
+const OWL_THING: &str = "http://www.w3.org/2002/07/owl#Thing";
+
+/// The namespaces of the vocabularies built into OWL 2, whose entities are never reported.
+const BUILT_IN_NAMESPACES: [&str; 4] = [
+    "http://www.w3.org/2002/07/owl#",
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+    "http://www.w3.org/2000/01/rdf-schema#",
+    "http://www.w3.org/2001/XMLSchema#",
+];
+
+type Ce = ClassExpression<RcStr>;
+type Ope = ObjectPropertyExpression<RcStr>;
+
+/// Checks an ontology against every profile of OWL 2.
+///
+/// `complete` tells whether every statement of the source graph was mapped to an axiom.
+pub fn check_profiles(ontology: &RcComponentMappedOntology, complete: bool) -> Vec<ProfileReport> {
+    let components = ontology
+        .into_iter()
+        .map(|annotated| &annotated.component)
+        .collect::<Vec<_>>();
+    let non_simple = non_simple_properties(&components);
+
+    let mut dl_violations = Vec::new();
+    if !complete {
+        dl_violations.push((
+            "The graph contains statements that do not map to any OWL 2 axiom".to_string(),
+            None,
+        ));
+    }
+    for component in &components {
+        if let Some(reason) = dl_violation(component, &non_simple) {
+            dl_violations.push((reason, Some(*component)));
+        }
+    }
+
+    OwlProfile::iter()
+        .map(|profile| {
+            // Every profile is a subset of OWL 2 DL.
+            let mut violations = dl_violations.clone();
+            if profile != OwlProfile::Dl {
+                for component in &components {
+                    let reason = match profile {
+                        OwlProfile::El => el_violation(component),
+                        OwlProfile::Ql => ql_violation(component),
+                        OwlProfile::Rl => rl_violation(component),
+                        OwlProfile::Dl => None,
+                    };
+                    if let Some(reason) = reason {
+                        violations.push((reason, Some(*component)));
+                    }
+                }
+            }
+            report(profile, &violations)
+        })
+        .collect()
+}
+
+/// Turns the reasons and axioms violating a profile into a report.
+fn report(
+    profile: OwlProfile,
+    violations: &[(String, Option<&Component<RcStr>>)],
+) -> ProfileReport {
+    let mut entities = BTreeSet::new();
+    let violations = violations
+        .iter()
+        .map(|(reason, component)| {
+            let message = if let Some(component) = component {
+                entities.extend(entity_iris(component));
+                format!(
+                    "Not in {profile}: {reason} in {}",
+                    component.as_functional()
+                )
+            } else {
+                format!("Not in {profile}: {reason}")
+            };
+            ErrorRecord::new(
+                get_timestamp(),
+                ErrorSeverity::Warning,
+                ErrorType::Profile,
+                message,
+                #[cfg(debug_assertions)]
+                None,
+            )
+        })
+        .collect();
+    ProfileReport {
+        profile,
+        violations,
+        entities: entities.into_iter().collect(),
+    }
+}
+
+/// Collects the IRIs of the entities named in components, except those built into OWL 2.
+#[derive(Default)]
+struct EntityIris(BTreeSet<String>);
+
+impl EntityIris {
+    fn insert(&mut self, iri: &IRI<RcStr>) {
+        let iri = iri.to_string();
+        if !BUILT_IN_NAMESPACES
+            .iter()
+            .any(|namespace| iri.starts_with(namespace))
+        {
+            self.0.insert(iri);
+        }
+    }
+}
+
+impl Visit<RcStr> for EntityIris {
+    fn visit_class(&mut self, class: &Class<RcStr>) {
+        self.insert(&class.0);
+    }
+
+    fn visit_datatype(&mut self, datatype: &Datatype<RcStr>) {
+        self.insert(&datatype.0);
+    }
+
+    fn visit_object_property(&mut self, property: &ObjectProperty<RcStr>) {
+        self.insert(&property.0);
+    }
+
+    fn visit_data_property(&mut self, property: &DataProperty<RcStr>) {
+        self.insert(&property.0);
+    }
+
+    fn visit_annotation_property(&mut self, property: &AnnotationProperty<RcStr>) {
+        self.insert(&property.0);
+    }
+
+    fn visit_named_individual(&mut self, individual: &NamedIndividual<RcStr>) {
+        self.insert(&individual.0);
+    }
+}
+
+/// Returns the IRIs of the entities named in a component, except those built into OWL 2.
+fn entity_iris(component: &Component<RcStr>) -> BTreeSet<String> {
+    let mut walk = Walk::new(EntityIris::default());
+    walk.component(component);
+    walk.into_visit().0
+}
+
+/// Returns the IRI of the object property in a property expression.
+fn property_iri(ope: &Ope) -> String {
+    match ope {
+        ObjectPropertyExpression::ObjectProperty(property)
+        | ObjectPropertyExpression::InverseObjectProperty(property) => property.0.to_string(),
+    }
+}
+
+/// Returns whether a class expression is `owl:Thing`.
+fn is_thing(ce: &Ce) -> bool {
+    matches!(ce, ClassExpression::Class(class) if class.0.to_string() == OWL_THING)
+}
+
+/// Returns the IRIs of the object properties that are not simple.
+///
+/// A property is not simple if it is transitive, the super property of a chain, or a super
+/// property of a property that is not simple.
+fn non_simple_properties(components: &[&Component<RcStr>]) -> HashSet<String> {
+    let mut non_simple = HashSet::new();
+    let mut super_properties = HashMap::<String, Vec<String>>::new();
+    for component in components {
+        match component {
+            Component::TransitiveObjectProperty(transitive) => {
+                non_simple.insert(property_iri(&transitive.0));
+            }
+            Component::SubObjectPropertyOf(axiom) => match &axiom.sub {
+                SubObjectPropertyExpression::ObjectPropertyChain(_) => {
+                    non_simple.insert(property_iri(&axiom.sup));
+                }
+                SubObjectPropertyExpression::ObjectPropertyExpression(sub) => {
+                    super_properties
+                        .entry(property_iri(sub))
+                        .or_default()
+                        .push(property_iri(&axiom.sup));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let mut pending = non_simple.iter().cloned().collect::<Vec<_>>();
+    while let Some(property) = pending.pop() {
+        for sup in super_properties.get(&property).into_iter().flatten() {
+            if non_simple.insert(sup.clone()) {
+                pending.push(sup.clone());
+            }
+        }
+    }
+    non_simple
+}
+
+/// Returns why using a property in a construct violates OWL 2 DL, if it is not simple.
+fn non_simple_use(ope: &Ope, construct: &str, non_simple: &HashSet<String>) -> Option<String> {
+    let property = property_iri(ope);
+    non_simple
+        .contains(&property)
+        .then(|| format!("the non-simple property <{property}> is used in {construct}"))
+}
+
+/// Returns why an axiom violates the global restrictions of OWL 2 DL, if it does.
+fn dl_violation(component: &Component<RcStr>, non_simple: &HashSet<String>) -> Option<String> {
+    let restricted = |ope: &Ope, construct: &str| non_simple_use(ope, construct, non_simple);
+    match component {
+        Component::FunctionalObjectProperty(axiom) => {
+            restricted(&axiom.0, "a functional property axiom")
+        }
+        Component::InverseFunctionalObjectProperty(axiom) => {
+            restricted(&axiom.0, "an inverse functional property axiom")
+        }
+        Component::IrreflexiveObjectProperty(axiom) => {
+            restricted(&axiom.0, "an irreflexive property axiom")
+        }
+        Component::AsymmetricObjectProperty(axiom) => {
+            restricted(&axiom.0, "an asymmetric property axiom")
+        }
+        Component::DisjointObjectProperties(axiom) => axiom
+            .0
+            .iter()
+            .find_map(|ope| restricted(ope, "a disjoint properties axiom")),
+        _ => class_expressions(component)
+            .into_iter()
+            .find_map(|ce| dl_class(ce, non_simple)),
+    }
+}
+
+/// Returns why a class expression violates the global restrictions of OWL 2 DL, if it does.
+fn dl_class(ce: &Ce, non_simple: &HashSet<String>) -> Option<String> {
+    let restricted = |ope: &Ope, construct: &str| non_simple_use(ope, construct, non_simple);
+    match ce {
+        ClassExpression::ObjectIntersectionOf(ces) | ClassExpression::ObjectUnionOf(ces) => {
+            ces.iter().find_map(|ce| dl_class(ce, non_simple))
+        }
+        ClassExpression::ObjectComplementOf(bce)
+        | ClassExpression::ObjectSomeValuesFrom { bce, .. }
+        | ClassExpression::ObjectAllValuesFrom { bce, .. } => dl_class(bce, non_simple),
+        ClassExpression::ObjectMinCardinality { ope, bce, .. }
+        | ClassExpression::ObjectMaxCardinality { ope, bce, .. }
+        | ClassExpression::ObjectExactCardinality { ope, bce, .. } => {
+            restricted(ope, "a cardinality restriction").or_else(|| dl_class(bce, non_simple))
+        }
+        ClassExpression::ObjectHasSelf(ope) => restricted(ope, "a self restriction"),
+        _ => None,
+    }
+}
+
+/// Returns why an axiom is outside OWL 2 EL, if it is.
+fn el_violation(component: &Component<RcStr>) -> Option<String> {
+    let disallowed = match component {
+        Component::DisjointUnion(_) => "disjoint unions",
+        Component::InverseObjectProperties(_) => "inverse properties",
+        Component::FunctionalObjectProperty(_) => "functional object properties",
+        Component::InverseFunctionalObjectProperty(_) => "inverse functional properties",
+        Component::SymmetricObjectProperty(_) => "symmetric properties",
+        Component::AsymmetricObjectProperty(_) => "asymmetric properties",
+        Component::IrreflexiveObjectProperty(_) => "irreflexive properties",
+        Component::DisjointObjectProperties(_) | Component::DisjointDataProperties(_) => {
+            "disjoint properties"
+        }
+        _ => {
+            return object_properties(component)
+                .into_iter()
+                .find_map(el_property)
+                .or_else(|| class_expressions(component).into_iter().find_map(el_class))
+                .map(|construct| format!("OWL 2 EL does not allow {construct}"));
+        }
+    };
+    Some(format!("OWL 2 EL does not allow {disallowed}"))
+}
+
+/// Returns the construct of a class expression OWL 2 EL does not allow, if it has one.
+fn el_class(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        ClassExpression::Class(_)
+        | ClassExpression::DataSomeValuesFrom { .. }
+        | ClassExpression::DataHasValue { .. } => None,
+        ClassExpression::ObjectIntersectionOf(ces) => ces.iter().find_map(el_class),
+        ClassExpression::ObjectSomeValuesFrom { ope, bce } => {
+            el_property(ope).or_else(|| el_class(bce))
+        }
+        ClassExpression::ObjectHasValue { ope, i } => el_property(ope)
+            .or_else(|| matches!(i, Individual::Anonymous(_)).then_some("anonymous individuals")),
+        ClassExpression::ObjectHasSelf(ope) => el_property(ope),
+        ClassExpression::ObjectOneOf(individuals) => {
+            (individuals.len() != 1).then_some("enumerations of more than one individual")
+        }
+        ClassExpression::ObjectUnionOf(_) => Some("unions"),
+        ClassExpression::ObjectComplementOf(_) => Some("complements"),
+        ClassExpression::ObjectAllValuesFrom { .. } | ClassExpression::DataAllValuesFrom { .. } => {
+            Some("universal restrictions")
+        }
+        ClassExpression::ObjectMinCardinality { .. }
+        | ClassExpression::ObjectMaxCardinality { .. }
+        | ClassExpression::ObjectExactCardinality { .. }
+        | ClassExpression::DataMinCardinality { .. }
+        | ClassExpression::DataMaxCardinality { .. }
+        | ClassExpression::DataExactCardinality { .. } => Some("cardinality restrictions"),
+    }
+}
+
+/// Returns the construct of a property expression OWL 2 EL does not allow, if it has one.
+const fn el_property(ope: &Ope) -> Option<&'static str> {
+    match ope {
+        ObjectPropertyExpression::ObjectProperty(_) => None,
+        ObjectPropertyExpression::InverseObjectProperty(_) => Some("inverse properties"),
+    }
+}
+
+/// Returns why an axiom is outside OWL 2 QL, if it is.
+fn ql_violation(component: &Component<RcStr>) -> Option<String> {
+    let construct = match component {
+        Component::DisjointUnion(_) => Some("disjoint unions"),
+        Component::FunctionalObjectProperty(_) | Component::FunctionalDataProperty(_) => {
+            Some("functional properties")
+        }
+        Component::InverseFunctionalObjectProperty(_) => Some("inverse functional properties"),
+        Component::TransitiveObjectProperty(_) => Some("transitive properties"),
+        Component::HasKey(_) => Some("keys"),
+        Component::SameIndividual(_) => Some("individual equality"),
+        Component::NegativeObjectPropertyAssertion(_)
+        | Component::NegativeDataPropertyAssertion(_) => Some("negative property assertions"),
+        Component::SubObjectPropertyOf(axiom) => matches!(
+            axiom.sub,
+            SubObjectPropertyExpression::ObjectPropertyChain(_)
+        )
+        .then_some("property chains"),
+        Component::SubClassOf(axiom) => ql_sub(&axiom.sub).or_else(|| ql_super(&axiom.sup)),
+        Component::EquivalentClasses(EquivalentClasses(ces))
+        | Component::DisjointClasses(DisjointClasses(ces)) => ces.iter().find_map(ql_sub),
+        Component::ObjectPropertyDomain(axiom) => ql_super(&axiom.ce),
+        Component::ObjectPropertyRange(axiom) => ql_super(&axiom.ce),
+        Component::DataPropertyDomain(axiom) => ql_super(&axiom.ce),
+        Component::ClassAssertion(axiom) => (!matches!(axiom.ce, ClassExpression::Class(_)))
+            .then_some("class expressions in class assertions"),
+        _ => None,
+    };
+    construct.map(|construct| format!("OWL 2 QL does not allow {construct}"))
+}
+
+/// Returns the construct OWL 2 QL does not allow on the left of a subclass axiom, if any.
+fn ql_sub(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        ClassExpression::Class(_) | ClassExpression::DataSomeValuesFrom { .. } => None,
+        ClassExpression::ObjectSomeValuesFrom { bce, .. } if is_thing(bce) => None,
+        ClassExpression::ObjectSomeValuesFrom { .. } => {
+            Some("qualified existential restrictions as subclasses")
+        }
+        _ => Some(class_construct(ce)),
+    }
+}
+
+/// Returns the construct OWL 2 QL does not allow on the right of a subclass axiom, if any.
+fn ql_super(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        ClassExpression::Class(_) | ClassExpression::DataSomeValuesFrom { .. } => None,
+        ClassExpression::ObjectIntersectionOf(ces) => ces.iter().find_map(ql_super),
+        ClassExpression::ObjectComplementOf(bce) => ql_sub(bce),
+        ClassExpression::ObjectSomeValuesFrom { bce, .. } => {
+            (!matches!(**bce, ClassExpression::Class(_)))
+                .then_some("existential restrictions on class expressions")
+        }
+        _ => Some(class_construct(ce)),
+    }
+}
+
+/// Returns why an axiom is outside OWL 2 RL, if it is.
+fn rl_violation(component: &Component<RcStr>) -> Option<String> {
+    let construct = match component {
+        Component::DisjointUnion(_) => Some("disjoint unions"),
+        Component::SubClassOf(axiom) => rl_sub(&axiom.sub).or_else(|| rl_super(&axiom.sup)),
+        Component::EquivalentClasses(EquivalentClasses(ces)) => ces.iter().find_map(rl_equivalent),
+        Component::DisjointClasses(DisjointClasses(ces)) => ces.iter().find_map(rl_sub),
+        Component::ObjectPropertyDomain(axiom) => rl_super(&axiom.ce),
+        Component::ObjectPropertyRange(axiom) => rl_super(&axiom.ce),
+        Component::DataPropertyDomain(axiom) => rl_super(&axiom.ce),
+        Component::ClassAssertion(axiom) => rl_super(&axiom.ce),
+        Component::HasKey(axiom) => rl_sub(&axiom.ce),
+        _ => None,
+    };
+    construct.map(|construct| format!("OWL 2 RL does not allow {construct}"))
+}
+
+/// Returns the construct OWL 2 RL does not allow on the left of a subclass axiom, if any.
+fn rl_sub(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        _ if is_thing(ce) => Some("owl:Thing as a subclass"),
+        ClassExpression::Class(_)
+        | ClassExpression::ObjectOneOf(_)
+        | ClassExpression::DataSomeValuesFrom { .. }
+        | ClassExpression::ObjectHasValue { .. }
+        | ClassExpression::DataHasValue { .. } => None,
+        ClassExpression::ObjectIntersectionOf(ces) | ClassExpression::ObjectUnionOf(ces) => {
+            ces.iter().find_map(rl_sub)
+        }
+        ClassExpression::ObjectSomeValuesFrom { bce, .. } if is_thing(bce) => None,
+        ClassExpression::ObjectSomeValuesFrom { bce, .. } => rl_sub(bce),
+        _ => Some(class_construct(ce)),
+    }
+}
+
+/// Returns the construct OWL 2 RL does not allow on the right of a subclass axiom, if any.
+fn rl_super(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        _ if is_thing(ce) => Some("owl:Thing as a superclass"),
+        ClassExpression::Class(_)
+        | ClassExpression::ObjectHasValue { .. }
+        | ClassExpression::DataAllValuesFrom { .. }
+        | ClassExpression::DataHasValue { .. } => None,
+        ClassExpression::ObjectIntersectionOf(ces) => ces.iter().find_map(rl_super),
+        ClassExpression::ObjectComplementOf(bce) => rl_sub(bce),
+        ClassExpression::ObjectAllValuesFrom { bce, .. } => rl_super(bce),
+        ClassExpression::ObjectMaxCardinality { n, bce, .. } => {
+            if *n > 1 {
+                Some("maximum cardinalities above 1")
+            } else if is_thing(bce) {
+                None
+            } else {
+                rl_sub(bce)
+            }
+        }
+        ClassExpression::DataMaxCardinality { n, .. } => {
+            (*n > 1).then_some("maximum cardinalities above 1")
+        }
+        _ => Some(class_construct(ce)),
+    }
+}
+
+/// Returns the construct OWL 2 RL does not allow in an equivalent classes axiom, if any.
+fn rl_equivalent(ce: &Ce) -> Option<&'static str> {
+    match ce {
+        _ if is_thing(ce) => Some("owl:Thing in equivalent classes"),
+        ClassExpression::Class(_)
+        | ClassExpression::ObjectHasValue { .. }
+        | ClassExpression::DataHasValue { .. } => None,
+        ClassExpression::ObjectIntersectionOf(ces) => ces.iter().find_map(rl_equivalent),
+        _ => Some(class_construct(ce)),
+    }
+}
+
+/// Returns a name for the kind of a class expression, used where a profile rules it out.
+const fn class_construct(ce: &Ce) -> &'static str {
+    match ce {
+        ClassExpression::Class(_) => "classes",
+        ClassExpression::ObjectIntersectionOf(_) => "intersections",
+        ClassExpression::ObjectUnionOf(_) => "unions",
+        ClassExpression::ObjectComplementOf(_) => "complements",
+        ClassExpression::ObjectOneOf(_) => "enumerations",
+        ClassExpression::ObjectSomeValuesFrom { .. }
+        | ClassExpression::DataSomeValuesFrom { .. } => "existential restrictions",
+        ClassExpression::ObjectAllValuesFrom { .. } | ClassExpression::DataAllValuesFrom { .. } => {
+            "universal restrictions"
+        }
+        ClassExpression::ObjectHasValue { .. } | ClassExpression::DataHasValue { .. } => {
+            "value restrictions"
+        }
+        ClassExpression::ObjectHasSelf(_) => "self restrictions",
+        ClassExpression::ObjectMinCardinality { .. }
+        | ClassExpression::ObjectMaxCardinality { .. }
+        | ClassExpression::ObjectExactCardinality { .. }
+        | ClassExpression::DataMinCardinality { .. }
+        | ClassExpression::DataMaxCardinality { .. }
+        | ClassExpression::DataExactCardinality { .. } => "cardinality restrictions",
+    }
+}
+
+/// Returns the class expressions an axiom states something about.
+fn class_expressions(component: &Component<RcStr>) -> Vec<&Ce> {
+    match component {
+        Component::SubClassOf(axiom) => vec![&axiom.sub, &axiom.sup],
+        Component::EquivalentClasses(EquivalentClasses(ces))
+        | Component::DisjointClasses(DisjointClasses(ces)) => ces.iter().collect(),
+        Component::DisjointUnion(axiom) => axiom.1.iter().collect(),
+        Component::ObjectPropertyDomain(axiom) => vec![&axiom.ce],
+        Component::ObjectPropertyRange(axiom) => vec![&axiom.ce],
+        Component::DataPropertyDomain(axiom) => vec![&axiom.ce],
+        Component::ClassAssertion(axiom) => vec![&axiom.ce],
+        Component::HasKey(axiom) => vec![&axiom.ce],
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the object property expressions of an axiom about object properties.
+fn object_properties(component: &Component<RcStr>) -> Vec<&Ope> {
+    match component {
+        Component::SubObjectPropertyOf(axiom) => {
+            let mut properties = match &axiom.sub {
+                SubObjectPropertyExpression::ObjectPropertyChain(chain) => chain.iter().collect(),
+                SubObjectPropertyExpression::ObjectPropertyExpression(sub) => vec![sub],
+            };
+            properties.push(&axiom.sup);
+            properties
+        }
+        Component::EquivalentObjectProperties(axiom) => axiom.0.iter().collect(),
+        Component::ObjectPropertyDomain(axiom) => vec![&axiom.ope],
+        Component::ObjectPropertyRange(axiom) => vec![&axiom.ope],
+        Component::ReflexiveObjectProperty(axiom) => vec![&axiom.0],
+        Component::TransitiveObjectProperty(axiom) => vec![&axiom.0],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use horned_owl::io::ParserConfiguration;
+    use horned_owl::io::ofn;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_check_profiles() -> Result<(), Box<dyn std::error::Error>> {
+        let ofn = "Prefix(:=<http://example.com/a#>)
+Ontology(<http://example.com/a>
+    Declaration(Class(:A))
+    Declaration(Class(:B))
+    Declaration(ObjectProperty(:p))
+    SubClassOf(:A ObjectSomeValuesFrom(:p :B))
+    SubClassOf(:B ObjectUnionOf(:A ObjectAllValuesFrom(:p :A)))
+)";
+        let (ontology, _): (RcComponentMappedOntology, _) =
+            ofn::reader::read(&mut Cursor::new(ofn), ParserConfiguration::default())?;
+
+        let reports = check_profiles(&ontology, true)
+            .into_iter()
+            .map(|report| (report.profile, report))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(reports[&OwlProfile::El].violations.len(), 1);
+        assert_eq!(
+            reports[&OwlProfile::El].entities,
+            [
+                "http://example.com/a#A",
+                "http://example.com/a#B",
+                "http://example.com/a#p"
+            ]
+        );
+        assert_eq!(reports[&OwlProfile::Ql].violations.len(), 1);
+        assert_eq!(reports[&OwlProfile::Rl].violations.len(), 2);
+        assert!(reports[&OwlProfile::Dl].conforms());
+        Ok(())
+    }
+
+    #[test]
+    fn test_entities_in_w3c_namespaces() -> Result<(), Box<dyn std::error::Error>> {
+        // Only the vocabularies built into OWL 2 are left out, not every ontology of the W3C.
+        let ofn = "Prefix(prov:=<http://www.w3.org/ns/prov#>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Prefix(xsd:=<http://www.w3.org/2001/XMLSchema#>)
+Ontology(<http://www.w3.org/ns/prov-o>
+    Declaration(Class(prov:Agent))
+    Declaration(Class(prov:Person))
+    Declaration(DataProperty(prov:value))
+    SubClassOf(prov:Agent ObjectUnionOf(prov:Person owl:Thing))
+    SubClassOf(prov:Agent DataAllValuesFrom(prov:value xsd:string))
+)";
+        let (ontology, _): (RcComponentMappedOntology, _) =
+            ofn::reader::read(&mut Cursor::new(ofn), ParserConfiguration::default())?;
+
+        let reports = check_profiles(&ontology, true)
+            .into_iter()
+            .map(|report| (report.profile, report))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(reports[&OwlProfile::El].violations.len(), 2);
+        assert_eq!(
+            reports[&OwlProfile::El].entities,
+            [
+                "http://www.w3.org/ns/prov#Agent",
+                "http://www.w3.org/ns/prov#Person",
+                "http://www.w3.org/ns/prov#value"
+            ]
+        );
+        Ok(())
+    }
+}
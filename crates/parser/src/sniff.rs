@@ -0,0 +1,175 @@
+//! Detection of the format of an input from its leading bytes.
+//!
+//! Sniffing only picks the parser to use. Turtle and its relatives cannot be told apart
+//! reliably from a prefix of the input, so a hint from the file extension or `Content-Type`
+//! decides between them.
+
+use vowlgrapher_util::prelude::DataType;
+
+/// How many leading bytes of an input are inspected.
+pub const SNIFF_LENGTH: usize = 8 * 1024;
+
+/// The namespace of OWL, which is also the namespace of OWL/XML elements.
+const OWL_NAMESPACE: &str = "http://www.w3.org/2002/07/owl#";
+
+/// Returns the format of an input, judged from its first bytes and `hint`.
+///
+/// `head` should contain at least the first [`SNIFF_LENGTH`] bytes of the input.
+/// `hint` is returned if the content does not point to any format,
+/// and decides between formats the content cannot tell apart.
+pub fn sniff_format(head: &[u8], hint: DataType) -> DataType {
+    let text = String::from_utf8_lossy(head);
+    let text = skip_comments(text.trim_start_matches('\u{feff}'));
+
+    if text.starts_with("<?") || text.starts_with("<!") {
+        return sniff_xml(text, hint);
+    }
+    if let Some(tag) = text.strip_prefix('<') {
+        // An IRI contains no whitespace, while the root element of an XML document
+        // declares at least one namespace.
+        let inner = tag.split('>').next().unwrap_or_default();
+        if inner.contains(char::is_whitespace) || inner.contains('=') {
+            return sniff_xml(text, hint);
+        }
+        return turtle_family(text, hint);
+    }
+    if text.starts_with("Prefix(") || text.starts_with("Ontology(") {
+        return DataType::OFN;
+    }
+    if (text.starts_with('{') || text.starts_with('['))
+        && ["\"@context\"", "\"@id\"", "\"@graph\"", "\"@type\""]
+            .iter()
+            .any(|keyword| text.contains(keyword))
+    {
+        return DataType::JsonLd;
+    }
+
+    let first_word = text
+        .split(|c: char| c.is_whitespace() || c == '<')
+        .next()
+        .unwrap_or_default();
+    if ["@prefix", "@base"].contains(&first_word)
+        || ["PREFIX", "BASE", "GRAPH"]
+            .iter()
+            .any(|keyword| first_word.eq_ignore_ascii_case(keyword))
+        || text.starts_with("_:")
+        || text.starts_with('[')
+    {
+        return turtle_family(text, hint);
+    }
+    hint
+}
+
+/// Returns the format of an XML document.
+///
+/// OWL/XML documents have an `Ontology` root element in the OWL namespace, identified by
+/// `ontologyIRI`. Every other XML document is treated as RDF/XML.
+fn sniff_xml(text: &str, hint: DataType) -> DataType {
+    let root = root_element(text).unwrap_or_default();
+    let name = root
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default();
+    let is_owx = name.rsplit(':').next() == Some("Ontology")
+        && root.contains(OWL_NAMESPACE)
+        && !root.contains("rdf:about")
+        && (root.contains("ontologyIRI") || !name.contains(':'));
+
+    if is_owx {
+        DataType::OWX
+    } else if hint == DataType::OWL {
+        // Both name RDF/XML, so keep the one the user chose.
+        DataType::OWL
+    } else {
+        DataType::RDF
+    }
+}
+
+/// Returns the content of the root element's start tag, without the angle brackets.
+fn root_element(text: &str) -> Option<&str> {
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("<?") {
+            rest = after.split_once("?>")?.1;
+        } else if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.split_once("-->")?.1;
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            // A DOCTYPE may declare entities in brackets, which contain `>` themselves.
+            let end = match (after.find('['), after.find('>')) {
+                (Some(open), Some(close)) if open < close => open + after[open..].find("]>")? + 1,
+                (_, close) => close?,
+            };
+            rest = &after[end + 1..];
+        } else {
+            let after = rest.strip_prefix('<')?;
+            return Some(after.split('>').next().unwrap_or(after));
+        }
+    }
+}
+
+/// Returns the format of a document in Turtle or one of its relatives.
+///
+/// The relatives are supersets or subsets of each other, so a hint among them wins.
+/// Otherwise, graph blocks point to `TriG` and everything else is parsed as Turtle.
+fn turtle_family(text: &str, hint: DataType) -> DataType {
+    match hint {
+        DataType::TTL | DataType::NTriples | DataType::NQuads | DataType::TriG | DataType::N3 => {
+            hint
+        }
+        _ if text.contains('{') => DataType::TriG,
+        _ => DataType::TTL,
+    }
+}
+
+/// Skips leading whitespace and `#` comment lines.
+fn skip_comments(text: &str) -> &str {
+    let mut rest = text.trim_start();
+    while rest.starts_with('#') {
+        rest = rest
+            .split_once('\n')
+            .map_or("", |(_, after)| after)
+            .trim_start();
+    }
+    rest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sniff_format() {
+        let rdf_xml = br#"<?xml version="1.0"?>
+            <!DOCTYPE rdf:RDF [ <!ENTITY owl "http://www.w3.org/2002/07/owl#" > ]>
+            <rdf:RDF xmlns="http://example.com/a#" xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">"#;
+        assert_eq!(sniff_format(rdf_xml, DataType::UNKNOWN), DataType::RDF);
+        assert_eq!(sniff_format(rdf_xml, DataType::OWL), DataType::OWL);
+
+        let owx = br#"<?xml version="1.0"?>
+            <Ontology xmlns="http://www.w3.org/2002/07/owl#" ontologyIRI="http://example.com/a">"#;
+        assert_eq!(sniff_format(owx, DataType::OWL), DataType::OWX);
+
+        let ofn = b"# comment\nPrefix(:=<http://example.com/a#>)\nOntology(<http://example.com/a>)";
+        assert_eq!(sniff_format(ofn, DataType::OWL), DataType::OFN);
+
+        let turtle =
+            b"@prefix : <http://example.com/a#> .\n: a <http://www.w3.org/2002/07/owl#Ontology> .";
+        assert_eq!(sniff_format(turtle, DataType::UNKNOWN), DataType::TTL);
+        assert_eq!(sniff_format(turtle, DataType::N3), DataType::N3);
+
+        let n_triples = b"<http://example.com/a> <http://example.com/p> <http://example.com/b> .";
+        assert_eq!(
+            sniff_format(n_triples, DataType::NTriples),
+            DataType::NTriples
+        );
+
+        let json_ld = br#"{ "@context": { "@vocab": "http://example.com/a#" }, "@id": "a" }"#;
+        assert_eq!(sniff_format(json_ld, DataType::UNKNOWN), DataType::JsonLd);
+
+        assert_eq!(
+            sniff_format(b"\x00\x01", DataType::UNKNOWN),
+            DataType::UNKNOWN
+        );
+    }
+}
@@ -4,16 +4,21 @@ use crate::{
         index::TermIndex,
     },
     errors::{SerializationError, SerializationErrorKind},
+    serializer_util::PROPERTY_EDGE_TYPES,
+    vocab::{owl, rdfs},
+};
+use grapher::prelude::{
+    Characteristic, ElementType, GraphDisplayData, OwlEdge, OwlType, RdfsEdge, RdfsType,
 };
-use grapher::prelude::{Characteristic, ElementType, GraphDisplayData, OwlEdge, OwlType};
 use log::debug;
+use oxrdf::{NamedNodeRef, NamedOrBlankNode, Term, Triple};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     fmt::{Display, Formatter},
     mem::take,
     sync::{Arc, RwLock},
 };
-use vowlgrapher_util::prelude::{ErrorRecord, VOWLGrapherError};
+use vowlgrapher_util::prelude::{Annotation, ErrorRecord, GraphAnnotations, VOWLGrapherError};
 
 /// An intermediate container for serialization data.
 ///
@@ -101,6 +106,22 @@ pub struct SerializationDataBuffer {
     ///
     /// For instance: `http://purl.obolibrary.org/obo/envo.owl`
     pub document_base: Arc<RwLock<Option<Arc<String>>>>,
+    /// Whether [`Self::document_base`] was set before serialization started.
+    ///
+    /// Further ontologies are then imports of the document rather than competing bases.
+    pub document_base_preset: bool,
+    /// Statements inferred by a reasoner rather than asserted.
+    ///
+    /// Edges which only follow from them are annotated with [`Annotation::Inferred`].
+    pub inferred_statements: Arc<HashSet<Triple>>,
+    /// The IRI of the element found by a search.
+    ///
+    /// It is annotated with [`Annotation::Highlighted`].
+    pub highlighted_element: Option<Arc<String>>,
+    /// The annotations of the elements with an IRI, by their IRI.
+    ///
+    /// They are set on the nodes of the IRIs and on the edges of properties with them.
+    pub annotated_terms: Arc<HashMap<String, BTreeSet<Annotation>>>,
     /// Data not visualized in the graph.
     pub metadata: GraphMetadataBuffer,
 }
@@ -109,6 +130,19 @@ impl SerializationDataBuffer {
         Self::default()
     }
 
+    /// Creates a new data buffer whose document base is already known.
+    ///
+    /// Terms outside of `document_base` are serialized as external elements.
+    pub fn with_document_base(document_base: Option<&str>) -> Self {
+        Self {
+            document_base_preset: document_base.is_some(),
+            document_base: Arc::new(RwLock::new(
+                document_base.map(|base| Arc::new(base.to_string())),
+            )),
+            ..Self::default()
+        }
+    }
+
     /// Unpack the predicate term id of the triple.
     ///
     /// Returns an error if the term id is None.
@@ -127,13 +161,15 @@ impl SerializationDataBuffer {
         reason = "this method clears most buffers and is expected to be called at the end of processing. 
         Thus, keeping locks longer than necessary doesn't matter"
     )]
-    /// Converts [`self`] into [`GraphDisplayData`].
+    /// Converts [`self`] into [`GraphDisplayData`] and the annotations of its elements.
     ///
     /// Works like [`TryFrom`] except it also returns non-critical errors in [`Result::Ok`].
     pub fn convert_into(
         &self,
-    ) -> Result<(GraphDisplayData, Option<VOWLGrapherError>), SerializationError> {
+    ) -> Result<(GraphDisplayData, GraphAnnotations, Option<VOWLGrapherError>), SerializationError>
+    {
         let mut display_data = GraphDisplayData::new();
+        let mut annotations = GraphAnnotations::default();
         let mut failed: Vec<ErrorRecord> = Vec::new();
 
         // Maps an RDF term's corresponding id to a [`GraphDisplayData`] index.
@@ -158,6 +194,8 @@ impl SerializationDataBuffer {
                 debug!("{msg}");
             }
             iricache.insert(term_id, display_data.elements.len());
+            annotations
+                .annotate_element(display_data.elements.len(), self.term_annotations(term_id)?);
             display_data.labels.push(label.flatten());
             display_data.elements.push(element);
         }
@@ -169,6 +207,13 @@ impl SerializationDataBuffer {
             let subject_idx = iricache.get(&edge.domain_term_id);
             let object_idx = iricache.get(&edge.range_term_id);
             let maybe_label = edge_label_buffer.remove(edge).flatten();
+            let mut edge_annotations = match edge.property_term_id {
+                Some(property_id) => self.term_annotations(property_id)?,
+                None => Vec::new(),
+            };
+            if self.is_inferred(edge)? {
+                edge_annotations.push(Annotation::Inferred);
+            }
             let characteristics = edge_characteristics.remove(edge);
             let cardinality = edge_cardinality_buffer.remove(edge);
 
@@ -199,6 +244,8 @@ impl SerializationDataBuffer {
                             display_data.elements.len() - 1
                         };
 
+                    annotations.annotate_element(edge_idx, edge_annotations.iter().copied());
+                    annotations.annotate_edge(display_data.edges.len(), edge_annotations);
                     display_data
                         .edges
                         .push([*subject_idx, edge_idx, *object_idx]);
@@ -272,11 +319,82 @@ impl SerializationDataBuffer {
         }
 
         if failed.is_empty() {
-            Ok((display_data, None))
+            Ok((display_data, annotations, None))
         } else {
-            Ok((display_data, Some(failed.into())))
+            Ok((display_data, annotations, Some(failed.into())))
         }
     }
+
+    /// Returns the annotations of a term in [`Self::annotated_terms`],
+    /// along with [`Annotation::Highlighted`] if it is [`Self::highlighted_element`].
+    fn term_annotations(&self, term_id: usize) -> Result<Vec<Annotation>, SerializationError> {
+        if self.annotated_terms.is_empty() && self.highlighted_element.is_none() {
+            return Ok(Vec::new());
+        }
+        let term = self.term_index.get(term_id)?;
+        let Term::NamedNode(node) = &*term else {
+            return Ok(Vec::new());
+        };
+        let mut annotations = self
+            .annotated_terms
+            .get(node.as_str())
+            .map(|annotations| annotations.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        if self
+            .highlighted_element
+            .as_deref()
+            .is_some_and(|highlighted| highlighted.as_str() == node.as_str())
+        {
+            annotations.push(Annotation::Highlighted);
+        }
+        Ok(annotations)
+    }
+
+    /// Returns whether the statement an edge was drawn from is one of [`Self::inferred_statements`].
+    ///
+    /// Property edges are drawn from the domain and range of the property,
+    /// so the edge counts as inferred if either of them was inferred.
+    fn is_inferred(&self, edge: &ArcEdge) -> Result<bool, SerializationError> {
+        if self.inferred_statements.is_empty() {
+            return Ok(false);
+        }
+        let domain = self.term_index.get(edge.domain_term_id)?;
+        let range = self.term_index.get(edge.range_term_id)?;
+
+        if PROPERTY_EDGE_TYPES.contains(&edge.edge_type)
+            && let Some(property_id) = edge.property_term_id
+        {
+            let property = self.term_index.get(property_id)?;
+            return Ok(self.is_inferred_statement(&property, rdfs::DOMAIN, &domain)
+                || self.is_inferred_statement(&property, rdfs::RANGE, &range));
+        }
+        Ok(match edge.edge_type {
+            ElementType::Rdfs(RdfsType::Edge(RdfsEdge::SubclassOf)) => {
+                self.is_inferred_statement(&domain, rdfs::SUB_CLASS_OF, &range)
+            }
+            ElementType::Owl(OwlType::Edge(OwlEdge::DisjointWith)) => {
+                self.is_inferred_statement(&domain, owl::DISJOINT_WITH, &range)
+                    || self.is_inferred_statement(&range, owl::DISJOINT_WITH, &domain)
+            }
+            _ => false,
+        })
+    }
+
+    /// Returns whether the statement `subject predicate object` is one of [`Self::inferred_statements`].
+    fn is_inferred_statement(
+        &self,
+        subject: &Term,
+        predicate: NamedNodeRef<'_>,
+        object: &Term,
+    ) -> bool {
+        let subject = match subject {
+            Term::NamedNode(node) => NamedOrBlankNode::from(node.clone()),
+            Term::BlankNode(node) => NamedOrBlankNode::from(node.clone()),
+            _ => return false,
+        };
+        self.inferred_statements
+            .contains(&Triple::new(subject, predicate, object.clone()))
+    }
 }
 
 impl Display for SerializationDataBuffer {
@@ -973,7 +973,14 @@ fn internal_serialize_triple(
                     let mut document_base = data_buffer.document_base.write()?;
                     let base_term = data_buffer.term_index.get(triple.subject_term_id)?;
                     let base = trim_tag_circumfix(&base_term.to_string());
-                    if let Some(base) = &*document_base {
+                    if data_buffer.document_base_preset {
+                        if document_base
+                            .as_ref()
+                            .is_some_and(|document_base| document_base.as_str() != base)
+                        {
+                            debug!("Treating ontology '{base}' as an import of the document");
+                        }
+                    } else if let Some(base) = &*document_base {
                         let msg = format!(
                             "Attempting to override document base '{base}' with new base '{base}'. Skipping"
                         );
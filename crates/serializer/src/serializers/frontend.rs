@@ -1,4 +1,11 @@
-use std::{mem::take, num::NonZero, thread::available_parallelism, time::Instant};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    mem::take,
+    num::NonZero,
+    sync::Arc,
+    thread::available_parallelism,
+    time::Instant,
+};
 
 use crate::{
     datastructures::serialization_data_buffer::SerializationDataBuffer,
@@ -11,24 +18,77 @@ use crate::{
 use futures::StreamExt;
 use grapher::prelude::GraphDisplayData;
 use log::{debug, error, info};
+use oxrdf::Triple;
 
 use rayon::ThreadPoolBuilder;
 use rdf_fusion::execution::results::{QuerySolution, QuerySolutionStream};
 
 use vowlgrapher_parser::errors::VOWLGrapherStoreError;
-use vowlgrapher_util::prelude::{ErrorRecord, VOWLGrapherError};
+use vowlgrapher_util::prelude::{Annotation, ErrorRecord, GraphAnnotations, VOWLGrapherError};
 
 /// Serializes a [`QuerySolutionStream`] into a [`GraphDisplayData`].
 #[derive(Default)]
-pub struct GraphDisplayDataSolutionSerializer;
+pub struct GraphDisplayDataSolutionSerializer {
+    /// The IRI of the ontology being serialized, if known before serialization.
+    document_base: Option<String>,
+    /// Statements inferred by a reasoner rather than asserted.
+    inferred_statements: Arc<HashSet<Triple>>,
+    /// The IRI of the element found by a search.
+    highlighted_element: Option<Arc<String>>,
+    /// The annotations of the elements with an IRI, by their IRI.
+    annotated_terms: Arc<HashMap<String, BTreeSet<Annotation>>>,
+}
 
 impl GraphDisplayDataSolutionSerializer {
     /// Creates an instance of [`self`]
-    pub const fn new() -> Self {
-        Self
+    pub fn new() -> Self {
+        Self {
+            document_base: None,
+            inferred_statements: Arc::new(HashSet::new()),
+            highlighted_element: None,
+            annotated_terms: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Creates an instance of [`self`] serializing the ontology `document_base`.
+    ///
+    /// Elements of other ontologies in the solutions, such as imports,
+    /// are serialized as external elements.
+    pub fn with_document_base(document_base: String) -> Self {
+        Self {
+            document_base: Some(document_base),
+            ..Self::new()
+        }
+    }
+
+    /// Annotates the edges which only follow from `inferred_statements` as inferred.
+    #[must_use]
+    pub fn with_inferred_statements(mut self, inferred_statements: HashSet<Triple>) -> Self {
+        self.inferred_statements = Arc::new(inferred_statements);
+        self
+    }
+
+    /// Annotates the element with the IRI `highlighted_element`, if any,
+    /// so the element found by a search stands out.
+    #[must_use]
+    pub fn with_highlighted_element(mut self, highlighted_element: Option<String>) -> Self {
+        self.highlighted_element = highlighted_element.map(Arc::new);
+        self
+    }
+
+    /// Annotates the nodes of the IRIs in `annotated_terms`
+    /// and the edges of the properties among them.
+    #[must_use]
+    pub fn with_annotated_terms(
+        mut self,
+        annotated_terms: HashMap<String, BTreeSet<Annotation>>,
+    ) -> Self {
+        self.annotated_terms = Arc::new(annotated_terms);
+        self
     }
 
-    /// Serializes a query solution stream into the data buffer using all available threads.
+    /// Serializes a query solution stream into `data` and the annotations of its elements
+    /// into `annotations` using all available threads.
     ///
     /// This method tries to continue serializing despite errors.
     /// As such, the `Ok` value contains non-fatal errors encountered during
@@ -42,6 +102,7 @@ impl GraphDisplayDataSolutionSerializer {
     pub async fn par_serialize_solution_stream(
         &self,
         data: &mut GraphDisplayData,
+        annotations: &mut GraphAnnotations,
         mut solution_stream: QuerySolutionStream,
     ) -> Result<Option<VOWLGrapherError>, VOWLGrapherError> {
         let thread_count = available_parallelism()
@@ -62,7 +123,13 @@ impl GraphDisplayDataSolutionSerializer {
             .map_err(|e| <SerializationError as Into<VOWLGrapherError>>::into(e.into()))?;
 
         let mut count: u64 = 0;
-        let mut data_buffer = SerializationDataBuffer::new();
+        let mut data_buffer =
+            SerializationDataBuffer::with_document_base(self.document_base.as_deref());
+        data_buffer.inferred_statements = Arc::clone(&self.inferred_statements);
+        data_buffer
+            .highlighted_element
+            .clone_from(&self.highlighted_element);
+        data_buffer.annotated_terms = Arc::clone(&self.annotated_terms);
         let mut query_time = None;
         let start_time = Instant::now();
 
@@ -120,14 +187,21 @@ impl GraphDisplayDataSolutionSerializer {
             }
         }
 
-        let all_errors =
-            Self::post_serialization_cleanup(data, &data_buffer, start_time, query_time, count)
-                .map_err(<SerializationError as Into<VOWLGrapherError>>::into)?;
+        let all_errors = Self::post_serialization_cleanup(
+            data,
+            annotations,
+            &data_buffer,
+            start_time,
+            query_time,
+            count,
+        )
+        .map_err(<SerializationError as Into<VOWLGrapherError>>::into)?;
 
         Ok(all_errors)
     }
 
-    /// Serializes a query solution stream into the data buffer.
+    /// Serializes a query solution stream into `data` and the annotations of its elements
+    /// into `annotations`.
     ///
     /// This method tries to continue serializing despite errors.
     /// As such, the `Ok` value contains non-fatal errors encountered during
@@ -138,11 +212,18 @@ impl GraphDisplayDataSolutionSerializer {
     pub async fn serialize_solution_stream(
         &self,
         data: &mut GraphDisplayData,
+        annotations: &mut GraphAnnotations,
         mut solution_stream: QuerySolutionStream,
     ) -> Result<Option<VOWLGrapherError>, VOWLGrapherError> {
         info!("Serializing query solution stream...");
         let mut count: u64 = 0;
-        let mut data_buffer = SerializationDataBuffer::new();
+        let mut data_buffer =
+            SerializationDataBuffer::with_document_base(self.document_base.as_deref());
+        data_buffer.inferred_statements = Arc::clone(&self.inferred_statements);
+        data_buffer
+            .highlighted_element
+            .clone_from(&self.highlighted_element);
+        data_buffer.annotated_terms = Arc::clone(&self.annotated_terms);
         let mut query_time = None;
         let start_time = Instant::now();
 
@@ -203,9 +284,15 @@ impl GraphDisplayDataSolutionSerializer {
             }
         }
 
-        let all_errors =
-            Self::post_serialization_cleanup(data, &data_buffer, start_time, query_time, count)
-                .map_err(<SerializationError as Into<VOWLGrapherError>>::into)?;
+        let all_errors = Self::post_serialization_cleanup(
+            data,
+            annotations,
+            &data_buffer,
+            start_time,
+            query_time,
+            count,
+        )
+        .map_err(<SerializationError as Into<VOWLGrapherError>>::into)?;
 
         Ok(all_errors)
     }
@@ -257,6 +344,7 @@ impl GraphDisplayDataSolutionSerializer {
     /// Must be called exactly once in any serialization implementation.
     fn post_serialization_cleanup(
         data: &mut GraphDisplayData,
+        annotations: &mut GraphAnnotations,
         data_buffer: &SerializationDataBuffer,
         start_time: Instant,
         query_time: Option<Instant>,
@@ -288,8 +376,9 @@ impl GraphDisplayDataSolutionSerializer {
             );
             Some(err)
         };
-        let (converted, convert_errors) = data_buffer.convert_into()?;
+        let (converted, converted_annotations, convert_errors) = data_buffer.convert_into()?;
         *data = converted;
+        *annotations = converted_annotations;
         debug!("{data}");
 
         let all_errors = match (serializer_errors, convert_errors) {
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use grapher::prelude::GraphDisplayData;
+
+use crate::diff::ChangeKind;
+use crate::shacl::ShaclSeverity;
+
+/// Why an element of the visualization is set apart from the others.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum Annotation {
+    /// The element differs between two compared versions of an ontology.
+    Change(ChangeKind),
+    /// An axiom mentioning the element violates the checked profile of OWL 2.
+    ProfileViolation,
+    /// The element is the focus node of a SHACL validation result of this severity.
+    FocusNode(ShaclSeverity),
+    /// The edge only follows from statements inferred by a reasoner.
+    Inferred,
+    /// The class is unsatisfiable, so it can have no instances.
+    Unsatisfiable,
+    /// The element was found by a search.
+    Highlighted,
+}
+
+impl Annotation {
+    /// Returns the sign marking the labels of elements with this annotation.
+    pub const fn marker(&self) -> &'static str {
+        match self {
+            Self::Change(change) => change.marker(),
+            Self::ProfileViolation => "⚠",
+            Self::FocusNode(_) => "✗",
+            Self::Inferred => "∴",
+            Self::Unsatisfiable => "⊥",
+            Self::Highlighted => "◉",
+        }
+    }
+}
+
+/// The annotations of the elements of a [`GraphDisplayData`].
+///
+/// Annotations are kept apart from the labels, so the renderer decides how they are drawn.
+/// As long as the renderer only draws labels, [`GraphAnnotations::mark_labels`] sets
+/// annotated elements apart by their label.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+)]
+pub struct GraphAnnotations {
+    /// The annotations of nodes and edge elements, by their index in `elements`.
+    pub elements: BTreeMap<usize, BTreeSet<Annotation>>,
+    /// The annotations of edges, by their index in `edges`.
+    pub edges: BTreeMap<usize, BTreeSet<Annotation>>,
+}
+
+impl GraphAnnotations {
+    /// Adds annotations to the node or edge element at `index` in `elements`.
+    pub fn annotate_element(
+        &mut self,
+        index: usize,
+        annotations: impl IntoIterator<Item = Annotation>,
+    ) {
+        Self::annotate(&mut self.elements, index, annotations);
+    }
+
+    /// Adds annotations to the edge at `index` in `edges`.
+    pub fn annotate_edge(
+        &mut self,
+        index: usize,
+        annotations: impl IntoIterator<Item = Annotation>,
+    ) {
+        Self::annotate(&mut self.edges, index, annotations);
+    }
+
+    /// Returns the annotations of the node or edge element at `index` in `elements`.
+    pub fn element(&self, index: usize) -> impl Iterator<Item = Annotation> + '_ {
+        self.elements.get(&index).into_iter().flatten().copied()
+    }
+
+    /// Returns the annotations of the edge at `index` in `edges`.
+    pub fn edge(&self, index: usize) -> impl Iterator<Item = Annotation> + '_ {
+        self.edges.get(&index).into_iter().flatten().copied()
+    }
+
+    /// Prefixes the label of every annotated element of `data` with the markers of its annotations.
+    ///
+    /// Unlabelled elements, such as most edges, are labelled with the markers alone.
+    pub fn mark_labels(&self, data: &mut GraphDisplayData) {
+        for (index, annotations) in &self.elements {
+            let Some(label) = data.labels.get_mut(*index) else {
+                continue;
+            };
+            let markers = annotations
+                .iter()
+                .map(Annotation::marker)
+                .collect::<Vec<_>>()
+                .join(" ");
+            *label = Some(match label.take() {
+                Some(label) => format!("{markers} {label}"),
+                None => markers,
+            });
+        }
+    }
+
+    /// Returns the index in `elements` of the element found by a search, if it is drawn.
+    pub fn highlighted_element(&self) -> Option<usize> {
+        self.elements
+            .iter()
+            .find(|(_, annotations)| annotations.contains(&Annotation::Highlighted))
+            .map(|(index, _)| *index)
+    }
+
+    /// Returns whether no element is annotated.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.edges.is_empty()
+    }
+
+    /// Adds annotations to the entry `index` of `annotated`, leaving it out if there are none.
+    fn annotate(
+        annotated: &mut BTreeMap<usize, BTreeSet<Annotation>>,
+        index: usize,
+        annotations: impl IntoIterator<Item = Annotation>,
+    ) {
+        let mut annotations = annotations.into_iter().peekable();
+        if annotations.peek().is_some() {
+            annotated.entry(index).or_default().extend(annotations);
+        }
+    }
+}
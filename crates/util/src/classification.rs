@@ -0,0 +1,34 @@
+use crate::error_handler::ErrorRecord;
+
+/// The result of classifying the ontology in a graph with the OWL 2 EL classifier.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+)]
+pub struct ClassificationReport {
+    /// The name of the graph, as given by the user when loading it.
+    pub graph_name: String,
+    /// The number of satisfiable classes in the inferred taxonomy.
+    pub class_count: u64,
+    /// The number of direct subclass relations in the inferred taxonomy.
+    pub subsumption_count: u64,
+    /// The number of axioms ignored in part or in whole, as they are outside of OWL 2 EL.
+    pub ignored_axiom_count: u64,
+    /// One record of type [`ErrorType::Inference`](crate::error_handler::ErrorType::Inference)
+    /// for every class equivalent to `owl:Nothing`.
+    pub unsatisfiable: Vec<ErrorRecord>,
+    /// The IRIs of the classes equivalent to `owl:Nothing`.
+    pub unsatisfiable_classes: Vec<String>,
+}
+
+impl ClassificationReport {
+    /// Returns whether every class is satisfiable.
+    pub const fn is_coherent(&self) -> bool {
+        self.unsatisfiable_classes.is_empty()
+    }
+}
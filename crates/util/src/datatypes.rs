@@ -110,6 +110,129 @@ impl DataType {
         }
     }
 
+    /// Returns the data type of a MIME type, ignoring any parameters.
+    ///
+    /// Returns [`DataType::UNKNOWN`] for unrecognized MIME types.
+    pub fn from_mime_type(mime_type: &str) -> Self {
+        let essence = mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match essence.as_str() {
+            "text/ofn" | "text/owl-functional" => Self::OFN,
+            "application/owl+xml" => Self::OWX,
+            "text/turtle" | "application/x-turtle" => Self::TTL,
+            "application/rdf+xml" => Self::RDF,
+            "application/n-triples" => Self::NTriples,
+            "application/n-quads" => Self::NQuads,
+            "application/trig" => Self::TriG,
+            "application/ld+json" => Self::JsonLd,
+            "text/n3" => Self::N3,
+            "application/sparql-results+json" => Self::SPARQLJSON,
+            "application/sparql-results+xml" => Self::SPARQLXML,
+            "text/csv" => Self::SPARQLCSV,
+            "text/tab-separated-values" => Self::SPARQLTSV,
+            _ => Self::UNKNOWN,
+        }
+    }
+
+    /// Picks the candidate best matching an HTTP `Accept` header.
+    ///
+    /// As in RFC 9110, the quality of a candidate is taken from the most specific media range
+    /// matching it, so `text/turtle` takes precedence over `text/*`, which takes precedence over `*/*`.
+    /// Candidates of equal quality are preferred in the given order,
+    /// regardless of the order of the media ranges in the header.
+    /// Returns `None` if the header accepts none of the candidates.
+    pub fn negotiate(accept: &str, candidates: &[Self]) -> Option<Self> {
+        if accept.trim().is_empty() {
+            return candidates.first().copied();
+        }
+
+        let media_ranges = accept
+            .split(',')
+            .map(|media_range| {
+                let mut params = media_range.split(';');
+                let media_type = params
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase();
+                let quality = params
+                    .filter_map(|param| {
+                        let (key, value) = param.split_once('=')?;
+                        key.trim()
+                            .eq_ignore_ascii_case("q")
+                            .then(|| value.trim().parse::<f32>().ok())?
+                    })
+                    .next()
+                    .unwrap_or(1.0);
+                (media_type, quality)
+            })
+            .collect::<Vec<_>>();
+
+        let mut best: Option<(Self, f32)> = None;
+        for candidate in candidates {
+            let mime_type = candidate.mime_type();
+            let quality = media_ranges
+                .iter()
+                .filter_map(|(media_type, quality)| {
+                    Self::match_specificity(media_type, mime_type)
+                        .map(|specificity| (specificity, *quality))
+                })
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, quality)| quality);
+            if let Some(quality) = quality
+                && quality > 0.0
+                && best.is_none_or(|(_, best_quality)| quality > best_quality)
+            {
+                best = Some((*candidate, quality));
+            }
+        }
+        best.map(|(candidate, _)| candidate)
+    }
+
+    /// Returns how specifically the media range `media_type` matches `mime_type`, if at all.
+    ///
+    /// `*/*` has the lowest specificity, followed by `type/*` and an exact match.
+    fn match_specificity(media_type: &str, mime_type: &str) -> Option<u8> {
+        if media_type == mime_type {
+            Some(2)
+        } else if media_type
+            .strip_suffix("/*")
+            .is_some_and(|top_level| mime_type.split('/').next() == Some(top_level))
+        {
+            Some(1)
+        } else if media_type == "*/*" {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of the HTTP `Accept` header sent when fetching an ontology.
+    ///
+    /// RDF/XML and Turtle are preferred, as nearly every ontology is published in one of them.
+    pub fn ontology_accept_header() -> String {
+        [
+            (Self::RDF, "1.0"),
+            (Self::TTL, "1.0"),
+            (Self::OWX, "0.9"),
+            (Self::OFN, "0.9"),
+            (Self::NTriples, "0.8"),
+            (Self::JsonLd, "0.7"),
+            (Self::TriG, "0.7"),
+            (Self::NQuads, "0.7"),
+            (Self::N3, "0.6"),
+        ]
+        .iter()
+        .map(|(data_type, quality)| format!("{};q={quality}", data_type.mime_type()))
+        .chain(std::iter::once("*/*;q=0.1".to_string()))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
     /// Returns the extension of the data.
     pub const fn extension(&self) -> &'static str {
         match self {
@@ -132,9 +255,29 @@ impl DataType {
     }
 }
 
+/// The extensions of compressed documents, which are decompressed before parsing.
+pub const COMPRESSION_EXTENSIONS: [&str; 4] = ["gz", "bz2", "xz", "zst"];
+
+/// Returns the file name a compressed document has once decompressed, e.g. `a.ttl` for `dir/a.ttl.gz`.
+///
+/// Paths without a compression extension are returned as they are.
+pub fn decompressed_name(path: &Path) -> &Path {
+    let compressed = path
+        .extension()
+        .and_then(|os| os.to_str())
+        .is_some_and(|extension| {
+            COMPRESSION_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        });
+    if compressed {
+        path.file_stem().map_or(path, Path::new)
+    } else {
+        path
+    }
+}
+
 impl From<&Path> for DataType {
     fn from(value: &Path) -> Self {
-        value
+        decompressed_name(value)
             .extension()
             .and_then(|os| os.to_str())
             .map_or(Self::UNKNOWN, std::convert::Into::into)
@@ -168,3 +311,48 @@ impl From<String> for DataType {
         value.to_lowercase().as_str().into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CANDIDATES: [DataType; 3] = [DataType::TTL, DataType::NTriples, DataType::N3];
+
+    #[test]
+    fn test_negotiate_quality() {
+        assert_eq!(
+            DataType::negotiate("text/turtle;q=0.5, application/n-triples", &CANDIDATES),
+            Some(DataType::NTriples)
+        );
+        assert_eq!(DataType::negotiate("application/json", &CANDIDATES), None);
+        assert_eq!(
+            DataType::negotiate("*/*, text/turtle;q=0", &CANDIDATES),
+            Some(DataType::NTriples),
+            "q=0 should exclude a type"
+        );
+        assert_eq!(DataType::negotiate("", &CANDIDATES), Some(DataType::TTL));
+    }
+
+    #[test]
+    fn test_negotiate_ties_follow_candidate_order() {
+        assert_eq!(
+            DataType::negotiate("application/n-triples, text/turtle", &CANDIDATES),
+            Some(DataType::TTL)
+        );
+        assert_eq!(DataType::negotiate("*/*", &CANDIDATES), Some(DataType::TTL));
+    }
+
+    #[test]
+    fn test_negotiate_specificity() {
+        assert_eq!(
+            DataType::negotiate("text/*;q=0.9, text/turtle;q=0.1", &CANDIDATES),
+            Some(DataType::N3),
+            "text/turtle should take its quality from the more specific range"
+        );
+        assert_eq!(
+            DataType::negotiate("*/*;q=0.2, text/*;q=0", &CANDIDATES),
+            Some(DataType::NTriples),
+            "text/* should override */* for text types"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+/// How an element or statement differs between two versions of an ontology.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum ChangeKind {
+    /// Only the new version contains it.
+    Added,
+    /// Only the old version contains it.
+    Removed,
+    /// Both versions contain it, but describe it differently.
+    Changed,
+}
+
+impl ChangeKind {
+    /// Returns the sign marking elements with this change in reports and in the graph.
+    pub const fn marker(&self) -> &'static str {
+        match self {
+            Self::Added => "+",
+            Self::Removed => "−",
+            Self::Changed => "~",
+        }
+    }
+}
+
+/// What kind of element of an ontology changed.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum ElementKind {
+    /// An `owl:Class` or `rdfs:Class`.
+    Class,
+    /// An object, datatype, annotation or RDF property.
+    Property,
+    /// An `owl:Restriction` on a class.
+    Restriction,
+    /// A named individual.
+    Individual,
+    /// Any other resource, such as the ontology itself.
+    Other,
+}
+
+/// An element that differs between two versions of an ontology.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct ElementChange {
+    /// The IRI of the element.
+    ///
+    /// For restrictions, the IRI of the class they restrict.
+    pub iri: String,
+    /// The label of the element, or the local name of its IRI if it has none.
+    ///
+    /// For restrictions, the label of the property they restrict.
+    pub label: String,
+    /// What kind of element changed.
+    pub kind: ElementKind,
+    /// How the element changed.
+    pub change: ChangeKind,
+}
+
+/// The differences between two graphs loaded by a user.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct OntologyDiff {
+    /// The name of the graph holding the old version.
+    pub old_graph: String,
+    /// The name of the graph holding the new version.
+    pub new_graph: String,
+    /// The number of quads only the new version contains.
+    pub added_quads: u64,
+    /// The number of quads only the old version contains.
+    pub removed_quads: u64,
+    /// The elements that differ, sorted by kind and label.
+    pub elements: Vec<ElementChange>,
+}
+
+/// The formats a change report can be downloaded in.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    strum::EnumIter,
+    PartialEq,
+    Eq,
+)]
+pub enum ReportFormat {
+    /// A changeset of reified statements.
+    ///
+    /// Vocabulary: [Changeset](https://vocab.org/changeset/)
+    Turtle,
+    /// A human-readable summary with the changed statements in N-Triples.
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Returns the MIME type of the format.
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Turtle => "text/turtle",
+            Self::Markdown => "text/markdown",
+        }
+    }
+
+    /// Returns the file extension of the format.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Turtle => "ttl",
+            Self::Markdown => "md",
+        }
+    }
+}
@@ -21,7 +21,7 @@ pub static VOWLGRAPHER_ENVIRONMENT: LazyLock<VOWLGrapherEnviron> =
 /// Client-side access to environment variables.
 #[server(input = Rkyv, output = Rkyv)]
 pub async fn environ() -> Result<VOWLGrapherEnviron, ServerFnError> {
-    Ok(*VOWLGRAPHER_ENVIRONMENT)
+    Ok(VOWLGRAPHER_ENVIRONMENT.clone())
 }
 
 /// Wrapper type to remotely derive impls for rkyv and serde.
@@ -52,7 +52,6 @@ impl From<SerilizableByteSize> for ByteSize {
     serde::Deserialize,
     serde::Serialize,
     Debug,
-    Copy,
     Clone,
 )]
 pub struct VOWLGrapherEnviron {
@@ -62,6 +61,63 @@ pub struct VOWLGrapherEnviron {
     pub max_input_size_bytes: ByteSize,
     /// Whether owl:imports should be fetched and loaded recursively.
     pub resolve_imports: bool,
+    /// The XML catalog consulted for every owl:imports, in addition to the
+    /// `catalog-v001.xml` next to local ontologies.
+    ///
+    /// This is a server-side path and is never sent to clients.
+    #[rkyv(with = rkyv::with::Skip)]
+    #[serde(skip)]
+    pub import_catalog_path: Option<String>,
+    /// The directory used to cache fetched owl:imports on disk.
+    ///
+    /// Cached imports are only read if fetching them fails or in offline mode.
+    ///
+    /// This is a server-side path and is never sent to clients.
+    #[rkyv(with = rkyv::with::Skip)]
+    #[serde(skip)]
+    pub import_cache_path: Option<String>,
+    /// Whether owl:imports may only be resolved through catalogs and the import cache,
+    /// never over the network.
+    pub offline: bool,
+    /// How many levels of nested owl:imports are loaded below an ontology.
+    pub max_import_depth: u32,
+    /// The maximum number of owl:imports loaded for a single ontology.
+    pub max_imports: u32,
+    /// The number of seconds fetching a single owl:imports may take.
+    ///
+    /// Each import is also limited to `max_input_size_bytes`.
+    pub import_timeout_secs: u64,
+    /// The directory used to persist the graph database on disk.
+    ///
+    /// If unset, the graph database only lives in memory.
+    ///
+    /// This is a server-side path and is never sent to clients.
+    #[rkyv(with = rkyv::with::Skip)]
+    #[serde(skip)]
+    pub store_path: Option<String>,
+    /// The key signing the session cookies, which must be at least 64 bytes long.
+    ///
+    /// If unset, the key is kept in `store_path`, or generated at every start
+    /// if the graph database only lives in memory.
+    ///
+    /// This is a server-side secret and is never sent to clients.
+    #[rkyv(with = rkyv::with::Skip)]
+    #[serde(skip)]
+    pub session_key: Option<String>,
+    /// The number of seconds a user graph may stay unused before it is evicted from the database.
+    ///
+    /// A value of `0` disables eviction.
+    pub graph_idle_ttl_secs: u64,
+    /// The maximum number of quads a single user may hold in the database.
+    ///
+    /// A value of `0` disables the quota.
+    pub max_user_quads: u64,
+    /// The maximum size, in bytes, of the input a single user may hold in the database.
+    ///
+    /// A value of `0` disables the quota.
+    #[rkyv(with = SerilizableByteSize)]
+    #[serde(with = "SerilizableByteSize")]
+    pub max_user_bytes: ByteSize,
 }
 
 impl VOWLGrapherEnviron {
@@ -70,9 +126,78 @@ impl VOWLGrapherEnviron {
         let max_input_size_bytes =
             Self::parse_environment("VOWLGRAPHER_MAX_INPUT_SIZE_BYTES", ByteSize::mb(50));
         let resolve_imports = Self::parse_environment("VOWLGRAPHER_RESOLVE_IMPORTS", true);
+        let import_catalog_path = Self::parse_optional_environment("VOWLGRAPHER_IMPORT_CATALOG");
+        let import_cache_path = Self::parse_optional_environment("VOWLGRAPHER_IMPORT_CACHE_PATH");
+        let offline = Self::parse_environment("VOWLGRAPHER_OFFLINE", false);
+        let max_import_depth = Self::parse_environment("VOWLGRAPHER_MAX_IMPORT_DEPTH", 8);
+        let max_imports = Self::parse_environment("VOWLGRAPHER_MAX_IMPORTS", 64);
+        let import_timeout_secs = Self::parse_environment("VOWLGRAPHER_IMPORT_TIMEOUT_SECS", 30);
+        let store_path = Self::parse_optional_environment("VOWLGRAPHER_STORE_PATH");
+        let session_key = Self::parse_secret_environment("VOWLGRAPHER_SESSION_KEY");
+        let graph_idle_ttl_secs =
+            Self::parse_environment("VOWLGRAPHER_GRAPH_IDLE_TTL_SECS", 60 * 60 * 24);
+        let max_user_quads = Self::parse_environment("VOWLGRAPHER_MAX_USER_QUADS", 0);
+        let max_user_bytes = Self::parse_environment("VOWLGRAPHER_MAX_USER_BYTES", ByteSize(0));
         Self {
             max_input_size_bytes,
             resolve_imports,
+            import_catalog_path,
+            import_cache_path,
+            offline,
+            max_import_depth,
+            max_imports,
+            import_timeout_secs,
+            store_path,
+            session_key,
+            graph_idle_ttl_secs,
+            max_user_quads,
+            max_user_bytes,
+        }
+    }
+
+    /// Returns the value of a key from the environment, if found and non-empty.
+    fn parse_optional_environment<K>(key: &K) -> Option<String>
+    where
+        K: ToString + ?Sized,
+    {
+        match var(key.to_string()) {
+            Ok(value) if !value.trim().is_empty() => {
+                info!(
+                    "Found variable {} with value '{}' in environment",
+                    key.to_string(),
+                    value
+                );
+                Some(value)
+            }
+            _ => {
+                warn!(
+                    "Did not find variable {} in environment. Leaving it unset",
+                    key.to_string()
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a secret key from the environment, if found and non-empty.
+    ///
+    /// Unlike [`Self::parse_optional_environment`], the value is never logged.
+    fn parse_secret_environment<K>(key: &K) -> Option<String>
+    where
+        K: ToString + ?Sized,
+    {
+        match var(key.to_string()) {
+            Ok(value) if !value.trim().is_empty() => {
+                info!("Found variable {} in environment", key.to_string());
+                Some(value)
+            }
+            _ => {
+                warn!(
+                    "Did not find variable {} in environment. Leaving it unset",
+                    key.to_string()
+                );
+                None
+            }
         }
     }
 
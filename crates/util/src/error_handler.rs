@@ -66,6 +66,12 @@ pub enum ErrorType {
     Parser,
     /// Errors related to the graph renderer (i.e. `WasmGrapher`)
     Renderer,
+    #[strum(serialize = "OWL 2 Profile")]
+    /// Axioms that violate an OWL 2 profile (e.g. OWL 2 EL).
+    Profile,
+    /// Inconsistencies found while inferring statements (e.g. an individual of two disjoint classes)
+    /// or classifying an ontology (e.g. a class equivalent to `owl:Nothing`).
+    Inference,
     #[strum(serialize = "GUI")]
     /// Errors related to the frontend GUI.
     Gui,
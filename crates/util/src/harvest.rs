@@ -0,0 +1,24 @@
+/// The progress of harvesting a SPARQL endpoint into a graph, one page at a time.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct HarvestReport {
+    /// The URL of the harvested endpoint.
+    pub endpoint: String,
+    /// The number of pages harvested so far.
+    pub page_count: u64,
+    /// The number of quads in the graph.
+    pub quad_count: u64,
+    /// The size of the harvested pages, in bytes.
+    pub byte_size: u64,
+    /// Whether the endpoint returned every page.
+    pub complete: bool,
+}
@@ -0,0 +1,30 @@
+use crate::error_handler::ErrorRecord;
+
+/// The result of materializing the statements entailed by an ontology.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+)]
+pub struct InferenceReport {
+    /// The name of the graph, as given by the user when loading it.
+    pub graph_name: String,
+    /// The number of statements in the graph and its imports.
+    pub asserted_count: u64,
+    /// The number of statements inferred from them.
+    pub inferred_count: u64,
+    /// One record of type [`ErrorType::Inference`](crate::error_handler::ErrorType::Inference)
+    /// for every inconsistency found.
+    pub inconsistencies: Vec<ErrorRecord>,
+}
+
+impl InferenceReport {
+    /// Returns whether no inconsistency was found.
+    pub const fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
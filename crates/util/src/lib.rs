@@ -1,21 +1,43 @@
 //! Utility code used by the entire `VOWLGrapher` workspace
 
+mod annotations;
+mod classification;
 mod datatypes;
+mod diff;
 mod env;
 mod error_handler;
+mod harvest;
+mod inference;
 mod layout;
+mod metrics;
+mod profiles;
+mod query_results;
+mod search;
+mod shacl;
 mod time;
+mod workspace;
 
 pub mod prelude {
     //! Export all types of the crate.
-    pub use crate::datatypes::DataType;
+    pub use crate::annotations::{Annotation, GraphAnnotations};
+    pub use crate::classification::ClassificationReport;
+    pub use crate::datatypes::{COMPRESSION_EXTENSIONS, DataType, decompressed_name};
+    pub use crate::diff::{ChangeKind, ElementChange, ElementKind, OntologyDiff, ReportFormat};
 
     #[cfg(feature = "server")]
     pub use crate::env::VOWLGRAPHER_ENVIRONMENT;
     pub use crate::env::{VOWLGrapherEnviron, environ};
     pub use crate::error_handler::{ErrorRecord, ErrorSeverity, ErrorType, VOWLGrapherError};
+    pub use crate::harvest::HarvestReport;
+    pub use crate::inference::InferenceReport;
     pub use crate::layout::TableHTML;
-    pub use crate::time::get_timestamp;
+    pub use crate::metrics::{AxiomCount, MetricsFormat, OntologyMetrics};
+    pub use crate::profiles::{OwlProfile, ProfileReport};
+    pub use crate::query_results::QuerySolutionRow;
+    pub use crate::search::{SearchField, SearchHit};
+    pub use crate::shacl::{ShaclSeverity, ValidationReport, ValidationResult};
+    pub use crate::time::{format_unix_timestamp, get_timestamp};
+    pub use crate::workspace::{GraphSummary, ImportSummary};
 
     #[cfg(feature = "ssr")]
     pub use crate::user_session::manage_user_id;
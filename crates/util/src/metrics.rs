@@ -0,0 +1,172 @@
+/// The number of axioms of one type in an ontology.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct AxiomCount {
+    /// The name of the axiom type in OWL 2 Functional Syntax, such as `SubClassOf`.
+    pub axiom: String,
+    /// The number of axioms of the type.
+    pub count: u64,
+}
+
+/// Statistics about the ontology in a named graph.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+)]
+pub struct OntologyMetrics {
+    /// The name of the graph, as given by the user when loading it.
+    pub graph_name: String,
+    /// The number of named classes.
+    pub class_count: u64,
+    /// The number of object properties.
+    pub object_property_count: u64,
+    /// The number of datatype properties.
+    pub datatype_property_count: u64,
+    /// The number of annotation properties.
+    pub annotation_property_count: u64,
+    /// The number of named individuals.
+    pub individual_count: u64,
+    /// The number of axioms of every type found in the ontology, sorted by type.
+    pub axiom_counts: Vec<AxiomCount>,
+    /// The number of levels of the subclass hierarchy, with classes without superclasses on the first.
+    pub hierarchy_depth: u64,
+    /// The largest number of classes on one level of the subclass hierarchy.
+    pub hierarchy_breadth: u64,
+    /// The largest number of direct subclasses of a single class.
+    pub max_subclasses: u64,
+    /// The share of entities with an `rdfs:label`, between 0 and 1.
+    pub labelled_share: f64,
+    /// The share of entities with an `rdfs:comment`, between 0 and 1.
+    pub commented_share: f64,
+    /// The share of entities marked `owl:deprecated`, between 0 and 1.
+    pub deprecated_share: f64,
+    /// The number of ontologies imported, directly or transitively.
+    pub import_count: u64,
+}
+
+impl OntologyMetrics {
+    /// Returns the metrics as `metric,value` rows, preceded by a header.
+    ///
+    /// Axiom counts are named `axioms.<type>`.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![
+            ("metric".to_string(), "value".to_string()),
+            ("graph_name".to_string(), csv_field(&self.graph_name)),
+            ("class_count".to_string(), self.class_count.to_string()),
+            (
+                "object_property_count".to_string(),
+                self.object_property_count.to_string(),
+            ),
+            (
+                "datatype_property_count".to_string(),
+                self.datatype_property_count.to_string(),
+            ),
+            (
+                "annotation_property_count".to_string(),
+                self.annotation_property_count.to_string(),
+            ),
+            (
+                "individual_count".to_string(),
+                self.individual_count.to_string(),
+            ),
+            (
+                "hierarchy_depth".to_string(),
+                self.hierarchy_depth.to_string(),
+            ),
+            (
+                "hierarchy_breadth".to_string(),
+                self.hierarchy_breadth.to_string(),
+            ),
+            (
+                "max_subclasses".to_string(),
+                self.max_subclasses.to_string(),
+            ),
+            (
+                "labelled_share".to_string(),
+                self.labelled_share.to_string(),
+            ),
+            (
+                "commented_share".to_string(),
+                self.commented_share.to_string(),
+            ),
+            (
+                "deprecated_share".to_string(),
+                self.deprecated_share.to_string(),
+            ),
+            ("import_count".to_string(), self.import_count.to_string()),
+        ];
+        rows.extend(
+            self.axiom_counts
+                .iter()
+                .map(|axiom| (format!("axioms.{}", axiom.axiom), axiom.count.to_string())),
+        );
+        rows.iter()
+            .map(|(metric, value)| format!("{metric},{value}\r\n"))
+            .collect()
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The formats metrics can be downloaded in.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    strum::EnumIter,
+    PartialEq,
+    Eq,
+)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum MetricsFormat {
+    /// Syntax: [JSON](https://www.rfc-editor.org/rfc/rfc8259)
+    Json,
+    /// Syntax: [CSV](https://www.rfc-editor.org/rfc/rfc4180)
+    Csv,
+}
+
+impl MetricsFormat {
+    /// Returns the MIME type of the format.
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv",
+        }
+    }
+
+    /// Returns the file extension of the format.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error_handler::ErrorRecord;
+
+/// The profiles of OWL 2 an ontology can be checked against.
+///
+/// See the [OWL 2 Profiles](https://www.w3.org/TR/owl2-profiles/) and the
+/// [OWL 2 DL](https://www.w3.org/TR/owl2-syntax/#Global_Restrictions_on_Axioms_in_OWL_2_DL)
+/// restrictions.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    strum::Display,
+    strum::EnumIter,
+    PartialEq,
+    Eq,
+)]
+pub enum OwlProfile {
+    /// Polynomial time reasoning for ontologies with large class hierarchies.
+    #[strum(serialize = "OWL 2 EL")]
+    El,
+    /// Query answering over large sets of instances by rewriting into database queries.
+    #[strum(serialize = "OWL 2 QL")]
+    Ql,
+    /// Reasoning with rules over the RDF triples of an ontology.
+    #[strum(serialize = "OWL 2 RL")]
+    Rl,
+    /// Decidable reasoning over the full structural specification of OWL 2.
+    #[strum(serialize = "OWL 2 DL")]
+    Dl,
+}
+
+/// The result of checking an ontology against an OWL 2 profile.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+)]
+pub struct ProfileReport {
+    /// The profile checked against.
+    pub profile: OwlProfile,
+    /// One record of type [`ErrorType::Profile`](crate::error_handler::ErrorType::Profile)
+    /// for every axiom violating the profile.
+    pub violations: Vec<ErrorRecord>,
+    /// The IRIs of the entities mentioned by the violating axioms.
+    pub entities: Vec<String>,
+}
+
+impl ProfileReport {
+    /// Returns whether the ontology falls into the profile.
+    pub const fn conforms(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
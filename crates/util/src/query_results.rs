@@ -0,0 +1,54 @@
+use leptos::prelude::*;
+
+use crate::layout::TableHTML;
+
+/// A solution of a SPARQL query, shown as a row of a table.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct QuerySolutionRow {
+    /// The variables of the query, in the order of the columns.
+    pub variables: Vec<String>,
+    /// The value bound to each variable, if any.
+    ///
+    /// IRIs are written in angle brackets and blank nodes with a `_:` prefix,
+    /// while literals are written by their lexical value.
+    pub values: Vec<Option<String>>,
+}
+
+impl TableHTML for QuerySolutionRow {
+    fn header(&self) -> impl IntoView {
+        let th_css =
+            "p-1 font-sans text-sm antialiased font-normal leading-normal text-blue-gray-900";
+        view! {
+            <tr class="border-b">
+                {self
+                    .variables
+                    .iter()
+                    .map(|variable| view! { <th class=th_css>{format!("?{variable}")}</th> })
+                    .collect_view()}
+            </tr>
+        }
+    }
+
+    fn row(&self) -> impl IntoView {
+        let td_css = "p-2 font-sans text-sm antialiased font-normal leading-normal whitespace-pre-wrap break-all text-blue-gray-900";
+        view! {
+            <tr class="border-b hover:bg-slate-200">
+                {self
+                    .values
+                    .iter()
+                    .map(|value| view! { <td class=td_css>{value.clone().unwrap_or_default()}</td> })
+                    .collect_view()}
+            </tr>
+        }
+    }
+}
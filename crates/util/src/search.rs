@@ -0,0 +1,48 @@
+use grapher::prelude::ElementType;
+
+/// The text of a resource a search matched.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum SearchField {
+    /// An `rdfs:label`, in any language.
+    Label,
+    /// A `skos:altLabel`, in any language.
+    #[strum(to_string = "Alternative label")]
+    AltLabel,
+    /// An `rdfs:comment`, in any language.
+    Comment,
+    /// The end of the IRI of the resource, after its last `#` or `/`.
+    #[strum(to_string = "Local name")]
+    LocalName,
+}
+
+/// An element of a graph matching a full-text search.
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// The IRI of the element.
+    pub iri: String,
+    /// The label the element is shown with, if it has one.
+    pub label: Option<String>,
+    /// The type the element is drawn as.
+    pub element_type: ElementType,
+    /// Which text of the element matched.
+    pub field: SearchField,
+    /// The text of the element which matched.
+    pub matched_text: String,
+    /// How well the element matches the search, where higher is better.
+    pub score: f32,
+}
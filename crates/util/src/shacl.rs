@@ -0,0 +1,79 @@
+/// The severity of a SHACL validation result, given by `sh:severity` on its shape.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Copy,
+    Clone,
+    strum::Display,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum ShaclSeverity {
+    /// `sh:Violation`, the default severity.
+    Violation,
+    /// `sh:Warning`
+    Warning,
+    /// `sh:Info`
+    Info,
+}
+
+/// A value node or focus node that does not conform to a constraint of a shape.
+///
+/// RDF terms are written in N-Triples syntax.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct ValidationResult {
+    /// The focus node that was validated.
+    pub focus_node: String,
+    /// The path from the focus node to the value, if the constraint belongs to a property shape.
+    pub result_path: Option<String>,
+    /// The value that violates the constraint, if the constraint is about single values.
+    pub value: Option<String>,
+    /// The shape the constraint belongs to.
+    pub source_shape: String,
+    /// The local name of the constraint component, such as `MinCountConstraintComponent`.
+    pub constraint_component: String,
+    /// The severity of the shape.
+    pub severity: ShaclSeverity,
+    /// The `sh:message` of the shape, or a description of the violation.
+    pub message: String,
+}
+
+/// The results of validating a data graph against a shapes graph with SHACL Core.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct ValidationReport {
+    /// The name of the validated graph, as given by the user when loading it.
+    pub data_graph: String,
+    /// The name of the graph holding the shapes.
+    pub shapes_graph: String,
+    /// Whether the data graph produced no validation results.
+    pub conforms: bool,
+    /// The validation results, sorted by severity, focus node and constraint component.
+    pub results: Vec<ValidationResult>,
+}
@@ -31,6 +31,21 @@ pub fn get_timestamp() -> String {
     }
 }
 
+/// Formats a number of seconds since the Unix epoch as a UTC date and time.
+pub fn format_unix_timestamp(secs: u64) -> String {
+    let fmt_desc = format_description!("[year]-[month]-[day] [hour]:[minute]:[second] UTC");
+
+    let t = match OffsetDateTime::from_unix_timestamp(i64::try_from(secs).unwrap_or(i64::MAX)) {
+        Ok(t) => t,
+        Err(e) => return e.to_string(),
+    };
+
+    match t.format(&fmt_desc) {
+        Ok(f) => f,
+        Err(e) => e.to_string(),
+    }
+}
+
 #[cfg(all(target_family = "wasm", target_os = "unknown"))]
 fn perf_to_system(amt: f64) -> SystemTime {
     let secs = (amt as u64) / 1_000;
@@ -0,0 +1,45 @@
+/// A named graph loaded by a user.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct GraphSummary {
+    /// The name of the graph, as given by the user when loading it.
+    pub name: String,
+    /// The number of quads in the graph.
+    pub quad_count: u64,
+    /// When the graph was loaded, in seconds since the Unix epoch.
+    ///
+    /// `None` if the server does not know when the graph was loaded.
+    pub loaded_at: Option<u64>,
+}
+
+/// An ontology imported, directly or transitively, by a named graph.
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub struct ImportSummary {
+    /// The IRI of the imported ontology.
+    pub iri: String,
+    /// The IRIs of the other imported ontologies importing this one.
+    ///
+    /// Empty if only the ontology of the graph itself imports it.
+    pub imported_by: Vec<String>,
+    /// The number of quads loaded from the import.
+    pub quad_count: u64,
+}
@@ -1,23 +1,33 @@
 mod about_menu;
+mod diff_menu;
 mod error_log;
 mod export_menu;
 mod filter_menu;
+mod metrics_menu;
 mod ontology_menu;
 mod options_menu;
+mod profiles_menu;
+mod reasoning_menu;
+mod search_menu;
+mod shacl_menu;
 
-// mod search_menu;1
 use crate::components::lists::{ListDetails, ListElement};
 use crate::components::menu::vertical_menu::VerticalMenu;
 use crate::components::user_input::internal_sparql::GraphDataContext;
 use crate::errors::ErrorLogContext;
 use about_menu::AboutMenu;
+use diff_menu::DiffMenu;
 use error_log::ErrorMenu;
 use export_menu::ExportMenu;
 use filter_menu::FilterMenu;
 use leptos::prelude::*;
+use metrics_menu::MetricsMenu;
 use ontology_menu::OntologyMenu;
 use options_menu::OptionsMenu;
-// use search_menu::SearchMenu;
+use profiles_menu::ProfilesMenu;
+use reasoning_menu::ReasoningMenu;
+use search_menu::SearchMenu;
+use shacl_menu::ShaclMenu;
 
 #[component]
 fn WorkbenchMenuItems(#[prop(into)] title: String, children: Children) -> impl IntoView {
@@ -52,14 +62,34 @@ pub fn NewWorkbench() -> impl IntoView {
                 <OntologyMenu />
             </ListElement>
 
-            // <ListElement title="Search" icon=icondata::BiMenuRegular>
-            // <SearchMenu />
-            // </ListElement>
+            <ListElement title="Search" icon=icondata::BiMenuRegular>
+                <SearchMenu />
+            </ListElement>
 
             <ListElement title="Filter" icon=icondata::BiMenuRegular>
                 <FilterMenu />
             </ListElement>
 
+            <ListElement title="Statistics" icon=icondata::BiMenuRegular>
+                <MetricsMenu />
+            </ListElement>
+
+            <ListElement title="Profiles" icon=icondata::BiMenuRegular>
+                <ProfilesMenu />
+            </ListElement>
+
+            <ListElement title="SHACL" icon=icondata::BiMenuRegular>
+                <ShaclMenu />
+            </ListElement>
+
+            <ListElement title="Reasoning" icon=icondata::BiMenuRegular>
+                <ReasoningMenu />
+            </ListElement>
+
+            <ListElement title="Compare" icon=icondata::BiMenuRegular>
+                <DiffMenu />
+            </ListElement>
+
             <ListElement title="Export" icon=icondata::BiMenuRegular>
                 <ExportMenu />
             </ListElement>
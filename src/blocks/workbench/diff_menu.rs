@@ -0,0 +1,244 @@
+use super::WorkbenchMenuItems;
+use super::export_menu::{ExportButton, save_download};
+use crate::components::user_input::internal_sparql::{GraphDataContext, draw_graph};
+use crate::components::user_input::workspace::list_graphs;
+use crate::errors::{ClientErrorKind, ErrorLogContext};
+use grapher::prelude::GraphDisplayData;
+use leptos::prelude::*;
+use leptos::server_fn::codec::{ByteStream, Rkyv, Streaming};
+use strum::IntoEnumIterator;
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{
+    ChangeKind, GraphAnnotations, OntologyDiff, ReportFormat, VOWLGrapherError,
+};
+use web_sys::HtmlInputElement;
+
+/// Compare two graphs of the current user and serialize the new one with its changes annotated.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn diff_graphs(
+    old_graph: String,
+    new_graph: String,
+) -> Result<
+    (
+        GraphDisplayData,
+        GraphAnnotations,
+        OntologyDiff,
+        Option<VOWLGrapherError>,
+    ),
+    VOWLGrapherError,
+> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    store.diff_display_data(&old_graph, &new_graph).await
+}
+
+#[server(output = Streaming)]
+/// Export a report of the changes between two graphs of the current user
+pub async fn export_diff_report(
+    old_graph: String,
+    new_graph: String,
+    format: ReportFormat,
+) -> Result<ByteStream<VOWLGrapherError>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    let report = store.diff_report(&old_graph, &new_graph, format).await?;
+    Ok(ByteStream::new(futures::stream::once(async move {
+        Ok(bytes::Bytes::from(report))
+    })))
+}
+
+/// Returns the text colour of elements with a change.
+const fn change_class(change: ChangeKind) -> &'static str {
+    match change {
+        ChangeKind::Added => "text-green-700",
+        ChangeKind::Removed => "text-red-700",
+        ChangeKind::Changed => "text-amber-600",
+    }
+}
+
+/// Compares two loaded graphs and draws the new one with its changes annotated.
+#[component]
+pub fn DiffMenu() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+
+    let old_graph = RwSignal::new(String::new());
+    let new_graph = RwSignal::new(String::new());
+    let diff = RwSignal::new(None::<OntologyDiff>);
+    let progress_message = RwSignal::new(String::new());
+
+    let graphs = LocalResource::new(move || {
+        active_graph_name.track();
+        async move {
+            list_graphs().await.unwrap_or_else(|e| {
+                error_context.extend(e.records);
+                Vec::new()
+            })
+        }
+    });
+
+    let selected_graphs = move || {
+        let (old, new) = (old_graph.get_untracked(), new_graph.get_untracked());
+        if old.is_empty() || new.is_empty() {
+            error_context.push(
+                ClientErrorKind::InvalidInput("Select two graphs to compare".to_string()).into(),
+            );
+            return None;
+        }
+        Some((old, new))
+    };
+
+    let compare = Action::new_local(move |(old, new): &(String, String)| {
+        let (old, new) = (old.clone(), new.clone());
+        async move {
+            match diff_graphs(old, new).await {
+                Ok((display_data, changes, result, non_fatal_error)) => {
+                    diff.set(Some(result));
+                    draw_graph(display_data, changes);
+                    if let Some(e) = non_fatal_error {
+                        error_context.extend(e.records);
+                    }
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let download = Action::new_local(move |(old, new, format): &(String, String, ReportFormat)| {
+        let (old, new, format) = (old.clone(), new.clone(), *format);
+        async move {
+            let result = match export_diff_report(old, new, format).await {
+                Ok(byte_stream) => {
+                    save_download(
+                        byte_stream,
+                        progress_message,
+                        &format!("changes.{}", format.extension()),
+                        format.mime_type(),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error_context.extend(e.records);
+            }
+        }
+    });
+
+    let graph_select = move |selected: RwSignal<String>, placeholder: &'static str| {
+        let options = move || {
+            graphs.get().map(|graphs| {
+                graphs
+                    .iter()
+                    .map(|graph| {
+                        view! { <option value=graph.name.clone()>{graph.name.clone()}</option> }
+                    })
+                    .collect_view()
+            })
+        };
+        view! {
+            <select
+                class="p-1 w-full text-sm bg-gray-200 rounded border-b-0"
+                prop:value=move || selected.get()
+                on:change=move |ev| {
+                    let target: HtmlInputElement = event_target(&ev);
+                    selected.set(target.value());
+                }
+            >
+                <option value="" disabled=true selected=true hidden=true>
+                    {placeholder}
+                </option>
+                {options}
+            </select>
+        }
+    };
+
+    let report_buttons = move || {
+        ReportFormat::iter()
+            .map(|format| {
+                view! {
+                    <ExportButton
+                        label=format.to_string()
+                        icon=icondata::BiExportRegular
+                        on_click=move || {
+                            if let Some((old, new)) = selected_graphs() {
+                                download.dispatch_local((old, new, format));
+                            }
+                        }
+                    />
+                }
+            })
+            .collect_view()
+    };
+
+    let summary = move || {
+        diff.get().map(|diff| {
+            let rows = diff
+                .elements
+                .iter()
+                .map(|element| {
+                    view! {
+                        <li class=format!("text-xs break-all {}", change_class(element.change))>
+                            {format!(
+                                "{} {} {}: {}",
+                                element.change.marker(),
+                                element.change,
+                                element.kind,
+                                element.label,
+                            )}
+                        </li>
+                    }
+                })
+                .collect_view();
+            view! {
+                <div class="mb-2 text-sm">
+                    {format!(
+                        "{} quads added, {} removed, {} elements changed",
+                        diff.added_quads,
+                        diff.removed_quads,
+                        diff.elements.len(),
+                    )}
+                </div>
+                <ul class="overflow-y-auto mb-2 max-h-64">{rows}</ul>
+            }
+        })
+    };
+
+    view! {
+        <WorkbenchMenuItems title="Compare Ontologies">
+            <label class="block mb-1">"Old version:"</label>
+            {graph_select(old_graph, "Select the old version")}
+            <label class="block mt-2 mb-1">"New version:"</label>
+            {graph_select(new_graph, "Select the new version")}
+            <button
+                class="p-1 my-2 w-full text-sm bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                on:click=move |_| {
+                    if let Some(graphs) = selected_graphs() {
+                        compare.dispatch_local(graphs);
+                    }
+                }
+            >
+                "Compare"
+            </button>
+            {summary}
+            <div class="workbench-menu-header">
+                <h3>"Change report"</h3>
+            </div>
+            <div class="flex flex-wrap justify-center w-full">{report_buttons()}</div>
+            {move || {
+                let msg = progress_message.get();
+                (!msg.is_empty())
+                    .then(|| {
+                        view! {
+                            <div class="mt-2 w-full text-sm text-center text-gray-600">
+                                {msg}
+                            </div>
+                        }
+                    })
+            }}
+        </WorkbenchMenuItems>
+    }
+}
@@ -11,15 +11,42 @@ use vowlgrapher_database::prelude::VOWLGrapherStore;
 #[cfg(feature = "ssr")]
 use vowlgrapher_util::prelude::manage_user_id;
 use vowlgrapher_util::prelude::{DataType, VOWLGrapherError};
-use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url, js_sys, wasm_bindgen::JsCast};
+use web_sys::{
+    Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url, js_sys, wasm_bindgen::JsCast,
+};
 #[server(output = Streaming)]
-/// Export a graph from the database
+/// Export a graph from the database, optionally merged with its imports
 pub async fn export_graph(
     resource_type: DataType,
     graph_name: String,
+    include_imports: bool,
 ) -> Result<ByteStream<VOWLGrapherError>, VOWLGrapherError> {
     let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
-    let stream = store.serialize_stream(resource_type, &graph_name).await?;
+    let stream = if include_imports {
+        store
+            .serialize_stream_with_imports(resource_type, &graph_name)
+            .await?
+    } else {
+        store.serialize_stream(resource_type, &graph_name).await?
+    };
+    Ok(ByteStream::new(stream.map(|chunk| {
+        chunk
+            .map(bytes::Bytes::from)
+            .map_err(std::convert::Into::into)
+    })))
+}
+
+#[server(output = Streaming)]
+/// Export the raw results of a SELECT or ASK query on a graph from the database
+pub async fn export_query_results(
+    query: String,
+    resource_type: DataType,
+    graph_name: String,
+) -> Result<ByteStream<VOWLGrapherError>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    let stream = store
+        .serialize_query_results(&query, &graph_name, resource_type)
+        .await?;
     Ok(ByteStream::new(stream.map(|chunk| {
         chunk
             .map(bytes::Bytes::from)
@@ -31,9 +58,41 @@ pub async fn download_ontology(
     resource_type: DataType,
     progress_message: RwSignal<String>,
     graph_name: String,
+    include_imports: bool,
 ) -> Result<(), VOWLGrapherError> {
-    let byte_stream = export_graph(resource_type, graph_name).await?;
+    let byte_stream = export_graph(resource_type, graph_name, include_imports).await?;
+    save_download(
+        byte_stream,
+        progress_message,
+        &format!("ontology.{}", resource_type.extension()),
+        resource_type.mime_type(),
+    )
+    .await
+}
 
+pub async fn download_query_results(
+    query: String,
+    resource_type: DataType,
+    progress_message: RwSignal<String>,
+    graph_name: String,
+) -> Result<(), VOWLGrapherError> {
+    let byte_stream = export_query_results(query, resource_type, graph_name).await?;
+    save_download(
+        byte_stream,
+        progress_message,
+        &format!("query-results.{}", resource_type.extension()),
+        resource_type.mime_type(),
+    )
+    .await
+}
+
+/// Downloads a stream from the server and lets the browser save it as `file_name`.
+pub async fn save_download(
+    byte_stream: ByteStream<VOWLGrapherError>,
+    progress_message: RwSignal<String>,
+    file_name: &str,
+    mime_type: &str,
+) -> Result<(), VOWLGrapherError> {
     // Download data from server
     progress_message.set("Downloaded: 0 MB".to_string());
     let mut stream = byte_stream.into_inner();
@@ -64,7 +123,7 @@ pub async fn download_ontology(
     blob_parts.push(&uint8_array.into());
 
     let blob_options = BlobPropertyBag::new();
-    blob_options.set_type(resource_type.mime_type());
+    blob_options.set_type(mime_type);
 
     let blob = Blob::new_with_str_sequence_and_options(&blob_parts, &blob_options)
         .map_err(|e| ClientErrorKind::JavaScriptError(format!("{e:#?}")))?;
@@ -78,7 +137,7 @@ pub async fn download_ontology(
         // SAFETY: Creating HTML element "a" will always have type 'HtmlAnchorElement'.
         .unchecked_into::<HtmlAnchorElement>();
     a.set_href(&url);
-    a.set_download(format!("ontology.{}", resource_type.extension()).as_str());
+    a.set_download(file_name);
     a.set_attribute("style", "display: none")
         .map_err(|e| ClientErrorKind::JavaScriptError(format!("{e:#?}")))?;
 
@@ -116,14 +175,17 @@ pub fn ExportButton(
 #[component]
 pub fn ExportMenu() -> impl IntoView {
     let progress_message = RwSignal::new(String::new());
+    let include_imports = RwSignal::new(true);
 
-    let download = Action::new(|input: &(DataType, RwSignal<String>)| {
-        let (resource_type, progress_message) = input.to_owned();
+    let download = Action::new(|input: &(DataType, RwSignal<String>, bool)| {
+        let (resource_type, progress_message, include_imports) = input.to_owned();
         async move {
             let graph_name = expect_context::<GraphDataContext>()
                 .active_graph_name
                 .get_untracked();
-            match download_ontology(resource_type, progress_message, graph_name).await {
+            match download_ontology(resource_type, progress_message, graph_name, include_imports)
+                .await
+            {
                 Ok(()) => (),
                 Err(e) => {
                     let error_context = expect_context::<ErrorLogContext>();
@@ -133,6 +195,46 @@ pub fn ExportMenu() -> impl IntoView {
         }
     });
 
+    let results_query = RwSignal::new("SELECT * WHERE {\n  ?s ?p ?o\n}\nLIMIT 100".to_string());
+    let download_results = Action::new(|input: &(String, DataType, RwSignal<String>)| {
+        let (query, resource_type, progress_message) = input.to_owned();
+        async move {
+            let graph_name = expect_context::<GraphDataContext>()
+                .active_graph_name
+                .get_untracked();
+            if let Err(e) =
+                download_query_results(query, resource_type, progress_message, graph_name).await
+            {
+                let error_context = expect_context::<ErrorLogContext>();
+                error_context.extend(e.records);
+            }
+        }
+    });
+
+    let results_buttons = move || {
+        [
+            DataType::SPARQLJSON,
+            DataType::SPARQLXML,
+            DataType::SPARQLCSV,
+            DataType::SPARQLTSV,
+        ]
+        .into_iter()
+        .map(|dtype| {
+            view! {
+                <ExportButton
+                    label=dtype.to_string()
+                    icon=icondata::BiExportRegular
+                    on_click=move || {
+                        download_results
+                            .dispatch((results_query.get_untracked(), dtype, progress_message));
+                    }
+                />
+            }
+            .into_any()
+        })
+        .collect_view()
+    };
+
     let export_buttons = move || {
         DataType::iter()
             .filter(|dtype| {
@@ -151,7 +253,8 @@ pub fn ExportMenu() -> impl IntoView {
                         label=dtype.to_string()
                         icon=icondata::BiExportRegular
                         on_click=move || {
-                            download.dispatch((dtype, progress_message));
+                            download
+                                .dispatch((dtype, progress_message, include_imports.get_untracked()));
                         }
                     />
                 }
@@ -167,6 +270,29 @@ pub fn ExportMenu() -> impl IntoView {
                 // <ExportButton label="TeX" icon=icondata::BiExportRegular />
                 {export_buttons()}
             </div>
+            <label class="flex gap-2 justify-center items-center w-full text-sm">
+                <input
+                    type="checkbox"
+                    prop:checked=include_imports
+                    on:change=move |ev| {
+                        let target: HtmlInputElement = event_target(&ev);
+                        include_imports.set(target.checked());
+                    }
+                />
+                "Include imports"
+            </label>
+            <div class="workbench-menu-header">
+                <h3>"Query results"</h3>
+            </div>
+            <textarea
+                class="p-1 w-full font-mono text-xs bg-gray-200 rounded border-b-0 min-h-24"
+                prop:value=results_query
+                on:input=move |ev| {
+                    let target: HtmlInputElement = event_target(&ev);
+                    results_query.set(target.value());
+                }
+            />
+            <div class="flex flex-wrap justify-center w-full">{results_buttons()}</div>
             {move || {
                 let msg = progress_message.get();
                 (!msg.is_empty())
@@ -17,6 +17,8 @@ use grapher::prelude::ElementType;
 use grapher::prelude::SimulatorEvent;
 use leptos::prelude::*;
 
+pub use element_legend_injection::ElementLegend;
+
 use classes::{is_owl_class, is_rdf_class};
 use filtergroup::FilterGroup;
 use meta_filter::filter;
@@ -0,0 +1,185 @@
+use super::WorkbenchMenuItems;
+use super::export_menu::{ExportButton, save_download};
+use crate::components::user_input::internal_sparql::GraphDataContext;
+use crate::errors::ErrorLogContext;
+use leptos::prelude::*;
+use leptos::server_fn::codec::{ByteStream, Rkyv, Streaming};
+use strum::IntoEnumIterator;
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{MetricsFormat, OntologyMetrics, VOWLGrapherError};
+
+/// Compute statistics about a graph of the current user.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn graph_metrics(graph_name: String) -> Result<OntologyMetrics, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.metrics(&graph_name).await?)
+}
+
+#[server(output = Streaming)]
+/// Export statistics about a graph of the current user
+pub async fn export_metrics(
+    graph_name: String,
+    format: MetricsFormat,
+) -> Result<ByteStream<VOWLGrapherError>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    let metrics = store.serialize_metrics(&graph_name, format).await?;
+    Ok(ByteStream::new(futures::stream::once(async move {
+        Ok(bytes::Bytes::from(metrics))
+    })))
+}
+
+/// Formats a share between 0 and 1 as a percentage.
+fn percent(share: f64) -> String {
+    format!("{:.1} %", share * 100.0)
+}
+
+/// Returns the rows of the statistics table.
+fn metric_rows(metrics: &OntologyMetrics) -> Vec<(String, String)> {
+    let mut rows = vec![
+        ("Classes".to_string(), metrics.class_count.to_string()),
+        (
+            "Object properties".to_string(),
+            metrics.object_property_count.to_string(),
+        ),
+        (
+            "Datatype properties".to_string(),
+            metrics.datatype_property_count.to_string(),
+        ),
+        (
+            "Annotation properties".to_string(),
+            metrics.annotation_property_count.to_string(),
+        ),
+        (
+            "Individuals".to_string(),
+            metrics.individual_count.to_string(),
+        ),
+        (
+            "Hierarchy depth".to_string(),
+            metrics.hierarchy_depth.to_string(),
+        ),
+        (
+            "Hierarchy breadth".to_string(),
+            metrics.hierarchy_breadth.to_string(),
+        ),
+        (
+            "Most subclasses".to_string(),
+            metrics.max_subclasses.to_string(),
+        ),
+        ("Labelled".to_string(), percent(metrics.labelled_share)),
+        ("Commented".to_string(), percent(metrics.commented_share)),
+        ("Deprecated".to_string(), percent(metrics.deprecated_share)),
+        ("Imports".to_string(), metrics.import_count.to_string()),
+    ];
+    rows.extend(
+        metrics
+            .axiom_counts
+            .iter()
+            .map(|axiom| (axiom.axiom.clone(), axiom.count.to_string())),
+    );
+    rows
+}
+
+/// Shows statistics about the active graph.
+#[component]
+pub fn MetricsMenu() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+    let progress_message = RwSignal::new(String::new());
+
+    let metrics = LocalResource::new(move || {
+        let graph_name = active_graph_name.get();
+        async move {
+            if graph_name.is_empty() {
+                return None;
+            }
+            graph_metrics(graph_name)
+                .await
+                .map_err(|e| error_context.extend(e.records))
+                .ok()
+        }
+    });
+
+    let download = Action::new_local(move |format: &MetricsFormat| {
+        let format = *format;
+        let graph_name = active_graph_name.get_untracked();
+        async move {
+            let result = match export_metrics(graph_name, format).await {
+                Ok(byte_stream) => {
+                    save_download(
+                        byte_stream,
+                        progress_message,
+                        &format!("metrics.{}", format.extension()),
+                        format.mime_type(),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error_context.extend(e.records);
+            }
+        }
+    });
+
+    let table = move || {
+        metrics.get().flatten().map(|metrics| {
+            let rows = metric_rows(&metrics)
+                .into_iter()
+                .map(|(name, value)| {
+                    view! {
+                        <tr class="border-b border-neutral-200">
+                            <td class="py-1 pr-4">{name}</td>
+                            <td class="py-1 text-right">{value}</td>
+                        </tr>
+                    }
+                })
+                .collect_view();
+            view! {
+                <table class="mb-2 w-full text-sm table-auto">
+                    <tbody>{rows}</tbody>
+                </table>
+            }
+        })
+    };
+
+    let export_buttons = move || {
+        MetricsFormat::iter()
+            .map(|format| {
+                view! {
+                    <ExportButton
+                        label=format.to_string()
+                        icon=icondata::BiExportRegular
+                        on_click=move || {
+                            download.dispatch_local(format);
+                        }
+                    />
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <WorkbenchMenuItems title="Statistics">
+            <Suspense fallback=|| {
+                view! { <div class="text-sm text-center text-gray-600">"Computing..."</div> }
+            }>{table}</Suspense>
+            <div class="flex flex-wrap justify-center w-full">{export_buttons()}</div>
+            {move || {
+                let msg = progress_message.get();
+                (!msg.is_empty())
+                    .then(|| {
+                        view! {
+                            <div class="mt-2 w-full text-sm text-center text-gray-600">
+                                {msg}
+                            </div>
+                        }
+                    })
+            }}
+        </WorkbenchMenuItems>
+    }
+}
@@ -1,21 +1,28 @@
 use super::WorkbenchMenuItems;
 use crate::components::progress_bar::LoadingCircle;
+use crate::components::table::Table;
 use crate::components::user_input::internal_sparql::GraphDataContext;
+use crate::components::user_input::internal_sparql::handle_sparql_update;
 use crate::components::user_input::internal_sparql::load_graph;
 use crate::components::user_input::stored_ontology::StoredOntology;
 use crate::components::user_input::stored_ontology::load_stored_ontology;
+use crate::components::user_input::workspace::{
+    delete_graph, duplicate_graph, list_graphs, list_imports, rename_graph,
+};
 use crate::components::{icon::Icon, user_input::file_upload::FileUpload};
 use crate::errors::ClientErrorKind;
 use crate::errors::ErrorLogContext;
 use leptos::prelude::*;
 use leptos::task::spawn_local_scoped_with_cancellation;
 use log::info;
+use std::collections::VecDeque;
 use std::iter::once;
 use strum::IntoEnumIterator;
 use vowlgrapher_sparql_queries::prelude::DEFAULT_QUERY;
-use vowlgrapher_util::prelude::VOWLGrapherEnviron;
-use web_sys::Event;
-use web_sys::HtmlInputElement;
+use vowlgrapher_util::prelude::{
+    HarvestReport, QuerySolutionRow, VOWLGrapherEnviron, format_unix_timestamp,
+};
+use web_sys::{DragEvent, Event, FileList, HtmlInputElement};
 
 #[component]
 pub fn SelectStaticInput() -> impl IntoView {
@@ -160,41 +167,64 @@ pub fn UploadInput() -> impl IntoView {
         }
     });
 
-    let upload_files = move |ev: Event| {
+    // Returns whether the files were accepted for upload.
+    let upload_file_list = move |files: &FileList| -> bool {
         let VOWLGrapherEnviron {
             max_input_size_bytes,
             ..
         } = expect_context::<VOWLGrapherEnviron>();
 
-        let input: HtmlInputElement = event_target(&ev);
-        if let Some(files) = input.files() {
-            #[expect(
-                clippy::cast_possible_truncation,
-                clippy::cast_sign_loss,
-                reason = "decimals don't matter in comparison"
-            )]
-            if let Some(file) = files.item(0)
-                && file.size() as u64 > max_input_size_bytes.0
-            {
-                let err_msg = format!(
-                    "File '{}' exceeds the maximum allowed size of {}",
-                    file.name(),
-                    max_input_size_bytes.display().si()
-                );
-                error_context.push(ClientErrorKind::FileUploadError(err_msg).into());
-                input.set_value("");
-                return;
+        let total_size = (0..files.length())
+            .filter_map(|i| files.item(i))
+            .map(|file| file.size())
+            .sum::<f64>();
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "decimals don't matter in comparison"
+        )]
+        if total_size as u64 > max_input_size_bytes.0 {
+            let err_msg = format!(
+                "The selected files exceed the maximum allowed size of {}",
+                max_input_size_bytes.display().si()
+            );
+            error_context.push(ClientErrorKind::FileUploadError(err_msg).into());
+            return false;
+        }
+
+        if let Err(e) = tracker_file.upload_files(files, move |form| {
+            info!("Uploading files");
+            upload.local_action.dispatch_local(form);
+            upload.mode.set("local".to_string());
+        }) {
+            error_context.extend(e.records);
+        }
+        true
+    };
+
+    let upload_files = {
+        let upload_file_list = upload_file_list.clone();
+        move |ev: Event| {
+            let input: HtmlInputElement = event_target(&ev);
+            if let Some(files) = input.files() {
+                if !upload_file_list(&files) {
+                    input.set_value("");
+                }
+            } else {
+                info!("Found no files to upload");
             }
+        }
+    };
 
-            if let Err(e) = tracker_file.upload_files(&files, move |form| {
-                info!("Uploading files");
-                upload.local_action.dispatch_local(form);
-                upload.mode.set("local".to_string());
-            }) {
-                error_context.extend(e.records);
+    let dragging = RwSignal::new(false);
+    let drop_files = move |ev: DragEvent| {
+        ev.prevent_default();
+        dragging.set(false);
+        match ev.data_transfer().and_then(|transfer| transfer.files()) {
+            Some(files) if files.length() > 0 => {
+                upload_file_list(&files);
             }
-        } else {
-            info!("Found no files to upload");
+            _ => info!("Found no files to upload"),
         }
     };
 
@@ -221,13 +251,23 @@ pub fn UploadInput() -> impl IntoView {
 
         <div class="mb-2">
             <label class="block mb-1">"From File:"</label>
-            <div class="relative">
+            <div
+                class="relative rounded"
+                class=("ring-2", move || dragging.get())
+                class=("ring-blue-500", move || dragging.get())
+                on:dragover=move |ev: DragEvent| {
+                    ev.prevent_default();
+                    dragging.set(true);
+                }
+                on:dragleave=move |_| dragging.set(false)
+                on:drop=drop_files
+            >
                 <input
                     id="file-upload"
                     type="file"
                     class="absolute inset-0 w-full h-full opacity-0 cursor-pointer"
                     multiple=""
-                    accept=".owl,.ofn,.owx,.xml,.json,.ttl,.rdf,.nt,.nq,.trig,.jsonld,.n3,.srj,.srx,.json,.xml,.csv,.tsv"
+                    accept=".owl,.ofn,.owx,.xml,.json,.ttl,.rdf,.nt,.nq,.trig,.jsonld,.n3,.srj,.srx,.json,.xml,.csv,.tsv,.gz,.bz2,.xz,.zst,.zip"
                     on:input=upload_files
                 />
                 <label
@@ -236,7 +276,7 @@ pub fn UploadInput() -> impl IntoView {
                 >
                     {move || {
                         if file_name.get().is_empty() {
-                            "Select ontology file".to_string()
+                            "Select or drop ontology files".to_string()
                         } else {
                             file_name.get()
                         }
@@ -314,16 +354,74 @@ pub fn FetchData() -> impl IntoView {
     }
 }
 
+/// The number of solutions harvested per page by default.
+const DEFAULT_HARVEST_PAGE_SIZE: u32 = 10_000;
+
+/// Queries a SPARQL endpoint, or harvests it page by page.
+///
+/// Results which form a graph are loaded and shown, other solutions are listed in a table.
 #[component]
 pub fn Sparql() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
     let upload = FileUpload::new();
+    let sparql_loading_done = upload.sparql_action.value();
+    let harvest_done = upload.harvest_action.value();
     let upload_progress = upload.tracker.upload_progress;
     let parsing_status = upload.tracker.parsing_status;
     let parsing_done = upload.tracker.parsing_done;
     let tracker_sparql = upload.tracker.clone();
+    let url_name = upload.tracker.url_name;
 
     let endpoint_signal = RwSignal::new(String::new());
     let query_signal = RwSignal::new(String::new());
+    let solutions = RwSignal::new(None::<VecDeque<QuerySolutionRow>>);
+    let harvest = RwSignal::new(false);
+    let page_size = RwSignal::new(DEFAULT_HARVEST_PAGE_SIZE);
+    let harvest_report = RwSignal::new(None::<HarvestReport>);
+
+    Effect::new(move || {
+        if let Some(value) = sparql_loading_done.get() {
+            match value {
+                Ok((_, _, table, warning)) => {
+                    if let Some(e) = warning {
+                        error_context.extend(e.records);
+                    }
+                    if table.is_none() {
+                        active_graph_name.set(url_name.get_untracked());
+                        spawn_local_scoped_with_cancellation(async move {
+                            load_graph(DEFAULT_QUERY.to_string(), true).await;
+                        });
+                    }
+                    solutions.set(table.map(VecDeque::from));
+                    harvest_report.set(None);
+                }
+                Err(e) => {
+                    error_context.extend(e.records);
+                }
+            }
+        }
+    });
+
+    Effect::new(move || {
+        if let Some(value) = harvest_done.get() {
+            match value {
+                Ok(report) => {
+                    active_graph_name.set(url_name.get_untracked());
+                    spawn_local_scoped_with_cancellation(async move {
+                        load_graph(DEFAULT_QUERY.to_string(), true).await;
+                    });
+                    solutions.set(None);
+                    harvest_report.set(Some(report));
+                }
+                Err(e) => {
+                    error_context.extend(e.records);
+                }
+            }
+        }
+    });
 
     let textarea_ref = NodeRef::<leptos::html::Textarea>::new();
 
@@ -338,16 +436,28 @@ pub fn Sparql() -> impl IntoView {
         }
     };
 
-    let run_sparql = move || {
+    // Resuming continues the last failed harvest from the endpoint.
+    let run_sparql = move |resume: bool| {
         tracker_sparql.upload_sparql(
             &endpoint_signal.get(),
             &query_signal.get(),
             move |(ep, q, fmt)| {
-                upload.sparql_action.dispatch((ep, q, fmt));
-                upload.mode.set("sparql".to_string());
+                if harvest.get_untracked() {
+                    upload.harvest_action.dispatch((
+                        ep,
+                        Some(q),
+                        page_size.get_untracked(),
+                        resume,
+                    ));
+                    upload.mode.set("harvest".to_string());
+                } else {
+                    upload.sparql_action.dispatch((ep, q, fmt));
+                    upload.mode.set("sparql".to_string());
+                }
             },
         );
     };
+    let resume_sparql = run_sparql.clone();
 
     view! {
         <fieldset>
@@ -373,7 +483,13 @@ pub fn Sparql() -> impl IntoView {
                         node_ref=textarea_ref
                         class="overflow-hidden p-1 w-full text-xs bg-gray-200 rounded border-b-0 resize-none min-h-24"
                         rows=1
-                        placeholder="Enter query"
+                        placeholder=move || {
+                            if harvest.get() {
+                                "Enter a CONSTRUCT query, or leave empty to harvest the schema"
+                            } else {
+                                "Enter query"
+                            }
+                        }
                         on:input=move |ev| {
                             let t: HtmlInputElement = event_target(&ev);
                             query_signal.set(t.value());
@@ -384,11 +500,77 @@ pub fn Sparql() -> impl IntoView {
 
                 <button
                     class="p-1 mt-1 text-xs text-white bg-blue-500 rounded"
-                    on:click=move |_| run_sparql()
+                    on:click=move |_| run_sparql(false)
                 >
-                    "Run query"
+                    {move || if harvest.get() { "Harvest" } else { "Run query" }}
                 </button>
 
+                <label class="flex gap-1 items-center text-xs">
+                    <input
+                        type="checkbox"
+                        prop:checked=harvest
+                        on:change=move |ev| harvest.set(event_target_checked(&ev))
+                    />
+                    "Harvest in pages"
+                </label>
+                <div class="flex flex-col gap-1" class:hidden=move || !harvest.get()>
+                    <label class="block text-xs text-gray">"Solutions per page"</label>
+                    <input
+                        type="number"
+                        min="1"
+                        class="p-1 w-full text-xs bg-gray-200 rounded border-b-0"
+                        prop:value=move || page_size.get().to_string()
+                        on:input=move |ev| {
+                            if let Ok(size) = event_target_value(&ev).parse::<u32>() {
+                                page_size.set(size.max(1));
+                            }
+                        }
+                    />
+                    <button
+                        class="p-1 text-xs bg-gray-200 rounded text-[#000000]"
+                        on:click=move |_| resume_sparql(true)
+                    >
+                        "Resume harvest"
+                    </button>
+                </div>
+
+                {move || {
+                    harvest_report
+                        .get()
+                        .map(|report| {
+                            view! {
+                                <div class="text-xs text-gray-600">
+                                    {format!(
+                                        "Harvested {} quads in {} pages from {}",
+                                        report.quad_count,
+                                        report.page_count,
+                                        report.endpoint,
+                                    )}
+                                </div>
+                            }
+                        })
+                }}
+
+                {move || {
+                    solutions
+                        .get()
+                        .map(|rows| {
+                            if rows.is_empty() {
+                                view! { <div class="text-sm text-gray-600">"No solutions"</div> }
+                                    .into_any()
+                            } else {
+                                view! {
+                                    <div class="overflow-auto max-h-96">
+                                        <Table items=Signal::derive(move || {
+                                            solutions.get().unwrap_or_default()
+                                        }) />
+                                    </div>
+                                }
+                                    .into_any()
+                            }
+                        })
+                }}
+
                 {move || {
                     let progress = upload_progress.get();
                     let parsing = parsing_status.get();
@@ -438,6 +620,306 @@ pub fn Sparql() -> impl IntoView {
     }
 }
 
+#[component]
+pub fn SparqlUpdate() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+    let update_signal = RwSignal::new(String::new());
+
+    let run_update = Action::new(move |update: &String| {
+        let update = update.clone();
+        async move {
+            let graph_name = active_graph_name.get_untracked();
+            match handle_sparql_update(update, graph_name).await {
+                Ok(()) => {
+                    load_graph(DEFAULT_QUERY.to_string(), true).await;
+                }
+                Err(e) => {
+                    error_context.extend(e.records);
+                }
+            }
+        }
+    });
+
+    view! {
+        <fieldset>
+            <legend>"Edit Ontology:"</legend>
+            <div class="flex flex-col gap-2">
+                <textarea
+                    class="p-1 w-full font-mono text-xs bg-gray-200 rounded border-b-0 resize-none min-h-24"
+                    placeholder="Enter SPARQL update, e.g. INSERT DATA { ... }"
+                    prop:value=update_signal
+                    on:input=move |ev| {
+                        let t: HtmlInputElement = event_target(&ev);
+                        update_signal.set(t.value());
+                    }
+                />
+                <button
+                    class="p-1 mt-1 text-xs text-white bg-blue-500 rounded"
+                    on:click=move |_| {
+                        run_update.dispatch(update_signal.get_untracked());
+                    }
+                >
+                    {move || {
+                        if run_update.pending().get() { "Updating..." } else { "Run update" }
+                    }}
+                </button>
+            </div>
+        </fieldset>
+    }
+}
+
+/// An operation on the active graph of the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphOperation {
+    Rename,
+    Duplicate,
+    Delete,
+}
+
+#[component]
+pub fn LoadedGraphs() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+
+    // Bumped after every operation so the list of graphs is fetched again.
+    let refresh = RwSignal::new(0_usize);
+    let new_name = RwSignal::new(String::new());
+
+    let graphs = LocalResource::new(move || {
+        active_graph_name.track();
+        refresh.track();
+        async move {
+            list_graphs().await.unwrap_or_else(|e| {
+                error_context.extend(e.records);
+                Vec::new()
+            })
+        }
+    });
+
+    let switch_graph = move |name: String| {
+        active_graph_name.set(name);
+        spawn_local_scoped_with_cancellation(async move {
+            load_graph(DEFAULT_QUERY.to_string(), true).await;
+        });
+    };
+
+    let run_operation = Action::new_local(move |operation: &GraphOperation| {
+        let operation = *operation;
+        let active = active_graph_name.get_untracked();
+        let target = new_name.get_untracked().trim().to_string();
+        async move {
+            if matches!(
+                operation,
+                GraphOperation::Rename | GraphOperation::Duplicate
+            ) && target.is_empty()
+            {
+                error_context.push(
+                    ClientErrorKind::InvalidInput("Enter a name for the graph".to_string()).into(),
+                );
+                return;
+            }
+
+            let result = match operation {
+                GraphOperation::Rename => rename_graph(active, target.clone()).await,
+                GraphOperation::Duplicate => duplicate_graph(active, target.clone()).await,
+                GraphOperation::Delete => delete_graph(active).await,
+            };
+            if let Err(e) = result {
+                error_context.extend(e.records);
+                return;
+            }
+
+            match operation {
+                GraphOperation::Rename => {
+                    active_graph_name.set(target);
+                }
+                GraphOperation::Duplicate => {
+                    switch_graph(target);
+                }
+                GraphOperation::Delete => match list_graphs().await {
+                    Ok(remaining) => {
+                        if let Some(graph) = remaining.into_iter().next() {
+                            switch_graph(graph.name);
+                        } else {
+                            active_graph_name.set(String::new());
+                        }
+                    }
+                    Err(e) => error_context.extend(e.records),
+                },
+            }
+            new_name.set(String::new());
+            refresh.update(|n| *n += 1);
+        }
+    });
+
+    let graph_options = move || {
+        graphs.get().map(|graphs| {
+            graphs
+                .iter()
+                .map(|graph| {
+                    let loaded_at = graph
+                        .loaded_at
+                        .map(|secs| format!(", loaded {}", format_unix_timestamp(secs)))
+                        .unwrap_or_default();
+                    view! {
+                        <option value=graph.name.clone()>
+                            {format!("{} ({} quads{loaded_at})", graph.name, graph.quad_count)}
+                        </option>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <div class="mb-2">
+            <label class="block mb-1">"Loaded Graphs:"</label>
+            <select
+                class="p-1 w-full text-sm bg-gray-200 rounded border-b-0"
+                prop:value=move || active_graph_name.get()
+                on:change=move |ev| {
+                    let target: HtmlInputElement = event_target(&ev);
+                    let name = target.value();
+                    if !name.is_empty() {
+                        switch_graph(name);
+                    }
+                }
+            >
+                {graph_options}
+            </select>
+            <input
+                class="p-1 mt-1 w-full text-xs bg-gray-200 rounded border-b-0"
+                placeholder="New graph name"
+                prop:value=new_name
+                on:input=move |ev| {
+                    let target: HtmlInputElement = event_target(&ev);
+                    new_name.set(target.value());
+                }
+            />
+            <div class="flex gap-1 mt-1">
+                <button
+                    class="flex-1 p-1 text-xs bg-gray-200 rounded text-[#000000]"
+                    on:click=move |_| {
+                        run_operation.dispatch_local(GraphOperation::Rename);
+                    }
+                >
+                    "rename"
+                </button>
+                <button
+                    class="flex-1 p-1 text-xs bg-gray-200 rounded text-[#000000]"
+                    on:click=move |_| {
+                        run_operation.dispatch_local(GraphOperation::Duplicate);
+                    }
+                >
+                    "duplicate"
+                </button>
+                <button
+                    class="flex-1 p-1 text-xs bg-gray-200 rounded text-[#000000]"
+                    on:click=move |_| {
+                        run_operation.dispatch_local(GraphOperation::Delete);
+                    }
+                >
+                    "delete"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// Lists the ontologies imported by the active graph and lets the user hide them.
+///
+/// Elements of imported ontologies are drawn as external elements.
+#[component]
+pub fn ImportedOntologies() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name,
+        hidden_imports,
+        ..
+    } = expect_context::<GraphDataContext>();
+
+    let imports = LocalResource::new(move || {
+        let graph_name = active_graph_name.get();
+        async move {
+            if graph_name.is_empty() {
+                return Vec::new();
+            }
+            list_imports(graph_name).await.unwrap_or_else(|e| {
+                error_context.extend(e.records);
+                Vec::new()
+            })
+        }
+    });
+
+    let toggle_import = move |iri: String, visible: bool| {
+        let graph_name = active_graph_name.get_untracked();
+        hidden_imports.update(|hidden| {
+            let hidden = hidden.entry(graph_name).or_default();
+            if visible {
+                hidden.remove(&iri);
+            } else {
+                hidden.insert(iri);
+            }
+        });
+        spawn_local_scoped_with_cancellation(async move {
+            load_graph(DEFAULT_QUERY.to_string(), true).await;
+        });
+    };
+
+    let import_rows = move || {
+        imports.get().map(|imports| {
+            imports
+                .iter()
+                .map(|import| {
+                    let iri = import.iri.clone();
+                    let is_visible = {
+                        let iri = iri.clone();
+                        move || {
+                            hidden_imports.with(|hidden| {
+                                hidden
+                                    .get(&active_graph_name.get())
+                                    .is_none_or(|iris| !iris.contains(&iri))
+                            })
+                        }
+                    };
+                    let imported_by = if import.imported_by.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", via {}", import.imported_by.join(", "))
+                    };
+                    view! {
+                        <label class="flex gap-1 items-start text-xs break-all">
+                            <input
+                                type="checkbox"
+                                prop:checked=is_visible
+                                on:change=move |ev| {
+                                    let target: HtmlInputElement = event_target(&ev);
+                                    toggle_import(iri.clone(), target.checked());
+                                }
+                            />
+                            {format!("{} ({} quads{imported_by})", import.iri, import.quad_count)}
+                        </label>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <Show when=move || imports.get().is_some_and(|imports| !imports.is_empty())>
+            <div class="mb-2">
+                <label class="block mb-1">"Imported Ontologies:"</label>
+                <div class="flex flex-col gap-1">{import_rows}</div>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 pub fn OntologyMenu() -> impl IntoView {
     view! {
@@ -445,6 +927,9 @@ pub fn OntologyMenu() -> impl IntoView {
             <SelectStaticInput />
             <UploadInput />
             <Sparql />
+            <LoadedGraphs />
+            <ImportedOntologies />
+            <SparqlUpdate />
             <FetchData />
         </WorkbenchMenuItems>
     }
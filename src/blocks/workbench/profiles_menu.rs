@@ -0,0 +1,146 @@
+use super::WorkbenchMenuItems;
+use crate::components::user_input::internal_sparql::{GraphDataContext, draw_graph};
+use crate::errors::{ClientErrorKind, ErrorLogContext};
+use grapher::prelude::GraphDisplayData;
+use leptos::prelude::*;
+use leptos::server_fn::codec::Rkyv;
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{GraphAnnotations, OwlProfile, ProfileReport, VOWLGrapherError};
+
+/// Check a graph of the current user against the profiles of OWL 2.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn check_profiles(graph_name: String) -> Result<Vec<ProfileReport>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.check_profiles(&graph_name).await?)
+}
+
+/// Serialize a graph of the current user with the entities violating a profile of OWL 2 annotated.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn highlight_profile_violations(
+    graph_name: String,
+    profile: OwlProfile,
+) -> Result<
+    (
+        GraphDisplayData,
+        GraphAnnotations,
+        ProfileReport,
+        Option<VOWLGrapherError>,
+    ),
+    VOWLGrapherError,
+> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    store.profile_display_data(&graph_name, profile).await
+}
+
+/// Shows which profiles of OWL 2 the active graph falls into.
+///
+/// The axioms violating a profile are added to the Error Log.
+#[component]
+pub fn ProfilesMenu() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+    let reports = RwSignal::new(Vec::<ProfileReport>::new());
+
+    let check = Action::new_local(move |graph_name: &String| {
+        let graph_name = graph_name.clone();
+        async move {
+            match check_profiles(graph_name).await {
+                Ok(result) => {
+                    for report in &result {
+                        error_context.extend(report.violations.clone());
+                    }
+                    reports.set(result);
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let highlight = Action::new_local(move |(graph_name, profile): &(String, OwlProfile)| {
+        let (graph_name, profile) = (graph_name.clone(), *profile);
+        async move {
+            match highlight_profile_violations(graph_name, profile).await {
+                Ok((display_data, violations, _, non_fatal_error)) => {
+                    draw_graph(display_data, violations);
+                    if let Some(e) = non_fatal_error {
+                        error_context.extend(e.records);
+                    }
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let rows = move || {
+        reports
+            .get()
+            .into_iter()
+            .map(|report| {
+                let profile = report.profile;
+                let (status, status_class) = if report.conforms() {
+                    ("✓".to_string(), "text-green-700")
+                } else {
+                    (
+                        format!("✗ {} violations", report.violations.len()),
+                        "text-red-700",
+                    )
+                };
+                view! {
+                    <tr class="border-b border-neutral-200">
+                        <td class="py-1 pr-4">{profile.to_string()}</td>
+                        <td class=format!("py-1 pr-4 {status_class}")>{status}</td>
+                        <td class="py-1 text-right">
+                            {(!report.conforms())
+                                .then(|| {
+                                    view! {
+                                        <button
+                                            class="px-1 text-xs bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                                            on:click=move |_| {
+                                                highlight
+                                                    .dispatch_local((
+                                                        active_graph_name.get_untracked(),
+                                                        profile,
+                                                    ));
+                                            }
+                                        >
+                                            "Highlight"
+                                        </button>
+                                    }
+                                })}
+                        </td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <WorkbenchMenuItems title="OWL 2 Profiles">
+            <button
+                class="p-1 mb-2 w-full text-sm bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                on:click=move |_| {
+                    let graph_name = active_graph_name.get_untracked();
+                    if graph_name.is_empty() {
+                        error_context
+                            .push(
+                                ClientErrorKind::InvalidInput("Load an ontology to check".to_string())
+                                    .into(),
+                            );
+                    } else {
+                        check.dispatch_local(graph_name);
+                    }
+                }
+            >
+                {move || if check.pending().get() { "Checking..." } else { "Check profiles" }}
+            </button>
+            <table class="w-full text-sm table-auto">
+                <tbody>{rows}</tbody>
+            </table>
+        </WorkbenchMenuItems>
+    }
+}
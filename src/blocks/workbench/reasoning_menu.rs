@@ -0,0 +1,211 @@
+use super::WorkbenchMenuItems;
+use crate::components::user_input::internal_sparql::{GraphDataContext, draw_graph, load_graph};
+use crate::errors::{ClientErrorKind, ErrorLogContext};
+use grapher::prelude::GraphDisplayData;
+use leptos::prelude::*;
+use leptos::server_fn::codec::Rkyv;
+use leptos::task::spawn_local_scoped_with_cancellation;
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+use vowlgrapher_sparql_queries::prelude::DEFAULT_QUERY;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{
+    ClassificationReport, GraphAnnotations, InferenceReport, VOWLGrapherError,
+};
+use web_sys::HtmlInputElement;
+
+/// Infer the statements entailed by a graph of the current user and its imports.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn materialize_inferences(
+    graph_name: String,
+) -> Result<InferenceReport, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.materialize_inferences(&graph_name).await?)
+}
+
+/// Classify a graph of the current user and its imports with the OWL 2 EL classifier and serialize the inferred taxonomy.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn classify_taxonomy(
+    graph_name: String,
+) -> Result<
+    (
+        GraphDisplayData,
+        GraphAnnotations,
+        ClassificationReport,
+        Option<VOWLGrapherError>,
+    ),
+    VOWLGrapherError,
+> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    store.taxonomy_display_data(&graph_name).await
+}
+
+/// Infers the statements entailed by the active graph with the rules of RDFS and OWL 2 RL.
+///
+/// Inconsistencies are added to the Error Log.
+/// Edges which only follow from inferred statements are annotated as inferred.
+///
+/// The active graph can also be classified with the OWL 2 EL classifier, which replaces the
+/// graph with its inferred taxonomy. Unsatisfiable classes are added to the Error Log and
+/// annotated as unsatisfiable.
+#[component]
+pub fn ReasoningMenu() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name,
+        show_inferred,
+        ..
+    } = expect_context::<GraphDataContext>();
+    let report = RwSignal::new(None::<InferenceReport>);
+    let classification = RwSignal::new(None::<ClassificationReport>);
+
+    let selected_graph = move || {
+        let graph_name = active_graph_name.get_untracked();
+        if graph_name.is_empty() {
+            error_context.push(
+                ClientErrorKind::InvalidInput("Load an ontology to reason over".to_string()).into(),
+            );
+            return None;
+        }
+        Some(graph_name)
+    };
+
+    let infer = Action::new_local(move |graph_name: &String| {
+        let graph_name = graph_name.clone();
+        async move {
+            match materialize_inferences(graph_name).await {
+                Ok(result) => {
+                    error_context.extend(result.inconsistencies.clone());
+                    report.set(Some(result));
+                    if show_inferred.get_untracked() {
+                        load_graph(DEFAULT_QUERY.to_string(), true).await;
+                    }
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let classify = Action::new_local(move |graph_name: &String| {
+        let graph_name = graph_name.clone();
+        async move {
+            match classify_taxonomy(graph_name).await {
+                Ok((display_data, unsatisfiable, result, non_fatal_error)) => {
+                    error_context.extend(result.unsatisfiable.clone());
+                    classification.set(Some(result));
+                    draw_graph(display_data, unsatisfiable);
+                    if let Some(e) = non_fatal_error {
+                        error_context.extend(e.records);
+                    }
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let summary = move || {
+        report.get().map(|report| {
+            let (status, status_class) = if report.is_consistent() {
+                ("No inconsistencies found".to_string(), "text-green-700")
+            } else {
+                (
+                    format!("{} inconsistencies found", report.inconsistencies.len()),
+                    "text-red-700",
+                )
+            };
+            view! {
+                <div class="mb-2 text-sm">
+                    {format!(
+                        "Inferred {} statements from {} asserted statements",
+                        report.inferred_count,
+                        report.asserted_count,
+                    )}
+                </div>
+                <div class=format!("mb-2 text-sm {status_class}")>{status}</div>
+            }
+        })
+    };
+
+    let classification_summary = move || {
+        classification.get().map(|report| {
+            let (status, status_class) = if report.is_coherent() {
+                (
+                    "No unsatisfiable classes found".to_string(),
+                    "text-green-700",
+                )
+            } else {
+                (
+                    format!("{} unsatisfiable classes found", report.unsatisfiable.len()),
+                    "text-red-700",
+                )
+            };
+            view! {
+                <div class="mb-2 text-sm">
+                    {format!(
+                        "Classified {} classes into {} direct subclass relations",
+                        report.class_count,
+                        report.subsumption_count,
+                    )}
+                </div>
+                {(report.ignored_axiom_count > 0)
+                    .then(|| {
+                        view! {
+                            <div class="mb-2 text-sm text-amber-600">
+                                {format!(
+                                    "Ignored {} axioms outside OWL 2 EL",
+                                    report.ignored_axiom_count,
+                                )}
+                            </div>
+                        }
+                    })}
+                <div class=format!("mb-2 text-sm {status_class}")>{status}</div>
+            }
+        })
+    };
+
+    view! {
+        <WorkbenchMenuItems title="Reasoning">
+            <button
+                class="p-1 mb-2 w-full text-sm bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                on:click=move |_| {
+                    if let Some(graph_name) = selected_graph() {
+                        infer.dispatch_local(graph_name);
+                    }
+                }
+            >
+                {move || if infer.pending().get() { "Inferring..." } else { "Infer statements" }}
+            </button>
+            {summary}
+            <label class="flex gap-1 items-center text-sm">
+                <input
+                    type="checkbox"
+                    prop:checked=move || show_inferred.get()
+                    on:change=move |ev| {
+                        let target: HtmlInputElement = event_target(&ev);
+                        show_inferred.set(target.checked());
+                        if !active_graph_name.get_untracked().is_empty() {
+                            spawn_local_scoped_with_cancellation(async move {
+                                load_graph(DEFAULT_QUERY.to_string(), true).await;
+                            });
+                        }
+                    }
+                />
+                "Show inferred statements"
+            </label>
+            <button
+                class="p-1 my-2 w-full text-sm bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                on:click=move |_| {
+                    if let Some(graph_name) = selected_graph() {
+                        classify.dispatch_local(graph_name);
+                    }
+                }
+            >
+                {move || {
+                    if classify.pending().get() { "Classifying..." } else { "Classify (OWL 2 EL)" }
+                }}
+            </button>
+            {classification_summary}
+        </WorkbenchMenuItems>
+    }
+}
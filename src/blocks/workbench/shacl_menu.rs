@@ -0,0 +1,244 @@
+use super::WorkbenchMenuItems;
+use super::export_menu::{ExportButton, save_download};
+use crate::components::user_input::internal_sparql::{GraphDataContext, draw_graph};
+use crate::components::user_input::workspace::list_graphs;
+use crate::errors::{ClientErrorKind, ErrorLogContext};
+use grapher::prelude::GraphDisplayData;
+use leptos::prelude::*;
+use leptos::server_fn::codec::{ByteStream, Rkyv, Streaming};
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{
+    GraphAnnotations, ShaclSeverity, VOWLGrapherError, ValidationReport,
+};
+use web_sys::HtmlInputElement;
+
+/// Validate a graph of the current user against SHACL shapes and serialize it with its focus nodes annotated.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn validate_shacl(
+    data_graph: String,
+    shapes_graph: String,
+) -> Result<
+    (
+        GraphDisplayData,
+        GraphAnnotations,
+        ValidationReport,
+        Option<VOWLGrapherError>,
+    ),
+    VOWLGrapherError,
+> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    store.shacl_display_data(&data_graph, &shapes_graph).await
+}
+
+#[server(output = Streaming)]
+/// Export the SHACL validation report of a graph of the current user
+pub async fn export_shacl_report(
+    data_graph: String,
+    shapes_graph: String,
+) -> Result<ByteStream<VOWLGrapherError>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    let report = store.shacl_report(&data_graph, &shapes_graph).await?;
+    Ok(ByteStream::new(futures::stream::once(async move {
+        Ok(bytes::Bytes::from(report))
+    })))
+}
+
+/// Returns the text colour of results with a severity.
+const fn severity_class(severity: ShaclSeverity) -> &'static str {
+    match severity {
+        ShaclSeverity::Violation => "text-red-700",
+        ShaclSeverity::Warning => "text-amber-600",
+        ShaclSeverity::Info => "text-sky-700",
+    }
+}
+
+/// Validates the active graph against the SHACL shapes in another loaded graph.
+#[component]
+pub fn ShaclMenu() -> impl IntoView {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext {
+        active_graph_name, ..
+    } = expect_context::<GraphDataContext>();
+
+    let shapes_graph = RwSignal::new(String::new());
+    let report = RwSignal::new(None::<ValidationReport>);
+    let progress_message = RwSignal::new(String::new());
+
+    let graphs = LocalResource::new(move || {
+        active_graph_name.track();
+        async move {
+            list_graphs().await.unwrap_or_else(|e| {
+                error_context.extend(e.records);
+                Vec::new()
+            })
+        }
+    });
+
+    let selected_graphs = move || {
+        let (data, shapes) = (
+            active_graph_name.get_untracked(),
+            shapes_graph.get_untracked(),
+        );
+        if data.is_empty() || shapes.is_empty() {
+            error_context.push(
+                ClientErrorKind::InvalidInput(
+                    "Load an ontology and select a shapes graph".to_string(),
+                )
+                .into(),
+            );
+            return None;
+        }
+        Some((data, shapes))
+    };
+
+    let validate = Action::new_local(move |(data, shapes): &(String, String)| {
+        let (data, shapes) = (data.clone(), shapes.clone());
+        async move {
+            match validate_shacl(data, shapes).await {
+                Ok((display_data, focus_nodes, result, non_fatal_error)) => {
+                    report.set(Some(result));
+                    draw_graph(display_data, focus_nodes);
+                    if let Some(e) = non_fatal_error {
+                        error_context.extend(e.records);
+                    }
+                }
+                Err(e) => error_context.extend(e.records),
+            }
+        }
+    });
+
+    let download = Action::new_local(move |(data, shapes): &(String, String)| {
+        let (data, shapes) = (data.clone(), shapes.clone());
+        async move {
+            let result = match export_shacl_report(data, shapes).await {
+                Ok(byte_stream) => {
+                    save_download(
+                        byte_stream,
+                        progress_message,
+                        "validation-report.ttl",
+                        "text/turtle",
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error_context.extend(e.records);
+            }
+        }
+    });
+
+    let options = move || {
+        graphs.get().map(|graphs| {
+            graphs
+                .iter()
+                .map(|graph| view! { <option value=graph.name.clone()>{graph.name.clone()}</option> })
+                .collect_view()
+        })
+    };
+
+    let results = move || {
+        report.get().map(|report| {
+            let rows = report
+                .results
+                .iter()
+                .map(|result| {
+                    view! {
+                        <tr class=format!(
+                            "border-b border-neutral-200 align-top {}",
+                            severity_class(result.severity),
+                        )>
+                            <td class="py-1 pr-2">{result.severity.to_string()}</td>
+                            <td class="py-1 pr-2 break-all">{result.focus_node.clone()}</td>
+                            <td class="py-1 pr-2 break-all">
+                                {result.result_path.clone().unwrap_or_default()}
+                            </td>
+                            <td class="py-1 pr-2 break-all">
+                                {result.value.clone().unwrap_or_default()}
+                            </td>
+                            <td class="py-1">{result.message.clone()}</td>
+                        </tr>
+                    }
+                })
+                .collect_view();
+            view! {
+                <div class="mb-2 text-sm">
+                    {if report.conforms {
+                        "The graph conforms to the shapes".to_string()
+                    } else {
+                        format!("{} validation results", report.results.len())
+                    }}
+                </div>
+                <div class="overflow-y-auto mb-2 max-h-64">
+                    <table class="w-full text-xs table-auto">
+                        <thead>
+                            <tr class="border-b">
+                                <th class="pr-2 text-left">"Severity"</th>
+                                <th class="pr-2 text-left">"Focus node"</th>
+                                <th class="pr-2 text-left">"Path"</th>
+                                <th class="pr-2 text-left">"Value"</th>
+                                <th class="text-left">"Message"</th>
+                            </tr>
+                        </thead>
+                        <tbody>{rows}</tbody>
+                    </table>
+                </div>
+            }
+        })
+    };
+
+    view! {
+        <WorkbenchMenuItems title="SHACL Validation">
+            <label class="block mb-1">"Shapes graph:"</label>
+            <select
+                class="p-1 w-full text-sm bg-gray-200 rounded border-b-0"
+                prop:value=move || shapes_graph.get()
+                on:change=move |ev| {
+                    let target: HtmlInputElement = event_target(&ev);
+                    shapes_graph.set(target.value());
+                }
+            >
+                <option value="" disabled=true selected=true hidden=true>
+                    "Select the graph holding the shapes"
+                </option>
+                {options}
+            </select>
+            <button
+                class="p-1 my-2 w-full text-sm bg-gray-200 rounded text-[#000000] hover:bg-[#dd9900]"
+                on:click=move |_| {
+                    if let Some(graphs) = selected_graphs() {
+                        validate.dispatch_local(graphs);
+                    }
+                }
+            >
+                {move || if validate.pending().get() { "Validating..." } else { "Validate" }}
+            </button>
+            {results}
+            <div class="flex flex-wrap justify-center w-full">
+                <ExportButton
+                    label="Validation report"
+                    icon=icondata::BiExportRegular
+                    on_click=move || {
+                        if let Some(graphs) = selected_graphs() {
+                            download.dispatch_local(graphs);
+                        }
+                    }
+                />
+            </div>
+            {move || {
+                let msg = progress_message.get();
+                (!msg.is_empty())
+                    .then(|| {
+                        view! {
+                            <div class="mt-2 w-full text-sm text-center text-gray-600">
+                                {msg}
+                            </div>
+                        }
+                    })
+            }}
+        </WorkbenchMenuItems>
+    }
+}
@@ -2,3 +2,4 @@ pub mod file_upload;
 pub mod internal_sparql;
 pub mod range_select;
 pub mod stored_ontology;
+pub mod workspace;
@@ -7,20 +7,22 @@ use leptos::server_fn::codec::{MultipartData, MultipartFormData, StreamingText,
 use leptos::task::spawn_local;
 use log::{debug, info, trace};
 #[cfg(feature = "server")]
-use reqwest::Client;
+use reqwest::Url;
 use std::cell::RefCell;
 #[cfg(feature = "server")]
 use std::path::Path;
 use std::rc::Rc;
 #[cfg(feature = "server")]
-use vowlgrapher_database::prelude::VOWLGrapherStore;
+use vowlgrapher_database::prelude::{
+    VOWLGrapherStore, fetch_ontology, query_endpoint, response_format,
+};
 #[cfg(feature = "server")]
 use vowlgrapher_parser::errors::VOWLGrapherStoreError;
 #[cfg(feature = "server")]
 use vowlgrapher_parser::errors::VOWLGrapherStoreErrorKind;
 #[cfg(feature = "ssr")]
 use vowlgrapher_util::prelude::manage_user_id;
-use vowlgrapher_util::prelude::{DataType, VOWLGrapherError};
+use vowlgrapher_util::prelude::{DataType, HarvestReport, QuerySolutionRow, VOWLGrapherError};
 use web_sys::{FileList, FormData};
 
 use crate::errors::ClientErrorKind;
@@ -106,10 +108,9 @@ pub async fn handle_local(
         .into_inner()
         .ok_or_else(|| ServerFnError::new("data must be server-side"))?;
     let mut count = 0;
-    let mut dtype = DataType::UNKNOWN;
-    let mut name = String::new();
+    let mut names = Vec::new();
     while let Ok(Some(mut field)) = data.next_field().await {
-        name = field.file_name().unwrap_or_default().to_string();
+        let name = field.file_name().unwrap_or_default().to_string();
 
         if name.is_empty() {
             return Err(ServerFnError::new("Received empty file string").into());
@@ -121,8 +122,6 @@ pub async fn handle_local(
 
         session.start_upload(&name)?;
 
-        dtype = Path::new(&name).into();
-
         while let Ok(Some(chunk)) = field.chunk().await {
             let len = chunk.len();
             count += len;
@@ -139,30 +138,27 @@ pub async fn handle_local(
             progress::add_chunk(&name, len).await;
         }
 
-        if !name.is_empty() {
-            progress::remove(&name);
-        }
+        progress::remove(&name);
+        names.push(name);
     }
 
-    let (parsed_dtype, import_warning) = session.complete_upload(&name).await?;
-    let extension_warning = if parsed_dtype != dtype
-        && parsed_dtype != DataType::UNKNOWN
-        && dtype != DataType::UNKNOWN
-    {
-        Some(<VOWLGrapherStoreError as Into<VOWLGrapherError>>::into(
-        VOWLGrapherStoreErrorKind::IncorrectFileExtension(format!(
-            "The uploaded file had an incorrect file extension. It was parsed as {parsed_dtype} instead of {dtype}"
-        ))
-        .into(),
-    ))
-    } else {
-        None
+    // All files are merged into the graph of the first one.
+    let Some(graph_name) = names.first() else {
+        return Err(ServerFnError::new("Received no files").into());
     };
+    let (parsed_dtypes, import_warning) = session
+        .complete_upload(graph_name, DataType::UNKNOWN)
+        .await?;
+    let warnings = names
+        .iter()
+        .zip(&parsed_dtypes)
+        .map(|(name, parsed_dtype)| extension_warning(name, *parsed_dtype))
+        .fold(import_warning, merge_warnings);
 
     Ok((
-        parsed_dtype,
+        parsed_dtypes.first().copied().unwrap_or(DataType::UNKNOWN),
         count,
-        merge_warnings(import_warning, extension_warning),
+        warnings,
     ))
 }
 
@@ -175,13 +171,10 @@ pub async fn handle_remote(
     trace!("User {user_id} is uploading a remote file");
 
     debug!("Sending request to remote: '{url}'");
-    let client = Client::new();
-    let resp = match client.get(&url).send().await {
-        Ok(r) => r,
-        Err(e) => {
-            return Err(ServerFnError::ServerError(format!("Error fetching URL: {e}")).into());
-        }
-    };
+    let parsed_url = Url::parse(&url)
+        .map_err(|e| ServerFnError::ServerError(format!("Invalid URL '{url}': {e}")))?;
+    let resp = fetch_ontology(&parsed_url).await?;
+    let announced_dtype = response_format(&resp);
 
     if let Some(content_length) = resp.content_length() {
         let size = usize::try_from(content_length).unwrap_or(usize::MAX);
@@ -201,7 +194,6 @@ pub async fn handle_remote(
     session.start_upload(&url)?;
 
     let mut total = 0;
-    let dtype = Path::new(&url).into();
 
     let mut stream = resp.bytes_stream();
     while let Some(chunk_result) = stream.next().await {
@@ -213,57 +205,68 @@ pub async fn handle_remote(
         progress::add_chunk(&progress_key, chunk.len()).await;
     }
     progress::remove(&progress_key);
-    let (parsed_dtype, import_warning) = session.complete_upload(&url).await?;
-    let extension_warning = if parsed_dtype != dtype
-        && parsed_dtype != DataType::UNKNOWN
-        && dtype != DataType::UNKNOWN
-    {
-        Some(<VOWLGrapherStoreError as Into<VOWLGrapherError>>::into(
-        VOWLGrapherStoreErrorKind::IncorrectFileExtension(format!(
-            "The uploaded file had an incorrect file extension. It was parsed as {parsed_dtype} instead of {dtype}"
-        ))
-        .into(),
-    ))
-    } else {
-        None
-    };
+    let (parsed_dtypes, import_warning) = session.complete_upload(&url, announced_dtype).await?;
+    let parsed_dtype = parsed_dtypes.first().copied().unwrap_or(DataType::UNKNOWN);
 
     Ok((
         parsed_dtype,
         total,
-        merge_warnings(import_warning, extension_warning),
+        merge_warnings(import_warning, extension_warning(&url, parsed_dtype)),
     ))
 }
 
-/// Sparql reads (endpoint + query) and calls for the datatype label and returns (label, data content)
+/// Warns if the extension of an uploaded file names another type than it was parsed as.
+#[cfg(feature = "server")]
+fn extension_warning(name: &str, parsed_dtype: DataType) -> Option<VOWLGrapherError> {
+    let dtype = DataType::from(Path::new(name));
+    (parsed_dtype != dtype && parsed_dtype != DataType::UNKNOWN && dtype != DataType::UNKNOWN)
+        .then(|| {
+            <VOWLGrapherStoreError as Into<VOWLGrapherError>>::into(
+                VOWLGrapherStoreErrorKind::IncorrectFileExtension(format!(
+                    "The uploaded file '{name}' had an incorrect file extension. It was parsed as {parsed_dtype} instead of {dtype}"
+                ))
+                .into(),
+            )
+        })
+}
+
+/// Sends a query to a SPARQL endpoint and loads its results into the graph named after the endpoint.
+///
+/// The results of CONSTRUCT and DESCRIBE queries are loaded as a graph, as are solutions binding
+/// `?s`, `?p` and `?o`. The results of SELECT queries are requested in the SPARQL results `format`
+/// ("json", "xml", "csv" or "tsv", JSON by default). Solutions which cannot be loaded as a graph
+/// are returned as rows of a table instead.
+///
+/// Returns the format of the results, their size, the rows of the table and warnings.
 #[server]
 pub async fn handle_sparql(
     endpoint: String,
     query: String,
     format: Option<String>,
-) -> Result<(DataType, usize, Option<VOWLGrapherError>), VOWLGrapherError> {
+) -> Result<
+    (
+        DataType,
+        usize,
+        Option<Vec<QuerySolutionRow>>,
+        Option<VOWLGrapherError>,
+    ),
+    VOWLGrapherError,
+> {
     let user_id = manage_user_id().await?;
     trace!("User {user_id} is quering SPARQL");
 
-    let client = Client::new();
-
-    let mut session = VOWLGrapherStore::new_for_user(user_id);
-
-    let accept_type = match format.as_deref() {
-        Some("xml") => DataType::SPARQLXML.mime_type(),
-        Some("tsv") => DataType::SPARQLTSV.mime_type(),
-        Some("csv") => DataType::SPARQLCSV.mime_type(),
-        Some("json") => DataType::SPARQLJSON.mime_type(),
-        _ => DataType::UNKNOWN.mime_type(),
+    let results_type = match format.as_deref() {
+        Some("xml") => DataType::SPARQLXML,
+        Some("tsv") => DataType::SPARQLTSV,
+        Some("csv") => DataType::SPARQLCSV,
+        _ => DataType::SPARQLJSON,
     };
+    let parsed_endpoint = Url::parse(&endpoint).map_err(|e| {
+        ServerFnError::ServerError(format!("Invalid endpoint URL '{endpoint}': {e}"))
+    })?;
+    let (resp, announced_dtype) = query_endpoint(&parsed_endpoint, &query, results_type).await?;
 
-    let resp = client
-        .post(&endpoint)
-        .header("Accept", accept_type)
-        .form(&[("query", query)])
-        .send()
-        .await
-        .map_err(|e| ServerFnError::new(format!("Error querying SPARQL endpoint: {e}")))?;
+    let mut session = VOWLGrapherStore::new_for_user(user_id);
 
     let progress_key = format!("sparql-{endpoint}");
     progress::reset(&progress_key);
@@ -276,19 +279,78 @@ pub async fn handle_sparql(
             chunk_result.map_err(|e| ServerFnError::new(format!("Error reading chunk: {e}")))?;
 
         total += chunk.len();
+        if total as u64 > VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes.0 {
+            progress::remove(&progress_key);
+            return Err(ServerFnError::ServerError(format!(
+                "The results of the query exceed the maximum allowed size of {}",
+                VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes.display().si()
+            ))
+            .into());
+        }
         session.upload_chunk(&chunk)?;
         progress::add_chunk(&progress_key, chunk.len()).await;
     }
 
     progress::remove(&progress_key);
-    session.complete_upload(&progress_key).await?;
+    let (parsed_dtype, table, warning) = session
+        .complete_endpoint_upload(&progress_key, announced_dtype)
+        .await?;
+    Ok((parsed_dtype, total, table, warning))
+}
 
-    let dtype = if accept_type.contains("xml") {
-        DataType::SPARQLXML
+/// Harvests a SPARQL endpoint into the graph named after the endpoint, one page of
+/// `page_size` solutions at a time.
+///
+/// `query` is a CONSTRUCT query without LIMIT or OFFSET. Without a query, the schema of the
+/// endpoint is harvested. If `resume` is set and an earlier harvest from the endpoint failed,
+/// that harvest continues with the page that failed, using its own query and page size.
+#[server]
+pub async fn harvest_sparql(
+    endpoint: String,
+    query: Option<String>,
+    page_size: u32,
+    resume: bool,
+) -> Result<HarvestReport, VOWLGrapherError> {
+    let user_id = manage_user_id().await?;
+    trace!("User {user_id} is harvesting SPARQL");
+
+    let session = VOWLGrapherStore::new_for_user(user_id);
+    let progress_key = format!("sparql-{endpoint}");
+    if resume && session.can_resume_harvest(&progress_key) {
+        info!("Resuming harvest of '{endpoint}'");
     } else {
-        DataType::SPARQLJSON
-    };
-    Ok((dtype, total, None))
+        let parsed_endpoint = Url::parse(&endpoint).map_err(|e| {
+            ServerFnError::ServerError(format!("Invalid endpoint URL '{endpoint}': {e}"))
+        })?;
+        session
+            .start_harvest(
+                &progress_key,
+                parsed_endpoint,
+                query.as_deref().filter(|query| !query.trim().is_empty()),
+                usize::try_from(page_size).unwrap_or(usize::MAX),
+            )
+            .await?;
+    }
+
+    progress::reset(&progress_key);
+    let harvested = async {
+        while let Some(page_bytes) = session.harvest_page(&progress_key).await? {
+            progress::add_chunk(
+                &progress_key,
+                usize::try_from(page_bytes).unwrap_or(usize::MAX),
+            )
+            .await;
+        }
+        Ok::<_, VOWLGrapherStoreError>(())
+    }
+    .await;
+    progress::remove(&progress_key);
+    harvested?;
+
+    session
+        .harvest_report(&progress_key)
+        .await?
+        .ok_or_else(|| ServerFnError::new("The harvest was removed before it completed").into())
 }
 
 pub struct UploadProgress {
@@ -400,11 +462,16 @@ impl UploadProgress {
             FormData::new().map_err(|e| ClientErrorKind::JavaScriptError(format!("{e:#?}")))?;
         info!("Preparing filelist with {len} files");
 
-        // let mut total_size = 0;
+        // The files are merged into the graph named after the first file.
         if let Some(file) = file_list.item(0) {
             self.filename.set(file.name());
-            self.file_size.set(file.size());
         }
+        self.file_size.set(
+            (0..len)
+                .filter_map(|i| file_list.item(i))
+                .map(|file| file.size())
+                .sum(),
+        );
 
         for i in 0..len {
             if let Some(file) = file_list.item(i) {
@@ -447,7 +514,7 @@ impl Default for UploadProgress {
     }
 }
 
-/// handles what server side function to use (local, remote or sparql)
+/// handles what server side function to use (local, remote, sparql or harvest)
 #[derive(Clone)]
 pub struct FileUpload {
     pub mode: RwSignal<String>,
@@ -460,8 +527,18 @@ pub struct FileUpload {
     #[expect(clippy::type_complexity)]
     pub sparql_action: Action<
         (String, String, Option<String>),
-        Result<(DataType, usize, Option<VOWLGrapherError>), VOWLGrapherError>,
+        Result<
+            (
+                DataType,
+                usize,
+                Option<Vec<QuerySolutionRow>>,
+                Option<VOWLGrapherError>,
+            ),
+            VOWLGrapherError,
+        >,
     >,
+    pub harvest_action:
+        Action<(String, Option<String>, u32, bool), Result<HarvestReport, VOWLGrapherError>>,
     pub tracker: Rc<UploadProgress>,
 }
 
@@ -481,11 +558,26 @@ impl FileUpload {
 
         let sparql_action = Action::<
             (String, String, Option<String>),
-            Result<(DataType, usize, Option<VOWLGrapherError>), VOWLGrapherError>,
+            Result<
+                (
+                    DataType,
+                    usize,
+                    Option<Vec<QuerySolutionRow>>,
+                    Option<VOWLGrapherError>,
+                ),
+                VOWLGrapherError,
+            >,
         >::new(|(endpoint, query, format)| {
             handle_sparql(endpoint.clone(), query.clone(), format.clone())
         });
 
+        let harvest_action = Action::<
+            (String, Option<String>, u32, bool),
+            Result<HarvestReport, VOWLGrapherError>,
+        >::new(|(endpoint, query, page_size, resume)| {
+            harvest_sparql(endpoint.clone(), query.clone(), *page_size, *resume)
+        });
+
         let tracker = Rc::new(UploadProgress::new());
 
         Self {
@@ -493,6 +585,7 @@ impl FileUpload {
             local_action,
             remote_action,
             sparql_action,
+            harvest_action,
             tracker,
         }
     }
@@ -504,7 +597,11 @@ impl FileUpload {
         match self.mode.get().as_str() {
             "local" => self.local_action.value().get(),
             "remote" => self.remote_action.value().get(),
-            "sparql" => self.sparql_action.value().get(),
+            "sparql" => self
+                .sparql_action
+                .value()
+                .get()
+                .map(|result| result.map(|(dtype, size, _, warning)| (dtype, size, warning))),
             _ => None,
         }
     }
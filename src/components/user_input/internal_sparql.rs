@@ -1,4 +1,8 @@
-use std::{collections::HashMap, mem::take, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    mem::take,
+    sync::Arc,
+};
 
 use crate::errors::{ClientErrorKind, ErrorLogContext};
 use grapher::prelude::{
@@ -8,17 +12,44 @@ use leptos::{prelude::*, server_fn::codec::Rkyv};
 use log::debug;
 #[cfg(feature = "server")]
 use vowlgrapher_database::prelude::VOWLGrapherStore;
-use vowlgrapher_util::prelude::VOWLGrapherError;
 #[cfg(feature = "ssr")]
 use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{GraphAnnotations, VOWLGrapherError};
 
+/// Run a query against a graph of the current user and its imports, except `excluded_imports`,
+/// and its inferred statements if `include_inferred` is set.
+///
+/// The element with the IRI `highlighted_element`, if any, is annotated as highlighted.
+///
+/// Returns the graph along with the annotations of its elements.
 #[server (input = Rkyv, output = Rkyv)]
 pub async fn handle_internal_sparql(
     query: String,
     graph_name: String,
-) -> Result<(GraphDisplayData, Option<VOWLGrapherError>), VOWLGrapherError> {
+    excluded_imports: Vec<String>,
+    include_inferred: bool,
+    highlighted_element: Option<String>,
+) -> Result<(GraphDisplayData, GraphAnnotations, Option<VOWLGrapherError>), VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    store
+        .query_with_imports(
+            query,
+            Some(graph_name),
+            &excluded_imports,
+            include_inferred,
+            highlighted_element,
+        )
+        .await
+}
+
+/// Run a SPARQL update against a graph of the current user.
+#[server (input = Rkyv, output = Rkyv)]
+pub async fn handle_sparql_update(
+    update: String,
+    graph_name: String,
+) -> Result<(), VOWLGrapherError> {
     let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
-    store.query(query, Some(graph_name)).await
+    Ok(store.update(&update, &graph_name).await?)
 }
 
 pub async fn load_graph(query: String, clean_load: bool) {
@@ -28,11 +59,29 @@ pub async fn load_graph(query: String, clean_load: bool) {
         element_checks,
         active_graph_name,
         graph_metadata,
+        hidden_imports,
+        show_inferred,
+        highlighted_element,
+        ..
     } = expect_context::<GraphDataContext>();
     let graph_name = active_graph_name.get_untracked();
     debug!("Loading graph with name: {graph_name}");
-    match handle_internal_sparql(query, graph_name.clone()).await {
-        Ok((mut result, non_fatal_error)) => {
+    let excluded_imports = hidden_imports.with_untracked(|hidden| {
+        hidden
+            .get(&graph_name)
+            .map(|iris| iris.iter().cloned().collect())
+            .unwrap_or_default()
+    });
+    match handle_internal_sparql(
+        query,
+        graph_name.clone(),
+        excluded_imports,
+        show_inferred.get_untracked(),
+        highlighted_element.get_untracked(),
+    )
+    .await
+    {
+        Ok((mut result, result_annotations, non_fatal_error)) => {
             if clean_load {
                 let new_graph_data = take(&mut result.graph_metadata);
                 let new_context = GraphDataContext::new(&result, new_graph_data, graph_name);
@@ -46,12 +95,7 @@ pub async fn load_graph(query: String, clean_load: bool) {
                     .update(|metadata| *metadata = new_context.graph_metadata.get_untracked());
             }
 
-            if let Err(e) = EVENT_DISPATCHER
-                .rend_write_chan
-                .send(RenderEvent::LoadGraph(Box::new(result)))
-            {
-                error_context.push(ClientErrorKind::EventHandlingError(e.to_string()).into());
-            }
+            draw_graph(result, result_annotations);
             if let Some(e) = non_fatal_error {
                 error_context.extend(e.records);
             }
@@ -62,12 +106,37 @@ pub async fn load_graph(query: String, clean_load: bool) {
     }
 }
 
+/// Draws a graph, setting its annotated elements apart.
+///
+/// The annotations replace those of the graph drawn before. As the renderer only draws labels,
+/// they are marked on the labels of the annotated elements.
+pub fn draw_graph(mut display_data: GraphDisplayData, graph_annotations: GraphAnnotations) {
+    let error_context = expect_context::<ErrorLogContext>();
+    let GraphDataContext { annotations, .. } = expect_context::<GraphDataContext>();
+    graph_annotations.mark_labels(&mut display_data);
+    annotations.set(graph_annotations);
+    if let Err(e) = EVENT_DISPATCHER
+        .rend_write_chan
+        .send(RenderEvent::LoadGraph(Box::new(display_data)))
+    {
+        error_context.push(ClientErrorKind::EventHandlingError(e.to_string()).into());
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct GraphDataContext {
     pub element_counts: RwSignal<HashMap<ElementType, usize>>,
     pub element_checks: RwSignal<HashMap<ElementType, bool>>,
     pub active_graph_name: RwSignal<String>,
     pub graph_metadata: RwSignal<Arc<GraphMetadata>>,
+    /// The IRIs of the imports hidden from the visualization, keyed by graph name.
+    pub hidden_imports: RwSignal<HashMap<String, HashSet<String>>>,
+    /// Whether statements inferred by the reasoner are shown along with the asserted ones.
+    pub show_inferred: RwSignal<bool>,
+    /// The IRI of the element found by a search, which is annotated as highlighted.
+    pub highlighted_element: RwSignal<Option<String>>,
+    /// The annotations of the elements of the graph being drawn, which set them apart.
+    pub annotations: RwSignal<GraphAnnotations>,
 }
 
 impl GraphDataContext {
@@ -89,6 +158,10 @@ impl GraphDataContext {
             element_checks: RwSignal::new(element_checks),
             active_graph_name: RwSignal::new(graph_name),
             graph_metadata: RwSignal::new(graph_metadata.into()),
+            hidden_imports: RwSignal::new(HashMap::new()),
+            show_inferred: RwSignal::new(false),
+            highlighted_element: RwSignal::new(None),
+            annotations: RwSignal::new(GraphAnnotations::default()),
         }
     }
 }
@@ -0,0 +1,42 @@
+use leptos::prelude::*;
+use leptos::server_fn::codec::Rkyv;
+#[cfg(feature = "server")]
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+#[cfg(feature = "ssr")]
+use vowlgrapher_util::prelude::manage_user_id;
+use vowlgrapher_util::prelude::{GraphSummary, ImportSummary, VOWLGrapherError};
+
+/// List the graphs loaded by the current user.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn list_graphs() -> Result<Vec<GraphSummary>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.list_graphs().await?)
+}
+
+/// List the ontologies imported by a graph of the current user.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn list_imports(graph_name: String) -> Result<Vec<ImportSummary>, VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.list_imports(&graph_name).await?)
+}
+
+/// Rename a graph loaded by the current user.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn rename_graph(from: String, to: String) -> Result<(), VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.rename_graph(&from, &to).await?)
+}
+
+/// Copy a graph loaded by the current user into a new graph.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn duplicate_graph(from: String, to: String) -> Result<(), VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.duplicate_graph(&from, &to).await?)
+}
+
+/// Delete a graph loaded by the current user.
+#[server(input = Rkyv, output = Rkyv)]
+pub async fn delete_graph(graph_name: String) -> Result<(), VOWLGrapherError> {
+    let store = VOWLGrapherStore::new_for_user(manage_user_id().await?);
+    Ok(store.delete_graph(&graph_name).await?)
+}
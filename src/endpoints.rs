@@ -0,0 +1,169 @@
+//! Plain HTTP endpoints served next to the Leptos application.
+
+pub mod graph_store;
+pub mod sparql;
+
+use actix_session::Session;
+use actix_web::HttpResponse;
+use actix_web::cookie::Key;
+use log::warn;
+use uuid::Uuid;
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+use vowlgrapher_parser::errors::{VOWLGrapherStoreError, VOWLGrapherStoreErrorKind};
+use vowlgrapher_util::prelude::{ErrorRecord, VOWLGRAPHER_ENVIRONMENT};
+
+/// Returns the key signing the session cookies.
+///
+/// The key is read from `VOWLGRAPHER_SESSION_KEY` or, if graphs are persisted, from a key file
+/// created once in the store directory. Either way, users keep their id, and with it their
+/// graphs, across server restarts. Otherwise, every session ends when the server stops.
+pub fn session_key() -> Key {
+    if let Some(secret) = &VOWLGRAPHER_ENVIRONMENT.session_key {
+        match Key::try_from(secret.as_bytes()) {
+            Ok(key) => return key,
+            Err(e) => warn!("Ignoring VOWLGRAPHER_SESSION_KEY: {e}"),
+        }
+    }
+    if let Some(master) =
+        VOWLGrapherStore::persisted_session_key(|| Key::generate().master().to_vec())
+    {
+        match Key::try_from(master.as_slice()) {
+            Ok(key) => return key,
+            Err(e) => warn!("Ignoring the persisted session key: {e}"),
+        }
+    }
+    warn!("Generating a session key. Sessions will end when the server stops");
+    Key::generate()
+}
+
+/// Returns the user of a session.
+///
+/// If the session has no user yet, a new one is created if `create` is set.
+fn session_user_id(session: &Session, create: bool) -> Option<String> {
+    if let Ok(Some(user_id)) = session.get::<String>("user_id") {
+        return Some(user_id);
+    }
+    if !create {
+        return None;
+    }
+
+    let user_id = Uuid::new_v4().to_string();
+    session.insert("user_id", &user_id).ok()?;
+    Some(user_id)
+}
+
+/// The response sent when a request requires an existing session.
+fn no_session_response() -> HttpResponse {
+    HttpResponse::Unauthorized()
+        .body("No session found. Load an ontology in VOWLGrapher to start a session")
+}
+
+/// Maps a store error to the matching HTTP error response.
+fn error_response(error: VOWLGrapherStoreError) -> HttpResponse {
+    let mut response = match error.kind() {
+        VOWLGrapherStoreErrorKind::InvalidQuery(_)
+        | VOWLGrapherStoreErrorKind::UnsupportedQueryType(_)
+        | VOWLGrapherStoreErrorKind::InvalidFileType(_)
+        | VOWLGrapherStoreErrorKind::IriParseError(_) => HttpResponse::BadRequest(),
+        VOWLGrapherStoreErrorKind::GraphNotFound(_) => HttpResponse::NotFound(),
+        VOWLGrapherStoreErrorKind::ForbiddenOperation(_) => HttpResponse::Forbidden(),
+        VOWLGrapherStoreErrorKind::QuotaExceeded(_) => HttpResponse::InsufficientStorage(),
+        _ => HttpResponse::InternalServerError(),
+    };
+    response.body(ErrorRecord::from(error).message)
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    //! Helpers shared by the endpoint tests.
+
+    use actix_session::storage::CookieSessionStore;
+    use actix_session::{Session, SessionMiddleware};
+    use actix_web::HttpResponse;
+    use actix_web::cookie::Key;
+
+    use super::session_user_id;
+
+    /// The result of an endpoint test.
+    pub type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    /// Returns the session middleware the endpoints run behind, without requiring HTTPS.
+    pub fn session_middleware() -> SessionMiddleware<CookieSessionStore> {
+        session_middleware_with(Key::generate())
+    }
+
+    /// Returns the session middleware signing cookies with `key`, without requiring HTTPS.
+    pub fn session_middleware_with(key: Key) -> SessionMiddleware<CookieSessionStore> {
+        SessionMiddleware::builder(CookieSessionStore::default(), key)
+            .cookie_secure(false)
+            .build()
+    }
+
+    /// Starts a session and responds with its user.
+    pub async fn login(session: Session) -> HttpResponse {
+        session_user_id(&session, true).map_or_else(
+            || HttpResponse::InternalServerError().finish(),
+            |user_id| HttpResponse::Ok().body(user_id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::endpoints::test_support::{TestResult, login, session_middleware_with};
+    use actix_web::{App, test, web};
+
+    #[actix_web::test]
+    async fn test_session_survives_restart() -> TestResult {
+        let master = Key::generate().master().to_vec();
+        let app = test::init_service(
+            App::new()
+                .wrap(session_middleware_with(Key::try_from(master.as_slice())?))
+                .route("/login", web::get().to(login)),
+        )
+        .await;
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/login").to_request()).await;
+        let cookie = response
+            .response()
+            .cookies()
+            .next()
+            .ok_or("no session cookie")?
+            .into_owned();
+        let user_id = test::read_body(response).await;
+
+        // A restarted server reading the same key keeps the user, and with it their graphs.
+        let restarted = test::init_service(
+            App::new()
+                .wrap(session_middleware_with(Key::try_from(master.as_slice())?))
+                .route("/login", web::get().to(login)),
+        )
+        .await;
+        let request = test::TestRequest::get()
+            .uri("/login")
+            .cookie(cookie.clone())
+            .to_request();
+        assert_eq!(
+            test::read_body(test::call_service(&restarted, request).await).await,
+            user_id
+        );
+
+        // A new key would have made every user a new one.
+        let rekeyed = test::init_service(
+            App::new()
+                .wrap(session_middleware_with(Key::generate()))
+                .route("/login", web::get().to(login)),
+        )
+        .await;
+        let request = test::TestRequest::get()
+            .uri("/login")
+            .cookie(cookie)
+            .to_request();
+        assert_ne!(
+            test::read_body(test::call_service(&rekeyed, request).await).await,
+            user_id
+        );
+        Ok(())
+    }
+}
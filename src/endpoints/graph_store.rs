@@ -0,0 +1,273 @@
+//! The SPARQL 1.1 Graph Store HTTP Protocol endpoint, mounted at `/graph-store`.
+//!
+//! Implements indirect graph identification as described in
+//! <https://www.w3.org/TR/sparql11-http-rdf-update/>: the graph is named by the `graph`
+//! parameter and belongs to the caller's session. As with `/sparql`, the parameter is either
+//! the name of the graph or its full IRI. A session is started by the first `PUT` or `POST`,
+//! so clients only need to keep the session cookie between requests.
+
+use actix_session::Session;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, web};
+use futures::StreamExt;
+use log::info;
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+use vowlgrapher_util::prelude::{DataType, VOWLGRAPHER_ENVIRONMENT};
+
+use crate::endpoints::{error_response, no_session_response, session_user_id};
+
+/// The types a graph can be downloaded as, in order of preference.
+const EXPORT_TYPES: [DataType; 9] = [
+    DataType::TTL,
+    DataType::NTriples,
+    DataType::NQuads,
+    DataType::TriG,
+    DataType::JsonLd,
+    DataType::N3,
+    DataType::RDF,
+    DataType::OWX,
+    DataType::OFN,
+];
+
+/// Downloads a graph in the type accepted by the client.
+pub async fn graph_store_get(request: HttpRequest, session: Session) -> HttpResponse {
+    let Some(graph_name) = graph_param(&request) else {
+        return missing_graph_response();
+    };
+    let Some(user_id) = session_user_id(&session, false) else {
+        return no_session_response();
+    };
+
+    let accept = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let Some(resource_type) = DataType::negotiate(accept, &EXPORT_TYPES) else {
+        return HttpResponse::NotAcceptable().body(format!(
+            "Supported types: {}",
+            EXPORT_TYPES
+                .iter()
+                .map(DataType::mime_type)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    };
+
+    let store = VOWLGrapherStore::new_for_user(user_id);
+    match store.contains_graph(&graph_name).await {
+        Ok(true) => (),
+        Ok(false) => return graph_not_found_response(&graph_name),
+        Err(e) => return error_response(e),
+    }
+    match store.serialize_stream(resource_type, &graph_name).await {
+        Ok(stream) => HttpResponse::Ok()
+            .content_type(resource_type.mime_type())
+            .insert_header((header::VARY, "Accept"))
+            .streaming(stream.map(|chunk| {
+                chunk
+                    .map(bytes::Bytes::from)
+                    .map_err(|e| std::io::Error::other(e.to_string()))
+            })),
+        Err(e) => error_response(e),
+    }
+}
+
+/// Replaces a graph with the request body.
+pub async fn graph_store_put(
+    request: HttpRequest,
+    session: Session,
+    body: web::Bytes,
+) -> HttpResponse {
+    store_graph(&request, &session, &body, false).await
+}
+
+/// Adds the request body to a graph.
+pub async fn graph_store_post(
+    request: HttpRequest,
+    session: Session,
+    body: web::Bytes,
+) -> HttpResponse {
+    store_graph(&request, &session, &body, true).await
+}
+
+/// Drops a graph.
+pub async fn graph_store_delete(request: HttpRequest, session: Session) -> HttpResponse {
+    let Some(graph_name) = graph_param(&request) else {
+        return missing_graph_response();
+    };
+    let Some(user_id) = session_user_id(&session, false) else {
+        return no_session_response();
+    };
+
+    let store = VOWLGrapherStore::new_for_user(user_id);
+    match store.delete_graph(&graph_name).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// Loads the request body into a graph, replacing the graph unless `append` is set.
+async fn store_graph(
+    request: &HttpRequest,
+    session: &Session,
+    body: &[u8],
+    append: bool,
+) -> HttpResponse {
+    let Some(graph_name) = graph_param(request) else {
+        return missing_graph_response();
+    };
+    let Some(user_id) = session_user_id(session, true) else {
+        return HttpResponse::InternalServerError().body("Failed to start a session");
+    };
+
+    let max_input_size = VOWLGRAPHER_ENVIRONMENT.max_input_size_bytes;
+    if body.len() as u64 > max_input_size.0 {
+        return HttpResponse::PayloadTooLarge().body(format!(
+            "Request body exceeds the maximum allowed size of {}",
+            max_input_size.display().si()
+        ));
+    }
+    let hinted_format = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(DataType::UNKNOWN, DataType::from_mime_type);
+
+    let store = VOWLGrapherStore::new_for_user(user_id);
+    let existed = match store.contains_graph(&graph_name).await {
+        Ok(existed) => existed,
+        Err(e) => return error_response(e),
+    };
+    match store
+        .insert_bytes(body, &graph_name, hinted_format, append)
+        .await
+    {
+        Ok((loaded_format, warnings)) => {
+            info!("Stored graph '{graph_name}' as {loaded_format:?} via the graph store protocol");
+            let mut response = if existed {
+                HttpResponse::Ok()
+            } else {
+                HttpResponse::Created()
+            };
+            let warnings = warnings
+                .map(|warnings| {
+                    warnings
+                        .records
+                        .into_iter()
+                        .map(|record| record.message)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            response.body(warnings)
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+/// The response sent when the `graph` parameter is missing.
+fn missing_graph_response() -> HttpResponse {
+    HttpResponse::BadRequest().body("Missing 'graph' parameter")
+}
+
+/// The response sent when a graph does not exist.
+fn graph_not_found_response(graph_name: &str) -> HttpResponse {
+    HttpResponse::NotFound().body(format!("Graph '{graph_name}' does not exist"))
+}
+
+/// Returns the value of the `graph` parameter of a request.
+fn graph_param(request: &HttpRequest) -> Option<String> {
+    form_urlencoded::parse(request.query_string().as_bytes())
+        .find(|(key, _)| key == "graph")
+        .map(|(_, value)| value.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::endpoints::test_support::{TestResult, login, session_middleware};
+    use actix_web::{App, test};
+
+    #[actix_web::test]
+    async fn test_graph_store_round_trip() -> TestResult {
+        let app = test::init_service(
+            App::new()
+                .wrap(session_middleware())
+                .route("/login", web::get().to(login))
+                .service(
+                    web::resource("/graph-store")
+                        .route(web::get().to(graph_store_get))
+                        .route(web::put().to(graph_store_put))
+                        .route(web::post().to(graph_store_post))
+                        .route(web::delete().to(graph_store_delete)),
+                ),
+        )
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/login").to_request()).await;
+        let cookie = response
+            .response()
+            .cookies()
+            .next()
+            .ok_or("no session cookie")?
+            .into_owned();
+        let user_id = String::from_utf8(test::read_body(response).await.to_vec())?;
+
+        let request = test::TestRequest::put()
+            .uri("/graph-store?graph=round-trip")
+            .insert_header((header::CONTENT_TYPE, "application/n-triples"))
+            .cookie(cookie.clone())
+            .set_payload("<http://example.com/a> <http://example.com/p> <http://example.com/b> .")
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 201);
+
+        let request = test::TestRequest::post()
+            .uri("/graph-store?graph=round-trip")
+            .insert_header((header::CONTENT_TYPE, "application/n-triples"))
+            .cookie(cookie.clone())
+            .set_payload("<http://example.com/b> <http://example.com/p> <http://example.com/c> .")
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 200);
+
+        let request = test::TestRequest::get()
+            .uri("/graph-store?graph=round-trip")
+            .insert_header((header::ACCEPT, "application/n-triples"))
+            .cookie(cookie.clone())
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        let body = String::from_utf8(test::read_body(response).await.to_vec())?;
+        assert!(body.contains("<http://example.com/a>"), "{body}");
+        assert!(body.contains("<http://example.com/c>"), "{body}");
+
+        // The graph can also be addressed by the IRI it has in /sparql.
+        let graph_iri = VOWLGrapherStore::new_for_user(user_id).get_graph_name("round-trip");
+        let uri = format!(
+            "/graph-store?{}",
+            form_urlencoded::Serializer::new(String::new())
+                .append_pair("graph", &graph_iri)
+                .finish()
+        );
+        let request = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((header::ACCEPT, "application/n-triples"))
+            .cookie(cookie.clone())
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 200);
+
+        let request = test::TestRequest::delete()
+            .uri(&uri)
+            .cookie(cookie.clone())
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 204);
+
+        let request = test::TestRequest::get()
+            .uri("/graph-store?graph=round-trip")
+            .cookie(cookie)
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 404);
+        Ok(())
+    }
+}
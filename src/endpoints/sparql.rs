@@ -0,0 +1,244 @@
+//! The SPARQL 1.1 Protocol endpoint, mounted at `/sparql`.
+//!
+//! Implements query operations over GET and POST as described in
+//! <https://www.w3.org/TR/sparql11-protocol/>. Queries only see the graphs loaded by the
+//! caller's session. The `default-graph-uri` and `named-graph-uri` parameters narrow this
+//! down to the given graphs, which must belong to the session. The `graph` parameter adds
+//! a graph as both a default and a named graph.
+
+use actix_session::Session;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, web};
+use log::debug;
+use vowlgrapher_database::prelude::VOWLGrapherStore;
+use vowlgrapher_util::prelude::DataType;
+
+use crate::endpoints::{error_response, no_session_response, session_user_id};
+
+/// The result types of SELECT and ASK queries, in order of preference.
+const RESULTS_TYPES: [DataType; 4] = [
+    DataType::SPARQLJSON,
+    DataType::SPARQLXML,
+    DataType::SPARQLCSV,
+    DataType::SPARQLTSV,
+];
+
+/// The result types of CONSTRUCT and DESCRIBE queries, in order of preference.
+const GRAPH_TYPES: [DataType; 7] = [
+    DataType::TTL,
+    DataType::NTriples,
+    DataType::RDF,
+    DataType::JsonLd,
+    DataType::N3,
+    DataType::NQuads,
+    DataType::TriG,
+];
+
+/// The parameters of a query operation.
+#[derive(Debug, Default)]
+struct ProtocolParams {
+    /// Every `query` parameter received.
+    queries: Vec<String>,
+    /// The graphs merged into the default graph of the query.
+    default_graphs: Vec<String>,
+    /// The graphs the query can address by name.
+    named_graphs: Vec<String>,
+}
+
+impl ProtocolParams {
+    /// Collects the parameters of an `application/x-www-form-urlencoded` string.
+    fn extend_from_urlencoded(&mut self, input: &[u8]) {
+        for (key, value) in form_urlencoded::parse(input) {
+            match key.as_ref() {
+                "query" => self.queries.push(value.into_owned()),
+                "graph" => {
+                    self.default_graphs.push(value.to_string());
+                    self.named_graphs.push(value.into_owned());
+                }
+                "default-graph-uri" => self.default_graphs.push(value.into_owned()),
+                "named-graph-uri" => self.named_graphs.push(value.into_owned()),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Handles a query via GET.
+pub async fn sparql_get(request: HttpRequest, session: Session) -> HttpResponse {
+    let mut params = ProtocolParams::default();
+    params.extend_from_urlencoded(request.query_string().as_bytes());
+    execute_query(&request, &session, params).await
+}
+
+/// Handles a query via POST, either URL-encoded or directly in the body.
+pub async fn sparql_post(request: HttpRequest, session: Session, body: web::Bytes) -> HttpResponse {
+    let mut params = ProtocolParams::default();
+    params.extend_from_urlencoded(request.query_string().as_bytes());
+
+    let content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    match content_type.as_str() {
+        "application/x-www-form-urlencoded" => params.extend_from_urlencoded(&body),
+        "application/sparql-query" => match String::from_utf8(body.to_vec()) {
+            Ok(query) => params.queries.push(query),
+            Err(e) => {
+                return HttpResponse::BadRequest().body(format!("Query is not valid UTF-8: {e}"));
+            }
+        },
+        _ => {
+            return HttpResponse::UnsupportedMediaType()
+                .body("Expected application/x-www-form-urlencoded or application/sparql-query");
+        }
+    }
+
+    execute_query(&request, &session, params).await
+}
+
+/// Executes a query operation and serializes its results in the type accepted by the client.
+async fn execute_query(
+    request: &HttpRequest,
+    session: &Session,
+    params: ProtocolParams,
+) -> HttpResponse {
+    let Some(user_id) = session_user_id(session, false) else {
+        return no_session_response();
+    };
+    let [query] = params.queries.as_slice() else {
+        return HttpResponse::BadRequest().body("Expected exactly one 'query' parameter");
+    };
+
+    // Without a match for one kind of query, its preferred type is used instead.
+    let accept = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let results_type = DataType::negotiate(accept, &RESULTS_TYPES);
+    let graph_type = DataType::negotiate(accept, &GRAPH_TYPES);
+    if results_type.is_none() && graph_type.is_none() {
+        return HttpResponse::NotAcceptable().body(format!(
+            "Supported types: {}",
+            RESULTS_TYPES
+                .iter()
+                .chain(GRAPH_TYPES.iter())
+                .map(DataType::mime_type)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    debug!(
+        "SPARQL protocol query for default graphs {:?} and named graphs {:?}",
+        params.default_graphs, params.named_graphs
+    );
+    let store = VOWLGrapherStore::new_for_user(user_id);
+    match store
+        .serialize_protocol_query(
+            query,
+            &params.default_graphs,
+            &params.named_graphs,
+            results_type.unwrap_or(RESULTS_TYPES[0]),
+            graph_type.unwrap_or(GRAPH_TYPES[0]),
+        )
+        .await
+    {
+        Ok((resource_type, data)) => HttpResponse::Ok()
+            .content_type(resource_type.mime_type())
+            .insert_header((header::VARY, "Accept"))
+            .body(data),
+        Err(e) => error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::endpoints::test_support::{TestResult, login, session_middleware};
+    use actix_web::{App, test};
+
+    const DATA: &[u8] = b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .";
+
+    #[test]
+    fn test_graph_params_stay_separate() {
+        let mut params = ProtocolParams::default();
+        params.extend_from_urlencoded(
+            b"query=ASK%7B%7D&default-graph-uri=a&named-graph-uri=b&graph=c",
+        );
+        assert_eq!(params.queries, ["ASK{}"]);
+        assert_eq!(params.default_graphs, ["a", "c"]);
+        assert_eq!(params.named_graphs, ["b", "c"]);
+    }
+
+    #[actix_web::test]
+    async fn test_sparql_endpoint() -> TestResult {
+        let app = test::init_service(
+            App::new()
+                .wrap(session_middleware())
+                .route("/login", web::get().to(login))
+                .route("/sparql", web::get().to(sparql_get))
+                .route("/sparql", web::post().to(sparql_post)),
+        )
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/login").to_request()).await;
+        let cookie = response
+            .response()
+            .cookies()
+            .next()
+            .ok_or("no session cookie")?
+            .into_owned();
+        let user_id = String::from_utf8(test::read_body(response).await.to_vec())?;
+
+        // Without any graphs, the user must not see the graphs of others.
+        let request = test::TestRequest::get()
+            .uri("/sparql?query=ASK%7B%7D")
+            .cookie(cookie.clone())
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 404);
+
+        let store = VOWLGrapherStore::new_for_user(user_id);
+        store
+            .insert_bytes(DATA, "endpoint.nt", DataType::NTriples, false)
+            .await?;
+
+        let request = test::TestRequest::get()
+            .uri("/sparql?query=SELECT%20%3Fs%20WHERE%20%7B%3Fs%20%3Fp%20%3Fo%7D&default-graph-uri=endpoint.nt")
+            .insert_header((header::ACCEPT, "text/csv, application/sparql-results+json;q=0.5"))
+            .cookie(cookie.clone())
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            test::read_body(response).await,
+            "s\r\nhttp://example.com/s\r\n"
+        );
+
+        // A graph that is only named is not part of the default graph.
+        let request = test::TestRequest::post()
+            .uri("/sparql?named-graph-uri=endpoint.nt")
+            .insert_header((header::CONTENT_TYPE, "application/sparql-query"))
+            .insert_header((header::ACCEPT, "text/csv"))
+            .cookie(cookie.clone())
+            .set_payload("SELECT ?s WHERE { ?s ?p ?o }")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(test::read_body(response).await, "s\r\n");
+
+        let request = test::TestRequest::get()
+            .uri("/sparql?query=ASK%7B%7D")
+            .insert_header((header::ACCEPT, "image/png"))
+            .cookie(cookie)
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 406);
+
+        store.delete_graph("endpoint.nt").await?;
+        Ok(())
+    }
+}
@@ -20,6 +20,8 @@ pub enum ClientErrorKind {
     ///
     /// #2 argument is the received error message.
     EnvironmentFetchError(String, ErrorRecord),
+    /// An error raised when the user enters invalid input.
+    InvalidInput(String),
 }
 
 impl From<ClientErrorKind> for ErrorRecord {
@@ -31,6 +33,7 @@ impl From<ClientErrorKind> for ErrorRecord {
             ClientErrorKind::FileUploadError(e) | ClientErrorKind::EventHandlingError(e) => {
                 (e, ErrorType::ClientError, ErrorSeverity::Error)
             }
+            ClientErrorKind::InvalidInput(e) => (e, ErrorType::ClientError, ErrorSeverity::Warning),
             ClientErrorKind::EnvironmentFetchError(msg, e) => (
                 format!("{msg}\n{e}"),
                 ErrorType::UnknownError,
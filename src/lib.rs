@@ -17,6 +17,8 @@ pub mod app;
 pub mod blocks;
 pub mod components;
 
+#[cfg(all(feature = "ssr", feature = "server"))]
+pub mod endpoints;
 pub mod errors;
 pub mod events;
 pub mod hydration_scripts;
@@ -5,7 +5,6 @@
 
 use actix_files::Files;
 use actix_session::{SessionMiddleware, storage::CookieSessionStore};
-use actix_web::cookie::Key;
 use actix_web::{App, HttpServer, middleware, web};
 use env_logger::Env;
 use leptos::prelude::*;
@@ -13,6 +12,7 @@ use leptos_actix::{LeptosRoutes, generate_route_list};
 use leptos_meta::MetaTags;
 use log::info;
 use vowlgrapher::app::App;
+use vowlgrapher::endpoints::{graph_store, session_key, sparql};
 use vowlgrapher::hydration_scripts::HydrationScripts as Hydro;
 
 #[actix_web::main]
@@ -29,7 +29,7 @@ async fn main() -> std::io::Result<()> {
     let conf = get_configuration(None).expect("could not load config");
     let addr = conf.leptos_options.site_addr;
 
-    let secret_key = Key::generate();
+    let secret_key = session_key();
 
     HttpServer::new(move || {
         // Generate the list of routes in your Leptos App
@@ -71,6 +71,15 @@ async fn main() -> std::io::Result<()> {
                     }
                 }
             })
+            .route("/sparql", web::get().to(sparql::sparql_get))
+            .route("/sparql", web::post().to(sparql::sparql_post))
+            .route("/graph-store", web::get().to(graph_store::graph_store_get))
+            .route("/graph-store", web::put().to(graph_store::graph_store_put))
+            .route("/graph-store", web::post().to(graph_store::graph_store_post))
+            .route(
+                "/graph-store",
+                web::delete().to(graph_store::graph_store_delete),
+            )
             .service(Files::new("/", site_root.as_ref()))
             .wrap(middleware::Compress::default())
             .wrap(SessionMiddleware::builder(CookieSessionStore::default(), secret_key.clone())